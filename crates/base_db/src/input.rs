@@ -145,6 +145,9 @@ impl CrateDisplayName {
         let crate_name = CrateName::normalize_dashes(&canonical_name);
         CrateDisplayName { crate_name, canonical_name }
     }
+    pub fn canonical_name(&self) -> &str {
+        &self.canonical_name
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -2,7 +2,7 @@
 
 use either::Either;
 use hir_def::{
-    attr::{AttrsWithOwner, Documentation},
+    attr::{AttrsWithOwner, Documentation, LintLevel},
     item_scope::ItemInNs,
     path::ModPath,
     per_ns::PerNs,
@@ -120,6 +120,21 @@ impl HasAttrs for AssocItem {
     }
 }
 
+impl Module {
+    /// The level set for a lint known by any of `names` by the attributes of
+    /// this module or, failing that, of the modules it is nested in.
+    pub fn lint_level(self, db: &dyn HirDatabase, names: &[&str]) -> Option<LintLevel> {
+        let mut module = Some(self);
+        while let Some(it) = module {
+            if let Some(level) = it.attrs(db).lint_level(names) {
+                return Some(level);
+            }
+            module = it.parent(db);
+        }
+        None
+    }
+}
+
 fn resolve_doc_path(
     db: &dyn HirDatabase,
     def: AttrDefId,
@@ -9,7 +9,7 @@ use hir_def::{path::ModPath, type_ref::Mutability};
 use hir_expand::{name::Name, HirFileId, InFile};
 use syntax::{ast, AstPtr, SyntaxNodePtr, TextRange};
 
-use crate::Type;
+use crate::{AssocItem, Field, GenericArgCountMismatch, Local, ModuleDef, TraitRef, Type};
 
 macro_rules! diagnostics {
     ($($diag:ident,)*) => {
@@ -34,19 +34,30 @@ diagnostics![
     IncorrectCase,
     MacroError,
     MismatchedArgCount,
+    MismatchedGenericArgCount,
     MissingFields,
     MissingMatchArms,
     MissingOkOrSomeInTailExpr,
     MissingUnsafe,
     NoSuchField,
+    PrivateAssocItem,
+    PrivateField,
+    PrivateItem,
     RemoveThisSemicolon,
     ReplaceFilterMapNextWithFindMap,
+    TypeMismatch,
     UnimplementedBuiltinMacro,
+    UnresolvedAssocItem,
     UnresolvedExternCrate,
+    UnresolvedField,
     UnresolvedImport,
     UnresolvedMacroCall,
+    UnresolvedMethodCall,
     UnresolvedModule,
+    UnreachablePattern,
     UnresolvedProcMacro,
+    UnsatisfiedTraitBound,
+    UseAfterMove,
 ];
 
 #[derive(Debug)]
@@ -135,6 +146,12 @@ pub struct MismatchedArgCount {
     pub found: usize,
 }
 
+#[derive(Debug)]
+pub struct MismatchedGenericArgCount {
+    pub generic_args: InFile<AstPtr<ast::GenericArgList>>,
+    pub mismatch: GenericArgCountMismatch,
+}
+
 #[derive(Debug)]
 pub struct RemoveThisSemicolon {
     pub expr: InFile<AstPtr<ast::Expr>>,
@@ -153,6 +170,12 @@ pub struct MissingMatchArms {
     pub file: HirFileId,
     pub match_expr: AstPtr<ast::Expr>,
     pub arms: AstPtr<ast::MatchArmList>,
+    pub uncovered_patterns: String,
+}
+
+#[derive(Debug)]
+pub struct UnreachablePattern {
+    pub pat: InFile<AstPtr<ast::Pat>>,
 }
 
 #[derive(Debug)]
@@ -161,4 +184,64 @@ pub struct AddReferenceHere {
     pub mutability: Mutability,
 }
 
-pub use hir_ty::diagnostics::IncorrectCase;
+#[derive(Debug)]
+pub struct TypeMismatch {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub expected: Type,
+    pub actual: Type,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedField {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub receiver: Type,
+    pub name: Name,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedMethodCall {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub receiver: Type,
+    pub name: Name,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedAssocItem {
+    pub expr_or_pat: InFile<Either<AstPtr<ast::Expr>, AstPtr<ast::Pat>>>,
+    pub ty: Type,
+    pub name: Name,
+}
+
+#[derive(Debug)]
+pub struct PrivateField {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub field: Field,
+}
+
+#[derive(Debug)]
+pub struct PrivateAssocItem {
+    pub expr_or_pat: InFile<Either<AstPtr<ast::Expr>, AstPtr<ast::Pat>>>,
+    pub item: AssocItem,
+}
+
+#[derive(Debug)]
+pub struct PrivateItem {
+    /// The path expression or pattern, or the path in a type.
+    pub node: InFile<SyntaxNodePtr>,
+    pub item: ModuleDef,
+}
+
+#[derive(Debug)]
+pub struct UnsatisfiedTraitBound {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub trait_ref: TraitRef,
+}
+
+#[derive(Debug)]
+pub struct UseAfterMove {
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub moved_at: InFile<AstPtr<ast::Expr>>,
+    pub local: Local,
+}
+
+pub use hir_ty::diagnostics::{CaseType, IncorrectCase};
@@ -37,9 +37,10 @@ use arrayvec::ArrayVec;
 use base_db::{CrateDisplayName, CrateId, Edition, FileId};
 use either::Either;
 use hir_def::{
-    adt::{ReprKind, VariantData},
-    body::{BodyDiagnostic, SyntheticSyntax},
+    adt::{ReprOptions, VariantData},
+    body::{BodyDiagnostic, BodySourceMap, SyntheticSyntax},
     expr::{BindingAnnotation, LabelId, Pat, PatId},
+    generics::TypeParamProvenance,
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
     nameres,
@@ -62,9 +63,9 @@ use hir_ty::{
     subst_prefix,
     traits::FnTrait,
     AliasEq, AliasTy, BoundVar, CallableDefId, CallableSig, Canonical, CanonicalVarKinds, Cast,
-    DebruijnIndex, InEnvironment, Interner, QuantifiedWhereClause, Scalar, Solution, Substitution,
-    TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind, TyVariableKind,
-    WhereClause,
+    DebruijnIndex, ExprOrPatId, InEnvironment, Interner, QuantifiedWhereClause, Scalar, Solution,
+    Substitution, TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind,
+    TyVariableKind, TypeRefSource, WhereClause,
 };
 use itertools::Itertools;
 use nameres::diagnostics::DefDiagnosticKind;
@@ -83,13 +84,15 @@ pub use crate::{
     attrs::{HasAttrs, Namespace},
     diagnostics::{
         AddReferenceHere, AnyDiagnostic, BreakOutsideOfLoop, InactiveCode, IncorrectCase,
-        MacroError, MismatchedArgCount, MissingFields, MissingMatchArms, MissingOkOrSomeInTailExpr,
-        MissingUnsafe, NoSuchField, RemoveThisSemicolon, ReplaceFilterMapNextWithFindMap,
-        UnimplementedBuiltinMacro, UnresolvedExternCrate, UnresolvedImport, UnresolvedMacroCall,
-        UnresolvedModule, UnresolvedProcMacro,
+        MacroError, MismatchedArgCount, MismatchedGenericArgCount, MissingFields, MissingMatchArms,
+        MissingOkOrSomeInTailExpr, MissingUnsafe, NoSuchField, PrivateAssocItem, PrivateField,
+        PrivateItem, RemoveThisSemicolon, ReplaceFilterMapNextWithFindMap, TypeMismatch,
+        UnimplementedBuiltinMacro, UnreachablePattern, UnresolvedAssocItem, UnresolvedExternCrate,
+        UnresolvedField, UnresolvedImport, UnresolvedMacroCall, UnresolvedMethodCall,
+        UnresolvedModule, UnresolvedProcMacro, UnsatisfiedTraitBound, UseAfterMove,
     },
     has_source::HasSource,
-    semantics::{PathResolution, Semantics, SemanticsScope, TypeInfo},
+    semantics::{Adjust, Adjustment, PathResolution, Semantics, SemanticsScope, TypeInfo},
 };
 
 // Be careful with these re-exports.
@@ -105,7 +108,7 @@ pub use {
     cfg::{CfgAtom, CfgExpr, CfgOptions},
     hir_def::{
         adt::StructKind,
-        attr::{Attr, Attrs, AttrsWithOwner, Documentation},
+        attr::{Attr, Attrs, AttrsWithOwner, Documentation, LintLevel},
         find_path::PrefixKind,
         import_map,
         nameres::ModuleSource,
@@ -117,7 +120,12 @@ pub use {
         name::{known, Name},
         ExpandResult, HirFileId, InFile, MacroFile, Origin,
     },
-    hir_ty::display::HirDisplay,
+    hir_ty::{
+        consteval::{ComputedExpr, ConstEvalError},
+        display::HirDisplay,
+        layout::{Layout, LayoutError},
+        GenericArgCountMismatch,
+    },
 };
 
 // These are negative re-exports: pub using these names is forbidden, they
@@ -712,6 +720,30 @@ impl Field {
         Type::new(db, self.parent.module(db).id.krate(), var_id, ty)
     }
 
+    /// The offset of the field from the start of the struct, union or enum it
+    /// is part of.
+    pub fn offset(&self, db: &dyn HirDatabase) -> Result<u64, LayoutError> {
+        let (layout, variant_idx) = match self.parent {
+            VariantDef::Struct(it) => (Adt::from(it).layout(db)?, None),
+            VariantDef::Union(it) => (Adt::from(it).layout(db)?, None),
+            VariantDef::Variant(it) => {
+                let variants = &db.enum_data(it.parent.id).variants;
+                let idx = variants.iter().position(|(id, _)| id == it.id);
+                (Adt::from(it.parent).layout(db)?, idx)
+            }
+        };
+        let fields = self.parent.variant_data(db);
+        let field_idx = fields.fields().iter().position(|(id, _)| id == self.id);
+        let field_offsets = match variant_idx {
+            Some(idx) => layout.variants.get(idx).map(|it| &it.field_offsets),
+            None => Some(&layout.field_offsets),
+        };
+        field_offsets
+            .zip(field_idx)
+            .and_then(|(offsets, idx)| offsets.get(idx).copied())
+            .ok_or(LayoutError::Unknown)
+    }
+
     pub fn parent_def(&self, _db: &dyn HirDatabase) -> VariantDef {
         self.parent
     }
@@ -753,8 +785,8 @@ impl Struct {
         Type::from_def(db, self.id.lookup(db.upcast()).container.krate(), self.id)
     }
 
-    pub fn repr(self, db: &dyn HirDatabase) -> Option<ReprKind> {
-        db.struct_data(self.id).repr.clone()
+    pub fn repr(self, db: &dyn HirDatabase) -> Option<ReprOptions> {
+        db.struct_data(self.id).repr
     }
 
     pub fn kind(self, db: &dyn HirDatabase) -> StructKind {
@@ -831,6 +863,18 @@ impl Enum {
     pub fn ty(self, db: &dyn HirDatabase) -> Type {
         Type::from_def(db, self.id.lookup(db.upcast()).container.krate(), self.id)
     }
+
+    /// The type of the discriminants, as given by a `repr` attribute.
+    pub fn discriminant_ty(self, db: &dyn HirDatabase) -> Type {
+        use hir_def::builtin_type::{BuiltinInt, BuiltinType};
+        let ty = match db.enum_data(self.id).repr.and_then(|it| it.int) {
+            Some(Either::Left(it)) => BuiltinType::Int(it),
+            Some(Either::Right(it)) => BuiltinType::Uint(it),
+            None => BuiltinType::Int(BuiltinInt::Isize),
+        };
+        let krate = self.id.lookup(db.upcast()).container.krate();
+        Type::new(db, krate, self.id, TyBuilder::builtin(ty))
+    }
 }
 
 impl HasVisibility for Enum {
@@ -870,6 +914,12 @@ impl Variant {
         self.variant_data(db).kind()
     }
 
+    /// Evaluates the discriminant of the variant, whether it is explicit or
+    /// not.
+    pub fn discriminant(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        hir_ty::consteval::eval_discriminant(db, self.into())
+    }
+
     pub(crate) fn variant_data(self, db: &dyn HirDatabase) -> Arc<VariantData> {
         db.enum_data(self.parent.id).variants[self.id].variant_data.clone()
     }
@@ -920,6 +970,13 @@ impl Adt {
             Adt::Enum(e) => e.name(db),
         }
     }
+
+    /// The layout of the ADT, which is only known if it doesn't depend on
+    /// its type parameters.
+    pub fn layout(self, db: &dyn HirDatabase) -> Result<Arc<Layout>, LayoutError> {
+        let id = AdtId::from(self);
+        db.layout_of_adt(id, TyBuilder::type_params_subst(db, id))
+    }
 }
 
 impl HasVisibility for Adt {
@@ -1110,6 +1167,104 @@ impl Function {
                         .expect("break outside of loop in synthetic syntax");
                     acc.push(BreakOutsideOfLoop { expr }.into())
                 }
+                hir_ty::InferenceDiagnostic::UnresolvedField { expr, receiver, name } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let receiver = Type::new(db, krate, self.id, receiver.clone());
+                        acc.push(UnresolvedField { expr, receiver, name: name.clone() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::UnresolvedMethodCall { expr, receiver, name } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let receiver = Type::new(db, krate, self.id, receiver.clone());
+                        acc.push(UnresolvedMethodCall { expr, receiver, name: name.clone() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::UnresolvedAssocItem { id, ty, name } => {
+                    if let Some(expr_or_pat) = expr_or_pat_syntax(&source_map, *id) {
+                        let ty = Type::new(db, krate, self.id, ty.clone());
+                        acc.push(UnresolvedAssocItem { expr_or_pat, ty, name: name.clone() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::PrivateField { expr, field } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        acc.push(PrivateField { expr, field: (*field).into() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::PrivateAssocItem { id, item } => {
+                    if let Some(expr_or_pat) = expr_or_pat_syntax(&source_map, *id) {
+                        acc.push(PrivateAssocItem { expr_or_pat, item: (*item).into() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::PrivateItem { id, item } => {
+                    if let Some(expr_or_pat) = expr_or_pat_syntax(&source_map, *id) {
+                        let node = expr_or_pat.map(|it| it.either(Into::into, Into::into));
+                        acc.push(PrivateItem { node, item: (*item).into() }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::UnsatisfiedTraitBound { expr, trait_ref } => {
+                    if let Ok(expr) = source_map.expr_syntax(*expr) {
+                        let trait_ref = TraitRef::new(db, krate, self.id, trait_ref.clone());
+                        acc.push(UnsatisfiedTraitBound { expr, trait_ref }.into())
+                    }
+                }
+                hir_ty::InferenceDiagnostic::MismatchedGenericArgCount { expr, mismatch } => {
+                    let generic_args = source_map.expr_syntax(*expr).ok().and_then(|expr| {
+                        let root = db.parse_or_expand(expr.file_id)?;
+                        let call = match expr.value.to_node(&root) {
+                            ast::Expr::MethodCallExpr(it) => it,
+                            _ => return None,
+                        };
+                        Some(expr.with_value(AstPtr::new(&call.generic_arg_list()?)))
+                    });
+                    if let Some(generic_args) = generic_args {
+                        acc.push(
+                            MismatchedGenericArgCount { generic_args, mismatch: *mismatch }.into(),
+                        )
+                    }
+                }
+                // Handled below, as the same type reference may be lowered
+                // more than once.
+                hir_ty::InferenceDiagnostic::TyLowering { .. } => (),
+            }
+        }
+
+        let ty_lowering_diagnostics = infer.diagnostics.iter().filter_map(|d| match d {
+            hir_ty::InferenceDiagnostic::TyLowering { source, diagnostic } => {
+                Some((source, diagnostic))
+            }
+            _ => None,
+        });
+        for (source, diagnostic) in ty_lowering_diagnostics.unique() {
+            match diagnostic {
+                hir_ty::TyLoweringDiagnostic::PrivateItem { path, item } => {
+                    for path in type_ref_paths(self, db, &source_map, *source, path) {
+                        let node = path.map(|it| it.into());
+                        acc.push(PrivateItem { node, item: (*item).into() }.into())
+                    }
+                }
+                hir_ty::TyLoweringDiagnostic::MismatchedGenericArgCount {
+                    path,
+                    segment,
+                    mismatch,
+                } => {
+                    let from_end = path.segments().len() - 1 - segment;
+                    for ast_path in type_ref_paths(self, db, &source_map, *source, path) {
+                        let root = match db.parse_or_expand(ast_path.file_id) {
+                            Some(it) => it,
+                            None => continue,
+                        };
+                        let generic_args =
+                            segment_generic_args(ast_path.value.to_node(&root), from_end);
+                        if let Some(generic_args) = generic_args {
+                            let generic_args =
+                                InFile::new(ast_path.file_id, AstPtr::new(&generic_args));
+                            acc.push(
+                                MismatchedGenericArgCount { generic_args, mismatch: *mismatch }
+                                    .into(),
+                            )
+                        }
+                    }
+                }
             }
         }
 
@@ -1123,6 +1278,15 @@ impl Function {
             }
         }
 
+        for d in hir_ty::diagnostics::use_after_move(db, self.id.into()) {
+            if let (Ok(expr), Ok(moved_at)) =
+                (source_map.expr_syntax(d.expr), source_map.expr_syntax(d.moved_at))
+            {
+                let local = Local { parent: self.id.into(), pat_id: d.binding };
+                acc.push(UseAfterMove { expr, moved_at, local }.into())
+            }
+        }
+
         for diagnostic in BodyValidationDiagnostic::collect(db, self.id.into()) {
             match diagnostic {
                 BodyValidationDiagnostic::RecordMissingFields {
@@ -1227,7 +1391,7 @@ impl Function {
                         Err(SyntheticSyntax) => (),
                     }
                 }
-                BodyValidationDiagnostic::MissingMatchArms { match_expr } => {
+                BodyValidationDiagnostic::MissingMatchArms { match_expr, uncovered_patterns } => {
                     match source_map.expr_syntax(match_expr) {
                         Ok(source_ptr) => {
                             let root = source_ptr.file_syntax(db.upcast());
@@ -1242,6 +1406,7 @@ impl Function {
                                             file: source_ptr.file_id,
                                             match_expr: AstPtr::new(&match_expr),
                                             arms: AstPtr::new(&arms),
+                                            uncovered_patterns,
                                         }
                                         .into(),
                                     )
@@ -1251,12 +1416,31 @@ impl Function {
                         Err(SyntheticSyntax) => (),
                     }
                 }
+                BodyValidationDiagnostic::UnreachablePattern { pat } => {
+                    if let Ok(InFile { file_id, value: Either::Left(pat) }) =
+                        source_map.pat_syntax(pat)
+                    {
+                        acc.push(UnreachablePattern { pat: InFile::new(file_id, pat) }.into())
+                    }
+                }
                 BodyValidationDiagnostic::AddReferenceHere { arg_expr, mutability } => {
                     match source_map.expr_syntax(arg_expr) {
                         Ok(expr) => acc.push(AddReferenceHere { expr, mutability }.into()),
                         Err(SyntheticSyntax) => (),
                     }
                 }
+                BodyValidationDiagnostic::TypeMismatch { expr, mismatch } => {
+                    if let Ok(expr) = source_map.expr_syntax(expr) {
+                        acc.push(
+                            TypeMismatch {
+                                expr,
+                                expected: Type::new(db, krate, self.id, mismatch.expected),
+                                actual: Type::new(db, krate, self.id, mismatch.actual),
+                            }
+                            .into(),
+                        )
+                    }
+                }
             }
         }
 
@@ -1375,6 +1559,122 @@ impl SelfParam {
     }
 }
 
+/// The paths equal to `path` in the type references `source` of `func`
+/// refers to.
+fn type_ref_paths(
+    func: Function,
+    db: &dyn HirDatabase,
+    source_map: &BodySourceMap,
+    source: TypeRefSource,
+    path: &Path,
+) -> Vec<InFile<AstPtr<ast::Path>>> {
+    let (file_id, roots) = match source {
+        TypeRefSource::Signature => {
+            let src = match func.source(db) {
+                Some(it) => it,
+                None => return Vec::new(),
+            };
+            let roots = src
+                .value
+                .param_list()
+                .map(|it| it.syntax().clone())
+                .into_iter()
+                .chain(src.value.ret_type().map(|it| it.syntax().clone()))
+                .collect::<Vec<_>>();
+            (src.file_id, roots)
+        }
+        TypeRefSource::Body(ExprOrPatId::ExprId(expr)) => {
+            let src = match source_map.expr_syntax(expr) {
+                Ok(it) => it,
+                Err(SyntheticSyntax) => return Vec::new(),
+            };
+            let root = match db.parse_or_expand(src.file_id) {
+                Some(it) => it,
+                None => return Vec::new(),
+            };
+            let roots = match src.value.to_node(&root) {
+                ast::Expr::CastExpr(it) => {
+                    it.ty().map(|it| it.syntax().clone()).into_iter().collect()
+                }
+                ast::Expr::ClosureExpr(it) => it
+                    .param_list()
+                    .map(|it| it.syntax().clone())
+                    .into_iter()
+                    .chain(it.ret_type().map(|it| it.syntax().clone()))
+                    .collect(),
+                ast::Expr::MethodCallExpr(it) => {
+                    it.generic_arg_list().map(|it| it.syntax().clone()).into_iter().collect()
+                }
+                it => vec![it.syntax().clone()],
+            };
+            (src.file_id, roots)
+        }
+        TypeRefSource::Body(ExprOrPatId::PatId(pat)) => {
+            let src = match source_map.pat_syntax(pat) {
+                Ok(InFile { file_id, value: Either::Left(pat) }) => InFile::new(file_id, pat),
+                _ => return Vec::new(),
+            };
+            let root = match db.parse_or_expand(src.file_id) {
+                Some(it) => it,
+                None => return Vec::new(),
+            };
+            let pat = src.value.to_node(&root);
+            let root = match pat.syntax().parent().and_then(<ast::LetStmt as AstNode>::cast) {
+                Some(let_stmt) => match let_stmt.ty() {
+                    Some(ty) => ty.syntax().clone(),
+                    None => return Vec::new(),
+                },
+                None => pat.syntax().clone(),
+            };
+            (src.file_id, vec![root])
+        }
+    };
+    let ctx = hir_def::body::LowerCtx::new(db.upcast(), file_id);
+    roots
+        .iter()
+        .flat_map(|root| root.descendants().filter_map(<ast::Path as AstNode>::cast))
+        .filter(|it| Path::from_src(it.clone(), &ctx).as_ref() == Some(path))
+        .map(|it| InFile::new(file_id, AstPtr::new(&it)))
+        .collect()
+}
+
+/// The generic arguments of the segment `from_end` segments before the last
+/// one of `path`, where the trait of `<T as Trait>` counts as its segments.
+fn segment_generic_args(path: ast::Path, from_end: usize) -> Option<ast::GenericArgList> {
+    let mut path = path;
+    let mut from_end = from_end;
+    loop {
+        let segment = path.segment()?;
+        if let Some(ast::PathSegmentKind::Type { trait_ref: Some(trait_ref), .. }) = segment.kind()
+        {
+            path = trait_ref.path()?;
+            continue;
+        }
+        if from_end == 0 {
+            return segment.generic_arg_list();
+        }
+        from_end -= 1;
+        path = path.qualifier()?;
+    }
+}
+
+fn expr_or_pat_syntax(
+    source_map: &BodySourceMap,
+    id: ExprOrPatId,
+) -> Option<InFile<Either<AstPtr<ast::Expr>, AstPtr<ast::Pat>>>> {
+    match id {
+        ExprOrPatId::ExprId(expr) => {
+            source_map.expr_syntax(expr).ok().map(|it| it.map(Either::Left))
+        }
+        ExprOrPatId::PatId(pat) => match source_map.pat_syntax(pat) {
+            Ok(InFile { file_id, value: Either::Left(pat) }) => {
+                Some(InFile::new(file_id, Either::Right(pat)))
+            }
+            _ => None,
+        },
+    }
+}
+
 impl HasVisibility for Function {
     fn visibility(&self, db: &dyn HirDatabase) -> Visibility {
         let function_data = db.function_data(self.id);
@@ -1405,6 +1705,11 @@ impl Const {
         let ty = ctx.lower_ty(&data.type_ref);
         Type::new_with_resolver_inner(db, krate.id, &resolver, ty)
     }
+
+    /// Evaluates the value of the constant.
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        db.const_eval(self.id)
+    }
 }
 
 impl HasVisibility for Const {
@@ -1441,6 +1746,13 @@ impl Static {
         let ty = ctx.lower_ty(&data.type_ref);
         Type::new_with_resolver_inner(db, krate, &resolver, ty)
     }
+
+    /// Evaluates the initializer of the static.
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        let body = db.body(self.id.into());
+        let infer = db.infer(self.id.into());
+        hir_ty::consteval::eval_const(db, self.id.into(), &body, &infer, body.body_expr)
+    }
 }
 
 impl HasVisibility for Static {
@@ -1990,6 +2302,7 @@ impl TypeParam {
     }
 
     pub fn default(self, db: &dyn HirDatabase) -> Option<Type> {
+        db.generic_params(self.id.parent).types[self.id.local_id].default.as_ref()?;
         let params = db.generic_defaults(self.id.parent);
         let local_idx = hir_ty::param_idx(db, self.id)?;
         let resolver = self.id.parent.resolver(db.upcast());
@@ -1999,6 +2312,13 @@ impl TypeParam {
         let ty = ty.substitute(&Interner, &subst_prefix(&subst, local_idx));
         Some(Type::new_with_resolver_inner(db, krate, &resolver, ty))
     }
+
+    /// Whether the parameter is declared in a generic parameter list, as
+    /// opposed to the `Self` of a trait or an `impl Trait` argument.
+    pub fn is_explicit(self, db: &dyn HirDatabase) -> bool {
+        let params = db.generic_params(self.id.parent);
+        params.types[self.id.local_id].provenance == TypeParamProvenance::TypeParamList
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -2214,6 +2534,10 @@ impl Type {
         Type::new(db, krate, def, ty)
     }
 
+    pub fn layout(&self, db: &dyn HirDatabase) -> Result<Arc<Layout>, LayoutError> {
+        hir_ty::layout::layout_of_ty(db, &self.ty, self.krate)
+    }
+
     pub fn is_unit(&self) -> bool {
         matches!(self.ty.kind(&Interner), TyKind::Tuple(0, ..))
     }
@@ -2381,7 +2705,7 @@ impl Type {
 
         let adt = adt_id.into();
         match adt {
-            Adt::Struct(s) => matches!(s.repr(db), Some(ReprKind::Packed)),
+            Adt::Struct(s) => matches!(s.repr(db), Some(ReprOptions { packed: Some(_), .. })),
             _ => false,
         }
     }
@@ -2715,6 +3039,38 @@ impl Type {
     }
 }
 
+/// A trait applied to some types, like `T: Clone` or `u32: From<u8>`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraitRef {
+    krate: CrateId,
+    env: Arc<TraitEnvironment>,
+    trait_ref: hir_ty::TraitRef,
+}
+
+impl TraitRef {
+    fn new(
+        db: &dyn HirDatabase,
+        krate: CrateId,
+        lexical_env: impl HasResolver,
+        trait_ref: hir_ty::TraitRef,
+    ) -> TraitRef {
+        let resolver = lexical_env.resolver(db.upcast());
+        let env = resolver
+            .generic_def()
+            .map_or_else(|| Arc::new(TraitEnvironment::empty(krate)), |d| db.trait_environment(d));
+        TraitRef { krate, env, trait_ref }
+    }
+
+    pub fn trait_(&self) -> Trait {
+        self.trait_ref.hir_trait_id().into()
+    }
+
+    pub fn self_ty(&self) -> Type {
+        let ty = self.trait_ref.self_type_parameter(&Interner);
+        Type { krate: self.krate, env: self.env.clone(), ty }
+    }
+}
+
 // FIXME: closures
 #[derive(Debug)]
 pub struct Callable {
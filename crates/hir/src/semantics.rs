@@ -25,8 +25,8 @@ use crate::{
     semantics::source_to_def::{ChildContainer, SourceToDefCache, SourceToDefCtx},
     source_analyzer::{resolve_hir_path, SourceAnalyzer},
     Access, AssocItem, Callable, ConstParam, Crate, Field, Function, HirFileId, Impl, InFile,
-    Label, LifetimeParam, Local, MacroDef, Module, ModuleDef, Name, Path, ScopeDef, Trait, Type,
-    TypeAlias, TypeParam, VariantDef,
+    Label, LifetimeParam, Local, MacroDef, Module, ModuleDef, Mutability, Name, Path, ScopeDef,
+    Trait, Type, TypeAlias, TypeParam, VariantDef,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -110,6 +110,30 @@ impl TypeInfo {
     }
 }
 
+/// An implicit conversion applied to an expression by type inference, like an
+/// auto-deref, an auto-ref or a reborrow.
+#[derive(Debug, Clone)]
+pub struct Adjustment {
+    pub kind: Adjust,
+    /// The type of the expression after this adjustment.
+    pub target: Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjust {
+    /// Go from `!` to any type.
+    NeverToAny,
+    /// Dereference once. Overloaded derefs through `Deref` or `DerefMut` have
+    /// the mutability of the reference they go through.
+    Deref(Option<Mutability>),
+    /// Take a `&` or `&mut` reference.
+    Borrow(Mutability),
+    /// Take a `*const` or `*mut` raw pointer.
+    RawBorrow(Mutability),
+    /// A pointer cast, like unsizing or turning a closure into a fn pointer.
+    Pointer,
+}
+
 /// Primary API to get semantic information, like types, from syntax trees.
 pub struct Semantics<'db, DB> {
     pub db: &'db DB,
@@ -251,6 +275,11 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.type_of_self(param)
     }
 
+    /// The implicit conversions applied to `expr`, innermost first.
+    pub fn expr_adjustments(&self, expr: &ast::Expr) -> Option<Vec<Adjustment>> {
+        self.imp.expr_adjustments(expr)
+    }
+
     pub fn resolve_method_call(&self, call: &ast::MethodCallExpr) -> Option<Function> {
         self.imp.resolve_method_call(call).map(Function::from)
     }
@@ -309,6 +338,12 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.record_pattern_missing_fields(pattern)
     }
 
+    /// Patterns for the values not covered by the arms of `match_expr`, as source code valid at
+    /// the location of the match. Returns `None` if the arms could not be checked.
+    pub fn missing_match_arm_patterns(&self, match_expr: &ast::MatchExpr) -> Option<Vec<String>> {
+        self.imp.missing_match_arm_patterns(match_expr)
+    }
+
     pub fn to_def<T: ToDef>(&self, src: &T) -> Option<T::Def> {
         let src = self.imp.find_file(src.syntax().clone()).with_value(src).cloned();
         T::to_def(&self.imp, src)
@@ -603,6 +638,10 @@ impl<'db> SemanticsImpl<'db> {
         self.analyze(param.syntax()).type_of_self(self.db, param)
     }
 
+    fn expr_adjustments(&self, expr: &ast::Expr) -> Option<Vec<Adjustment>> {
+        self.analyze(expr.syntax()).expr_adjustments(self.db, expr)
+    }
+
     fn resolve_method_call(&self, call: &ast::MethodCallExpr) -> Option<FunctionId> {
         self.analyze(call.syntax()).resolve_method_call(self.db, call).map(|(id, _)| id)
     }
@@ -679,6 +718,10 @@ impl<'db> SemanticsImpl<'db> {
             .unwrap_or_default()
     }
 
+    fn missing_match_arm_patterns(&self, match_expr: &ast::MatchExpr) -> Option<Vec<String>> {
+        self.analyze(match_expr.syntax()).missing_match_arm_patterns(self.db, match_expr)
+    }
+
     fn with_ctx<F: FnOnce(&mut SourceToDefCtx) -> T, T>(&self, f: F) -> T {
         let mut cache = self.s2d_cache.borrow_mut();
         let mut ctx = SourceToDefCtx { db: self.db, cache: &mut *cache };
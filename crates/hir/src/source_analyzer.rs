@@ -20,8 +20,10 @@ use hir_def::{
 };
 use hir_expand::{hygiene::Hygiene, name::AsName, HirFileId, InFile};
 use hir_ty::{
-    diagnostics::{record_literal_missing_fields, record_pattern_missing_fields},
-    InferenceResult, Interner, Substitution, TyExt, TyLoweringContext,
+    diagnostics::{
+        missing_match_arm_patterns, record_literal_missing_fields, record_pattern_missing_fields,
+    },
+    AutoBorrow, InferenceResult, Interner, Substitution, TyExt, TyLoweringContext,
 };
 use syntax::{
     ast::{self, AstNode},
@@ -29,8 +31,10 @@ use syntax::{
 };
 
 use crate::{
-    db::HirDatabase, semantics::PathResolution, Adt, BuiltinType, Const, Field, Function, Local,
-    MacroDef, ModuleDef, Static, Struct, Trait, Type, TypeAlias, TypeParam, Variant,
+    db::HirDatabase,
+    semantics::{Adjust, Adjustment, PathResolution},
+    Adt, BuiltinType, Const, Field, Function, Local, MacroDef, ModuleDef, Mutability, Static,
+    Struct, Trait, Type, TypeAlias, TypeParam, Variant,
 };
 use base_db::CrateId;
 
@@ -132,6 +136,37 @@ impl SourceAnalyzer {
         mk_ty(ty).zip(Some(coerced.and_then(mk_ty)))
     }
 
+    pub(crate) fn expr_adjustments(
+        &self,
+        db: &dyn HirDatabase,
+        expr: &ast::Expr,
+    ) -> Option<Vec<Adjustment>> {
+        let expr_id = self.expr_id(db, expr)?;
+        let infer = self.infer.as_ref()?;
+        let mutability = |it| Mutability::from_mutable(it == hir_ty::Mutability::Mut);
+        infer
+            .expr_adjustments
+            .get(&expr_id)?
+            .iter()
+            .map(|adjustment| {
+                let kind = match adjustment.kind {
+                    hir_ty::Adjust::NeverToAny => Adjust::NeverToAny,
+                    hir_ty::Adjust::Deref(overloaded) => {
+                        Adjust::Deref(overloaded.map(|it| mutability(it.0)))
+                    }
+                    hir_ty::Adjust::Borrow(AutoBorrow::Ref(m)) => Adjust::Borrow(mutability(m)),
+                    hir_ty::Adjust::Borrow(AutoBorrow::RawPtr(m)) => {
+                        Adjust::RawBorrow(mutability(m))
+                    }
+                    hir_ty::Adjust::Pointer(_) => Adjust::Pointer,
+                };
+                let target =
+                    Type::new_with_resolver(db, &self.resolver, adjustment.target.clone())?;
+                Some(Adjustment { kind, target })
+            })
+            .collect()
+    }
+
     pub(crate) fn type_of_pat(
         &self,
         db: &dyn HirDatabase,
@@ -353,6 +388,19 @@ impl SourceAnalyzer {
         Some(res)
     }
 
+    pub(crate) fn missing_match_arm_patterns(
+        &self,
+        db: &dyn HirDatabase,
+        match_expr: &ast::MatchExpr,
+    ) -> Option<Vec<String>> {
+        let owner = self.resolver.body_owner()?;
+        let module = self.resolver.module()?;
+        let infer = self.infer.as_ref()?;
+
+        let expr_id = self.expr_id(db, &match_expr.clone().into())?;
+        missing_match_arm_patterns(db, owner, infer, expr_id, module)
+    }
+
     pub(crate) fn record_pattern_missing_fields(
         &self,
         db: &dyn HirDatabase,
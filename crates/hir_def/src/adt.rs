@@ -14,13 +14,14 @@ use tt::{Delimiter, DelimiterKind, Leaf, Subtree, TokenTree};
 
 use crate::{
     body::{CfgExpander, LowerCtx},
+    builtin_type::{BuiltinInt, BuiltinUint},
     db::DefDatabase,
     intern::Interned,
     item_tree::{AttrOwner, Field, Fields, ItemTree, ModItem, RawVisibilityId},
     src::HasChildSource,
     src::HasSource,
     trace::Trace,
-    type_ref::TypeRef,
+    type_ref::{ConstRef, TypeRef},
     visibility::RawVisibility,
     EnumId, LocalEnumVariantId, LocalFieldId, Lookup, ModuleId, StructId, UnionId, VariantId,
 };
@@ -31,7 +32,7 @@ use cfg::CfgOptions;
 pub struct StructData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
-    pub repr: Option<ReprKind>,
+    pub repr: Option<ReprOptions>,
     pub visibility: RawVisibility,
 }
 
@@ -39,6 +40,7 @@ pub struct StructData {
 pub struct EnumData {
     pub name: Name,
     pub variants: Arena<EnumVariantData>,
+    pub repr: Option<ReprOptions>,
     pub visibility: RawVisibility,
 }
 
@@ -46,6 +48,7 @@ pub struct EnumData {
 pub struct EnumVariantData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
+    pub discriminant: Option<ConstRef>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,10 +66,17 @@ pub struct FieldData {
     pub visibility: RawVisibility,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ReprKind {
-    Packed,
-    Other,
+/// The options of the `#[repr]` attributes of a type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReprOptions {
+    pub c: bool,
+    pub transparent: bool,
+    /// The maximum alignment of the fields, 1 for `packed`.
+    pub packed: Option<u64>,
+    /// The minimum alignment of the type.
+    pub align: Option<u64>,
+    /// The type of the discriminant of an enum, e.g. `u8` for `repr(u8)`.
+    pub int: Option<Either<BuiltinInt, BuiltinUint>>,
 }
 
 fn repr_from_value(
@@ -74,20 +84,52 @@ fn repr_from_value(
     krate: CrateId,
     item_tree: &ItemTree,
     of: AttrOwner,
-) -> Option<ReprKind> {
-    item_tree.attrs(db, krate, of).by_key("repr").tt_values().find_map(parse_repr_tt)
+) -> Option<ReprOptions> {
+    let attrs = item_tree.attrs(db, krate, of);
+    let mut tts = attrs.by_key("repr").tt_values().peekable();
+    tts.peek()?;
+    let mut res = ReprOptions::default();
+    for tt in tts {
+        parse_repr_tt(tt, &mut res);
+    }
+    Some(res)
 }
 
-fn parse_repr_tt(tt: &Subtree) -> Option<ReprKind> {
+fn parse_repr_tt(tt: &Subtree, res: &mut ReprOptions) {
     match tt.delimiter {
         Some(Delimiter { kind: DelimiterKind::Parenthesis, .. }) => {}
-        _ => return None,
+        _ => return,
     }
 
-    let mut it = tt.token_trees.iter();
-    match it.next()? {
-        TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "packed" => Some(ReprKind::Packed),
-        _ => Some(ReprKind::Other),
+    let mut it = tt.token_trees.iter().peekable();
+    while let Some(tt) = it.next() {
+        let ident = match tt {
+            TokenTree::Leaf(Leaf::Ident(ident)) => ident.text.as_str(),
+            _ => continue,
+        };
+        // The argument of `packed(N)` and `align(N)`.
+        let arg = match it.peek() {
+            Some(TokenTree::Subtree(subtree)) => {
+                it.next();
+                match subtree.token_trees.first() {
+                    Some(TokenTree::Leaf(Leaf::Literal(lit))) => lit.text.parse::<u64>().ok(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match ident {
+            "C" => res.c = true,
+            "transparent" => res.transparent = true,
+            "packed" => res.packed = Some(arg.unwrap_or(1)),
+            "align" => res.align = arg,
+            _ => {
+                res.int = BuiltinInt::from_suffix(ident)
+                    .map(Either::Left)
+                    .or_else(|| BuiltinUint::from_suffix(ident).map(Either::Right))
+                    .or(res.int)
+            }
+        }
     }
 }
 
@@ -132,6 +174,7 @@ impl EnumData {
         let loc = e.lookup(db);
         let krate = loc.container.krate;
         let item_tree = loc.id.item_tree(db);
+        let repr = repr_from_value(db, krate, &item_tree, ModItem::from(loc.id.value).into());
         let cfg_options = db.crate_graph()[krate].cfg_options.clone();
 
         let enum_ = &item_tree[loc.id.value];
@@ -151,6 +194,7 @@ impl EnumData {
                 variants.alloc(EnumVariantData {
                     name: var.name.clone(),
                     variant_data: Arc::new(var_data),
+                    discriminant: var.discriminant.clone(),
                 });
             }
         }
@@ -158,6 +202,7 @@ impl EnumData {
         Arc::new(EnumData {
             name: enum_.name.clone(),
             variants,
+            repr,
             visibility: item_tree[enum_.visibility].clone(),
         })
     }
@@ -194,12 +239,14 @@ fn lower_enum(
         .into_iter()
         .flat_map(|it| it.variants())
         .filter(|var| expander.is_cfg_enabled(db, var));
+    let ctx = LowerCtx::new(db, ast.file_id);
     for var in variants {
         trace.alloc(
             || var.clone(),
             || EnumVariantData {
                 name: var.name().map_or_else(Name::missing, |it| it.as_name()),
                 variant_data: Arc::new(VariantData::new(db, ast.with_value(var.kind()), module_id)),
+                discriminant: var.expr().map(|it| ConstRef::from_expr(&ctx, it)),
             },
         );
     }
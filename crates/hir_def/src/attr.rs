@@ -297,6 +297,50 @@ impl Attrs {
                 matches!(&*tt.token_trees, [tt::TokenTree::Leaf(tt::Leaf::Ident(ident))] if ident.text == "hidden")
         })
     }
+
+    /// The level the lint attributes set for a lint known by any of `names`,
+    /// e.g. the lint itself and its groups. Later attributes win.
+    pub fn lint_level(&self, names: &[&str]) -> Option<LintLevel> {
+        self.iter().rev().find_map(|attr| {
+            let level = LintLevel::from_attr_name(&attr.path.as_ident()?.to_string())?;
+            let tt = match attr.input.as_deref()? {
+                AttrInput::TokenTree(it) => it,
+                AttrInput::Literal(_) => return None,
+            };
+            let applies = tt.token_trees.iter().any(|tt| match tt {
+                tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => names.contains(&ident.text.as_str()),
+                _ => false,
+            });
+            if applies {
+                Some(level)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// The level set for a lint by an `#[allow]`, `#[warn]`, `#[deny]` or
+/// `#[forbid]` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+    Forbid,
+}
+
+impl LintLevel {
+    pub fn from_attr_name(name: &str) -> Option<LintLevel> {
+        let level = match name {
+            "allow" => LintLevel::Allow,
+            "warn" => LintLevel::Warn,
+            "deny" => LintLevel::Deny,
+            "forbid" => LintLevel::Forbid,
+            _ => return None,
+        };
+        Some(level)
+    }
 }
 
 impl AttrsWithOwner {
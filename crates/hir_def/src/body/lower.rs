@@ -17,7 +17,7 @@ use syntax::{
         self, ArgListOwner, ArrayExprKind, AstChildren, LiteralKind, LoopBodyOwner, NameOwner,
         SlicePatComponents,
     },
-    AstNode, AstPtr, SyntaxNodePtr,
+    AstNode, AstPtr, SyntaxNodePtr, T,
 };
 
 use crate::{
@@ -854,16 +854,10 @@ impl ExprCollector<'_> {
                     suffix: suffix.into_iter().map(|p| self.collect_pat(p)).collect(),
                 }
             }
-            ast::Pat::LiteralPat(lit) => {
-                if let Some(ast_lit) = lit.literal() {
-                    let expr = Expr::Literal(ast_lit.kind().into());
-                    let expr_ptr = AstPtr::new(&ast::Expr::Literal(ast_lit));
-                    let expr_id = self.alloc_expr(expr, expr_ptr);
-                    Pat::Lit(expr_id)
-                } else {
-                    Pat::Missing
-                }
-            }
+            ast::Pat::LiteralPat(lit) => match self.collect_lit_pat(lit) {
+                Some(expr_id) => Pat::Lit(expr_id),
+                None => Pat::Missing,
+            },
             ast::Pat::RestPat(_) => {
                 // `RestPat` requires special handling and should not be mapped
                 // to a Pat. Here we are using `Pat::Missing` as a fallback for
@@ -900,13 +894,54 @@ impl ExprCollector<'_> {
                 }
                 None => Pat::Missing,
             },
-            // FIXME: implement
-            ast::Pat::RangePat(_) => Pat::Missing,
+            ast::Pat::RangePat(p) => match p.op_kind() {
+                Some(range_type) => {
+                    let start = p.start().and_then(|it| self.collect_range_pat_bound(it));
+                    let end = p.end().and_then(|it| self.collect_range_pat_bound(it));
+                    Pat::Range { start, end, range_type }
+                }
+                None => Pat::Missing,
+            },
         };
         let ptr = AstPtr::new(&pat);
         self.alloc_pat(pattern, Either::Left(ptr))
     }
 
+    fn collect_lit_pat(&mut self, lit: &ast::LiteralPat) -> Option<ExprId> {
+        let ast_lit = lit.literal()?;
+        let mut literal = Literal::from(ast_lit.kind());
+        let is_negated = lit.syntax().children_with_tokens().any(|it| it.kind() == T![-]);
+        if is_negated {
+            literal = match literal {
+                Literal::Int(value, suffix) => Literal::Int(-value, suffix),
+                Literal::Uint(value, None) => Literal::Int(-(value as i128), None),
+                it => it,
+            };
+        }
+        let expr_ptr = AstPtr::new(&ast::Expr::Literal(ast_lit));
+        Some(self.alloc_expr(Expr::Literal(literal), expr_ptr))
+    }
+
+    /// Range patterns are bounded by literals or paths to constants, which we
+    /// lower as expressions.
+    fn collect_range_pat_bound(&mut self, pat: ast::Pat) -> Option<ExprId> {
+        match pat {
+            ast::Pat::LiteralPat(lit) => self.collect_lit_pat(&lit),
+            ast::Pat::PathPat(p) => {
+                let path = p.path().and_then(|path| self.expander.parse_path(self.db, path))?;
+                Some(self.alloc_expr_desugared(Expr::Path(path)))
+            }
+            // A lone identifier like `LOW` in `0..LOW` parses as a binding.
+            ast::Pat::IdentPat(p)
+                if p.pat().is_none() && p.ref_token().is_none() && p.mut_token().is_none() =>
+            {
+                let name = p.name()?.as_name();
+                Some(self.alloc_expr_desugared(Expr::Path(name.into())))
+            }
+            _ => None,
+        }
+    }
+
     fn collect_pat_opt(&mut self, pat: Option<ast::Pat>) -> PatId {
         if let Some(pat) = pat {
             self.collect_pat(pat)
@@ -977,10 +1012,15 @@ impl From<ast::LiteralKind> for Literal {
                 let text = bs.value().map(Vec::from).unwrap_or_else(Default::default);
                 Literal::ByteString(text)
             }
-            LiteralKind::String(_) => Literal::String(Default::default()),
-            LiteralKind::Byte => Literal::Uint(Default::default(), Some(BuiltinUint::U8)),
+            LiteralKind::String(s) => {
+                let text = s.value().map(String::from).unwrap_or_else(Default::default);
+                Literal::String(text)
+            }
+            LiteralKind::Byte(b) => {
+                Literal::Uint(b.value().unwrap_or_default() as u128, Some(BuiltinUint::U8))
+            }
             LiteralKind::Bool(val) => Literal::Bool(val),
-            LiteralKind::Char => Literal::Char(Default::default()),
+            LiteralKind::Char(c) => Literal::Char(c.value().unwrap_or_default()),
         }
     }
 }
@@ -381,7 +381,7 @@ pub enum Pat {
     Tuple { args: Vec<PatId>, ellipsis: Option<usize> },
     Or(Vec<PatId>),
     Record { path: Option<Box<Path>>, args: Vec<RecordFieldPat>, ellipsis: bool },
-    Range { start: ExprId, end: ExprId },
+    Range { start: Option<ExprId>, end: Option<ExprId>, range_type: RangeOp },
     Slice { prefix: Vec<PatId>, slice: Option<PatId>, suffix: Vec<PatId> },
     Path(Box<Path>),
     Lit(ExprId),
@@ -65,7 +65,7 @@ use crate::{
     generics::GenericParams,
     intern::Interned,
     path::{path, AssociatedTypeBinding, GenericArgs, ImportAlias, ModPath, Path, PathKind},
-    type_ref::{Mutability, TraitRef, TypeBound, TypeRef},
+    type_ref::{ConstRef, Mutability, TraitRef, TypeBound, TypeRef},
     visibility::RawVisibility,
     BlockId,
 };
@@ -946,6 +946,8 @@ impl From<AssocItem> for ModItem {
 pub struct Variant {
     pub name: Name,
     pub fields: Fields,
+    /// The explicit discriminant, e.g. `1` in `A = 1`.
+    pub discriminant: Option<ConstRef>,
 }
 
 /// A range of densely allocated ItemTree IDs.
@@ -310,7 +310,8 @@ impl<'a> Ctx<'a> {
     fn lower_variant(&mut self, variant: &ast::Variant) -> Option<Variant> {
         let name = variant.name()?.as_name();
         let fields = self.lower_fields(&variant.kind());
-        let res = Variant { name, fields };
+        let discriminant = variant.expr().map(|it| ConstRef::from_expr(&self.body_ctx, it));
+        let res = Variant { name, fields, discriminant };
         Some(res)
     }
 
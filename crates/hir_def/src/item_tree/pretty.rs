@@ -8,7 +8,7 @@ use crate::{
     attr::RawAttrs,
     generics::{WherePredicate, WherePredicateTypeTarget},
     path::GenericArg,
-    type_ref::TraitBoundModifier,
+    type_ref::{ConstRef, TraitBoundModifier},
     visibility::RawVisibility,
 };
 
@@ -306,10 +306,14 @@ impl<'a> Printer<'a> {
                 self.print_where_clause_and_opening_brace(generic_params);
                 self.indented(|this| {
                     for variant in variants.clone() {
-                        let Variant { name, fields } = &this.tree[variant];
+                        let Variant { name, fields, discriminant } = &this.tree[variant];
                         this.print_attrs_of(variant);
                         w!(this, "{}", name);
                         this.print_fields(fields);
+                        if let Some(discriminant) = discriminant {
+                            w!(this, " = ");
+                            this.print_const_ref(discriminant);
+                        }
                         wln!(this, ",");
                     }
                 });
@@ -496,7 +500,9 @@ impl<'a> Printer<'a> {
             TypeRef::Array(elem, len) => {
                 w!(self, "[");
                 self.print_type_ref(elem);
-                w!(self, "; {}]", len);
+                w!(self, "; ");
+                self.print_const_ref(len);
+                w!(self, "]");
             }
             TypeRef::Slice(elem) => {
                 w!(self, "[");
@@ -522,6 +528,10 @@ impl<'a> Printer<'a> {
                 w!(self, ") -> ");
                 self.print_type_ref(ret);
             }
+            TypeRef::ForLifetime(lifetimes, inner) => {
+                w!(self, "for<{}> ", lifetimes.iter().format(", "));
+                self.print_type_ref(inner);
+            }
             TypeRef::Macro(_ast_id) => {
                 w!(self, "<macro>");
             }
@@ -537,6 +547,32 @@ impl<'a> Printer<'a> {
         }
     }
 
+    fn print_const_ref(&mut self, const_ref: &ConstRef) {
+        match const_ref {
+            ConstRef::Scalar(scalar) => w!(self, "{}", scalar),
+            ConstRef::Path(path) => self.print_path(path),
+            ConstRef::UnaryOp(op, operand) => {
+                w!(self, "{}", op);
+                self.print_const_ref_operand(operand);
+            }
+            ConstRef::BinaryOp(lhs, op, rhs) => {
+                self.print_const_ref_operand(lhs);
+                w!(self, " {} ", op);
+                self.print_const_ref_operand(rhs);
+            }
+        }
+    }
+
+    fn print_const_ref_operand(&mut self, operand: &ConstRef) {
+        if let ConstRef::BinaryOp(..) = operand {
+            w!(self, "(");
+            self.print_const_ref(operand);
+            w!(self, ")");
+        } else {
+            self.print_const_ref(operand);
+        }
+    }
+
     fn print_type_bounds(&mut self, bounds: &[Interned<TypeBound>]) {
         for (i, bound) in bounds.iter().enumerate() {
             if i != 0 {
@@ -633,6 +669,7 @@ impl<'a> Printer<'a> {
         match arg {
             GenericArg::Type(ty) => self.print_type_ref(ty),
             GenericArg::Lifetime(lt) => w!(self, "{}", lt.name),
+            GenericArg::Const(c) => self.print_const_ref(c),
         }
     }
 
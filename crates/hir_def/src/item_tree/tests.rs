@@ -112,6 +112,12 @@ enum E {
         a: u8,
     }
 }
+
+enum Discriminants {
+    A = 1 << 2,
+    B,
+    C = -(BASE + 1),
+}
         "#,
         expect![[r##"
             pub(self) struct Unit;
@@ -144,6 +150,12 @@ enum E {
                     pub(self) a: u8,
                 },
             }
+
+            pub(self) enum Discriminants {
+                A = 1 << 2,
+                B,
+                C = -(BASE + 1),
+            }
         "##]],
     );
 }
@@ -283,6 +295,7 @@ struct S {
     b: <Fully as Qualified>::Syntax,
     c: <TypeAnchored>::Path::<'a>,
     d: dyn for<'a> Trait<'a>,
+    e: for<'a> fn(&'a u8) -> &'a u8,
 }
         "#,
         expect![[r#"
@@ -291,6 +304,7 @@ struct S {
                 pub(self) b: Qualified<Self=Fully>::Syntax,
                 pub(self) c: <TypeAnchored>::Path<'a>,
                 pub(self) d: dyn for<'a> Trait<'a>,
+                pub(self) e: for<'a> fn(&'a u8) -> &'a u8,
             }
         "#]],
     )
@@ -14,7 +14,7 @@ use hir_expand::{
 };
 use syntax::ast;
 
-use crate::type_ref::{TypeBound, TypeRef};
+use crate::type_ref::{ConstRef, TypeBound, TypeRef};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ModPath {
@@ -156,6 +156,7 @@ pub struct AssociatedTypeBinding {
 pub enum GenericArg {
     Type(TypeRef),
     Lifetime(LifetimeRef),
+    Const(ConstRef),
 }
 
 impl Path {
@@ -12,7 +12,7 @@ use super::AssociatedTypeBinding;
 use crate::{
     body::LowerCtx,
     path::{GenericArg, GenericArgs, ModPath, Path, PathKind},
-    type_ref::{LifetimeRef, TypeBound, TypeRef},
+    type_ref::{ConstRef, LifetimeRef, TypeBound, TypeRef},
 };
 
 pub(super) use lower_use::convert_path;
@@ -185,8 +185,10 @@ pub(super) fn lower_generic_args(
                     args.push(GenericArg::Lifetime(lifetime_ref))
                 }
             }
-            // constants are ignored for now.
-            ast::GenericArg::ConstArg(_) => (),
+            ast::GenericArg::ConstArg(const_arg) => {
+                let const_ref = ConstRef::from_expr_opt(lower_ctx, const_arg.expr());
+                args.push(GenericArg::Const(const_ref))
+            }
         }
     }
 
@@ -3,7 +3,7 @@
 
 use hir_expand::{name::Name, AstId, InFile};
 use std::convert::TryInto;
-use syntax::ast;
+use syntax::ast::{self, BinaryOp, UnaryOp};
 
 use crate::{body::LowerCtx, intern::Interned, path::Path};
 
@@ -84,13 +84,12 @@ pub enum TypeRef {
     Path(Path),
     RawPtr(Box<TypeRef>, Mutability),
     Reference(Box<TypeRef>, Option<LifetimeRef>, Mutability),
-    // FIXME: for full const generics, the latter element (length) here is going to have to be an
-    // expression that is further lowered later in hir_ty.
-    Array(Box<TypeRef>, ConstScalar),
+    Array(Box<TypeRef>, ConstRef),
     Slice(Box<TypeRef>),
     /// A fn pointer. Last element of the vector is the return type.
     Fn(Vec<TypeRef>, bool /*varargs*/),
-    // For
+    /// A type with a `for<'a>` binder, e.g. `for<'a> fn(&'a u8)`.
+    ForLifetime(Box<[Name]>, Box<TypeRef>),
     ImplTrait(Vec<Interned<TypeBound>>),
     DynTrait(Vec<Interned<TypeBound>>),
     Macro(AstId<ast::MacroCall>),
@@ -155,15 +154,7 @@ impl TypeRef {
                 TypeRef::RawPtr(Box::new(inner_ty), mutability)
             }
             ast::Type::ArrayType(inner) => {
-                // FIXME: This is a hack. We should probably reuse the machinery of
-                // `hir_def::body::lower` to lower this into an `Expr` and then evaluate it at the
-                // `hir_ty` level, which would allow knowing the type of:
-                // let v: [u8; 2 + 2] = [0u8; 4];
-                let len = inner
-                    .expr()
-                    .map(ConstScalar::usize_from_literal_expr)
-                    .unwrap_or(ConstScalar::Unknown);
-
+                let len = ConstRef::from_expr_opt(ctx, inner.expr());
                 TypeRef::Array(Box::new(TypeRef::from_ast_opt(ctx, inner.ty())), len)
             }
             ast::Type::SliceType(inner) => {
@@ -195,8 +186,15 @@ impl TypeRef {
                 params.push(ret_ty);
                 TypeRef::Fn(params, is_varargs)
             }
-            // for types are close enough for our purposes to the inner type for now...
-            ast::Type::ForType(inner) => TypeRef::from_ast_opt(ctx, inner.ty()),
+            ast::Type::ForType(inner) => {
+                let lifetimes = for_lifetimes(inner.generic_param_list());
+                let inner_ty = TypeRef::from_ast_opt(ctx, inner.ty());
+                if lifetimes.is_empty() {
+                    inner_ty
+                } else {
+                    TypeRef::ForLifetime(lifetimes, Box::new(inner_ty))
+                }
+            }
             ast::Type::ImplTraitType(inner) => {
                 TypeRef::ImplTrait(type_bounds_from_ast(ctx, inner.type_bound_list()))
             }
@@ -237,7 +235,8 @@ impl TypeRef {
                 TypeRef::RawPtr(type_ref, _)
                 | TypeRef::Reference(type_ref, ..)
                 | TypeRef::Array(type_ref, _)
-                | TypeRef::Slice(type_ref) => go(type_ref, f),
+                | TypeRef::Slice(type_ref)
+                | TypeRef::ForLifetime(_, type_ref) => go(type_ref, f),
                 TypeRef::ImplTrait(bounds) | TypeRef::DynTrait(bounds) => {
                     for bound in bounds {
                         match bound.as_ref() {
@@ -264,7 +263,8 @@ impl TypeRef {
                             crate::path::GenericArg::Type(type_ref) => {
                                 go(type_ref, f);
                             }
-                            crate::path::GenericArg::Lifetime(_) => {}
+                            crate::path::GenericArg::Lifetime(_)
+                            | crate::path::GenericArg::Const(_) => {}
                         }
                     }
                     for binding in &args_and_bindings.bindings {
@@ -297,6 +297,17 @@ pub(crate) fn type_bounds_from_ast(
     }
 }
 
+/// The lifetimes bound by a `for<...>` binder.
+pub(crate) fn for_lifetimes(generic_param_list: Option<ast::GenericParamList>) -> Box<[Name]> {
+    match generic_param_list {
+        Some(gpl) => gpl
+            .lifetime_params()
+            .flat_map(|lp| lp.lifetime().map(|lt| Name::new_lifetime(&lt)))
+            .collect(),
+        None => Box::default(),
+    }
+}
+
 impl TypeBound {
     pub(crate) fn from_ast(ctx: &LowerCtx, node: ast::TypeBound) -> Self {
         let lower_path_type = |path_type: ast::PathType| ctx.lower_path(path_type.path()?);
@@ -312,13 +323,7 @@ impl TypeBound {
                     .unwrap_or(TypeBound::Error)
             }
             ast::TypeBoundKind::ForType(for_type) => {
-                let lt_refs = match for_type.generic_param_list() {
-                    Some(gpl) => gpl
-                        .lifetime_params()
-                        .flat_map(|lp| lp.lifetime().map(|lt| Name::new_lifetime(&lt)))
-                        .collect(),
-                    None => Box::default(),
-                };
+                let lt_refs = for_lifetimes(for_type.generic_param_list());
                 let path = for_type.ty().and_then(|ty| match ty {
                     ast::Type::PathType(path_type) => lower_path_type(path_type),
                     _ => None,
@@ -376,20 +381,12 @@ impl ConstScalar {
         }
     }
 
-    // FIXME: as per the comments on `TypeRef::Array`, this evaluation should not happen at this
-    // parse stage.
-    fn usize_from_literal_expr(expr: ast::Expr) -> ConstScalar {
-        match expr {
-            ast::Expr::Literal(lit) => {
-                let lkind = lit.kind();
-                match lkind {
-                    ast::LiteralKind::IntNumber(num)
-                        if num.suffix() == None || num.suffix() == Some("usize") =>
-                    {
-                        num.value().and_then(|v| v.try_into().ok())
-                    }
-                    _ => None,
-                }
+    fn usize_from_literal(lit: ast::Literal) -> ConstScalar {
+        match lit.kind() {
+            ast::LiteralKind::IntNumber(num)
+                if num.suffix().is_none() || num.suffix() == Some("usize") =>
+            {
+                num.value().and_then(|v| v.try_into().ok())
             }
             _ => None,
         }
@@ -397,3 +394,53 @@ impl ConstScalar {
         .unwrap_or(ConstScalar::Unknown)
     }
 }
+
+/// A constant expression in a type, like the length of an array. Only a few
+/// kinds of expressions are kept, the paths in them are resolved and the
+/// result is evaluated in `hir_ty`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ConstRef {
+    Scalar(ConstScalar),
+    Path(Path),
+    UnaryOp(UnaryOp, Box<ConstRef>),
+    BinaryOp(Box<ConstRef>, BinaryOp, Box<ConstRef>),
+}
+
+impl ConstRef {
+    pub(crate) fn from_expr_opt(ctx: &LowerCtx, expr: Option<ast::Expr>) -> Self {
+        match expr {
+            Some(expr) => ConstRef::from_expr(ctx, expr),
+            None => ConstRef::Scalar(ConstScalar::Unknown),
+        }
+    }
+
+    pub(crate) fn from_expr(ctx: &LowerCtx, expr: ast::Expr) -> Self {
+        match expr {
+            ast::Expr::Literal(lit) => ConstRef::Scalar(ConstScalar::usize_from_literal(lit)),
+            ast::Expr::PathExpr(it) => match it.path().and_then(|it| ctx.lower_path(it)) {
+                Some(path) => ConstRef::Path(path),
+                None => ConstRef::Scalar(ConstScalar::Unknown),
+            },
+            ast::Expr::ParenExpr(it) => ConstRef::from_expr_opt(ctx, it.expr()),
+            // `{ N + 1 }`, as required for expressions in generic arguments.
+            ast::Expr::BlockExpr(it) if it.statements().next().is_none() => {
+                ConstRef::from_expr_opt(ctx, it.tail_expr())
+            }
+            ast::Expr::PrefixExpr(it) => match it.op_kind() {
+                Some(op) => {
+                    ConstRef::UnaryOp(op, Box::new(ConstRef::from_expr_opt(ctx, it.expr())))
+                }
+                None => ConstRef::Scalar(ConstScalar::Unknown),
+            },
+            ast::Expr::BinExpr(it) => match it.op_kind() {
+                Some(op) => ConstRef::BinaryOp(
+                    Box::new(ConstRef::from_expr_opt(ctx, it.lhs())),
+                    op,
+                    Box::new(ConstRef::from_expr_opt(ctx, it.rhs())),
+                ),
+                None => ConstRef::Scalar(ConstScalar::Unknown),
+            },
+            _ => ConstRef::Scalar(ConstScalar::Unknown),
+        }
+    }
+}
@@ -208,6 +208,7 @@ pub mod known {
         iter_mut,
         len,
         is_empty,
+        to_string,
         // Builtin macros
         file,
         column,
@@ -1,14 +1,31 @@
 //! Constant evaluation details
 
-use std::convert::TryInto;
+#[cfg(test)]
+mod tests;
+
+use std::{convert::TryFrom, fmt};
 
 use hir_def::{
-    builtin_type::BuiltinUint,
-    expr::{Expr, Literal},
-    type_ref::ConstScalar,
+    body::Body,
+    expr::{
+        ArithOp, BinaryOp, CmpOp, Expr, ExprId, Literal, LogicOp, Ordering, Pat, PatId, Statement,
+        UnaryOp,
+    },
+    path::{GenericArg, GenericArgs, Path},
+    resolver::HasResolver,
+    resolver::{resolver_for_expr, Resolver, ValueNs},
+    type_ref::{ConstRef, ConstScalar, TypeRef},
+    AssocContainerId, AssocItemId, ConstId, DefWithBodyId, EnumVariantId, HasModule, Lookup,
 };
+use rustc_hash::FxHashMap;
 
-use crate::{Const, ConstData, ConstValue, Interner, TyKind};
+use crate::{
+    db::HirDatabase,
+    layout::TargetDataLayout,
+    method_resolution::TyFingerprint,
+    primitive::{IntTy, UintTy},
+    Const, ConstData, ConstValue, InferenceResult, Interner, Scalar, Ty, TyKind, TyLoweringContext,
+};
 
 /// Extension trait for [`Const`]
 pub trait ConstExt {
@@ -35,11 +52,165 @@ impl ConstExt for Const {
     }
 }
 
-// FIXME: support more than just evaluating literals
-pub fn eval_usize(expr: &Expr) -> Option<u64> {
-    match expr {
-        Expr::Literal(Literal::Uint(v, None | Some(BuiltinUint::Usize))) => (*v).try_into().ok(),
-        _ => None,
+/// The value of a constant expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputedExpr {
+    /// An integer which fits in an `i128`.
+    Int(i128),
+    /// A `u128` which is too large for `Int`.
+    UInt(u128),
+    Bool(bool),
+    Char(char),
+    Str(String),
+}
+
+impl ComputedExpr {
+    /// Gets a target usize out of the value, if it is one.
+    pub fn as_usize(&self) -> Option<u64> {
+        match *self {
+            ComputedExpr::Int(it) => u64::try_from(it).ok(),
+            ComputedExpr::UInt(it) => u64::try_from(it).ok(),
+            _ => None,
+        }
+    }
+
+    fn from_u128(value: u128) -> ComputedExpr {
+        match i128::try_from(value) {
+            Ok(it) => ComputedExpr::Int(it),
+            Err(_) => ComputedExpr::UInt(value),
+        }
+    }
+
+    /// The two's complement bits of an integer.
+    fn bits(&self) -> Option<u128> {
+        match *self {
+            ComputedExpr::Int(it) => Some(it as u128),
+            ComputedExpr::UInt(it) => Some(it),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ComputedExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputedExpr::Int(it) => fmt::Display::fmt(it, f),
+            ComputedExpr::UInt(it) => fmt::Display::fmt(it, f),
+            ComputedExpr::Bool(it) => fmt::Display::fmt(it, f),
+            ComputedExpr::Char(it) => fmt::Debug::fmt(it, f),
+            ComputedExpr::Str(it) => fmt::Debug::fmt(it, f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstEvalError {
+    /// The expression uses something the evaluator doesn't handle (yet).
+    NotSupported(&'static str),
+    /// The expression has missing parts or doesn't type check.
+    IncompleteExpr,
+    /// Evaluating the expression panics, e.g. because of an overflow.
+    Panic(String),
+    /// The constant depends on its own value.
+    Loop,
+}
+
+pub(crate) fn const_eval_query(
+    db: &dyn HirDatabase,
+    def: ConstId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let body = db.body(def.into());
+    let infer = db.infer(def.into());
+    eval_const(db, def.into(), &body, &infer, body.body_expr)
+}
+
+pub(crate) fn const_eval_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &ConstId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    Err(ConstEvalError::Loop)
+}
+
+/// The discriminant of an enum variant, either explicit or one more than the
+/// one of the previous variant.
+pub fn eval_discriminant(
+    db: &dyn HirDatabase,
+    variant: EnumVariantId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let enum_data = db.enum_data(variant.parent);
+    let variants = enum_data.variants.iter().map(|(_, data)| data);
+    let position = enum_data
+        .variants
+        .iter()
+        .position(|(id, _)| id == variant.local_id)
+        .ok_or(ConstEvalError::IncompleteExpr)?;
+    let explicit = variants
+        .take(position + 1)
+        .enumerate()
+        .rev()
+        .find_map(|(idx, data)| Some((idx, data.discriminant.as_ref()?)));
+    let (base, offset) = match explicit {
+        Some((idx, discriminant)) => {
+            let resolver = variant.parent.resolver(db.upcast());
+            (eval_const_ref(db, &resolver, discriminant)?, position - idx)
+        }
+        None => (ComputedExpr::Int(0), position),
+    };
+    match base {
+        ComputedExpr::Int(it) => int(it.checked_add(offset as i128), None, "add"),
+        ComputedExpr::UInt(it) => match it.checked_add(offset as u128) {
+            Some(it) => Ok(ComputedExpr::UInt(it)),
+            None => Err(ConstEvalError::Panic("attempt to add with overflow".to_string())),
+        },
+        _ => Err(ConstEvalError::IncompleteExpr),
+    }
+}
+
+/// Evaluates `expr`, an expression in the body of `owner`. `infer` provides
+/// the types of the expressions and the resolutions of associated items; it
+/// may be incomplete when this is called during inference.
+pub fn eval_const(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    expr: ExprId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let krate = owner.module(db.upcast()).krate();
+    let pointer_bits = TargetDataLayout::for_crate(db, krate).pointer_size as u32 * 8;
+    ConstEvalCtx { db, owner, body, infer, pointer_bits, locals: FxHashMap::default() }.eval(expr)
+}
+
+/// Evaluates a constant expression in a type, resolving the paths in it with
+/// `resolver`. The types of the expressions aren't known, so integers are
+/// never considered to overflow.
+pub(crate) fn eval_const_ref(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    const_ref: &ConstRef,
+) -> Result<ComputedExpr, ConstEvalError> {
+    match const_ref {
+        ConstRef::Scalar(scalar) => match scalar.as_usize() {
+            Some(it) => Ok(ComputedExpr::Int(it.into())),
+            None => Err(ConstEvalError::IncompleteExpr),
+        },
+        ConstRef::Path(path) => {
+            match resolver.resolve_path_in_value_ns_fully(db.upcast(), path.mod_path()) {
+                Some(ValueNs::ConstId(it)) => db.const_eval(it),
+                _ => Err(ConstEvalError::NotSupported("path")),
+            }
+        }
+        ConstRef::UnaryOp(op, operand) => {
+            unary_op(*op, eval_const_ref(db, resolver, operand)?, None)
+        }
+        ConstRef::BinaryOp(lhs, op, rhs) => {
+            let lhs = eval_const_ref(db, resolver, lhs)?;
+            match short_circuit(*op, &lhs) {
+                Some(it) => Ok(it),
+                None => binary_op(lhs, *op, eval_const_ref(db, resolver, rhs)?, None),
+            }
+        }
     }
 }
 
@@ -53,3 +224,432 @@ pub fn usize_const(value: Option<u64>) -> Const {
     }
     .intern(&Interner)
 }
+
+struct ConstEvalCtx<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    /// The width of `usize` and `isize` on the target.
+    pointer_bits: u32,
+    /// The values of the `let` bindings in scope.
+    locals: FxHashMap<PatId, ComputedExpr>,
+}
+
+impl ConstEvalCtx<'_> {
+    fn eval(&mut self, expr: ExprId) -> Result<ComputedExpr, ConstEvalError> {
+        match &self.body.exprs[expr] {
+            Expr::Missing => Err(ConstEvalError::IncompleteExpr),
+            Expr::Literal(literal) => match *literal {
+                Literal::Int(it, _) => Ok(ComputedExpr::Int(it)),
+                Literal::Uint(it, _) => Ok(ComputedExpr::from_u128(it)),
+                Literal::Bool(it) => Ok(ComputedExpr::Bool(it)),
+                Literal::Char(it) => Ok(ComputedExpr::Char(it)),
+                Literal::String(ref it) => Ok(ComputedExpr::Str(it.clone())),
+                Literal::ByteString(_) | Literal::Float(..) => {
+                    Err(ConstEvalError::NotSupported("literal"))
+                }
+            },
+            Expr::Path(path) => {
+                if let Some(AssocItemId::ConstId(it)) = self.infer.assoc_resolutions_for_expr(expr)
+                {
+                    let it = self.impl_const(it, path, expr).unwrap_or(it);
+                    return self.db.const_eval(it);
+                }
+                let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+                match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path()) {
+                    // Don't query the value of the constant being inferred.
+                    Some(ValueNs::ConstId(it)) if self.owner == it.into() => {
+                        Err(ConstEvalError::Loop)
+                    }
+                    Some(ValueNs::ConstId(it)) => self.db.const_eval(it),
+                    Some(ValueNs::LocalBinding(pat)) => {
+                        self.locals.get(&pat).cloned().ok_or(ConstEvalError::IncompleteExpr)
+                    }
+                    Some(_) => Err(ConstEvalError::NotSupported("path")),
+                    None => Err(ConstEvalError::IncompleteExpr),
+                }
+            }
+            &Expr::UnaryOp { expr: operand, op } => {
+                let operand = self.eval(operand)?;
+                unary_op(op, operand, self.int_ty(expr))
+            }
+            &Expr::BinaryOp { lhs, rhs, op } => {
+                let op = op.ok_or(ConstEvalError::IncompleteExpr)?;
+                let ty = match op {
+                    BinaryOp::ArithOp(ArithOp::Shl | ArithOp::Shr) => self.int_ty(lhs),
+                    _ => {
+                        self.int_ty(expr).or_else(|| self.int_ty(lhs)).or_else(|| self.int_ty(rhs))
+                    }
+                };
+                let lhs = self.eval(lhs)?;
+                match short_circuit(op, &lhs) {
+                    Some(it) => Ok(it),
+                    None => {
+                        let rhs = self.eval(rhs)?;
+                        binary_op(lhs, op, rhs, ty)
+                    }
+                }
+            }
+            &Expr::Cast { expr: operand, .. } => {
+                let value = self.eval(operand)?;
+                let target = self.infer.type_of_expr.get(expr).map(|it| it.kind(&Interner));
+                match (value, target) {
+                    (ComputedExpr::Int(it), Some(TyKind::Scalar(Scalar::Char))) => {
+                        // Only `u8` can be cast to `char`.
+                        match u8::try_from(it) {
+                            Ok(it) => Ok(ComputedExpr::Char(it.into())),
+                            Err(_) => Err(ConstEvalError::IncompleteExpr),
+                        }
+                    }
+                    (value, Some(TyKind::Scalar(Scalar::Int(_) | Scalar::Uint(_)))) => {
+                        let value = match value {
+                            ComputedExpr::Bool(it) => ComputedExpr::Int(it.into()),
+                            ComputedExpr::Char(it) => ComputedExpr::Int(u32::from(it).into()),
+                            ComputedExpr::Str(_) => return Err(ConstEvalError::IncompleteExpr),
+                            it => it,
+                        };
+                        Ok(match (self.int_ty(expr), value.bits()) {
+                            (Some(ty), Some(bits)) => ty.wrap(bits),
+                            _ => value,
+                        })
+                    }
+                    (value @ ComputedExpr::Bool(_), Some(TyKind::Scalar(Scalar::Bool)))
+                    | (value @ ComputedExpr::Char(_), Some(TyKind::Scalar(Scalar::Char))) => {
+                        Ok(value)
+                    }
+                    _ => Err(ConstEvalError::NotSupported("cast")),
+                }
+            }
+            &Expr::If { condition, then_branch, else_branch } => {
+                match (self.eval(condition)?, else_branch) {
+                    (ComputedExpr::Bool(true), _) => self.eval(then_branch),
+                    (ComputedExpr::Bool(false), Some(else_branch)) => self.eval(else_branch),
+                    (ComputedExpr::Bool(false), None) => {
+                        Err(ConstEvalError::NotSupported("unit value"))
+                    }
+                    _ => Err(ConstEvalError::IncompleteExpr),
+                }
+            }
+            Expr::Block { statements, tail, .. } => {
+                for statement in statements.iter() {
+                    match statement {
+                        &Statement::Let { pat, initializer, .. } => {
+                            let pat = self.binding(pat)?;
+                            let initializer = initializer.ok_or(ConstEvalError::IncompleteExpr)?;
+                            let value = self.eval(initializer)?;
+                            self.locals.insert(pat, value);
+                        }
+                        Statement::Expr { .. } => {
+                            return Err(ConstEvalError::NotSupported("expression statement"))
+                        }
+                    }
+                }
+                match tail {
+                    Some(tail) => self.eval(*tail),
+                    None => Err(ConstEvalError::NotSupported("unit value")),
+                }
+            }
+            Expr::Const { body } => self.eval(*body),
+            _ => Err(ConstEvalError::NotSupported("expression")),
+        }
+    }
+
+    /// Paths to the associated constants of traits resolve to the constant's
+    /// declaration in the trait, this finds the constant of the impl `path`
+    /// refers to.
+    fn impl_const(&self, konst: ConstId, path: &Path, expr: ExprId) -> Option<ConstId> {
+        let trait_ = match konst.lookup(self.db.upcast()).container {
+            AssocContainerId::TraitId(it) => it,
+            _ => return None,
+        };
+        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+        let ctx = TyLoweringContext::new(self.db, &resolver);
+        let qualifier = path.qualifier()?;
+        let self_ty = match qualifier.segments().last()?.args_and_bindings {
+            // `<S as Trait>::C`
+            Some(GenericArgs { has_self_type: true, args, .. }) => match args.first()? {
+                GenericArg::Type(it) => ctx.lower_ty(it),
+                GenericArg::Lifetime(_) | GenericArg::Const(_) => return None,
+            },
+            _ => ctx.lower_ty(&TypeRef::Path(qualifier)),
+        };
+        let fingerprint = TyFingerprint::for_trait_impl(&self_ty)?;
+        let name = self.db.const_data(konst).name.clone()?;
+        let impls = self.db.trait_impls_in_deps(resolver.krate()?);
+        let res = impls
+            .for_trait_and_self_ty(trait_, fingerprint)
+            .filter(|&impl_| self.db.impl_self_ty(impl_).skip_binders() == &self_ty)
+            .find_map(|impl_| {
+                self.db.impl_data(impl_).items.iter().find_map(|&item| match item {
+                    AssocItemId::ConstId(it)
+                        if self.db.const_data(it).name.as_ref() == Some(&name) =>
+                    {
+                        Some(it)
+                    }
+                    _ => None,
+                })
+            });
+        res
+    }
+
+    /// The local defined by `pat`, if it's a simple binding.
+    fn binding(&self, pat: PatId) -> Result<PatId, ConstEvalError> {
+        match &self.body.pats[pat] {
+            Pat::Bind { subpat: None, .. } => Ok(pat),
+            _ => Err(ConstEvalError::NotSupported("pattern")),
+        }
+    }
+
+    fn int_ty(&self, expr: ExprId) -> Option<IntType> {
+        IntType::from_ty(self.infer.type_of_expr.get(expr)?, self.pointer_bits)
+    }
+}
+
+/// The value of `op` applied to `lhs` if it doesn't depend on the right-hand
+/// side, which then isn't evaluated.
+fn short_circuit(op: BinaryOp, lhs: &ComputedExpr) -> Option<ComputedExpr> {
+    match (op, lhs) {
+        (BinaryOp::LogicOp(LogicOp::And), ComputedExpr::Bool(false))
+        | (BinaryOp::LogicOp(LogicOp::Or), ComputedExpr::Bool(true)) => Some(lhs.clone()),
+        _ => None,
+    }
+}
+
+fn unary_op(
+    op: UnaryOp,
+    operand: ComputedExpr,
+    ty: Option<IntType>,
+) -> Result<ComputedExpr, ConstEvalError> {
+    match (op, operand) {
+        (UnaryOp::Not, ComputedExpr::Bool(it)) => Ok(ComputedExpr::Bool(!it)),
+        (UnaryOp::Not, it @ (ComputedExpr::Int(_) | ComputedExpr::UInt(_))) => {
+            let bits = !it.bits().unwrap_or_default();
+            Ok(match ty {
+                Some(ty) => ty.wrap(bits),
+                None => match it {
+                    ComputedExpr::Int(it) => ComputedExpr::Int(!it),
+                    _ => ComputedExpr::from_u128(bits),
+                },
+            })
+        }
+        (UnaryOp::Neg, ComputedExpr::Int(it)) => int(it.checked_neg(), ty, "negate"),
+        (UnaryOp::Neg, ComputedExpr::UInt(_)) => {
+            Err(ConstEvalError::Panic("attempt to negate with overflow".to_string()))
+        }
+        (UnaryOp::Deref, _) => Err(ConstEvalError::NotSupported("dereference")),
+        _ => Err(ConstEvalError::IncompleteExpr),
+    }
+}
+
+fn binary_op(
+    lhs: ComputedExpr,
+    op: BinaryOp,
+    rhs: ComputedExpr,
+    ty: Option<IntType>,
+) -> Result<ComputedExpr, ConstEvalError> {
+    match (lhs, op, rhs) {
+        (
+            lhs @ (ComputedExpr::Int(_) | ComputedExpr::UInt(_)),
+            BinaryOp::ArithOp(op),
+            rhs @ (ComputedExpr::Int(_) | ComputedExpr::UInt(_)),
+        ) => {
+            let is_u128 = match ty {
+                Some(ty) => ty.bits == 128 && !ty.signed,
+                None => {
+                    matches!(lhs, ComputedExpr::UInt(_)) || matches!(rhs, ComputedExpr::UInt(_))
+                }
+            };
+            if is_u128 {
+                return u128_op(lhs, op, rhs);
+            }
+            let (lhs, rhs) = match (lhs, rhs) {
+                (ComputedExpr::Int(lhs), ComputedExpr::Int(rhs)) => (lhs, rhs),
+                _ => return Err(ConstEvalError::IncompleteExpr),
+            };
+            match op {
+                ArithOp::Add => int(lhs.checked_add(rhs), ty, "add"),
+                ArithOp::Sub => int(lhs.checked_sub(rhs), ty, "subtract"),
+                ArithOp::Mul => int(lhs.checked_mul(rhs), ty, "multiply"),
+                ArithOp::Div | ArithOp::Rem if rhs == 0 => {
+                    let what =
+                        if op == ArithOp::Div { "divide" } else { "calculate the remainder" };
+                    Err(ConstEvalError::Panic(format!(
+                        "attempt to {} with a divisor of zero",
+                        what
+                    )))
+                }
+                ArithOp::Div => int(lhs.checked_div(rhs), ty, "divide"),
+                ArithOp::Rem => int(lhs.checked_rem(rhs), ty, "calculate the remainder"),
+                ArithOp::Shl | ArithOp::Shr => {
+                    let bits = ty.map_or(128, |ty| ty.bits);
+                    let shift = match u32::try_from(rhs) {
+                        Ok(it) if it < bits => it,
+                        _ => {
+                            let what = if op == ArithOp::Shl { "left" } else { "right" };
+                            let msg = format!("attempt to shift {} with overflow", what);
+                            return Err(ConstEvalError::Panic(msg));
+                        }
+                    };
+                    // Bits shifted out are lost, that's not an overflow.
+                    let value = if op == ArithOp::Shl { lhs << shift } else { lhs >> shift };
+                    Ok(match ty {
+                        Some(ty) => ty.wrap(value as u128),
+                        None => ComputedExpr::Int(value),
+                    })
+                }
+                ArithOp::BitAnd => Ok(ComputedExpr::Int(lhs & rhs)),
+                ArithOp::BitOr => Ok(ComputedExpr::Int(lhs | rhs)),
+                ArithOp::BitXor => Ok(ComputedExpr::Int(lhs ^ rhs)),
+            }
+        }
+        (ComputedExpr::Bool(lhs), BinaryOp::ArithOp(op), ComputedExpr::Bool(rhs)) => match op {
+            ArithOp::BitAnd => Ok(ComputedExpr::Bool(lhs & rhs)),
+            ArithOp::BitOr => Ok(ComputedExpr::Bool(lhs | rhs)),
+            ArithOp::BitXor => Ok(ComputedExpr::Bool(lhs ^ rhs)),
+            _ => Err(ConstEvalError::IncompleteExpr),
+        },
+        (ComputedExpr::Bool(_), BinaryOp::LogicOp(_), rhs @ ComputedExpr::Bool(_)) => Ok(rhs),
+        (lhs, BinaryOp::CmpOp(op), rhs) => {
+            let ordering = match (lhs, rhs) {
+                (ComputedExpr::Int(lhs), ComputedExpr::Int(rhs)) => lhs.cmp(&rhs),
+                (ComputedExpr::UInt(lhs), ComputedExpr::UInt(rhs)) => lhs.cmp(&rhs),
+                // A `UInt` is larger than any `Int`.
+                (ComputedExpr::Int(_), ComputedExpr::UInt(_)) => std::cmp::Ordering::Less,
+                (ComputedExpr::UInt(_), ComputedExpr::Int(_)) => std::cmp::Ordering::Greater,
+                (ComputedExpr::Bool(lhs), ComputedExpr::Bool(rhs)) => lhs.cmp(&rhs),
+                (ComputedExpr::Char(lhs), ComputedExpr::Char(rhs)) => lhs.cmp(&rhs),
+                _ => return Err(ConstEvalError::IncompleteExpr),
+            };
+            let res = match op {
+                CmpOp::Eq { negated } => ordering.is_eq() != negated,
+                CmpOp::Ord { ordering: Ordering::Less, strict: true } => ordering.is_lt(),
+                CmpOp::Ord { ordering: Ordering::Less, strict: false } => ordering.is_le(),
+                CmpOp::Ord { ordering: Ordering::Greater, strict: true } => ordering.is_gt(),
+                CmpOp::Ord { ordering: Ordering::Greater, strict: false } => ordering.is_ge(),
+            };
+            Ok(ComputedExpr::Bool(res))
+        }
+        (_, BinaryOp::Assignment { .. }, _) => Err(ConstEvalError::NotSupported("assignment")),
+        _ => Err(ConstEvalError::IncompleteExpr),
+    }
+}
+
+/// An arithmetic operation on `u128`s, whose values don't all fit in an
+/// `i128`.
+fn u128_op(
+    lhs: ComputedExpr,
+    op: ArithOp,
+    rhs: ComputedExpr,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let (lhs, rhs) = match (lhs, rhs) {
+        (ComputedExpr::Int(lhs), ComputedExpr::Int(rhs)) if lhs >= 0 && rhs >= 0 => {
+            (lhs as u128, rhs as u128)
+        }
+        (ComputedExpr::Int(lhs), ComputedExpr::UInt(rhs)) if lhs >= 0 => (lhs as u128, rhs),
+        (ComputedExpr::UInt(lhs), ComputedExpr::Int(rhs)) if rhs >= 0 => (lhs, rhs as u128),
+        (ComputedExpr::UInt(lhs), ComputedExpr::UInt(rhs)) => (lhs, rhs),
+        _ => return Err(ConstEvalError::IncompleteExpr),
+    };
+    let overflow = |what: &str| ConstEvalError::Panic(format!("attempt to {} with overflow", what));
+    let value = match op {
+        ArithOp::Add => lhs.checked_add(rhs).ok_or_else(|| overflow("add"))?,
+        ArithOp::Sub => lhs.checked_sub(rhs).ok_or_else(|| overflow("subtract"))?,
+        ArithOp::Mul => lhs.checked_mul(rhs).ok_or_else(|| overflow("multiply"))?,
+        ArithOp::Div | ArithOp::Rem if rhs == 0 => {
+            let what = if op == ArithOp::Div { "divide" } else { "calculate the remainder" };
+            return Err(ConstEvalError::Panic(format!(
+                "attempt to {} with a divisor of zero",
+                what
+            )));
+        }
+        ArithOp::Div => lhs / rhs,
+        ArithOp::Rem => lhs % rhs,
+        ArithOp::Shl | ArithOp::Shr => match u32::try_from(rhs) {
+            Ok(shift) if shift < 128 => {
+                if op == ArithOp::Shl {
+                    lhs << shift
+                } else {
+                    lhs >> shift
+                }
+            }
+            _ => {
+                return Err(overflow(if op == ArithOp::Shl { "shift left" } else { "shift right" }))
+            }
+        },
+        ArithOp::BitAnd => lhs & rhs,
+        ArithOp::BitOr => lhs | rhs,
+        ArithOp::BitXor => lhs ^ rhs,
+    };
+    Ok(ComputedExpr::from_u128(value))
+}
+
+/// The result of an integer operation, which overflows if it doesn't fit in
+/// `ty`.
+fn int(
+    value: Option<i128>,
+    ty: Option<IntType>,
+    what: &str,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let in_range = |it: i128| match ty {
+        Some(ty) => ty.contains(it),
+        None => true,
+    };
+    match value {
+        Some(it) if in_range(it) => Ok(ComputedExpr::Int(it)),
+        _ => Err(ConstEvalError::Panic(format!("attempt to {} with overflow", what))),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IntType {
+    bits: u32,
+    signed: bool,
+}
+
+impl IntType {
+    /// The integer type `ty`, with `usize` and `isize` being `pointer_bits`
+    /// wide.
+    fn from_ty(ty: &Ty, pointer_bits: u32) -> Option<IntType> {
+        let (bits, signed) = match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Int(it)) => match it {
+                IntTy::I8 => (8, true),
+                IntTy::I16 => (16, true),
+                IntTy::I32 => (32, true),
+                IntTy::I64 => (64, true),
+                IntTy::I128 => (128, true),
+                IntTy::Isize => (pointer_bits, true),
+            },
+            TyKind::Scalar(Scalar::Uint(it)) => match it {
+                UintTy::U8 => (8, false),
+                UintTy::U16 => (16, false),
+                UintTy::U32 => (32, false),
+                UintTy::U64 => (64, false),
+                UintTy::U128 => (128, false),
+                UintTy::Usize => (pointer_bits, false),
+            },
+            _ => return None,
+        };
+        Some(IntType { bits, signed })
+    }
+
+    fn contains(self, value: i128) -> bool {
+        match (self.signed, self.bits) {
+            (true, 128) => true,
+            (false, 128) => value >= 0,
+            (true, bits) => -(1 << (bits - 1)) <= value && value < (1 << (bits - 1)),
+            (false, bits) => 0 <= value && value < (1 << bits),
+        }
+    }
+
+    /// The value of this type with the lowest bits of `bits`, like an `as`
+    /// cast gives.
+    fn wrap(self, bits: u128) -> ComputedExpr {
+        let shift = 128 - self.bits;
+        if self.signed {
+            ComputedExpr::Int((bits << shift) as i128 >> shift)
+        } else {
+            ComputedExpr::from_u128(bits << shift >> shift)
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, AdtId, EnumVariantId, ModuleDefId};
+
+use crate::{db::HirDatabase, test_db::TestDB};
+
+use super::{eval_discriminant, ComputedExpr, ConstEvalError};
+
+fn eval_goal(ra_fixture: &str) -> Result<ComputedExpr, ConstEvalError> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let const_id = scope
+        .declarations()
+        .find_map(|it| match it {
+            ModuleDefId::ConstId(it) => {
+                let name = db.const_data(it).name.clone()?;
+                if name.to_string() == "GOAL" {
+                    Some(it)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .expect("no `GOAL` const in the fixture");
+    db.const_eval(const_id)
+}
+
+/// The discriminants of the variants of the first enum in the fixture.
+fn eval_discriminants(ra_fixture: &str) -> Vec<Result<ComputedExpr, ConstEvalError>> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let enum_id = scope
+        .declarations()
+        .find_map(|it| match it {
+            ModuleDefId::AdtId(AdtId::EnumId(it)) => Some(it),
+            _ => None,
+        })
+        .expect("no enum in the fixture");
+    db.enum_data(enum_id)
+        .variants
+        .iter()
+        .map(|(local_id, _)| eval_discriminant(&db, EnumVariantId { parent: enum_id, local_id }))
+        .collect()
+}
+
+fn check_number(ra_fixture: &str, answer: i128) {
+    assert_eq!(eval_goal(ra_fixture), Ok(ComputedExpr::Int(answer)));
+}
+
+fn check_bool(ra_fixture: &str, answer: bool) {
+    assert_eq!(eval_goal(ra_fixture), Ok(ComputedExpr::Bool(answer)));
+}
+
+fn check_panic(ra_fixture: &str, message: &str) {
+    assert_eq!(eval_goal(ra_fixture), Err(ConstEvalError::Panic(message.to_string())));
+}
+
+#[test]
+fn literals() {
+    check_number("const GOAL: usize = 2;", 2);
+    check_number("const GOAL: i8 = -3;", -3);
+    check_bool("const GOAL: bool = true;", true);
+    assert_eq!(eval_goal("const GOAL: char = 'x';"), Ok(ComputedExpr::Char('x')));
+    assert_eq!(
+        eval_goal(r#"const GOAL: &str = "hello\n";"#),
+        Ok(ComputedExpr::Str("hello\n".to_string()))
+    );
+}
+
+#[test]
+fn arithmetic() {
+    check_number("const GOAL: usize = 2 + 3 * 4 - 1;", 13);
+    check_number("const GOAL: i32 = 7 / 2 + -7 % 3;", 2);
+    check_number("const GOAL: u32 = 1 << 3 | 0b100;", 12);
+    check_number("const GOAL: u8 = 0xF0 & 0x3C ^ 0x01;", 0x31);
+    check_number("const GOAL: u8 = !1;", 254);
+    check_number("const GOAL: i8 = !1;", -2);
+    check_number("const GOAL: u8 = 3 << 7;", 128);
+}
+
+#[test]
+fn comparisons_and_logic() {
+    check_bool("const GOAL: bool = 1 + 1 == 2 && 'a' < 'b';", true);
+    check_bool("const GOAL: bool = 2 <= 1 || !(3 != 3);", true);
+    check_bool("const GOAL: bool = false & true;", false);
+}
+
+#[test]
+fn overflow_and_division_by_zero() {
+    check_panic("const GOAL: u8 = 255 + 1;", "attempt to add with overflow");
+    check_panic("const GOAL: usize = 0 - 1;", "attempt to subtract with overflow");
+    check_panic("const GOAL: i8 = -(-128);", "attempt to negate with overflow");
+    check_panic("const GOAL: i32 = 1 / 0;", "attempt to divide with a divisor of zero");
+    check_panic("const GOAL: u32 = 1 << 32;", "attempt to shift left with overflow");
+    check_number("const GOAL: i128 = 1 << 100;", 1 << 100);
+}
+
+#[test]
+fn large_u128() {
+    let check_u128 = |ra_fixture: &str, answer: u128| {
+        assert_eq!(eval_goal(ra_fixture), Ok(ComputedExpr::UInt(answer)));
+    };
+    check_u128(
+        r#"
+#[lang = "u128"]
+impl u128 {
+    pub const MAX: u128 = !0;
+}
+const GOAL: u128 = u128::MAX;
+"#,
+        u128::MAX,
+    );
+    check_u128("const GOAL: u128 = !0u128;", u128::MAX);
+    check_u128("const GOAL: u128 = 1u128 << 127;", 1 << 127);
+    check_u128("const GOAL: u128 = 340282366920938463463374607431768211455 - 1;", u128::MAX - 1);
+    check_number("const GOAL: u128 = (1u128 << 127) >> 126;", 2);
+    check_number("const GOAL: i128 = !0;", -1);
+    check_bool("const GOAL: bool = 1u128 << 127 > 1;", true);
+    check_panic("const GOAL: u128 = !0u128 + 1;", "attempt to add with overflow");
+    check_panic("const GOAL: u128 = 0 - 1;", "attempt to subtract with overflow");
+}
+
+#[test]
+fn pointer_width() {
+    check_number("const GOAL: usize = !0;", u64::MAX.into());
+    check_number(
+        "//- /main.rs cfg:target_pointer_width=32\nconst GOAL: usize = !0;",
+        u32::MAX.into(),
+    );
+    check_number("//- /main.rs cfg:target_pointer_width=32\nconst GOAL: isize = -1 as isize;", -1);
+    check_panic(
+        "//- /main.rs cfg:target_pointer_width=32\nconst GOAL: usize = 1 << 32;",
+        "attempt to shift left with overflow",
+    );
+}
+
+#[test]
+fn casts() {
+    check_number("const GOAL: u8 = 300i32 as u8;", 44);
+    check_number("const GOAL: i8 = 200u8 as i8;", -56);
+    check_number("const GOAL: u32 = 'a' as u32 + true as u32;", 98);
+    assert_eq!(eval_goal("const GOAL: char = 98u8 as char;"), Ok(ComputedExpr::Char('b')));
+}
+
+#[test]
+fn blocks_and_ifs() {
+    check_number("const GOAL: usize = { 1 << 3 };", 8);
+    check_number(
+        r#"
+const GOAL: usize = {
+    let a = 3;
+    let b = a * 2;
+    if b > 5 { a + b } else { 0 }
+};
+"#,
+        9,
+    );
+}
+
+#[test]
+fn other_consts() {
+    check_number(
+        r#"
+const SIZE: usize = 4 + 4;
+const GOAL: usize = SIZE * 2;
+"#,
+        16,
+    );
+    check_number(
+        r#"
+mod m {
+    pub const A: i32 = -1;
+}
+const GOAL: i32 = m::A - { const B: i32 = 2; B };
+"#,
+        -3,
+    );
+}
+
+#[test]
+fn associated_consts() {
+    check_number(
+        r#"
+struct S;
+impl S {
+    const A: u32 = 2;
+    const B: u32 = Self::A * 3;
+}
+trait Tr {
+    const C: u32;
+}
+impl Tr for S {
+    const C: u32 = 5;
+}
+const GOAL: u32 = S::B + <S as Tr>::C;
+"#,
+        11,
+    );
+}
+
+#[test]
+fn cycles() {
+    assert_eq!(
+        eval_goal(
+            r#"
+const A: usize = GOAL + 1;
+const GOAL: usize = A;
+"#
+        ),
+        Err(ConstEvalError::Loop)
+    );
+    assert_eq!(
+        eval_goal(
+            r#"
+const GOAL: usize = {
+    let a = [0u8; GOAL];
+    1
+};
+"#
+        ),
+        Err(ConstEvalError::NotSupported("expression"))
+    );
+    assert_eq!(
+        eval_goal(
+            r#"
+const A: usize = {
+    let a = [0u8; GOAL];
+    1
+};
+const GOAL: usize = A;
+"#
+        ),
+        Err(ConstEvalError::Loop)
+    );
+}
+
+#[test]
+fn not_supported() {
+    assert_eq!(
+        eval_goal(r#"const GOAL: &[u8] = b"hello";"#),
+        Err(ConstEvalError::NotSupported("literal"))
+    );
+    assert_eq!(
+        eval_goal("const fn f() -> usize { 1 } const GOAL: usize = f();"),
+        Err(ConstEvalError::NotSupported("expression"))
+    );
+}
+
+#[test]
+fn enum_discriminants() {
+    let ints =
+        |values: &[i128]| values.iter().map(|&it| Ok(ComputedExpr::Int(it))).collect::<Vec<_>>();
+    assert_eq!(eval_discriminants("enum E { A, B, C }"), ints(&[0, 1, 2]));
+    assert_eq!(
+        eval_discriminants(
+            r#"
+const BASE: isize = 1 << 4;
+enum E { A = -1, B, C = BASE + 1, D, #[cfg(never)] X, E }
+"#
+        ),
+        ints(&[-1, 0, 17, 18, 19])
+    );
+    assert_eq!(
+        eval_discriminants("fn f() -> isize { 0 } enum E { A = f(), B }"),
+        vec![Err(ConstEvalError::IncompleteExpr); 2]
+    );
+}
@@ -1,6 +1,7 @@
 //! Type inference-based diagnostics.
 mod expr;
 mod match_check;
+mod move_check;
 mod unsafe_check;
 mod decl_check;
 
@@ -15,8 +16,10 @@ use crate::db::HirDatabase;
 
 pub use crate::diagnostics::{
     expr::{
-        record_literal_missing_fields, record_pattern_missing_fields, BodyValidationDiagnostic,
+        missing_match_arm_patterns, record_literal_missing_fields, record_pattern_missing_fields,
+        BodyValidationDiagnostic,
     },
+    move_check::{use_after_move, UseAfterMove},
     unsafe_check::missing_unsafe,
 };
 
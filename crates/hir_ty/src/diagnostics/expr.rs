@@ -6,7 +6,7 @@ use std::{cell::RefCell, sync::Arc};
 
 use hir_def::{
     expr::Statement, path::path, resolver::HasResolver, type_ref::Mutability, AssocItemId,
-    DefWithBodyId, HasModule,
+    DefWithBodyId, HasModule, ModuleId,
 };
 use hir_expand::name;
 use itertools::Either;
@@ -16,14 +16,18 @@ use crate::{
     db::HirDatabase,
     diagnostics::match_check::{
         self,
-        usefulness::{compute_match_usefulness, expand_pattern, MatchCheckCtx, PatternArena},
+        usefulness::{
+            compute_match_usefulness, expand_pattern, MatchCheckCtx, PatternArena, Reachability,
+        },
     },
-    AdtId, InferenceResult, Interner, Ty, TyExt, TyKind,
+    display::HirDisplay,
+    infer::is_reliable,
+    AdtId, InferenceResult, Interner, Ty, TyExt, TyKind, TypeMismatch,
 };
 
 pub(crate) use hir_def::{
     body::{Body, BodySourceMap},
-    expr::{Expr, ExprId, MatchArm, Pat, PatId},
+    expr::{Expr, ExprId, Pat, PatId},
     LocalFieldId, VariantId,
 };
 
@@ -50,11 +54,19 @@ pub enum BodyValidationDiagnostic {
     },
     MissingMatchArms {
         match_expr: ExprId,
+        uncovered_patterns: String,
+    },
+    UnreachablePattern {
+        pat: PatId,
     },
     AddReferenceHere {
         arg_expr: ExprId,
         mutability: Mutability,
     },
+    TypeMismatch {
+        expr: ExprId,
+        mismatch: TypeMismatch,
+    },
 }
 
 impl BodyValidationDiagnostic {
@@ -95,8 +107,8 @@ impl ExprValidator {
             }
 
             match expr {
-                Expr::Match { expr, arms } => {
-                    self.validate_match(id, *expr, arms, db, self.infer.clone());
+                Expr::Match { .. } => {
+                    self.validate_match(id, db);
                 }
                 Expr::Call { .. } | Expr::MethodCall { .. } => {
                     self.validate_call(db, id, expr);
@@ -139,6 +151,48 @@ impl ExprValidator {
                 diagnostics
                     .push(BodyValidationDiagnostic::AddReferenceHere { arg_expr, mutability });
             });
+
+        self.validate_type_mismatches(&body);
+    }
+
+    fn validate_type_mismatches(&mut self, body: &Body) {
+        // Mismatches a more specific diagnostic explains already. A wrong tail
+        // expression makes the other tails of the body mismatch as well.
+        let mut explained = FxHashSet::default();
+        let mut explained_tail_mismatch = None;
+        for diagnostic in self.diagnostics.iter() {
+            match diagnostic {
+                BodyValidationDiagnostic::MissingOkOrSomeInTailExpr { .. }
+                | BodyValidationDiagnostic::RemoveThisSemicolon { .. } => {
+                    explained_tail_mismatch = self.infer.type_mismatch_for_expr(body.body_expr);
+                }
+                BodyValidationDiagnostic::AddReferenceHere { arg_expr, .. } => {
+                    explained.insert(*arg_expr);
+                }
+                _ => (),
+            }
+        }
+
+        for (expr, mismatch) in self.infer.expr_type_mismatches() {
+            let is_explained = explained.contains(&expr)
+                || matches!(explained_tail_mismatch, Some(tail) if mismatch == tail
+                    || (mismatch.expected == tail.actual && mismatch.actual == tail.expected));
+            if is_explained || !is_reliable(&mismatch.expected) || !is_reliable(&mismatch.actual) {
+                continue;
+            }
+            // Blocks, `if`s and `match`es mismatch whenever their tail
+            // expressions do, only report the innermost mismatch.
+            let mut mismatches_in_child = false;
+            body[expr].walk_child_exprs(|child| {
+                mismatches_in_child |= self.infer.type_mismatch_for_expr(child) == Some(mismatch);
+            });
+            if !mismatches_in_child {
+                self.diagnostics.push(BodyValidationDiagnostic::TypeMismatch {
+                    expr,
+                    mismatch: mismatch.clone(),
+                });
+            }
+        }
     }
 
     fn check_for_filter_map_next(&mut self, db: &dyn HirDatabase) {
@@ -256,124 +310,24 @@ impl ExprValidator {
         }
     }
 
-    fn validate_match(
-        &mut self,
-        id: ExprId,
-        match_expr: ExprId,
-        arms: &[MatchArm],
-        db: &dyn HirDatabase,
-        infer: Arc<InferenceResult>,
-    ) {
-        let (body, source_map): (Arc<Body>, Arc<BodySourceMap>) =
-            db.body_with_source_map(self.owner);
-
-        let match_expr_ty = if infer.type_of_expr[match_expr].is_unknown() {
-            return;
-        } else {
-            &infer.type_of_expr[match_expr]
+    fn validate_match(&mut self, id: ExprId, db: &dyn HirDatabase) {
+        let report = match check_match(db, self.owner, &self.infer, id) {
+            Some(it) => it,
+            None => return,
         };
 
-        let pattern_arena = RefCell::new(PatternArena::new());
-
-        let mut m_arms = Vec::new();
-        let mut has_lowering_errors = false;
-        for arm in arms {
-            if let Some(pat_ty) = infer.type_of_pat.get(arm.pat) {
-                // We only include patterns whose type matches the type
-                // of the match expression. If we had an InvalidMatchArmPattern
-                // diagnostic or similar we could raise that in an else
-                // block here.
-                //
-                // When comparing the types, we also have to consider that rustc
-                // will automatically de-reference the match expression type if
-                // necessary.
-                //
-                // FIXME we should use the type checker for this.
-                if (pat_ty == match_expr_ty
-                    || match_expr_ty
-                        .as_reference()
-                        .map(|(match_expr_ty, ..)| match_expr_ty == pat_ty)
-                        .unwrap_or(false))
-                    && types_of_subpatterns_do_match(arm.pat, &body, &infer)
-                {
-                    // If we had a NotUsefulMatchArm diagnostic, we could
-                    // check the usefulness of each pattern as we added it
-                    // to the matrix here.
-                    let m_arm = match_check::MatchArm {
-                        pat: self.lower_pattern(
-                            arm.pat,
-                            &mut pattern_arena.borrow_mut(),
-                            db,
-                            &body,
-                            &mut has_lowering_errors,
-                        ),
-                        has_guard: arm.guard.is_some(),
-                    };
-                    m_arms.push(m_arm);
-                    if !has_lowering_errors {
-                        continue;
-                    }
-                }
-            }
-
-            // If we can't resolve the type of a pattern, or the pattern type doesn't
-            // fit the match expression, we skip this diagnostic. Skipping the entire
-            // diagnostic rather than just not including this match arm is preferred
-            // to avoid the chance of false positives.
-            cov_mark::hit!(validate_match_bailed_out);
-            return;
+        for arm_pat in report.unreachable_arms {
+            self.diagnostics.push(BodyValidationDiagnostic::UnreachablePattern { pat: arm_pat });
         }
 
-        let cx = MatchCheckCtx {
-            module: self.owner.module(db.upcast()),
-            match_expr,
-            infer: &infer,
-            db,
-            pattern_arena: &pattern_arena,
-            panic_context: &|| {
-                use syntax::AstNode;
-                let match_expr_text = source_map
-                    .expr_syntax(match_expr)
-                    .ok()
-                    .and_then(|scrutinee_sptr| {
-                        let root = scrutinee_sptr.file_syntax(db.upcast());
-                        scrutinee_sptr.value.to_node(&root).syntax().parent()
-                    })
-                    .map(|node| node.to_string());
-                format!(
-                    "expression:\n{}",
-                    match_expr_text.as_deref().unwrap_or("<synthesized expr>")
-                )
-            },
-        };
-        let report = compute_match_usefulness(&cx, &m_arms);
-
-        // FIXME Report unreacheble arms
-        // https://github.com/rust-lang/rust/blob/25c15cdbe/compiler/rustc_mir_build/src/thir/pattern/check_match.rs#L200-L201
-
-        let witnesses = report.non_exhaustiveness_witnesses;
-        // FIXME Report witnesses
-        // eprintln!("compute_match_usefulness(..) -> {:?}", &witnesses);
+        let witnesses = report.witnesses;
         if !witnesses.is_empty() {
-            self.diagnostics.push(BodyValidationDiagnostic::MissingMatchArms { match_expr: id });
-        }
-    }
-
-    fn lower_pattern(
-        &self,
-        pat: PatId,
-        pattern_arena: &mut PatternArena,
-        db: &dyn HirDatabase,
-        body: &Body,
-        have_errors: &mut bool,
-    ) -> match_check::PatId {
-        let mut patcx = match_check::PatCtxt::new(db, &self.infer, body);
-        let pattern = patcx.lower_pattern(pat);
-        let pattern = pattern_arena.alloc(expand_pattern(pattern));
-        if !patcx.errors.is_empty() {
-            *have_errors = true;
+            let witnesses: Vec<_> = witnesses.iter().map(|it| it.display(db).to_string()).collect();
+            self.diagnostics.push(BodyValidationDiagnostic::MissingMatchArms {
+                match_expr: id,
+                uncovered_patterns: joined_uncovered_patterns(&witnesses),
+            });
         }
-        pattern
     }
 
     fn validate_results_in_tail_expr(&mut self, body_id: ExprId, id: ExprId, db: &dyn HirDatabase) {
@@ -498,6 +452,149 @@ pub fn record_pattern_missing_fields(
     Some((variant_def, missed_fields, exhaustive))
 }
 
+struct MatchCheckReport {
+    /// The patterns of the arms which can never be reached.
+    unreachable_arms: Vec<PatId>,
+    /// Values not covered by any of the arms.
+    witnesses: Vec<match_check::Pat>,
+}
+
+/// Checks the arms of the `match` expression `match_expr` for exhaustiveness and reachability.
+/// Returns `None` if the patterns couldn't be checked.
+fn check_match(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    infer: &InferenceResult,
+    match_expr: ExprId,
+) -> Option<MatchCheckReport> {
+    let (body, source_map): (Arc<Body>, Arc<BodySourceMap>) = db.body_with_source_map(owner);
+    let (scrutinee, arms) = match &body[match_expr] {
+        Expr::Match { expr, arms } => (*expr, arms),
+        _ => return None,
+    };
+
+    let scrutinee_ty = &infer.type_of_expr[scrutinee];
+    if scrutinee_ty.is_unknown() {
+        return None;
+    }
+
+    let pattern_arena = RefCell::new(PatternArena::new());
+
+    let mut m_arms = Vec::new();
+    let mut has_lowering_errors = false;
+    for arm in arms {
+        if let Some(pat_ty) = infer.type_of_pat.get(arm.pat) {
+            // We only include patterns whose type matches the type
+            // of the match expression. If we had an InvalidMatchArmPattern
+            // diagnostic or similar we could raise that in an else
+            // block here.
+            //
+            // When comparing the types, we also have to consider that rustc
+            // will automatically de-reference the match expression type if
+            // necessary.
+            //
+            // FIXME we should use the type checker for this.
+            if type_matches_scrutinee(pat_ty, scrutinee_ty)
+                && types_of_subpatterns_do_match(arm.pat, &body, infer)
+            {
+                let mut patcx = match_check::PatCtxt::new(db, owner, infer, &body);
+                let pattern = patcx.lower_pattern(arm.pat);
+                let m_arm = match_check::MatchArm {
+                    pat: pattern_arena.borrow_mut().alloc(expand_pattern(pattern)),
+                    has_guard: arm.guard.is_some(),
+                };
+                m_arms.push(m_arm);
+                has_lowering_errors |= !patcx.errors.is_empty();
+                if !has_lowering_errors {
+                    continue;
+                }
+            }
+        }
+
+        // If we can't resolve the type of a pattern, or the pattern type doesn't
+        // fit the match expression, we skip this diagnostic. Skipping the entire
+        // diagnostic rather than just not including this match arm is preferred
+        // to avoid the chance of false positives.
+        cov_mark::hit!(validate_match_bailed_out);
+        return None;
+    }
+
+    let cx = MatchCheckCtx {
+        module: owner.module(db.upcast()),
+        match_expr: scrutinee,
+        infer,
+        db,
+        pattern_arena: &pattern_arena,
+        panic_context: &|| {
+            use syntax::AstNode;
+            let match_expr_text = source_map
+                .expr_syntax(scrutinee)
+                .ok()
+                .and_then(|scrutinee_sptr| {
+                    let root = scrutinee_sptr.file_syntax(db.upcast());
+                    scrutinee_sptr.value.to_node(&root).syntax().parent()
+                })
+                .map(|node| node.to_string());
+            format!("expression:\n{}", match_expr_text.as_deref().unwrap_or("<synthesized expr>"))
+        },
+    };
+    let report = compute_match_usefulness(&cx, &m_arms);
+
+    // FIXME: report unreachable alternatives of or-patterns as well
+    let unreachable_arms = arms
+        .iter()
+        .zip(&report.arm_usefulness)
+        .filter(|(_, (_, reachability))| matches!(reachability, Reachability::Unreachable))
+        .map(|(arm, _)| arm.pat)
+        .collect();
+
+    Some(MatchCheckReport { unreachable_arms, witnesses: report.non_exhaustiveness_witnesses })
+}
+
+/// Returns the patterns of the values not covered by the arms of the `match` expression
+/// `match_expr`, as source code valid in `module`.
+pub fn missing_match_arm_patterns(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    infer: &InferenceResult,
+    match_expr: ExprId,
+    module: ModuleId,
+) -> Option<Vec<String>> {
+    let report = check_match(db, owner, infer, match_expr)?;
+    report.witnesses.iter().map(|it| it.display_source_code(db, module).ok()).collect()
+}
+
+/// Lists witnesses the way rustc does, e.g. "`A`, `B` and 3 more".
+fn joined_uncovered_patterns(witnesses: &[String]) -> String {
+    const LIMIT: usize = 3;
+    match witnesses {
+        [] => String::new(),
+        [witness] => format!("`{}`", witness),
+        [head @ .., tail] if head.len() < LIMIT => {
+            format!("`{}` and `{}`", head.join("`, `"), tail)
+        }
+        _ => {
+            let (head, tail) = witnesses.split_at(LIMIT);
+            format!("`{}` and {} more", head.join("`, `"), tail.len())
+        }
+    }
+}
+
+/// Whether a pattern of type `pat_ty` can match the scrutinee, possibly through (implicitly
+/// dereferenced) references.
+fn type_matches_scrutinee(pat_ty: &Ty, scrutinee_ty: &Ty) -> bool {
+    let mut ty = scrutinee_ty;
+    loop {
+        if ty == pat_ty {
+            return true;
+        }
+        match ty.as_reference() {
+            Some((inner, ..)) => ty = inner,
+            None => return false,
+        }
+    }
+}
+
 fn types_of_subpatterns_do_match(pat: PatId, body: &Body, infer: &InferenceResult) -> bool {
     fn walk(pat: PatId, body: &Body, infer: &InferenceResult, has_type_mismatches: &mut bool) {
         match infer.type_mismatch_for_pat(pat) {
@@ -10,12 +10,24 @@ mod pat_util;
 
 pub(crate) mod usefulness;
 
-use hir_def::{body::Body, EnumVariantId, LocalFieldId, VariantId};
+use hir_def::{
+    adt::VariantData,
+    body::Body,
+    expr::{Expr, Literal, RangeOp},
+    item_scope::ItemInNs,
+    resolver::{HasResolver, ValueNs},
+    AssocItemId, ConstId, DefWithBodyId, EnumVariantId, LocalFieldId, ModuleDefId, VariantId,
+};
 use la_arena::Idx;
 
-use crate::{db::HirDatabase, InferenceResult, Interner, Substitution, Ty, TyKind};
+use crate::{
+    consteval::ComputedExpr,
+    db::HirDatabase,
+    display::{DisplaySourceCodeError, DisplayTarget, HirDisplay, HirDisplayError, HirFormatter},
+    InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
+};
 
-use self::pat_util::EnumerateAndAdjustIterator;
+use self::{deconstruct_pat::IntRange, pat_util::EnumerateAndAdjustIterator};
 
 pub(crate) use self::usefulness::MatchArm;
 
@@ -25,8 +37,11 @@ pub(crate) type PatId = Idx<Pat>;
 pub(crate) enum PatternError {
     Unimplemented,
     UnresolvedVariant,
+    UnresolvedConst,
+    InvalidRange,
     MissingField,
     ExtraFields,
+    UnexpectedType,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -76,9 +91,24 @@ pub(crate) enum PatKind {
         subpattern: Pat,
     },
 
-    // FIXME: for now, only bool literals are implemented
-    LiteralBool {
-        value: bool,
+    /// A literal or a constant, e.g. `5`, `'a'`, `"foo"` or `FOO`.
+    Constant {
+        value: Literal,
+    },
+
+    /// `lo..=hi` or `lo..hi`. Missing bounds are filled in with the bounds of the type.
+    Range {
+        lo: Literal,
+        hi: Literal,
+        end: RangeOp,
+    },
+
+    /// Matches against a slice or an array, e.g. `[x, y]` or `[x, .., y]`. `slice` is the
+    /// wildcard standing for the `..` part, if any.
+    Slice {
+        prefix: Vec<Pat>,
+        slice: Option<Pat>,
+        suffix: Vec<Pat>,
     },
 
     /// An or-pattern, e.g. `p | q`.
@@ -90,14 +120,20 @@ pub(crate) enum PatKind {
 
 pub(crate) struct PatCtxt<'a> {
     db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
     infer: &'a InferenceResult,
     body: &'a Body,
     pub(crate) errors: Vec<PatternError>,
 }
 
 impl<'a> PatCtxt<'a> {
-    pub(crate) fn new(db: &'a dyn HirDatabase, infer: &'a InferenceResult, body: &'a Body) -> Self {
-        Self { db, infer, body, errors: Vec::new() }
+    pub(crate) fn new(
+        db: &'a dyn HirDatabase,
+        owner: DefWithBodyId,
+        infer: &'a InferenceResult,
+        body: &'a Body,
+    ) -> Self {
+        Self { db, owner, infer, body, errors: Vec::new() }
     }
 
     pub(crate) fn lower_pattern(&mut self, pat: hir_def::expr::PatId) -> Pat {
@@ -123,12 +159,16 @@ impl<'a> PatCtxt<'a> {
         let kind = match self.body[pat] {
             hir_def::expr::Pat::Wild => PatKind::Wild,
 
-            hir_def::expr::Pat::Lit(expr) => self.lower_lit(expr),
+            hir_def::expr::Pat::Lit(expr) => self.lower_lit(ty, expr),
 
             hir_def::expr::Pat::Path(ref path) => {
                 return self.lower_path(pat, path);
             }
 
+            hir_def::expr::Pat::Range { start, end, range_type } => {
+                self.lower_range(ty, start, end, range_type)
+            }
+
             hir_def::expr::Pat::Tuple { ref args, ellipsis } => {
                 let arity = match *ty.kind(&Interner) {
                     TyKind::Tuple(arity, _) => arity,
@@ -138,6 +178,19 @@ impl<'a> PatCtxt<'a> {
                 PatKind::Leaf { subpatterns }
             }
 
+            hir_def::expr::Pat::Slice { ref prefix, slice, ref suffix } => PatKind::Slice {
+                prefix: self.lower_patterns(prefix),
+                // The rest is either `..` or `name @ ..`, neither of which restricts the
+                // matched elements.
+                slice: slice.map(|slice| Pat::wildcard_from_ty(self.infer[slice].clone())),
+                suffix: self.lower_patterns(suffix),
+            },
+
+            hir_def::expr::Pat::Ref { pat: subpat, .. }
+            | hir_def::expr::Pat::Box { inner: subpat } => {
+                PatKind::Deref { subpattern: self.lower_pattern(subpat) }
+            }
+
             hir_def::expr::Pat::Bind { subpat, .. } => {
                 if let TyKind::Ref(.., rty) = ty.kind(&Interner) {
                     ty = rty;
@@ -178,7 +231,7 @@ impl<'a> PatCtxt<'a> {
 
             hir_def::expr::Pat::Or(ref pats) => PatKind::Or { pats: self.lower_patterns(pats) },
 
-            _ => {
+            hir_def::expr::Pat::ConstBlock(_) | hir_def::expr::Pat::Missing => {
                 self.errors.push(PatternError::Unimplemented);
                 PatKind::Wild
             }
@@ -244,31 +297,328 @@ impl<'a> PatCtxt<'a> {
         kind
     }
 
-    fn lower_path(&mut self, pat: hir_def::expr::PatId, _path: &hir_def::path::Path) -> Pat {
+    fn lower_path(&mut self, pat: hir_def::expr::PatId, path: &hir_def::path::Path) -> Pat {
         let ty = &self.infer[pat];
 
         let pat_from_kind = |kind| Pat { ty: ty.clone(), kind: Box::new(kind) };
 
-        match self.infer.variant_resolution_for_pat(pat) {
-            Some(_) => pat_from_kind(self.lower_variant_or_leaf(pat, ty, Vec::new())),
+        if let Some(variant) = self.infer.variant_resolution_for_pat(pat) {
+            // A bare path to a variant with fields, like `Some`, doesn't match
+            // the scrutinee's type.
+            if !variant.variant_data(self.db.upcast()).fields().is_empty() {
+                self.errors.push(PatternError::UnexpectedType);
+                return pat_from_kind(PatKind::Wild);
+            }
+            return pat_from_kind(self.lower_variant_or_leaf(pat, ty, Vec::new()));
+        }
+        let konst = match self.infer.assoc_resolutions_for_pat(pat) {
+            Some(AssocItemId::ConstId(konst)) => Some(konst),
+            Some(_) => None,
+            None => self.resolve_const(path),
+        };
+        let kind = match konst {
+            Some(konst) => self.lower_const(ty, konst),
             None => {
                 self.errors.push(PatternError::UnresolvedVariant);
-                pat_from_kind(PatKind::Wild)
+                PatKind::Wild
+            }
+        };
+        pat_from_kind(kind)
+    }
+
+    fn lower_lit(&mut self, ty: &Ty, expr: hir_def::expr::ExprId) -> PatKind {
+        match &self.body[expr] {
+            Expr::Literal(value) => self.lower_literal(ty, value.clone()),
+            _ => {
+                self.errors.push(PatternError::Unimplemented);
+                PatKind::Wild
             }
         }
     }
 
-    fn lower_lit(&mut self, expr: hir_def::expr::ExprId) -> PatKind {
-        use hir_def::expr::{Expr, Literal::Bool};
+    fn lower_literal(&mut self, ty: &Ty, value: Literal) -> PatKind {
+        match value {
+            // String literals are matched through a reference, like rustc does.
+            Literal::String(_) | Literal::ByteString(_) => match ty.as_reference() {
+                Some((inner, ..)) => PatKind::Deref {
+                    subpattern: Pat {
+                        ty: inner.clone(),
+                        kind: Box::new(PatKind::Constant { value }),
+                    },
+                },
+                None => PatKind::Constant { value },
+            },
+            _ => PatKind::Constant { value },
+        }
+    }
 
-        match self.body[expr] {
-            Expr::Literal(Bool(value)) => PatKind::LiteralBool { value },
-            _ => {
+    fn lower_range(
+        &mut self,
+        ty: &Ty,
+        start: Option<hir_def::expr::ExprId>,
+        end: Option<hir_def::expr::ExprId>,
+        range_type: RangeOp,
+    ) -> PatKind {
+        let (min, max) = match IntRange::type_bounds(ty) {
+            Some(bounds) => bounds,
+            None => {
+                // Float and `&str` ranges can't make a match exhaustive.
                 self.errors.push(PatternError::Unimplemented);
+                return PatKind::Wild;
+            }
+        };
+        let lo = match start {
+            Some(start) => self.lower_range_bound(start),
+            None => Some(min),
+        };
+        let (hi, end) = match end {
+            Some(end) => (self.lower_range_bound(end), range_type),
+            // `lo..` covers everything up to and including the maximum.
+            None => (Some(max), RangeOp::Inclusive),
+        };
+        match (lo, hi) {
+            (Some(lo), Some(hi)) if IntRange::from_range_pat(&lo, &hi, end, ty).is_some() => {
+                PatKind::Range { lo, hi, end }
+            }
+            (Some(_), Some(_)) => {
+                self.errors.push(PatternError::InvalidRange);
+                PatKind::Wild
+            }
+            _ => PatKind::Wild,
+        }
+    }
+
+    fn lower_range_bound(&mut self, expr: hir_def::expr::ExprId) -> Option<Literal> {
+        let konst = match &self.body[expr] {
+            Expr::Literal(value) => return Some(value.clone()),
+            Expr::Path(path) => match self.infer.assoc_resolutions_for_expr(expr) {
+                Some(AssocItemId::ConstId(konst)) => Some(konst),
+                Some(_) => None,
+                None => self.resolve_const(path),
+            },
+            _ => None,
+        };
+        let value = konst.and_then(|konst| eval_const(self.db, konst));
+        if value.is_none() {
+            self.errors.push(PatternError::UnresolvedConst);
+        }
+        value
+    }
+
+    fn lower_const(&mut self, ty: &Ty, konst: ConstId) -> PatKind {
+        match eval_const(self.db, konst) {
+            Some(value) => self.lower_literal(ty, value),
+            None => {
+                self.errors.push(PatternError::UnresolvedConst);
                 PatKind::Wild
             }
         }
     }
+
+    fn resolve_const(&self, path: &hir_def::path::Path) -> Option<ConstId> {
+        let resolver = self.owner.resolver(self.db.upcast());
+        match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())? {
+            ValueNs::ConstId(konst) => Some(konst),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluates a constant used in a pattern to the literal it is matched against.
+fn eval_const(db: &dyn HirDatabase, konst: ConstId) -> Option<Literal> {
+    let value = match db.const_eval(konst).ok()? {
+        ComputedExpr::Int(it) => Literal::Int(it, None),
+        ComputedExpr::UInt(it) => Literal::Uint(it, None),
+        ComputedExpr::Bool(it) => Literal::Bool(it),
+        ComputedExpr::Char(it) => Literal::Char(it),
+        ComputedExpr::Str(it) => Literal::String(it),
+    };
+    Some(value)
+}
+
+impl HirDisplay for Pat {
+    fn hir_fmt(&self, f: &mut HirFormatter) -> Result<(), HirDisplayError> {
+        match &*self.kind {
+            PatKind::Wild => write!(f, "_"),
+            PatKind::Binding { subpattern: Some(subpattern) } => subpattern.hir_fmt(f),
+            PatKind::Binding { subpattern: None } => write!(f, "_"),
+            PatKind::Variant { subpatterns, .. } | PatKind::Leaf { subpatterns } => {
+                let variant = match *self.kind {
+                    PatKind::Variant { enum_variant, .. } => Some(VariantId::from(enum_variant)),
+                    _ => self.ty.as_adt().and_then(|(adt, _)| match adt {
+                        hir_def::AdtId::StructId(s) => Some(s.into()),
+                        hir_def::AdtId::UnionId(u) => Some(u.into()),
+                        hir_def::AdtId::EnumId(_) => None,
+                    }),
+                };
+
+                let num_fields = match variant {
+                    Some(variant) => {
+                        write_variant_path(f, variant)?;
+                        let variant_data = variant.variant_data(f.db.upcast());
+                        if let VariantData::Record(fields) = &*variant_data {
+                            let subpats = subpatterns
+                                .iter()
+                                .filter(|p| !p.pattern.is_wildcard())
+                                .map(|p| {
+                                    WriteWith(move |f| {
+                                        write!(f, "{}: ", fields[p.field].name)?;
+                                        p.pattern.hir_fmt(f)
+                                    })
+                                })
+                                .collect::<Vec<_>>();
+                            write!(f, " {{ ")?;
+                            f.write_joined(subpats.iter(), ", ")?;
+                            if subpats.len() < fields.len() {
+                                if !subpats.is_empty() {
+                                    write!(f, ", ")?;
+                                }
+                                write!(f, "..")?;
+                            }
+                            return write!(f, " }}");
+                        }
+                        variant_data.fields().len()
+                    }
+                    None => subpatterns.len(),
+                };
+
+                if num_fields != 0 || variant.is_none() {
+                    let subpats = (0..num_fields).map(|i| {
+                        WriteWith(move |f| {
+                            let field = LocalFieldId::from_raw((i as u32).into());
+                            match subpatterns.iter().find(|p| p.field == field) {
+                                Some(p) => p.pattern.hir_fmt(f),
+                                None => write!(f, "_"),
+                            }
+                        })
+                    });
+                    write!(f, "(")?;
+                    f.write_joined(subpats, ", ")?;
+                    if variant.is_none() && num_fields == 1 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            PatKind::Deref { subpattern } => {
+                match self.ty.kind(&Interner) {
+                    TyKind::Adt(..) => write!(f, "box ")?,
+                    TyKind::Ref(chalk_ir::Mutability::Mut, ..) => write!(f, "&mut ")?,
+                    _ => write!(f, "&")?,
+                }
+                subpattern.hir_fmt(f)
+            }
+            PatKind::Constant { value } => write_literal(f, value),
+            PatKind::Range { lo, hi, end } => {
+                let bounds = IntRange::type_bounds(&self.ty);
+                let (min, max) = match &bounds {
+                    Some((min, max)) => (Some(min), Some(max)),
+                    None => (None, None),
+                };
+                write_range_bound(f, lo, &self.ty, min, "MIN")?;
+                match end {
+                    RangeOp::Inclusive => write!(f, "..=")?,
+                    RangeOp::Exclusive => write!(f, "..")?,
+                }
+                write_range_bound(f, hi, &self.ty, max, "MAX")
+            }
+            PatKind::Slice { prefix, slice, suffix } => {
+                // `None` stands for the `..`.
+                let subpats = prefix
+                    .iter()
+                    .map(Some)
+                    .chain(slice.as_ref().map(|_| None))
+                    .chain(suffix.iter().map(Some))
+                    .map(|p| {
+                        WriteWith(move |f| match p {
+                            Some(p) => p.hir_fmt(f),
+                            None => write!(f, ".."),
+                        })
+                    });
+                write!(f, "[")?;
+                f.write_joined(subpats, ", ")?;
+                write!(f, "]")
+            }
+            PatKind::Or { pats } => f.write_joined(pats.iter(), " | "),
+        }
+    }
+}
+
+struct WriteWith<F>(F)
+where
+    F: Fn(&mut HirFormatter) -> Result<(), HirDisplayError>;
+
+impl<F> HirDisplay for WriteWith<F>
+where
+    F: Fn(&mut HirFormatter) -> Result<(), HirDisplayError>,
+{
+    fn hir_fmt(&self, f: &mut HirFormatter) -> Result<(), HirDisplayError> {
+        (self.0)(f)
+    }
+}
+
+fn write_variant_path(f: &mut HirFormatter, variant: VariantId) -> Result<(), HirDisplayError> {
+    if let DisplayTarget::SourceCode { module_id } = f.display_target {
+        let item = match variant {
+            VariantId::EnumVariantId(it) => ModuleDefId::EnumVariantId(it),
+            VariantId::StructId(it) => ModuleDefId::AdtId(it.into()),
+            VariantId::UnionId(it) => ModuleDefId::AdtId(it.into()),
+        };
+        return match hir_def::find_path::find_path(f.db.upcast(), ItemInNs::Types(item), module_id)
+        {
+            Some(path) => write!(f, "{}", path),
+            None => {
+                Err(HirDisplayError::DisplaySourceCodeError(DisplaySourceCodeError::PathNotFound))
+            }
+        };
+    }
+    match variant {
+        VariantId::EnumVariantId(it) => {
+            write!(f, "{}", f.db.enum_data(it.parent).variants[it.local_id].name)
+        }
+        VariantId::StructId(it) => write!(f, "{}", f.db.struct_data(it).name),
+        VariantId::UnionId(it) => write!(f, "{}", f.db.union_data(it).name),
+    }
+}
+
+/// Writes the bound of a range pattern, spelling the bounds of integer types as `u8::MAX` and
+/// similar.
+fn write_range_bound(
+    f: &mut HirFormatter,
+    value: &Literal,
+    ty: &Ty,
+    type_bound: Option<&Literal>,
+    bound_name: &str,
+) -> Result<(), HirDisplayError> {
+    let is_type_bound = !matches!(value, Literal::Char(_))
+        && type_bound.map(|type_bound| {
+            IntRange::literal_bits(value, ty) == IntRange::literal_bits(type_bound, ty)
+        }) == Some(true);
+    if is_type_bound {
+        ty.hir_fmt(f)?;
+        write!(f, "::{}", bound_name)
+    } else {
+        write_literal(f, value)
+    }
+}
+
+fn write_literal(f: &mut HirFormatter, value: &Literal) -> Result<(), HirDisplayError> {
+    match value {
+        Literal::String(it) => write!(f, "{:?}", it),
+        Literal::ByteString(it) => {
+            write!(f, "b\"")?;
+            for &byte in it {
+                write!(f, "{}", std::ascii::escape_default(byte))?;
+            }
+            write!(f, "\"")
+        }
+        Literal::Char(it) => write!(f, "'{}'", it.escape_debug()),
+        Literal::Bool(it) => write!(f, "{}", it),
+        Literal::Int(it, _) => write!(f, "{}", it),
+        Literal::Uint(it, _) => write!(f, "{}", it),
+        Literal::Float(it, _) => write!(f, "{}", f64::from_bits(*it)),
+    }
 }
 
 pub(crate) trait PatternFoldable: Sized {
@@ -320,7 +670,7 @@ macro_rules! clone_impls {
     }
 }
 
-clone_impls! { LocalFieldId, Ty, Substitution, EnumVariantId }
+clone_impls! { LocalFieldId, Ty, Substitution, EnumVariantId, Literal, RangeOp }
 
 impl PatternFoldable for FieldPat {
     fn super_fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
@@ -360,7 +710,17 @@ impl PatternFoldable for PatKind {
             PatKind::Deref { subpattern } => {
                 PatKind::Deref { subpattern: subpattern.fold_with(folder) }
             }
-            &PatKind::LiteralBool { value } => PatKind::LiteralBool { value },
+            PatKind::Constant { value } => PatKind::Constant { value: value.fold_with(folder) },
+            PatKind::Range { lo, hi, end } => PatKind::Range {
+                lo: lo.fold_with(folder),
+                hi: hi.fold_with(folder),
+                end: end.fold_with(folder),
+            },
+            PatKind::Slice { prefix, slice, suffix } => PatKind::Slice {
+                prefix: prefix.fold_with(folder),
+                slice: slice.fold_with(folder),
+                suffix: suffix.fold_with(folder),
+            },
             PatKind::Or { pats } => PatKind::Or { pats: pats.fold_with(folder) },
         }
     }
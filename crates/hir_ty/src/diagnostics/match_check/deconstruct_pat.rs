@@ -43,21 +43,27 @@
 
 use std::{
     cmp::{max, min},
+    convert::TryInto,
     iter::once,
     ops::RangeInclusive,
 };
 
-use hir_def::{EnumVariantId, HasModule, LocalFieldId, VariantId};
+use chalk_ir::{IntTy, UintTy};
+use hir_def::{
+    expr::{Literal, RangeOp},
+    type_ref::ConstScalar,
+    EnumVariantId, HasModule, LocalFieldId, VariantId,
+};
 use smallvec::{smallvec, SmallVec};
 
-use crate::{AdtId, Interner, Scalar, Ty, TyExt, TyKind};
+use crate::{AdtId, Const, ConstValue, Interner, Scalar, Ty, TyExt, TyKind};
 
 use super::{
-    usefulness::{MatchCheckCtx, PatCtxt},
+    usefulness::{helper::Captures, MatchCheckCtx, PatCtxt},
     FieldPat, Pat, PatId, PatKind,
 };
 
-use self::Constructor::*;
+use self::{Constructor::*, SliceKind::*};
 
 /// [Constructor] uses this in umimplemented variants.
 /// It allows porting match expressions from upstream algorithm without losing semantics.
@@ -96,18 +102,128 @@ impl IntRange {
         (*self.range.start(), *self.range.end())
     }
 
-    #[inline]
-    fn from_bool(value: bool) -> IntRange {
-        let val = value as u128;
-        IntRange { range: val..=val }
+    /// The size in bits of the values of an integral type, and the bias added to them to make
+    /// the minimum value encode as `0`.
+    fn integral_size_and_signed_bias(ty: &Ty) -> Option<(u32, u128)> {
+        let size = match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Bool) => return Some((1, 0)),
+            TyKind::Scalar(Scalar::Char) => return Some((32, 0)),
+            TyKind::Scalar(Scalar::Int(ity)) => match ity {
+                IntTy::I8 => 8,
+                IntTy::I16 => 16,
+                IntTy::I32 => 32,
+                // FIXME: use the target pointer width
+                IntTy::I64 | IntTy::Isize => 64,
+                IntTy::I128 => 128,
+            },
+            TyKind::Scalar(Scalar::Uint(uty)) => {
+                let size = match uty {
+                    UintTy::U8 => 8,
+                    UintTy::U16 => 16,
+                    UintTy::U32 => 32,
+                    UintTy::U64 | UintTy::Usize => 64,
+                    UintTy::U128 => 128,
+                };
+                return Some((size, 0));
+            }
+            _ => return None,
+        };
+        Some((size, 1u128 << (size - 1)))
+    }
+
+    /// Encodes `value` as a value of the integral type `ty`, or returns `None` if it isn't one.
+    pub(super) fn literal_bits(value: &Literal, ty: &Ty) -> Option<u128> {
+        let (size, bias) = Self::integral_size_and_signed_bias(ty)?;
+        let mask = u128::MAX >> (128 - size);
+        let bits = match (value, ty.kind(&Interner)) {
+            (&Literal::Bool(value), TyKind::Scalar(Scalar::Bool)) => value as u128,
+            (&Literal::Char(value), TyKind::Scalar(Scalar::Char)) => value as u128,
+            (&Literal::Uint(value, _), TyKind::Scalar(Scalar::Uint(_))) if value <= mask => value,
+            (&Literal::Int(value, _), TyKind::Scalar(Scalar::Uint(_)))
+                if value >= 0 && value as u128 <= mask =>
+            {
+                value as u128
+            }
+            (&Literal::Uint(value, _), TyKind::Scalar(Scalar::Int(_))) if value < bias => {
+                value ^ bias
+            }
+            (&Literal::Int(value, _), TyKind::Scalar(Scalar::Int(_)))
+                if i128::MIN >> (128 - size) <= value && value <= i128::MAX >> (128 - size) =>
+            {
+                (value as u128 & mask) ^ bias
+            }
+            _ => return None,
+        };
+        Some(bits)
+    }
+
+    /// The inverse of `literal_bits`.
+    fn literal_from_bits(bits: u128, ty: &Ty) -> Literal {
+        match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Bool) => Literal::Bool(bits != 0),
+            TyKind::Scalar(Scalar::Char) => {
+                Literal::Char(std::char::from_u32(bits as u32).unwrap_or_default())
+            }
+            TyKind::Scalar(Scalar::Int(_)) => {
+                let (size, bias) = Self::integral_size_and_signed_bias(ty).unwrap();
+                // Sign-extend the unbiased value.
+                let shift = 128 - size;
+                Literal::Int((((bits ^ bias) << shift) as i128) >> shift, None)
+            }
+            _ => Literal::Uint(bits, None),
+        }
+    }
+
+    /// The smallest and the largest value of an integral type.
+    pub(super) fn type_bounds(ty: &Ty) -> Option<(Literal, Literal)> {
+        let (size, _) = Self::integral_size_and_signed_bias(ty)?;
+        let max = match ty.kind(&Interner) {
+            TyKind::Scalar(Scalar::Char) => std::char::MAX as u128,
+            _ => u128::MAX >> (128 - size),
+        };
+        Some((Self::literal_from_bits(0, ty), Self::literal_from_bits(max, ty)))
     }
 
     #[inline]
-    fn from_range(lo: u128, hi: u128, scalar_ty: Scalar) -> IntRange {
-        if let Scalar::Bool = scalar_ty {
-            IntRange { range: lo..=hi }
+    fn from_range(lo: u128, hi: u128) -> IntRange {
+        IntRange { range: lo..=hi }
+    }
+
+    fn from_literal(value: &Literal, ty: &Ty) -> Option<IntRange> {
+        let bits = Self::literal_bits(value, ty)?;
+        Some(IntRange { range: bits..=bits })
+    }
+
+    /// Returns `None` for empty ranges and for bounds that are not values of `ty`.
+    pub(super) fn from_range_pat(
+        lo: &Literal,
+        hi: &Literal,
+        end: RangeOp,
+        ty: &Ty,
+    ) -> Option<IntRange> {
+        let lo = Self::literal_bits(lo, ty)?;
+        let hi = Self::literal_bits(hi, ty)?;
+        let hi = match end {
+            RangeOp::Inclusive => hi,
+            RangeOp::Exclusive => hi.checked_sub(1)?,
+        };
+        if lo <= hi {
+            Some(IntRange { range: lo..=hi })
+        } else {
+            None
+        }
+    }
+
+    fn to_pat(&self, ty: &Ty) -> PatKind {
+        let (lo, hi) = self.boundaries();
+        if lo == hi {
+            PatKind::Constant { value: Self::literal_from_bits(lo, ty) }
         } else {
-            unimplemented!()
+            PatKind::Range {
+                lo: Self::literal_from_bits(lo, ty),
+                hi: Self::literal_from_bits(hi, ty),
+                end: RangeOp::Inclusive,
+            }
         }
     }
 
@@ -233,16 +349,193 @@ impl SplitIntRange {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SliceKind {
+    /// Patterns of length `n` (`[x, y]`).
+    FixedLen(usize),
+    /// Patterns using the `..` notation (`[x, .., y]`).
+    /// Captures any array constructor of `length >= i + j`.
+    /// In the case where `array_len` is `Some(_)`,
+    /// this indicates that we only care about the first `i` and the last `j` values of the array,
+    /// and everything in between is a wildcard `_`.
+    VarLen(usize, usize),
+}
+
+impl SliceKind {
+    fn arity(self) -> usize {
+        match self {
+            FixedLen(length) => length,
+            VarLen(prefix, suffix) => prefix + suffix,
+        }
+    }
+
+    /// Whether this pattern includes patterns of length `other_len`.
+    fn covers_length(self, other_len: usize) -> bool {
+        match self {
+            FixedLen(len) => len == other_len,
+            VarLen(prefix, suffix) => prefix + suffix <= other_len,
+        }
+    }
+}
+
 /// A constructor for array and slice patterns.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(super) struct Slice {
-    _unimplemented: Void,
+    /// `None` if the matched value is a slice, `Some(n)` if it is an array of size `n`.
+    array_len: Option<usize>,
+    /// The kind of pattern it is: fixed-length `[x, y]` or variable length `[x, .., y]`.
+    kind: SliceKind,
 }
 
 impl Slice {
+    fn new(array_len: Option<usize>, kind: SliceKind) -> Self {
+        let kind = match (array_len, kind) {
+            // If the middle `..` is empty, we effectively have a fixed-length pattern.
+            (Some(len), VarLen(prefix, suffix)) if prefix + suffix >= len => FixedLen(len),
+            _ => kind,
+        };
+        Slice { array_len, kind }
+    }
+
+    fn arity(self) -> usize {
+        self.kind.arity()
+    }
+
     /// See `Constructor::is_covered_by`
-    fn is_covered_by(self, _other: Self) -> bool {
-        unimplemented!() // never called as Slice contains Void
+    fn is_covered_by(self, other: Self) -> bool {
+        other.kind.covers_length(self.arity())
+    }
+}
+
+/// This computes constructor splitting for variable-length slices, as explained at the top of the
+/// file.
+///
+/// A slice pattern `[x, .., y]` behaves like the infinite or-pattern `[x, y] | [x, _, y] | [x, _,
+/// _, y] | ...`. The corresponding value constructors are fixed-length array constructors above a
+/// given minimum length. We obviously can't list this infinitude of constructors. Thankfully,
+/// it turns out that for each finite set of slice patterns, all sufficiently large array lengths
+/// are equivalent.
+///
+/// Let's look at an example, where we are trying to split the last pattern:
+/// ```
+/// match x {
+///     [true, true, ..] => {}
+///     [.., false, false] => {}
+///     [..] => {}
+/// }
+/// ```
+/// Here are the results of specialization for the first few lengths:
+/// ```
+/// // length 0
+/// [] => {}
+/// // length 1
+/// [_] => {}
+/// // length 2
+/// [true, true] => {}
+/// [false, false] => {}
+/// [_, _] => {}
+/// // length 3
+/// [true, true,  _    ] => {}
+/// [_,    false, false] => {}
+/// [_,    _,     _    ] => {}
+/// // length 4
+/// [true, true, _,     _    ] => {}
+/// [_,    _,    false, false] => {}
+/// [_,    _,    _,     _    ] => {}
+/// // length 5
+/// [true, true, _, _,     _    ] => {}
+/// [_,    _,    _, false, false] => {}
+/// [_,    _,    _, _,     _    ] => {}
+/// ```
+///
+/// If we went above length 5, we would simply be inserting more columns full of wildcards in the
+/// middle. This means that the set of witnesses for length `l >= 5` if equivalent to the set for
+/// any other `l' >= 5`: simply add or remove wildcards in the middle to convert between them.
+///
+/// This applies to any set of slice patterns: there will be a length `L` above which all lengths
+/// behave the same. This is exactly what we need for constructor splitting. Therefore a
+/// variable-length slice can be split into a variable-length slice of minimal length `L`, and many
+/// fixed-length slices of lengths `< L`.
+///
+/// For each variable-length pattern `p` with a prefix of length `plₚ` and suffix of length `slₚ`,
+/// only the first `plₚ` and the last `slₚ` elements are examined. Therefore, as long as `L` is
+/// positive (to avoid concerns about empty types), all elements after the maximum prefix length
+/// and before the maximum suffix length are not examined by any variable-length pattern, and
+/// therefore can be added/removed without affecting them - creating equivalent patterns from any
+/// sufficiently-large length.
+///
+/// Of course, if fixed-length patterns exist, we must be sure that our length is large enough to
+/// miss them all, so we can pick `L = max(max(FIXED_LEN)+1, max(PREFIX_LEN) + max(SUFFIX_LEN))`
+///
+/// `max_slice` below will be made to have arity `L`.
+#[derive(Debug)]
+struct SplitVarLenSlice {
+    /// If the type is an array, this is its size.
+    array_len: Option<usize>,
+    /// The arity of the input slice.
+    arity: usize,
+    /// The smallest slice bigger than any slice seen. `max_slice.arity()` is the length `L`
+    /// described above.
+    max_slice: SliceKind,
+}
+
+impl SplitVarLenSlice {
+    fn new(prefix: usize, suffix: usize, array_len: Option<usize>) -> Self {
+        SplitVarLenSlice { array_len, arity: prefix + suffix, max_slice: VarLen(prefix, suffix) }
+    }
+
+    /// Pass a set of slices relative to which to split this one.
+    fn split(&mut self, slices: impl Iterator<Item = SliceKind>) {
+        let (mut max_prefix_len, mut max_suffix_len) = match self.max_slice {
+            VarLen(prefix, suffix) => (prefix, suffix),
+            FixedLen(_) => return, // No need to split
+        };
+        // We grow `self.max_slice` to be larger than all slices encountered, as described above.
+        // For diagnostics, we keep the prefix and suffix lengths separate, but grow them so that
+        // `L = max_prefix_len + max_suffix_len`.
+        let mut max_fixed_len = 0;
+        for slice in slices {
+            match slice {
+                FixedLen(len) => {
+                    max_fixed_len = max(max_fixed_len, len);
+                }
+                VarLen(prefix, suffix) => {
+                    max_prefix_len = max(max_prefix_len, prefix);
+                    max_suffix_len = max(max_suffix_len, suffix);
+                }
+            }
+        }
+        // We want `L = max(L, max_fixed_len + 1)`, modulo the fact that we keep prefix and
+        // suffix separate.
+        if max_fixed_len + 1 >= max_prefix_len + max_suffix_len {
+            // The subtraction can't overflow thanks to the above check.
+            // The new `max_prefix_len` is larger than its previous value.
+            max_prefix_len = max_fixed_len + 1 - max_suffix_len;
+        }
+
+        // We cap the arity of `max_slice` at the array size.
+        match self.array_len {
+            Some(len) if max_prefix_len + max_suffix_len >= len => self.max_slice = FixedLen(len),
+            _ => self.max_slice = VarLen(max_prefix_len, max_suffix_len),
+        }
+    }
+
+    /// Iterate over the partition of this slice.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = Slice> + Captures<'a> {
+        let smaller_lengths = match self.array_len {
+            // The only admissible fixed-length slice is one of the array size. Whether `max_slice`
+            // is fixed-length or variable-length, it will be the only relevant slice to output
+            // here.
+            Some(_) => 0..0, // empty range
+            // We cover all arities in the range `(self.arity..infinity)`. We split that range into
+            // two: lengths smaller than `max_slice.arity()` are treated independently as
+            // fixed-lengths slices, and lengths above are captured by `max_slice`.
+            None => self.arity..self.max_slice.arity(),
+        };
+        smaller_lengths
+            .map(FixedLen)
+            .chain(once(self.max_slice))
+            .map(move |kind| Slice::new(self.array_len, kind))
     }
 }
 
@@ -319,11 +612,35 @@ impl Constructor {
 
     /// Determines the constructor that the given pattern can be specialized to.
     pub(super) fn from_pat(cx: &MatchCheckCtx<'_>, pat: PatId) -> Self {
-        match cx.pattern_arena.borrow()[pat].kind.as_ref() {
+        let pat = &cx.pattern_arena.borrow()[pat];
+        match pat.kind.as_ref() {
             PatKind::Binding { .. } | PatKind::Wild => Wildcard,
             PatKind::Leaf { .. } | PatKind::Deref { .. } => Single,
             &PatKind::Variant { enum_variant, .. } => Variant(enum_variant),
-            &PatKind::LiteralBool { value } => IntRange(IntRange::from_bool(value)),
+            PatKind::Constant { value } => match IntRange::from_literal(value, &pat.ty) {
+                Some(range) => IntRange(range),
+                // Strings, floats and out of range integers can't be inspected.
+                None => Opaque,
+            },
+            PatKind::Range { lo, hi, end } => {
+                match IntRange::from_range_pat(lo, hi, *end, &pat.ty) {
+                    Some(range) => IntRange(range),
+                    None => Opaque,
+                }
+            }
+            PatKind::Slice { prefix, slice, suffix } => {
+                let array_len = match pat.ty.kind(&Interner) {
+                    TyKind::Array(_, len) => array_len(len),
+                    TyKind::Slice(_) => None,
+                    _ => cx.bug(&format!("bad ty {:?} for slice pattern", pat.ty)),
+                };
+                let kind = if slice.is_some() {
+                    VarLen(prefix.len(), suffix.len())
+                } else {
+                    FixedLen(prefix.len() + suffix.len())
+                };
+                Slice(Slice::new(array_len, kind))
+            }
             PatKind::Or { .. } => cx.bug("Or-pattern should have been expanded earlier on."),
         }
     }
@@ -360,7 +677,12 @@ impl Constructor {
                 split_range.split(int_ranges.cloned());
                 split_range.iter().map(IntRange).collect()
             }
-            Slice(_) => unimplemented!(),
+            &Slice(Slice { kind: VarLen(self_prefix, self_suffix), array_len }) => {
+                let mut split_self = SplitVarLenSlice::new(self_prefix, self_suffix, array_len);
+                let slices = ctors.filter_map(|c| c.as_slice()).map(|s| s.kind);
+                split_self.split(slices);
+                split_self.iter().map(Slice).collect()
+            }
             // Any other constructor can be used unchanged.
             _ => smallvec![self.clone()],
         }
@@ -459,11 +781,7 @@ pub(super) struct SplitWildcard {
 impl SplitWildcard {
     pub(super) fn new(pcx: PatCtxt<'_>) -> Self {
         let cx = pcx.cx;
-        let make_range = |start, end, scalar| IntRange(IntRange::from_range(start, end, scalar));
-
-        // Unhandled types are treated as non-exhaustive. Being explicit here instead of falling
-        // to catchall arm to ease further implementation.
-        let unhandled = || smallvec![NonExhaustive];
+        let make_range = |start, end| IntRange(IntRange::from_range(start, end));
 
         // This determines the set of all possible constructors for the type `pcx.ty`. For numbers,
         // arrays and slices we use ranges and variable-length slices when appropriate.
@@ -474,9 +792,15 @@ impl SplitWildcard {
         // Invariant: this is empty if and only if the type is uninhabited (as determined by
         // `cx.is_uninhabited()`).
         let all_ctors = match pcx.ty.kind(&Interner) {
-            TyKind::Scalar(Scalar::Bool) => smallvec![make_range(0, 1, Scalar::Bool)],
-            // TyKind::Array(..) if ... => unhandled(),
-            TyKind::Array(..) | TyKind::Slice(..) => unhandled(),
+            TyKind::Scalar(Scalar::Bool) => smallvec![make_range(0, 1)],
+            TyKind::Array(_, len) if array_len(len).is_some() => {
+                let len = array_len(len).unwrap();
+                smallvec![Slice(Slice::new(Some(len), VarLen(0, 0)))]
+            }
+            // Treat arrays of a constant but unknown length like slices.
+            TyKind::Array(..) | TyKind::Slice(..) => {
+                smallvec![Slice(Slice::new(None, VarLen(0, 0)))]
+            }
             &TyKind::Adt(AdtId(hir_def::AdtId::EnumId(enum_id)), ref _substs) => {
                 let enum_data = cx.db.enum_data(enum_id);
 
@@ -518,8 +842,20 @@ impl SplitWildcard {
                         .collect()
                 }
             }
-            TyKind::Scalar(Scalar::Char) => unhandled(),
-            TyKind::Scalar(Scalar::Int(..) | Scalar::Uint(..)) => unhandled(),
+            TyKind::Scalar(Scalar::Char) => smallvec![
+                // The valid Unicode Scalar Value ranges.
+                make_range('\u{0000}' as u128, '\u{D7FF}' as u128),
+                make_range('\u{E000}' as u128, '\u{10FFFF}' as u128),
+            ],
+            TyKind::Scalar(Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize)) => {
+                // `usize`/`isize` are not allowed to be matched exhaustively unless the
+                // `precise_pointer_size_matching` feature is enabled.
+                smallvec![NonExhaustive]
+            }
+            TyKind::Scalar(Scalar::Int(..) | Scalar::Uint(..)) => {
+                let (size, _) = IntRange::integral_size_and_signed_bias(pcx.ty).unwrap();
+                smallvec![make_range(0, u128::MAX >> (128 - size))]
+            }
             TyKind::Never if !cx.feature_exhaustive_patterns() && !pcx.is_top_level => {
                 smallvec![NonExhaustive]
             }
@@ -542,7 +878,8 @@ impl SplitWildcard {
         // Since `all_ctors` never contains wildcards, this won't recurse further.
         self.all_ctors =
             self.all_ctors.iter().flat_map(|ctor| ctor.split(pcx, ctors.clone())).collect();
-        self.matrix_ctors = ctors.filter(|c| !c.is_wildcard()).cloned().collect();
+        self.matrix_ctors =
+            ctors.filter(|c| !c.is_wildcard() && !matches!(c, Opaque)).cloned().collect();
     }
 
     /// Whether there are any value constructors for this type that are not present in the matrix.
@@ -686,9 +1023,13 @@ impl Fields {
                     cx.bug(&format!("Unexpected type for `Single` constructor: {:?}", ty_kind))
                 }
             },
-            Slice(..) => {
-                unimplemented!()
-            }
+            Slice(slice) => match ty.kind(&Interner) {
+                TyKind::Slice(ty) | TyKind::Array(ty, _) => {
+                    let arity = slice.arity();
+                    Fields::wildcards_from_tys(cx, (0..arity).map(|_| ty.clone()))
+                }
+                _ => cx.bug(&format!("bad slice pattern {:?} {:?}", constructor, ty)),
+            },
             Str(..) | FloatRange(..) | IntRange(..) | NonExhaustive | Opaque | Missing
             | Wildcard => Fields::Vec(Default::default()),
         };
@@ -714,11 +1055,12 @@ impl Fields {
         let subpatterns_and_indices = self.patterns_and_indices();
         let mut subpatterns =
             subpatterns_and_indices.iter().map(|&(_, p)| pcx.cx.pattern_arena.borrow()[p].clone());
-        // FIXME(iDawer) witnesses are not yet used
-        const UNHANDLED: PatKind = PatKind::Wild;
 
         let pat = match ctor {
             Single | Variant(_) => match pcx.ty.kind(&Interner) {
+                &TyKind::Adt(AdtId(adt), _) if adt_is_box(adt, pcx.cx) => {
+                    PatKind::Deref { subpattern: subpatterns.next().unwrap() }
+                }
                 TyKind::Adt(..) | TyKind::Tuple(..) => {
                     // We want the real indices here.
                     let subpatterns = subpatterns_and_indices
@@ -753,10 +1095,33 @@ impl Fields {
                 }
                 _ => PatKind::Wild,
             },
-            Constructor::Slice(_) => UNHANDLED,
-            Str(_) => UNHANDLED,
-            FloatRange(..) => UNHANDLED,
-            Constructor::IntRange(_) => UNHANDLED,
+            Slice(slice) => match slice.kind {
+                FixedLen(_) => {
+                    PatKind::Slice { prefix: subpatterns.collect(), slice: None, suffix: vec![] }
+                }
+                VarLen(prefix, _) => {
+                    let mut prefix: Vec<_> = subpatterns.by_ref().take(prefix).collect();
+                    if slice.array_len.is_some() {
+                        // Improves diagnostics a bit: if the type is a known-size array, instead
+                        // of reporting `[x, _, .., _, y]`, we prefer to report `[x, .., y]`.
+                        // This is incorrect if the size is not known, since `[_, ..]` captures
+                        // arrays of lengths `>= 1` whereas `[..]` captures any length.
+                        while !prefix.is_empty() && prefix.last().unwrap().is_wildcard() {
+                            prefix.pop();
+                        }
+                    }
+                    let suffix: Vec<_> = if slice.array_len.is_some() {
+                        // Same as above.
+                        subpatterns.skip_while(Pat::is_wildcard).collect()
+                    } else {
+                        subpatterns.collect()
+                    };
+                    let wild = Pat::wildcard_from_ty(pcx.ty.clone());
+                    PatKind::Slice { prefix, slice: Some(wild), suffix }
+                }
+            },
+            &Str(void) | &FloatRange(void) => match void {},
+            IntRange(range) => range.to_pat(pcx.ty),
             NonExhaustive => PatKind::Wild,
             Wildcard => return Pat::wildcard_from_ty(pcx.ty.clone()),
             Opaque => pcx.cx.bug("we should not try to apply an opaque constructor"),
@@ -876,10 +1241,23 @@ impl Fields {
                     .map(|field_pat| (field_pat.field, arena.alloc(field_pat.pattern.clone())));
                 self.replace_with_fieldpats(subpatterns)
             }
+            PatKind::Slice { prefix, suffix, .. } => {
+                let arity = self.len();
+                let suffix_start = arity - suffix.len();
+                let subpatterns: Vec<_> = prefix
+                    .iter()
+                    .cloned()
+                    .enumerate()
+                    .chain(suffix.iter().cloned().enumerate().map(|(i, p)| (suffix_start + i, p)))
+                    .collect();
+                let subpatterns = subpatterns.into_iter().map(|(i, p)| (i, arena.alloc(p)));
+                self.replace_fields_indexed(subpatterns)
+            }
 
             PatKind::Wild
             | PatKind::Binding { .. }
-            | PatKind::LiteralBool { .. }
+            | PatKind::Constant { .. }
+            | PatKind::Range { .. }
             | PatKind::Or { .. } => self.clone(),
         }
     }
@@ -901,3 +1279,12 @@ fn adt_is_box(adt: hir_def::AdtId, cx: &MatchCheckCtx<'_>) -> bool {
         _ => false,
     }
 }
+
+fn array_len(len: &Const) -> Option<usize> {
+    match len.data(&Interner).value {
+        ConstValue::Concrete(chalk_ir::ConcreteConst { interned: ConstScalar::Usize(len) }) => {
+            len.try_into().ok()
+        }
+        _ => None,
+    }
+}
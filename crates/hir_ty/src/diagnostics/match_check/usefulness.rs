@@ -361,7 +361,7 @@ impl PatternFolder for LiteralExpander {
 }
 
 impl Pat {
-    fn _is_wildcard(&self) -> bool {
+    pub(super) fn is_wildcard(&self) -> bool {
         matches!(*self.kind, PatKind::Binding { subpattern: None, .. } | PatKind::Wild)
     }
 }
@@ -1102,6 +1102,8 @@ pub(crate) struct MatchArm {
 }
 
 /// Indicates whether or not a given arm is reachable.
+// FIXME: the unreachable or-pattern branches are not reported yet.
+#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub(crate) enum Reachability {
     /// The arm is reachable. This additionally carries a set of or-pattern branches that have been
@@ -1115,7 +1117,7 @@ pub(crate) enum Reachability {
 /// The output of checking a match for exhaustiveness and arm reachability.
 pub(crate) struct UsefulnessReport {
     /// For each arm of the input, whether that arm is reachable after the arms above it.
-    pub(crate) _arm_usefulness: Vec<(MatchArm, Reachability)>,
+    pub(crate) arm_usefulness: Vec<(MatchArm, Reachability)>,
     /// If the match is exhaustive, this is empty. If not, this contains witnesses for the lack of
     /// exhaustiveness.
     pub(crate) non_exhaustiveness_witnesses: Vec<Pat>,
@@ -1159,12 +1161,12 @@ pub(crate) fn compute_match_usefulness(
         WithWitnesses(pats) => pats.into_iter().map(Witness::single_pattern).collect(),
         NoWitnesses(_) => panic!("bug"),
     };
-    UsefulnessReport { _arm_usefulness: arm_usefulness, non_exhaustiveness_witnesses }
+    UsefulnessReport { arm_usefulness, non_exhaustiveness_witnesses }
 }
 
 pub(crate) type PatternArena = Arena<Pat>;
 
-mod helper {
+pub(super) mod helper {
     use super::MatchCheckCtx;
 
     pub(super) trait PatIdExt: Sized {
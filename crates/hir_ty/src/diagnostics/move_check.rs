@@ -0,0 +1,487 @@
+//! Finds uses of local variables after their value was moved out.
+//!
+//! This is a much simplified version of what the borrow checker does: only
+//! moves of whole locals are tracked, and a local counts as moved at a point
+//! of the body if it is moved on any path leading there.
+
+use std::{mem, sync::Arc};
+
+use chalk_ir::{cast::Cast, Mutability};
+use hir_def::{
+    body::Body,
+    expr::{
+        Array, BinaryOp, BindingAnnotation, Expr, ExprId, LabelId, MatchGuard, Pat, PatId,
+        Statement, UnaryOp,
+    },
+    lang_item::LangItemTarget,
+    resolver::{resolver_for_expr, ValueNs},
+    DefWithBodyId, HasModule,
+};
+use hir_expand::name::Name;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    db::HirDatabase, to_chalk_trait_id, AliasTy, Canonical, CanonicalVarKinds, InEnvironment,
+    InferenceResult, Interner, Substitution, TraitEnvironment, TraitRef, Ty, TyExt, TyKind,
+    TypeWalk,
+};
+
+#[derive(Debug)]
+pub struct UseAfterMove {
+    /// The local which was moved.
+    pub binding: PatId,
+    /// The expression using the moved value.
+    pub expr: ExprId,
+    /// The expression which moved the value out of the local.
+    pub moved_at: ExprId,
+}
+
+pub fn use_after_move(db: &dyn HirDatabase, def: DefWithBodyId) -> Vec<UseAfterMove> {
+    let _p = profile::span("use_after_move");
+    let body = db.body(def);
+    let infer = db.infer(def);
+    let trait_env = match def.as_generic_def_id() {
+        Some(it) => db.trait_environment(it),
+        None => Arc::new(TraitEnvironment::empty(def.module(db.upcast()).krate())),
+    };
+    let copy_trait = match db.lang_item(trait_env.krate, "copy".into()) {
+        Some(LangItemTarget::TraitId(it)) => it,
+        // Without `Copy`, there's no telling what gets moved.
+        _ => return Vec::new(),
+    };
+    let mut checker = MoveChecker {
+        db,
+        def,
+        body: &body,
+        infer: &infer,
+        trait_env,
+        copy_trait,
+        is_copy: FxHashMap::default(),
+        breakables: Vec::new(),
+        reported: FxHashSet::default(),
+        result: Vec::new(),
+    };
+    checker.walk(body.body_expr, Some(Moves::default()));
+    checker.result
+}
+
+/// The locals which may have been moved out of at some point, with the
+/// expression moving them. `None` stands for unreachable code.
+type State = Option<Moves>;
+type Moves = FxHashMap<PatId, ExprId>;
+
+fn join(a: State, b: State) -> State {
+    match (a, b) {
+        (Some(mut a), Some(b)) => {
+            for (binding, moved_at) in b {
+                a.entry(binding).or_insert(moved_at);
+            }
+            Some(a)
+        }
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+struct Breakable {
+    label: Option<Name>,
+    is_loop: bool,
+    breaks: State,
+    continues: State,
+}
+
+struct MoveChecker<'a> {
+    db: &'a dyn HirDatabase,
+    def: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    trait_env: Arc<TraitEnvironment>,
+    copy_trait: hir_def::TraitId,
+    is_copy: FxHashMap<Ty, bool>,
+    breakables: Vec<Breakable>,
+    reported: FxHashSet<ExprId>,
+    result: Vec<UseAfterMove>,
+}
+
+impl MoveChecker<'_> {
+    /// Walks an expression whose value is used, moving it if it's a local.
+    fn walk(&mut self, expr: ExprId, state: State) -> State {
+        let state = state?;
+        let body = self.body;
+        let state = match &body[expr] {
+            Expr::Path(_) => {
+                let mut state = state;
+                if let Some(binding) = self.use_local(expr, &state) {
+                    let infer = self.infer;
+                    if self.is_moved_by_value(&infer[expr]) {
+                        state.entry(binding).or_insert(expr);
+                    }
+                }
+                Some(state)
+            }
+            Expr::If { condition, then_branch, else_branch } => {
+                let state = self.walk(*condition, Some(state));
+                let then_state = self.walk(*then_branch, state.clone());
+                let else_state = match else_branch {
+                    Some(it) => self.walk(*it, state),
+                    None => state,
+                };
+                join(then_state, else_state)
+            }
+            Expr::Block { statements, tail, label, .. } => {
+                self.with_breakable(self.label_name(*label), false, |this| {
+                    let mut state = Some(state);
+                    for statement in statements {
+                        state = match statement {
+                            Statement::Let { pat, initializer, .. } => {
+                                let state = match initializer {
+                                    Some(init) if binds_by_value(body, *pat) => {
+                                        this.walk(*init, state)
+                                    }
+                                    Some(init) => this.walk_place(*init, state),
+                                    None => state,
+                                };
+                                this.bind(*pat, state)
+                            }
+                            Statement::Expr { expr, .. } => this.walk(*expr, state),
+                        };
+                    }
+                    match tail {
+                        Some(tail) => this.walk(*tail, state),
+                        None => state,
+                    }
+                })
+            }
+            Expr::Loop { body: loop_body, label } => {
+                self.walk_loop(Some(state), self.label_name(*label), |this, state| {
+                    (this.walk(*loop_body, state), None)
+                })
+            }
+            Expr::While { condition, body: loop_body, label } => {
+                self.walk_loop(Some(state), self.label_name(*label), |this, state| {
+                    let state = this.walk(*condition, state);
+                    (this.walk(*loop_body, state.clone()), state)
+                })
+            }
+            Expr::For { iterable, pat, body: loop_body, label } => {
+                let state = self.walk(*iterable, Some(state));
+                self.walk_loop(state, self.label_name(*label), |this, state| {
+                    let exit = state.clone();
+                    let state = this.bind(*pat, state);
+                    (this.walk(*loop_body, state), exit)
+                })
+            }
+            Expr::Call { callee, args } => {
+                let state = self.walk_place(*callee, Some(state));
+                self.walk_all(args.iter().copied(), state)
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                let state = if self.receiver_is_borrowed(expr) {
+                    self.walk_place(*receiver, Some(state))
+                } else {
+                    self.walk(*receiver, Some(state))
+                };
+                self.walk_all(args.iter().copied(), state)
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                let state = self.walk_place(*scrutinee, Some(state));
+                let mut result = None;
+                for arm in arms {
+                    let arm_state = self.bind(arm.pat, state.clone());
+                    let arm_state = match &arm.guard {
+                        Some(MatchGuard::If { expr }) => self.walk(*expr, arm_state),
+                        Some(MatchGuard::IfLet { pat, expr }) => {
+                            let arm_state = self.walk_place(*expr, arm_state);
+                            self.bind(*pat, arm_state)
+                        }
+                        None => arm_state,
+                    };
+                    result = join(result, self.walk(arm.expr, arm_state));
+                }
+                result
+            }
+            Expr::Continue { label } => {
+                if let Some(breakable) = self.find_breakable(label.as_ref()) {
+                    breakable.continues = join(breakable.continues.take(), Some(state));
+                }
+                None
+            }
+            Expr::Break { expr: value, label } => {
+                let state = match value {
+                    Some(it) => self.walk(*it, Some(state)),
+                    None => Some(state),
+                };
+                if let Some(breakable) = self.find_breakable(label.as_ref()) {
+                    breakable.breaks = join(breakable.breaks.take(), state);
+                }
+                None
+            }
+            Expr::Return { expr: value } => {
+                if let Some(value) = value {
+                    self.walk(*value, Some(state));
+                }
+                None
+            }
+            Expr::Lambda { body: closure_body, .. } | Expr::Async { body: closure_body } => {
+                // What a closure captures isn't known, so only check the uses
+                // in its body.
+                let breakables = mem::take(&mut self.breakables);
+                self.walk(*closure_body, Some(state.clone()));
+                self.breakables = breakables;
+                Some(state)
+            }
+            Expr::Const { .. } => Some(state),
+            Expr::RecordLit { fields, spread, .. } => {
+                let state = self.walk_all(fields.iter().map(|it| it.expr), Some(state));
+                match spread {
+                    Some(it) => self.walk_place(*it, state),
+                    None => state,
+                }
+            }
+            Expr::Field { .. }
+            | Expr::Index { .. }
+            | Expr::Ref { .. }
+            | Expr::UnaryOp { op: UnaryOp::Deref, .. } => self.walk_place(expr, Some(state)),
+            Expr::BinaryOp { lhs, rhs, op } => match op {
+                Some(BinaryOp::Assignment { op: None }) => {
+                    let state = self.walk(*rhs, Some(state));
+                    match self.local(*lhs) {
+                        // Assigning a new value makes the local usable again.
+                        Some(binding) => state.map(|mut it| {
+                            it.remove(&binding);
+                            it
+                        }),
+                        None => self.walk_place(*lhs, state),
+                    }
+                }
+                Some(BinaryOp::Assignment { op: Some(_) }) | Some(BinaryOp::CmpOp(_)) => {
+                    let state = self.walk_place(*lhs, Some(state));
+                    self.walk_place(*rhs, state)
+                }
+                Some(BinaryOp::LogicOp(_)) => {
+                    let state = self.walk(*lhs, Some(state));
+                    join(state.clone(), self.walk(*rhs, state))
+                }
+                Some(BinaryOp::ArithOp(_)) | None => {
+                    let state = self.walk(*lhs, Some(state));
+                    self.walk(*rhs, state)
+                }
+            },
+            Expr::Array(Array::Repeat { initializer, .. }) => self.walk(*initializer, Some(state)),
+            Expr::Missing
+            | Expr::Yield { .. }
+            | Expr::Await { .. }
+            | Expr::Try { .. }
+            | Expr::TryBlock { .. }
+            | Expr::Cast { .. }
+            | Expr::Box { .. }
+            | Expr::UnaryOp { .. }
+            | Expr::Range { .. }
+            | Expr::Tuple { .. }
+            | Expr::Unsafe { .. }
+            | Expr::MacroStmts { .. }
+            | Expr::Array(_)
+            | Expr::Literal(_) => {
+                let mut children = Vec::new();
+                body[expr].walk_child_exprs(|it| children.push(it));
+                self.walk_all(children.into_iter(), Some(state))
+            }
+        };
+        if self.infer[expr].is_never() {
+            return None;
+        }
+        state
+    }
+
+    /// Walks an expression which is only used as a place, like the operand of
+    /// `&` or the base of a field access, so a local in it isn't moved.
+    fn walk_place(&mut self, expr: ExprId, state: State) -> State {
+        let state = state?;
+        match &self.body[expr] {
+            Expr::Path(_) => {
+                self.use_local(expr, &state);
+                Some(state)
+            }
+            Expr::Field { expr: base, .. }
+            | Expr::Ref { expr: base, .. }
+            | Expr::UnaryOp { expr: base, op: UnaryOp::Deref } => {
+                self.walk_place(*base, Some(state))
+            }
+            Expr::Index { base, index } => {
+                let state = self.walk_place(*base, Some(state));
+                self.walk(*index, state)
+            }
+            _ => self.walk(expr, Some(state)),
+        }
+    }
+
+    fn walk_all(&mut self, exprs: impl Iterator<Item = ExprId>, mut state: State) -> State {
+        for expr in exprs {
+            state = self.walk(expr, state);
+        }
+        state
+    }
+
+    /// Walks a loop until the locals moved in one iteration are known at the
+    /// start of the next one. `iteration` walks one iteration and returns the
+    /// state at its end and the state when the loop condition fails.
+    fn walk_loop(
+        &mut self,
+        state: State,
+        label: Option<Name>,
+        mut iteration: impl FnMut(&mut Self, State) -> (State, State),
+    ) -> State {
+        let mut head = state;
+        loop {
+            self.breakables.push(Breakable {
+                label: label.clone(),
+                is_loop: true,
+                breaks: None,
+                continues: None,
+            });
+            let (end, exit) = iteration(self, head.clone());
+            let breakable = self.breakables.pop().unwrap();
+            let next = join(join(head.clone(), end), breakable.continues);
+            if next == head {
+                return join(exit, breakable.breaks);
+            }
+            head = next;
+        }
+    }
+
+    fn with_breakable(
+        &mut self,
+        label: Option<Name>,
+        is_loop: bool,
+        f: impl FnOnce(&mut Self) -> State,
+    ) -> State {
+        if label.is_none() {
+            return f(self);
+        }
+        self.breakables.push(Breakable { label, is_loop, breaks: None, continues: None });
+        let state = f(self);
+        let breakable = self.breakables.pop().unwrap();
+        join(state, breakable.breaks)
+    }
+
+    fn find_breakable(&mut self, label: Option<&Name>) -> Option<&mut Breakable> {
+        match label {
+            Some(label) => {
+                self.breakables.iter_mut().rev().find(|it| it.label.as_ref() == Some(label))
+            }
+            None => self.breakables.iter_mut().rev().find(|it| it.is_loop),
+        }
+    }
+
+    fn label_name(&self, label: Option<LabelId>) -> Option<Name> {
+        label.map(|it| self.body[it].name.clone())
+    }
+
+    /// Makes the locals bound by `pat` usable, as they get a new value.
+    fn bind(&self, pat: PatId, state: State) -> State {
+        let mut state = state?;
+        let mut stack = vec![pat];
+        while let Some(pat) = stack.pop() {
+            if let Pat::Bind { .. } = self.body[pat] {
+                state.remove(&pat);
+            }
+            self.body[pat].walk_child_pats(|it| stack.push(it));
+        }
+        Some(state)
+    }
+
+    /// Checks the use of a local in `expr`, returning the local.
+    fn use_local(&mut self, expr: ExprId, state: &Moves) -> Option<PatId> {
+        let binding = self.local(expr)?;
+        if let Some(&moved_at) = state.get(&binding) {
+            if self.reported.insert(expr) {
+                self.result.push(UseAfterMove { binding, expr, moved_at });
+            }
+        }
+        Some(binding)
+    }
+
+    fn local(&self, expr: ExprId) -> Option<PatId> {
+        let path = match &self.body[expr] {
+            Expr::Path(it) => it,
+            _ => return None,
+        };
+        let resolver = resolver_for_expr(self.db.upcast(), self.def, expr);
+        match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())? {
+            ValueNs::LocalBinding(it) => Some(it),
+            _ => None,
+        }
+    }
+
+    fn receiver_is_borrowed(&self, method_call: ExprId) -> bool {
+        // Without knowing the method, assume it doesn't take `self`.
+        let (func, subst) = match self.infer.method_resolution(method_call) {
+            Some(it) => it,
+            None => return true,
+        };
+        let sig = self.db.callable_item_signature(func.into()).substitute(&Interner, &subst);
+        match sig.params().first() {
+            Some(self_ty) => matches!(self_ty.kind(&Interner), TyKind::Ref(..)),
+            None => true,
+        }
+    }
+
+    /// Whether using a value of type `ty` moves it. Unknown types and mutable
+    /// references, which are implicitly reborrowed, are treated as `Copy`.
+    fn is_moved_by_value(&mut self, ty: &Ty) -> bool {
+        if matches!(
+            ty.kind(&Interner),
+            TyKind::Scalar(_)
+                | TyKind::Ref(Mutability::Not, ..)
+                | TyKind::Raw(..)
+                | TyKind::Function(_)
+                | TyKind::Never
+        ) {
+            return false;
+        }
+        let mut unknown = false;
+        ty.walk(&mut |ty| {
+            if matches!(
+                ty.kind(&Interner),
+                TyKind::Error
+                    | TyKind::InferenceVar(..)
+                    | TyKind::AssociatedType(..)
+                    | TyKind::Alias(AliasTy::Projection(_))
+                    | TyKind::Closure(..)
+                    | TyKind::FnDef(..)
+                    | TyKind::Ref(Mutability::Mut, ..)
+            ) {
+                unknown = true;
+            }
+        });
+        if unknown {
+            return false;
+        }
+        if let Some(&is_copy) = self.is_copy.get(ty) {
+            return !is_copy;
+        }
+        let trait_ref = TraitRef {
+            trait_id: to_chalk_trait_id(self.copy_trait),
+            substitution: Substitution::from1(&Interner, ty.clone()),
+        };
+        let goal = Canonical {
+            value: InEnvironment::new(&self.trait_env.env, trait_ref.cast(&Interner)),
+            binders: CanonicalVarKinds::empty(&Interner),
+        };
+        let is_copy = self.db.trait_solve(self.trait_env.krate, goal).is_some();
+        self.is_copy.insert(ty.clone(), is_copy);
+        !is_copy
+    }
+}
+
+/// Whether `pat` binds the whole value it's matched against, moving it.
+fn binds_by_value(body: &Body, pat: PatId) -> bool {
+    matches!(
+        body[pat],
+        Pat::Bind {
+            mode: BindingAnnotation::Unannotated | BindingAnnotation::Mutable,
+            subpat: None,
+            ..
+        }
+    )
+}
@@ -14,7 +14,7 @@ use hir_def::{
     intern::{Internable, Interned},
     item_scope::ItemInNs,
     path::{Path, PathKind},
-    type_ref::{TraitBoundModifier, TypeBound, TypeRef},
+    type_ref::{ConstRef, TraitBoundModifier, TypeBound, TypeRef},
     visibility::Visibility,
     AssocContainerId, HasModule, Lookup, ModuleId, TraitId,
 };
@@ -41,7 +41,7 @@ pub struct HirFormatter<'a> {
     curr_size: usize,
     pub(crate) max_size: Option<usize>,
     omit_verbose_types: bool,
-    display_target: DisplayTarget,
+    pub(crate) display_target: DisplayTarget,
 }
 
 pub trait HirDisplay {
@@ -1062,7 +1062,9 @@ impl HirDisplay for TypeRef {
             TypeRef::Array(inner, len) => {
                 write!(f, "[")?;
                 inner.hir_fmt(f)?;
-                write!(f, "; {}]", len)?;
+                write!(f, "; ")?;
+                len.hir_fmt(f)?;
+                write!(f, "]")?;
             }
             TypeRef::Slice(inner) => {
                 write!(f, "[")?;
@@ -1086,6 +1088,10 @@ impl HirDisplay for TypeRef {
                     }
                 }
             }
+            TypeRef::ForLifetime(lifetimes, inner) => {
+                write!(f, "for<{}> ", lifetimes.iter().format(", "))?;
+                inner.hir_fmt(f)?;
+            }
             TypeRef::ImplTrait(bounds) => {
                 write!(f, "impl ")?;
                 f.write_joined(bounds, " + ")?;
@@ -1132,6 +1138,32 @@ impl HirDisplay for TypeBound {
     }
 }
 
+impl HirDisplay for ConstRef {
+    fn hir_fmt(&self, f: &mut HirFormatter) -> Result<(), HirDisplayError> {
+        let fmt_operand = |operand: &ConstRef, f: &mut HirFormatter| match operand {
+            ConstRef::BinaryOp(..) => {
+                write!(f, "(")?;
+                operand.hir_fmt(f)?;
+                write!(f, ")")
+            }
+            _ => operand.hir_fmt(f),
+        };
+        match self {
+            ConstRef::Scalar(scalar) => write!(f, "{}", scalar),
+            ConstRef::Path(path) => path.hir_fmt(f),
+            ConstRef::UnaryOp(op, operand) => {
+                write!(f, "{}", op)?;
+                fmt_operand(operand, f)
+            }
+            ConstRef::BinaryOp(lhs, op, rhs) => {
+                fmt_operand(lhs, f)?;
+                write!(f, " {} ", op)?;
+                fmt_operand(rhs, f)
+            }
+        }
+    }
+}
+
 impl HirDisplay for Path {
     fn hir_fmt(&self, f: &mut HirFormatter) -> Result<(), HirDisplayError> {
         match (self.type_anchor(), self.kind()) {
@@ -1205,6 +1237,7 @@ impl HirDisplay for hir_def::path::GenericArg {
         match self {
             hir_def::path::GenericArg::Type(ty) => ty.hir_fmt(f),
             hir_def::path::GenericArg::Lifetime(lifetime) => write!(f, "{}", lifetime.name),
+            hir_def::path::GenericArg::Const(c) => c.hir_fmt(f),
         }
     }
 }
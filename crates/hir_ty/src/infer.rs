@@ -16,7 +16,7 @@
 use std::ops::Index;
 use std::sync::Arc;
 
-use chalk_ir::{cast::Cast, DebruijnIndex, Mutability, Safety, Scalar};
+use chalk_ir::{cast::Cast, DebruijnIndex, Mutability, Safety, Scalar, TyVariableKind};
 use hir_def::{
     body::Body,
     data::{ConstData, FunctionData, StaticData},
@@ -26,18 +26,21 @@ use hir_def::{
     resolver::{HasResolver, ResolveValueResult, Resolver, TypeNs, ValueNs},
     type_ref::TypeRef,
     AdtId, AssocItemId, DefWithBodyId, EnumVariantId, FieldId, FunctionId, HasModule, Lookup,
-    TraitId, TypeAliasId, VariantId,
+    ModuleDefId, TraitId, TypeAliasId, VariantId,
 };
-use hir_expand::name::name;
+use hir_expand::name::{name, Name};
 use la_arena::ArenaMap;
 use rustc_hash::FxHashMap;
 use stdx::impl_from;
 use syntax::SmolStr;
 
 use crate::{
-    db::HirDatabase, fold_tys, infer::coerce::CoerceMany, lower::ImplTraitLoweringMode,
+    db::HirDatabase,
+    fold_tys,
+    infer::coerce::CoerceMany,
+    lower::{GenericArgCountMismatch, ImplTraitLoweringMode, TyLoweringDiagnostic},
     to_assoc_type_id, AliasEq, AliasTy, DomainGoal, Goal, InEnvironment, Interner, ProjectionTy,
-    Substitution, TraitEnvironment, TraitRef, Ty, TyBuilder, TyExt, TyKind,
+    Substitution, TraitEnvironment, TraitRef, Ty, TyBuilder, TyExt, TyKind, TypeWalk,
 };
 
 // This lint has a false positive here. See the link below for details.
@@ -71,13 +74,33 @@ pub(crate) fn infer_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<Infer
     Arc::new(ctx.resolve_all())
 }
 
+/// Inference can only depend on itself through the evaluation of constants,
+/// e.g. with `const N: usize = { [0; N]; 0 };`, which is an error.
+pub(crate) fn infer_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &DefWithBodyId,
+) -> Arc<InferenceResult> {
+    Arc::new(InferenceResult::default())
+}
+
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-enum ExprOrPatId {
+pub enum ExprOrPatId {
     ExprId(ExprId),
     PatId(PatId),
 }
 impl_from!(ExprId, PatId for ExprOrPatId);
 
+/// Where a type reference lowered during inference is written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TypeRefSource {
+    /// The parameter and return types of the function.
+    Signature,
+    /// An expression or pattern, like the generic arguments of a path, or the
+    /// type of a `let` statement for its pattern.
+    Body(ExprOrPatId),
+}
+
 /// Binding modes inferred for patterns.
 /// <https://doc.rust-lang.org/reference/patterns.html#binding-modes>
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -120,8 +143,88 @@ pub(crate) type InferResult<T> = Result<InferOk<T>, TypeError>;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum InferenceDiagnostic {
-    NoSuchField { expr: ExprId },
-    BreakOutsideOfLoop { expr: ExprId },
+    NoSuchField {
+        expr: ExprId,
+    },
+    BreakOutsideOfLoop {
+        expr: ExprId,
+    },
+    UnresolvedField {
+        expr: ExprId,
+        receiver: Ty,
+        name: Name,
+    },
+    UnresolvedMethodCall {
+        expr: ExprId,
+        receiver: Ty,
+        name: Name,
+    },
+    UnresolvedAssocItem {
+        id: ExprOrPatId,
+        ty: Ty,
+        name: Name,
+    },
+    PrivateField {
+        expr: ExprId,
+        field: FieldId,
+    },
+    PrivateAssocItem {
+        id: ExprOrPatId,
+        item: AssocItemId,
+    },
+    PrivateItem {
+        id: ExprOrPatId,
+        item: ModuleDefId,
+    },
+    UnsatisfiedTraitBound {
+        expr: ExprId,
+        trait_ref: TraitRef,
+    },
+    TyLowering {
+        source: TypeRefSource,
+        diagnostic: TyLoweringDiagnostic,
+    },
+    /// The generic arguments of the method call `expr` don't match the
+    /// parameters of the method.
+    MismatchedGenericArgCount {
+        expr: ExprId,
+        mismatch: GenericArgCountMismatch,
+    },
+}
+
+impl InferenceDiagnostic {
+    /// The type on which something failed to resolve, if any.
+    fn receiver_mut(&mut self) -> Option<&mut Ty> {
+        match self {
+            InferenceDiagnostic::UnresolvedField { receiver, .. }
+            | InferenceDiagnostic::UnresolvedMethodCall { receiver, .. }
+            | InferenceDiagnostic::UnresolvedAssocItem { ty: receiver, .. } => Some(receiver),
+            InferenceDiagnostic::NoSuchField { .. }
+            | InferenceDiagnostic::BreakOutsideOfLoop { .. }
+            | InferenceDiagnostic::PrivateField { .. }
+            | InferenceDiagnostic::PrivateAssocItem { .. }
+            | InferenceDiagnostic::PrivateItem { .. }
+            | InferenceDiagnostic::UnsatisfiedTraitBound { .. }
+            | InferenceDiagnostic::TyLowering { .. }
+            | InferenceDiagnostic::MismatchedGenericArgCount { .. } => None,
+        }
+    }
+}
+
+/// Whether a diagnostic involving `ty` is worth reporting. Inference gives up
+/// on a lot of code, and the unknown types and unnormalized projections it
+/// leaves behind make for errors which aren't real.
+pub(crate) fn is_reliable(ty: &Ty) -> bool {
+    let mut reliable = true;
+    ty.walk(&mut |ty| {
+        if matches!(
+            ty.kind(&Interner),
+            TyKind::Error | TyKind::AssociatedType(..) | TyKind::Alias(AliasTy::Projection(_))
+        ) {
+            reliable = false;
+        }
+    });
+    reliable
 }
 
 /// A mismatch between an expected and an inferred type.
@@ -209,7 +312,7 @@ pub enum Adjust {
 /// The target type is `U` in both cases, with the region and mutability
 /// being those shared by both the receiver and the returned reference.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct OverloadedDeref(Mutability);
+pub struct OverloadedDeref(pub Mutability);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum AutoBorrow {
@@ -350,6 +453,9 @@ struct InferenceContext<'a> {
     return_ty: Ty,
     diverges: Diverges,
     breakables: Vec<BreakableContext>,
+    /// The trait bounds of the functions called in the body, checked once
+    /// inference is done.
+    call_bounds: Vec<(ExprId, TraitRef)>,
 }
 
 #[derive(Clone, Debug)]
@@ -386,6 +492,7 @@ impl<'a> InferenceContext<'a> {
             resolver,
             diverges: Diverges::Maybe,
             breakables: Vec::new(),
+            call_bounds: Vec::new(),
         }
     }
 
@@ -413,6 +520,38 @@ impl<'a> InferenceContext<'a> {
         for (_, subst) in result.method_resolutions.values_mut() {
             *subst = self.table.resolve_completely(subst.clone());
         }
+        let diagnostics = std::mem::take(&mut result.diagnostics);
+        result.diagnostics = diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                if let Some(receiver) = diagnostic.receiver_mut() {
+                    *receiver = self.table.resolve_completely(receiver.clone());
+                    if !is_reliable(receiver) {
+                        return None;
+                    }
+                }
+                Some(diagnostic)
+            })
+            .collect();
+        for (expr, trait_ref) in std::mem::take(&mut self.call_bounds) {
+            let trait_ref = self.table.resolve_completely(trait_ref);
+            let reliable = trait_ref
+                .substitution
+                .iter(&Interner)
+                .filter_map(|arg| arg.ty(&Interner))
+                .all(is_reliable);
+            if !reliable {
+                continue;
+            }
+            let goal: Goal = trait_ref.clone().cast(&Interner);
+            let canonicalized =
+                self.table.canonicalize(InEnvironment::new(&self.trait_env.env, goal));
+            if self.db.trait_solve(self.trait_env.krate, canonicalized.value).is_none() {
+                result
+                    .diagnostics
+                    .push(InferenceDiagnostic::UnsatisfiedTraitBound { expr, trait_ref });
+            }
+        }
         for adjustment in result.expr_adjustments.values_mut().flatten() {
             adjustment.target = self.table.resolve_completely(adjustment.target.clone());
         }
@@ -454,21 +593,44 @@ impl<'a> InferenceContext<'a> {
         self.result.diagnostics.push(diagnostic);
     }
 
+    fn push_ty_lowering_diagnostics(
+        &mut self,
+        source: TypeRefSource,
+        diagnostics: Vec<TyLoweringDiagnostic>,
+    ) {
+        for diagnostic in diagnostics {
+            self.push_diagnostic(InferenceDiagnostic::TyLowering { source, diagnostic });
+        }
+    }
+
+    /// Whether the type something is looked up on is known well enough for a
+    /// failed lookup to be an error. A plain type variable might still turn
+    /// out to be a type which has the item.
+    fn is_known_receiver(&mut self, ty: &Ty) -> bool {
+        !matches!(
+            self.resolve_ty_shallow(ty).kind(&Interner),
+            TyKind::Error | TyKind::InferenceVar(_, TyVariableKind::General)
+        )
+    }
+
     fn make_ty_with_mode(
         &mut self,
         type_ref: &TypeRef,
         impl_trait_mode: ImplTraitLoweringMode,
+        source: TypeRefSource,
     ) -> Ty {
         // FIXME use right resolver for block
         let ctx = crate::lower::TyLoweringContext::new(self.db, &self.resolver)
-            .with_impl_trait_mode(impl_trait_mode);
+            .with_impl_trait_mode(impl_trait_mode)
+            .with_diagnostics();
         let ty = ctx.lower_ty(type_ref);
+        self.push_ty_lowering_diagnostics(source, ctx.take_diagnostics());
         let ty = self.insert_type_vars(ty);
         self.normalize_associated_types_in(ty)
     }
 
-    fn make_ty(&mut self, type_ref: &TypeRef) -> Ty {
-        self.make_ty_with_mode(type_ref, ImplTraitLoweringMode::Disallowed)
+    fn make_ty(&mut self, type_ref: &TypeRef, source: TypeRefSource) -> Ty {
+        self.make_ty_with_mode(type_ref, ImplTraitLoweringMode::Disallowed, source)
     }
 
     /// Replaces Ty::Unknown by a new type var, so we can maybe still infer it.
@@ -686,19 +848,21 @@ impl<'a> InferenceContext<'a> {
     }
 
     fn collect_const(&mut self, data: &ConstData) {
-        self.return_ty = self.make_ty(&data.type_ref);
+        self.return_ty = self.make_ty(&data.type_ref, TypeRefSource::Signature);
     }
 
     fn collect_static(&mut self, data: &StaticData) {
-        self.return_ty = self.make_ty(&data.type_ref);
+        self.return_ty = self.make_ty(&data.type_ref, TypeRefSource::Signature);
     }
 
     fn collect_fn(&mut self, data: &FunctionData) {
         let body = Arc::clone(&self.body); // avoid borrow checker problem
         let ctx = crate::lower::TyLoweringContext::new(self.db, &self.resolver)
-            .with_impl_trait_mode(ImplTraitLoweringMode::Param);
+            .with_impl_trait_mode(ImplTraitLoweringMode::Param)
+            .with_diagnostics();
         let param_tys =
             data.params.iter().map(|type_ref| ctx.lower_ty(type_ref)).collect::<Vec<_>>();
+        self.push_ty_lowering_diagnostics(TypeRefSource::Signature, ctx.take_diagnostics());
         for (ty, pat) in param_tys.into_iter().zip(body.params.iter()) {
             let ty = self.insert_type_vars(ty);
             let ty = self.normalize_associated_types_in(ty);
@@ -711,7 +875,12 @@ impl<'a> InferenceContext<'a> {
         } else {
             &*data.ret_type
         };
-        let return_ty = self.make_ty_with_mode(return_ty, ImplTraitLoweringMode::Disallowed); // FIXME implement RPIT
+        // FIXME implement RPIT
+        let return_ty = self.make_ty_with_mode(
+            return_ty,
+            ImplTraitLoweringMode::Disallowed,
+            TypeRefSource::Signature,
+        );
         self.return_ty = return_ty;
     }
 
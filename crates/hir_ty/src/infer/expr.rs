@@ -17,7 +17,6 @@ use hir_def::{
     AssocContainerId, FieldId, FunctionId, Lookup,
 };
 use hir_expand::name::{name, Name};
-use stdx::always;
 use syntax::ast::RangeOp;
 
 use crate::{
@@ -31,13 +30,14 @@ use crate::{
     static_lifetime, to_chalk_trait_id,
     traits::FnTrait,
     utils::{generics, Generics},
-    AdtId, Binders, CallableDefId, FnPointer, FnSig, FnSubst, InEnvironment, Interner,
+    AdtId, Binders, CallableDefId, Canonical, FnPointer, FnSig, FnSubst, InEnvironment, Interner,
     ProjectionTyExt, Rawness, Scalar, Substitution, TraitRef, Ty, TyBuilder, TyExt, TyKind,
+    WhereClause,
 };
 
 use super::{
     find_breakable, BindingMode, BreakableContext, Diverges, Expectation, InferenceContext,
-    InferenceDiagnostic, TypeMismatch,
+    InferenceDiagnostic, TypeMismatch, TypeRefSource,
 };
 
 impl<'a> InferenceContext<'a> {
@@ -267,7 +267,7 @@ impl<'a> InferenceContext<'a> {
                 // collect explicitly written argument types
                 for arg_type in arg_types.iter() {
                     let arg_ty = if let Some(type_ref) = arg_type {
-                        self.make_ty(type_ref)
+                        self.make_ty(type_ref, TypeRefSource::Body(tgt_expr.into()))
                     } else {
                         self.table.new_type_var()
                     };
@@ -276,7 +276,7 @@ impl<'a> InferenceContext<'a> {
 
                 // add return type
                 let ret_ty = match ret_type {
-                    Some(type_ref) => self.make_ty(type_ref),
+                    Some(type_ref) => self.make_ty(type_ref, TypeRefSource::Body(tgt_expr.into())),
                     None => self.table.new_type_var(),
                 };
                 sig_tys.push(ret_ty.clone());
@@ -342,7 +342,7 @@ impl<'a> InferenceContext<'a> {
                     }
                     None => (Vec::new(), self.err_ty()),
                 };
-                self.register_obligations_for_call(&callee_ty);
+                self.register_obligations_for_call(&callee_ty, Some(tgt_expr));
 
                 let expected_inputs = self.expected_inputs_for_expected_output(
                     expected,
@@ -501,7 +501,7 @@ impl<'a> InferenceContext<'a> {
             }
             Expr::Field { expr, name } => {
                 let receiver_ty = self.infer_expr_inner(*expr, &Expectation::none());
-                let canonicalized = self.canonicalize(receiver_ty);
+                let canonicalized = self.canonicalize(receiver_ty.clone());
 
                 let mut autoderef = Autoderef::new(
                     self.db,
@@ -511,6 +511,9 @@ impl<'a> InferenceContext<'a> {
                         environment: self.trait_env.env.clone(),
                     },
                 );
+                // The first field we can't access, which we fall back to if
+                // there is no visible one, with its type.
+                let mut private_field = None;
                 let ty = autoderef.by_ref().find_map(|(derefed_ty, _)| {
                     let def_db = self.db.upcast();
                     let module = self.resolver.module();
@@ -522,51 +525,63 @@ impl<'a> InferenceContext<'a> {
                             })
                             .unwrap_or(true)
                     };
-                    match canonicalized.decanonicalize_ty(derefed_ty.value).kind(&Interner) {
-                        TyKind::Tuple(_, substs) => name.as_tuple_index().and_then(|idx| {
-                            substs
-                                .as_slice(&Interner)
-                                .get(idx)
-                                .map(|a| a.assert_ty_ref(&Interner))
-                                .cloned()
-                        }),
-                        TyKind::Adt(AdtId(hir_def::AdtId::StructId(s)), parameters) => {
-                            let local_id = self.db.struct_data(*s).variant_data.field(name)?;
-                            let field = FieldId { parent: (*s).into(), local_id };
-                            if is_visible(&field) {
-                                self.write_field_resolution(tgt_expr, field);
-                                Some(
-                                    self.db.field_types((*s).into())[field.local_id]
-                                        .clone()
-                                        .substitute(&Interner, &parameters),
-                                )
-                            } else {
-                                None
+                    let (field, parameters) =
+                        match canonicalized.decanonicalize_ty(derefed_ty.value).kind(&Interner) {
+                            TyKind::Tuple(_, substs) => {
+                                return name.as_tuple_index().and_then(|idx| {
+                                    substs
+                                        .as_slice(&Interner)
+                                        .get(idx)
+                                        .map(|a| a.assert_ty_ref(&Interner))
+                                        .cloned()
+                                })
                             }
-                        }
-                        TyKind::Adt(AdtId(hir_def::AdtId::UnionId(u)), parameters) => {
-                            let local_id = self.db.union_data(*u).variant_data.field(name)?;
-                            let field = FieldId { parent: (*u).into(), local_id };
-                            if is_visible(&field) {
-                                self.write_field_resolution(tgt_expr, field);
-                                Some(
-                                    self.db.field_types((*u).into())[field.local_id]
-                                        .clone()
-                                        .substitute(&Interner, &parameters),
-                                )
-                            } else {
-                                None
+                            TyKind::Adt(AdtId(hir_def::AdtId::StructId(s)), parameters) => {
+                                let local_id = self.db.struct_data(*s).variant_data.field(name)?;
+                                (FieldId { parent: (*s).into(), local_id }, parameters.clone())
                             }
+                            TyKind::Adt(AdtId(hir_def::AdtId::UnionId(u)), parameters) => {
+                                let local_id = self.db.union_data(*u).variant_data.field(name)?;
+                                (FieldId { parent: (*u).into(), local_id }, parameters.clone())
+                            }
+                            _ => return None,
+                        };
+                    let ty = self.db.field_types(field.parent)[field.local_id]
+                        .clone()
+                        .substitute(&Interner, &parameters);
+                    if is_visible(&field) {
+                        self.write_field_resolution(tgt_expr, field);
+                        Some(ty)
+                    } else {
+                        if private_field.is_none() {
+                            private_field = Some((field, ty));
                         }
-                        _ => None,
+                        None
                     }
                 });
-                let ty = match ty {
-                    Some(ty) => {
+                let ty = match (ty, private_field) {
+                    (Some(ty), _) => {
                         self.write_expr_adj(*expr, self.auto_deref_adjust_steps(&autoderef));
                         ty
                     }
-                    None => self.err_ty(),
+                    (None, Some((field, ty))) => {
+                        self.write_field_resolution(tgt_expr, field);
+                        self.push_diagnostic(InferenceDiagnostic::PrivateField {
+                            expr: tgt_expr,
+                            field,
+                        });
+                        ty
+                    }
+                    (None, None) => {
+                        if self.is_known_receiver(&receiver_ty) {
+                            self.push_diagnostic(InferenceDiagnostic::UnresolvedField {
+                                expr: tgt_expr,
+                                receiver: receiver_ty,
+                                name: name.clone(),
+                            });
+                        }
+                        self.err_ty()
+                    }
                 };
                 let ty = self.insert_type_vars(ty);
                 self.normalize_associated_types_in(ty)
@@ -582,7 +597,7 @@ impl<'a> InferenceContext<'a> {
             Expr::Cast { expr, type_ref } => {
                 // FIXME: propagate the "castable to" expectation (and find a test case that shows this is necessary)
                 let _inner_ty = self.infer_expr_inner(*expr, &Expectation::none());
-                let cast_ty = self.make_ty(type_ref);
+                let cast_ty = self.make_ty(type_ref, TypeRefSource::Body(tgt_expr.into()));
                 // FIXME check the cast...
                 cast_ty
             }
@@ -800,8 +815,9 @@ impl<'a> InferenceContext<'a> {
                             ),
                         );
 
-                        let repeat_expr = &self.body.exprs[repeat];
-                        consteval::eval_usize(repeat_expr)
+                        consteval::eval_const(self.db, self.owner, &self.body, &self.result, repeat)
+                            .ok()
+                            .and_then(|it| it.as_usize())
                     }
                 };
 
@@ -882,7 +898,8 @@ impl<'a> InferenceContext<'a> {
         self.write_method_resolution(tgt_expr, func, subst.clone());
 
         let method_ty = self.db.value_ty(func.into()).substitute(&Interner, &subst);
-        self.register_obligations_for_call(&method_ty);
+        // Unimplemented operators are reported by type mismatches instead.
+        self.register_obligations_for_call(&method_ty, None);
 
         self.infer_expr_coerce(rhs, &Expectation::has_type(rhs_ty.clone()));
 
@@ -920,7 +937,7 @@ impl<'a> InferenceContext<'a> {
                 Statement::Let { pat, type_ref, initializer } => {
                     let decl_ty = type_ref
                         .as_ref()
-                        .map(|tr| self.make_ty(tr))
+                        .map(|tr| self.make_ty(tr, TypeRefSource::Body((*pat).into())))
                         .unwrap_or_else(|| self.err_ty());
 
                     // Always use the declared type when specified
@@ -990,11 +1007,47 @@ impl<'a> InferenceContext<'a> {
                 method_name,
             )
         });
+        let resolved = match resolved {
+            Some(it) => Some(it),
+            // Like rustc, go on with a method we can't call from here.
+            None => match self.lookup_private_method(&canonicalized_receiver.value, method_name) {
+                Some((ty, func)) => {
+                    self.push_diagnostic(InferenceDiagnostic::PrivateAssocItem {
+                        id: tgt_expr.into(),
+                        item: func.into(),
+                    });
+                    Some((ty, func))
+                }
+                None => {
+                    if self.is_known_receiver(&receiver_ty) {
+                        self.push_diagnostic(InferenceDiagnostic::UnresolvedMethodCall {
+                            expr: tgt_expr,
+                            receiver: receiver_ty.clone(),
+                            name: method_name.clone(),
+                        });
+                    }
+                    None
+                }
+            },
+        };
         let (receiver_ty, method_ty, substs) = match resolved {
             Some((ty, func)) => {
                 let ty = canonicalized_receiver.decanonicalize_ty(ty);
+                if let Some(generic_args) = generic_args {
+                    let mismatches = crate::lower::generic_arg_count_mismatches(
+                        self.db,
+                        func.into(),
+                        generic_args,
+                    );
+                    for mismatch in mismatches {
+                        self.push_diagnostic(InferenceDiagnostic::MismatchedGenericArgCount {
+                            expr: tgt_expr,
+                            mismatch,
+                        });
+                    }
+                }
                 let generics = generics(self.db.upcast(), func.into());
-                let substs = self.substs_for_method_call(generics, generic_args, &ty);
+                let substs = self.substs_for_method_call(tgt_expr, generics, generic_args, &ty);
                 self.write_method_resolution(tgt_expr, func, substs.clone());
                 (ty, self.db.value_ty(func.into()), substs)
             }
@@ -1005,7 +1058,7 @@ impl<'a> InferenceContext<'a> {
             ),
         };
         let method_ty = method_ty.substitute(&Interner, &substs);
-        self.register_obligations_for_call(&method_ty);
+        self.register_obligations_for_call(&method_ty, Some(tgt_expr));
         let (formal_receiver_ty, param_tys, ret_ty) = match method_ty.callable_sig(self.db) {
             Some(sig) => {
                 if !sig.params().is_empty() {
@@ -1025,6 +1078,27 @@ impl<'a> InferenceContext<'a> {
         self.normalize_associated_types_in(ret_ty)
     }
 
+    /// Looks up `name` on `receiver` ignoring visibility, for when it only
+    /// fails to resolve because the method isn't visible from here.
+    fn lookup_private_method(
+        &self,
+        receiver: &Canonical<Ty>,
+        name: &Name,
+    ) -> Option<(Ty, FunctionId)> {
+        let traits_in_scope = self.resolver.traits_in_scope(self.db.upcast());
+        self.resolver.krate().and_then(|krate| {
+            method_resolution::lookup_method(
+                receiver,
+                self.db,
+                self.trait_env.clone(),
+                krate,
+                &traits_in_scope,
+                None,
+                name,
+            )
+        })
+    }
+
     fn expected_inputs_for_expected_output(
         &mut self,
         expected_output: &Expectation,
@@ -1106,6 +1180,7 @@ impl<'a> InferenceContext<'a> {
 
     fn substs_for_method_call(
         &mut self,
+        expr: ExprId,
         def_generics: Generics,
         generic_args: Option<&GenericArgs>,
         receiver_ty: &Ty,
@@ -1134,10 +1209,10 @@ impl<'a> InferenceContext<'a> {
             {
                 match arg {
                     GenericArg::Type(type_ref) => {
-                        let ty = self.make_ty(type_ref);
+                        let ty = self.make_ty(type_ref, TypeRefSource::Body(expr.into()));
                         substs.push(ty);
                     }
-                    GenericArg::Lifetime(_) => {}
+                    GenericArg::Lifetime(_) | GenericArg::Const(_) => {}
                 }
             }
         };
@@ -1149,18 +1224,25 @@ impl<'a> InferenceContext<'a> {
         Substitution::from_iter(&Interner, substs)
     }
 
-    fn register_obligations_for_call(&mut self, callable_ty: &Ty) {
+    /// Registers the where clauses of the called function. If `call` is
+    /// given, the trait bounds among them are also checked at the end of
+    /// inference.
+    fn register_obligations_for_call(&mut self, callable_ty: &Ty, call: Option<ExprId>) {
         let callable_ty = self.resolve_ty_shallow(callable_ty);
         if let TyKind::FnDef(fn_def, parameters) = callable_ty.kind(&Interner) {
             let def: CallableDefId = from_chalk(self.db, *fn_def);
             let generic_predicates = self.db.generic_predicates(def.into());
             for predicate in generic_predicates.iter() {
-                let (predicate, binders) = predicate
-                    .clone()
-                    .substitute(&Interner, parameters)
-                    .into_value_and_skipped_binders();
-                always!(binders.len(&Interner) == 0); // quantified where clauses not yet handled
-                self.push_obligation(predicate.cast(&Interner));
+                let predicate = predicate.clone().substitute(&Interner, parameters);
+                if let Some(call) = call {
+                    if let WhereClause::Implemented(trait_ref) =
+                        crate::erase_late_bound_lifetimes(predicate.clone())
+                    {
+                        self.call_bounds.push((call, trait_ref));
+                    }
+                }
+                // Higher-ranked bounds become `forall` goals
+                self.table.register_obligation(predicate.cast(&Interner));
             }
             // add obligation for trait implementation, if this is a trait method
             match def {
@@ -1172,10 +1254,12 @@ impl<'a> InferenceContext<'a> {
                             &*parameters,
                             generics(self.db.upcast(), trait_.into()).len(),
                         );
-                        self.push_obligation(
-                            TraitRef { trait_id: to_chalk_trait_id(trait_), substitution: substs }
-                                .cast(&Interner),
-                        );
+                        let trait_ref =
+                            TraitRef { trait_id: to_chalk_trait_id(trait_), substitution: substs };
+                        if let Some(call) = call {
+                            self.call_bounds.push((call, trait_ref.clone()));
+                        }
+                        self.push_obligation(trait_ref.cast(&Interner));
                     }
                 }
                 CallableDefId::StructId(_) | CallableDefId::EnumVariantId(_) => {}
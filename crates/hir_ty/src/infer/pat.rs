@@ -243,10 +243,12 @@ impl<'a> InferenceContext<'a> {
                 pat_ty
             }
             Pat::Wild => expected.clone(),
-            Pat::Range { start, end } => {
-                let start_ty = self.infer_expr(*start, &Expectation::has_type(expected.clone()));
-                let end_ty = self.infer_expr(*end, &Expectation::has_type(start_ty));
-                end_ty
+            Pat::Range { start, end, .. } => {
+                let mut ty = expected.clone();
+                for bound in start.iter().chain(end) {
+                    ty = self.infer_expr(*bound, &Expectation::has_type(ty));
+                }
+                ty
             }
             Pat::Lit(expr) => self.infer_expr(*expr, &Expectation::has_type(expected.clone())),
             Pat::Box { inner } => match self.resolve_boxed_box() {
@@ -4,9 +4,9 @@ use std::iter;
 
 use chalk_ir::cast::Cast;
 use hir_def::{
-    path::{Path, PathSegment},
+    path::{Path, PathKind, PathSegment},
     resolver::{ResolveValueResult, Resolver, TypeNs, ValueNs},
-    AdtId, AssocContainerId, AssocItemId, EnumVariantId, Lookup,
+    AdtId, AssocContainerId, AssocItemId, EnumVariantId, Lookup, ModuleDefId,
 };
 use hir_expand::name::Name;
 
@@ -15,7 +15,7 @@ use crate::{
     ValueTyDefId,
 };
 
-use super::{ExprOrPatId, InferenceContext, TraitRef};
+use super::{ExprOrPatId, InferenceContext, InferenceDiagnostic, TraitRef, TypeRefSource};
 
 impl<'a> InferenceContext<'a> {
     pub(super) fn infer_path(
@@ -41,7 +41,7 @@ impl<'a> InferenceContext<'a> {
                 // This can't actually happen syntax-wise
                 return None;
             }
-            let ty = self.make_ty(type_ref);
+            let ty = self.make_ty(type_ref, TypeRefSource::Body(id));
             let remaining_segments_for_ty = path.segments().take(path.segments().len() - 1);
             let ctx = crate::lower::TyLoweringContext::new(self.db, resolver);
             let (ty, _) = ctx.lower_ty_relative_path(ty, None, remaining_segments_for_ty);
@@ -55,7 +55,10 @@ impl<'a> InferenceContext<'a> {
                 resolver.resolve_path_in_value_ns(self.db.upcast(), path.mod_path())?;
 
             match value_or_partial {
-                ResolveValueResult::ValueNs(it) => (it, None),
+                ResolveValueResult::ValueNs(it) => {
+                    self.check_item_visibility(resolver, path, &it, id);
+                    (it, None)
+                }
                 ResolveValueResult::Partial(def, remaining_index) => {
                     self.resolve_assoc_item(def, path, remaining_index, id)?
                 }
@@ -96,8 +99,9 @@ impl<'a> InferenceContext<'a> {
         };
 
         let parent_substs = self_subst.unwrap_or_else(|| Substitution::empty(&Interner));
-        let ctx = crate::lower::TyLoweringContext::new(self.db, &self.resolver);
+        let ctx = crate::lower::TyLoweringContext::new(self.db, &self.resolver).with_diagnostics();
         let substs = ctx.substs_from_path(path, typable, true);
+        self.push_ty_lowering_diagnostics(TypeRefSource::Body(id), ctx.take_diagnostics());
         let ty = TyBuilder::value_ty(self.db, typable)
             .use_parent_substs(&parent_substs)
             .fill(substs.as_slice(&Interner)[parent_substs.len(&Interner)..].iter().cloned())
@@ -121,6 +125,13 @@ impl<'a> InferenceContext<'a> {
         let remaining_segments = path.segments().skip(remaining_index);
         let is_before_last = remaining_segments.len() == 1;
 
+        if let Some(generic_def) = crate::lower::generic_def_for_type_ns(&def) {
+            let ctx =
+                crate::lower::TyLoweringContext::new(self.db, &self.resolver).with_diagnostics();
+            ctx.check_generic_arg_count(path, remaining_index - 1, generic_def);
+            self.push_ty_lowering_diagnostics(TypeRefSource::Body(id), ctx.take_diagnostics());
+        }
+
         match (def, is_before_last) {
             (TypeNs::TraitId(trait_), true) => {
                 let segment =
@@ -221,7 +232,7 @@ impl<'a> InferenceContext<'a> {
         let krate = self.resolver.krate()?;
         let traits_in_scope = self.resolver.traits_in_scope(self.db.upcast());
 
-        method_resolution::iterate_method_candidates(
+        let resolved = method_resolution::iterate_method_candidates(
             &canonical_ty.value,
             self.db,
             self.table.trait_env.clone(),
@@ -230,7 +241,7 @@ impl<'a> InferenceContext<'a> {
             None,
             Some(name),
             method_resolution::LookupMode::Path,
-            move |_ty, item| {
+            |_ty, item| {
                 let (def, container) = match item {
                     AssocItemId::FunctionId(f) => {
                         (ValueNs::FunctionId(f), f.lookup(self.db.upcast()).container)
@@ -265,7 +276,83 @@ impl<'a> InferenceContext<'a> {
                 self.write_assoc_resolution(id, item);
                 Some((def, substs))
             },
-        )
+        );
+        match resolved {
+            Some((ValueNs::FunctionId(it), _)) => self.check_assoc_item_visibility(it.into(), id),
+            Some((ValueNs::ConstId(it), _)) => self.check_assoc_item_visibility(it.into(), id),
+            Some(_) => (),
+            None => {
+                if self.is_known_receiver(&ty) {
+                    self.push_diagnostic(InferenceDiagnostic::UnresolvedAssocItem {
+                        id,
+                        ty,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+        resolved
+    }
+
+    /// Reports a qualified path to an item which isn't visible from here.
+    fn check_item_visibility(
+        &mut self,
+        resolver: &Resolver,
+        path: &Path,
+        value: &ValueNs,
+        id: ExprOrPatId,
+    ) {
+        let item: ModuleDefId = match *value {
+            ValueNs::FunctionId(it) => it.into(),
+            ValueNs::ConstId(it) => it.into(),
+            ValueNs::StaticId(it) => it.into(),
+            ValueNs::StructId(it) => AdtId::from(it).into(),
+            // Local names are always visible, and variants have the
+            // visibility of their enum.
+            ValueNs::LocalBinding(_)
+            | ValueNs::ImplSelf(_)
+            | ValueNs::EnumVariantId(_)
+            | ValueNs::GenericParam(_) => return,
+        };
+        let mod_path = path.mod_path();
+        if mod_path.kind == PathKind::Plain && mod_path.segments().len() == 1 {
+            return;
+        }
+        let module = match resolver.module() {
+            Some(it) => it,
+            None => return,
+        };
+        let per_ns = resolver.resolve_module_path_in_items(self.db.upcast(), mod_path);
+        if let Some((def, vis)) = per_ns.values {
+            if def == item && !vis.is_visible_from(self.db.upcast(), module) {
+                self.push_diagnostic(InferenceDiagnostic::PrivateItem { id, item });
+            }
+        }
+    }
+
+    /// Reports an associated item of an inherent impl which isn't visible from
+    /// here. Trait items are as visible as their trait.
+    fn check_assoc_item_visibility(&mut self, item: AssocItemId, id: ExprOrPatId) {
+        let module = match self.resolver.module() {
+            Some(it) => it,
+            None => return,
+        };
+        let (container, vis) = match item {
+            AssocItemId::FunctionId(it) => {
+                (it.lookup(self.db.upcast()).container, self.db.function_visibility(it))
+            }
+            AssocItemId::ConstId(it) => {
+                (it.lookup(self.db.upcast()).container, self.db.const_visibility(it))
+            }
+            AssocItemId::TypeAliasId(_) => return,
+        };
+        let is_inherent = match container {
+            AssocContainerId::ImplId(it) => self.db.impl_trait(it).is_none(),
+            AssocContainerId::TraitId(_) | AssocContainerId::ModuleId(_) => false,
+        };
+        if is_inherent && !vis.is_visible_from(self.db.upcast(), module) {
+            self.push_diagnostic(InferenceDiagnostic::PrivateAssocItem { id, item });
+        }
     }
 
     fn resolve_enum_variant_on_ty(
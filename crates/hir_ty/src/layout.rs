@@ -0,0 +1,482 @@
+//! Computes the memory layout of types: their size, their alignment and the
+//! offsets of their fields.
+//!
+//! This follows the algorithm of rustc, including the reordering of fields,
+//! but only the simplest niche optimization: an enum with a single variant
+//! with data stores its discriminant in the invalid values of a field of that
+//! variant, like `Option<&T>` does.
+
+#[cfg(test)]
+mod tests;
+
+use std::{cmp, sync::Arc};
+
+use base_db::CrateId;
+use either::Either;
+use hir_def::{
+    adt::{ReprOptions, VariantData},
+    builtin_type::{BuiltinInt, BuiltinUint},
+    type_ref::ConstScalar,
+    AdtId, EnumId, EnumVariantId, HasModule, VariantId,
+};
+
+use crate::{
+    consteval::{self, ComputedExpr},
+    db::HirDatabase,
+    primitive::{FloatTy, IntTy, UintTy},
+    ConstValue, Interner, Scalar, Substitution, Ty, TyKind,
+};
+
+/// The properties of the compilation target which matter for layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetDataLayout {
+    pub pointer_size: u64,
+}
+
+impl TargetDataLayout {
+    /// The target of `krate`, as given by its `target_pointer_width` cfg.
+    /// Defaults to a 64-bit target.
+    pub fn for_crate(db: &dyn HirDatabase, krate: CrateId) -> TargetDataLayout {
+        let crate_graph = db.crate_graph();
+        let bits = crate_graph[krate]
+            .cfg_options
+            .get_cfg_values("target_pointer_width")
+            .into_iter()
+            .find_map(|it| it.parse::<u64>().ok())
+            .unwrap_or(64);
+        TargetDataLayout { pointer_size: bits / 8 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub size: u64,
+    pub align: u64,
+    /// The offsets of the fields of a struct, union or tuple, in declaration
+    /// order.
+    pub field_offsets: Vec<u64>,
+    /// The layouts of the variants of an enum, in declaration order. The
+    /// offsets of their fields are relative to the start of the enum.
+    pub variants: Vec<Layout>,
+    /// The number of invalid values of a field of the type, which an enum
+    /// containing it can use to encode its discriminant.
+    niches: u128,
+}
+
+impl Layout {
+    fn scalar(size: u64, niches: u128) -> Layout {
+        // 128-bit integers are only aligned like 64-bit ones.
+        let align = size.min(8);
+        Layout { size, align, field_offsets: Vec::new(), variants: Vec::new(), niches }
+    }
+
+    fn zst() -> Layout {
+        Layout { size: 0, align: 1, field_offsets: Vec::new(), variants: Vec::new(), niches: 0 }
+    }
+
+    fn is_zst(&self) -> bool {
+        self.size == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The layout depends on generic parameters.
+    HasPlaceholder,
+    /// The type is dynamically sized, like `str`.
+    Unsized,
+    /// The type contains itself without indirection.
+    Recursive,
+    SizeOverflow,
+    /// A part of the type is unknown, or its layout isn't supported.
+    Unknown,
+}
+
+/// The layout of `ty` on the target of `krate`.
+pub fn layout_of_ty(
+    db: &dyn HirDatabase,
+    ty: &Ty,
+    krate: CrateId,
+) -> Result<Arc<Layout>, LayoutError> {
+    let dl = TargetDataLayout::for_crate(db, krate);
+    let res = match ty.kind(&Interner) {
+        TyKind::Adt(crate::AdtId(def), subst) => return db.layout_of_adt(*def, subst.clone()),
+        TyKind::Scalar(scalar) => scalar_layout(dl, *scalar),
+        TyKind::Tuple(_, subst) => {
+            let fields = subst
+                .iter(&Interner)
+                .map(|it| layout_of_ty(db, it.assert_ty_ref(&Interner), krate))
+                .collect::<Result<Vec<_>, _>>()?;
+            univariant(&fields, &ReprOptions::default(), None)?
+        }
+        TyKind::Array(elem, len) => {
+            let len = match &len.data(&Interner).value {
+                ConstValue::Concrete(it) => match it.interned {
+                    ConstScalar::Usize(it) => it,
+                    ConstScalar::Unknown => return Err(LayoutError::Unknown),
+                },
+                _ => return Err(LayoutError::HasPlaceholder),
+            };
+            let elem = layout_of_ty(db, elem, krate)?;
+            let size = elem.size.checked_mul(len).ok_or(LayoutError::SizeOverflow)?;
+            let niches = if len == 0 { 0 } else { elem.niches };
+            Layout { size, align: elem.align, niches, ..Layout::zst() }
+        }
+        TyKind::Ref(_, _, pointee) | TyKind::Raw(_, pointee) => {
+            let size = if is_unsized(db, pointee)? { 2 * dl.pointer_size } else { dl.pointer_size };
+            // References can't be null, raw pointers can.
+            let niches = if matches!(ty.kind(&Interner), TyKind::Ref(..)) { 1 } else { 0 };
+            Layout { size, align: dl.pointer_size, niches, ..Layout::zst() }
+        }
+        TyKind::Function(_) => Layout::scalar(dl.pointer_size, 1),
+        TyKind::FnDef(..) | TyKind::Never => Layout::zst(),
+        TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => return Err(LayoutError::Unsized),
+        TyKind::Placeholder(_) | TyKind::BoundVar(_) => return Err(LayoutError::HasPlaceholder),
+        _ => return Err(LayoutError::Unknown),
+    };
+    Ok(Arc::new(res))
+}
+
+pub(crate) fn layout_of_adt_query(
+    db: &dyn HirDatabase,
+    def: AdtId,
+    subst: Substitution,
+) -> Result<Arc<Layout>, LayoutError> {
+    let krate = def.module(db.upcast()).krate();
+    let field_layouts = |variant: VariantId, data: &VariantData| {
+        let field_types = db.field_types(variant);
+        data.fields()
+            .iter()
+            .map(|(id, _)| {
+                let ty = field_types[id].clone().substitute(&Interner, &subst);
+                layout_of_ty(db, &ty, krate)
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let res = match def {
+        AdtId::StructId(id) => {
+            let data = db.struct_data(id);
+            let fields = field_layouts(id.into(), &data.variant_data)?;
+            univariant(&fields, &data.repr.unwrap_or_default(), None)?
+        }
+        AdtId::UnionId(id) => {
+            let data = db.union_data(id);
+            let fields = field_layouts(id.into(), &data.variant_data)?;
+            union_layout(&fields, &data.repr.unwrap_or_default())?
+        }
+        AdtId::EnumId(id) => {
+            let data = db.enum_data(id);
+            let variants = data
+                .variants
+                .iter()
+                .map(|(local_id, it)| {
+                    field_layouts(EnumVariantId { parent: id, local_id }.into(), &it.variant_data)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            enum_layout(db, id, &variants, TargetDataLayout::for_crate(db, krate))?
+        }
+    };
+    Ok(Arc::new(res))
+}
+
+pub(crate) fn layout_of_adt_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &[String],
+    _def: &AdtId,
+    _subst: &Substitution,
+) -> Result<Arc<Layout>, LayoutError> {
+    Err(LayoutError::Recursive)
+}
+
+fn scalar_layout(dl: TargetDataLayout, scalar: Scalar) -> Layout {
+    match scalar {
+        Scalar::Bool => Layout::scalar(1, 254),
+        // Only the values up to `char::MAX` are valid.
+        Scalar::Char => Layout::scalar(4, (1 << 32) - 0x11_0000),
+        Scalar::Int(it) => {
+            let size = match it {
+                IntTy::Isize => dl.pointer_size,
+                IntTy::I8 => 1,
+                IntTy::I16 => 2,
+                IntTy::I32 => 4,
+                IntTy::I64 => 8,
+                IntTy::I128 => 16,
+            };
+            Layout::scalar(size, 0)
+        }
+        Scalar::Uint(it) => {
+            let size = match it {
+                UintTy::Usize => dl.pointer_size,
+                UintTy::U8 => 1,
+                UintTy::U16 => 2,
+                UintTy::U32 => 4,
+                UintTy::U64 => 8,
+                UintTy::U128 => 16,
+            };
+            Layout::scalar(size, 0)
+        }
+        Scalar::Float(FloatTy::F32) => Layout::scalar(4, 0),
+        Scalar::Float(FloatTy::F64) => Layout::scalar(8, 0),
+    }
+}
+
+/// Whether pointers to `ty` carry metadata, i.e. whether `ty` is dynamically
+/// sized.
+fn is_unsized(db: &dyn HirDatabase, ty: &Ty) -> Result<bool, LayoutError> {
+    // Only the last field of a struct can be unsized, follow those fields.
+    // The limit guards against types which contain themselves.
+    let mut ty = ty.clone();
+    for _ in 0..64 {
+        let tail = match ty.kind(&Interner) {
+            TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => return Ok(true),
+            TyKind::Adt(crate::AdtId(AdtId::StructId(id)), subst) => {
+                let data = db.struct_data(*id);
+                match data.variant_data.fields().iter().last() {
+                    Some((field, _)) => {
+                        db.field_types((*id).into())[field].clone().substitute(&Interner, subst)
+                    }
+                    None => return Ok(false),
+                }
+            }
+            TyKind::Tuple(_, subst) => match subst.iter(&Interner).last() {
+                Some(it) => it.assert_ty_ref(&Interner).clone(),
+                None => return Ok(false),
+            },
+            // Without looking at the bounds of the parameter, it could be
+            // `?Sized`.
+            TyKind::Placeholder(_) | TyKind::BoundVar(_) => {
+                return Err(LayoutError::HasPlaceholder)
+            }
+            TyKind::Alias(_) | TyKind::Error | TyKind::InferenceVar(..) => {
+                return Err(LayoutError::Unknown)
+            }
+            _ => return Ok(false),
+        };
+        ty = tail;
+    }
+    Err(LayoutError::Recursive)
+}
+
+/// The layout of a struct with the given fields, after a prefix, which is the
+/// size and alignment of the tag of an enum.
+fn univariant(
+    fields: &[Arc<Layout>],
+    repr: &ReprOptions,
+    prefix: Option<(u64, u64)>,
+) -> Result<Layout, LayoutError> {
+    let field_align = |field: &Layout| match repr.packed {
+        Some(pack) => field.align.min(pack),
+        None => field.align,
+    };
+
+    let mut memory_order = (0..fields.len()).collect::<Vec<_>>();
+    if !repr.c && repr.int.is_none() {
+        match prefix {
+            // Put the fields with the largest alignment first, to minimize the
+            // padding between the fields.
+            None => memory_order.sort_by_key(|&idx| {
+                let field = &fields[idx];
+                (!field.is_zst(), cmp::Reverse(field_align(field)))
+            }),
+            // After the tag, the fields with the smallest alignment come first,
+            // so that they can share the alignment padding of the tag.
+            Some(_) => memory_order.sort_by_key(|&idx| field_align(&fields[idx])),
+        }
+    }
+
+    let (mut offset, mut align) = match prefix {
+        Some((size, align)) => {
+            let align = repr.packed.map_or(align, |pack| align.min(pack));
+            (align_to(size, align)?, align)
+        }
+        None => (0, 1),
+    };
+    let mut field_offsets = vec![0; fields.len()];
+    let mut niches = 0;
+    for idx in memory_order {
+        let field = &fields[idx];
+        let field_align = field_align(field);
+        offset = align_to(offset, field_align)?;
+        field_offsets[idx] = offset;
+        offset = offset.checked_add(field.size).ok_or(LayoutError::SizeOverflow)?;
+        align = align.max(field_align);
+        niches = niches.max(field.niches);
+    }
+    if let Some(repr_align) = repr.align {
+        align = align.max(repr_align);
+    }
+    let size = align_to(offset, align)?;
+    Ok(Layout { size, align, field_offsets, variants: Vec::new(), niches })
+}
+
+fn union_layout(fields: &[Arc<Layout>], repr: &ReprOptions) -> Result<Layout, LayoutError> {
+    let mut size = 0;
+    let mut align = 1;
+    for field in fields {
+        size = size.max(field.size);
+        align = align.max(repr.packed.map_or(field.align, |pack| field.align.min(pack)));
+    }
+    if let Some(repr_align) = repr.align {
+        align = align.max(repr_align);
+    }
+    let size = align_to(size, align)?;
+    Ok(Layout { size, align, field_offsets: vec![0; fields.len()], ..Layout::zst() })
+}
+
+fn enum_layout(
+    db: &dyn HirDatabase,
+    id: EnumId,
+    variants: &[Vec<Arc<Layout>>],
+    dl: TargetDataLayout,
+) -> Result<Layout, LayoutError> {
+    let data = db.enum_data(id);
+    let repr = data.repr.unwrap_or_default();
+    // With `repr(C)` or `repr(u8)` etc. the discriminant is always stored in
+    // a tag of the given size.
+    let inhibit_optimization = repr.c || repr.int.is_some();
+
+    if variants.is_empty() {
+        return Ok(Layout::zst());
+    }
+    if variants.len() == 1 && !inhibit_optimization {
+        let layout = univariant(&variants[0], &repr, None)?;
+        return Ok(Layout { variants: vec![layout.clone()], ..layout });
+    }
+
+    let no_explicit_discriminants = data.variants.iter().all(|(_, it)| it.discriminant.is_none());
+    if !inhibit_optimization && no_explicit_discriminants {
+        if let Some(layout) = niche_filling_layout(variants, &repr)? {
+            return Ok(layout);
+        }
+    }
+
+    let discriminants = data
+        .variants
+        .iter()
+        .map(|(local_id, _)| {
+            match consteval::eval_discriminant(db, EnumVariantId { parent: id, local_id }) {
+                Ok(ComputedExpr::Int(it)) => Ok(it),
+                _ => Err(LayoutError::Unknown),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let min = discriminants.iter().copied().min().unwrap_or(0);
+    let max = discriminants.iter().copied().max().unwrap_or(0);
+    let min_tag_size = match repr.int {
+        Some(Either::Left(it)) => match it {
+            BuiltinInt::Isize => dl.pointer_size,
+            BuiltinInt::I8 => 1,
+            BuiltinInt::I16 => 2,
+            BuiltinInt::I32 => 4,
+            BuiltinInt::I64 => 8,
+            BuiltinInt::I128 => 16,
+        },
+        Some(Either::Right(it)) => match it {
+            BuiltinUint::Usize => dl.pointer_size,
+            BuiltinUint::U8 => 1,
+            BuiltinUint::U16 => 2,
+            BuiltinUint::U32 => 4,
+            BuiltinUint::U64 => 8,
+            BuiltinUint::U128 => 16,
+        },
+        // A C enum is an `int`, unless its discriminants don't fit in one.
+        None => discriminant_size(min, max).max(if repr.c { 4 } else { 1 }),
+    };
+    let tag = Layout::scalar(min_tag_size, 0);
+
+    // With `repr(C)`, the fields of all variants are stored in a union after
+    // the tag.
+    let prefix_align = if repr.c {
+        variants.iter().flatten().map(|it| it.align).fold(tag.align, cmp::max)
+    } else {
+        tag.align
+    };
+    let mut size = 0;
+    let mut align = tag.align;
+    // The smallest alignment of the first fields of the variants, the tag can
+    // grow up to it without moving any field.
+    let mut start_align = 256;
+    let mut variant_layouts = Vec::with_capacity(variants.len());
+    for fields in variants {
+        let layout = univariant(fields, &repr, Some((tag.size, prefix_align)))?;
+        let mut by_offset = fields.iter().zip(&layout.field_offsets).collect::<Vec<_>>();
+        by_offset.sort_by_key(|(_, &offset)| offset);
+        if let Some((field, _)) =
+            by_offset.into_iter().find(|(field, _)| !field.is_zst() || field.align != 1)
+        {
+            start_align = start_align.min(field.align);
+        }
+        size = size.max(layout.size);
+        align = align.max(layout.align);
+        variant_layouts.push(layout);
+    }
+    let tag_size = if !inhibit_optimization && start_align <= 8 {
+        tag.size.max(start_align)
+    } else {
+        tag.size
+    };
+
+    let used = (max.wrapping_sub(min) as u128).saturating_add(1);
+    let niches = match 1u128.checked_shl(8 * tag_size as u32) {
+        Some(values) => values.saturating_sub(used),
+        None => u128::MAX - (used - 1),
+    };
+    let size = align_to(size, align)?;
+    Ok(Layout { size, align, field_offsets: Vec::new(), variants: variant_layouts, niches })
+}
+
+/// The layout of an enum with a single variant with data, which stores the
+/// discriminant in the invalid values of a field of that variant.
+fn niche_filling_layout(
+    variants: &[Vec<Arc<Layout>>],
+    repr: &ReprOptions,
+) -> Result<Option<Layout>, LayoutError> {
+    let mut dataful = None;
+    for (idx, fields) in variants.iter().enumerate() {
+        if fields.iter().all(|it| it.is_zst()) {
+            continue;
+        }
+        if dataful.is_some() {
+            return Ok(None);
+        }
+        dataful = Some(idx);
+    }
+    let dataful = match dataful {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+
+    let variant_layouts =
+        variants.iter().map(|it| univariant(it, repr, None)).collect::<Result<Vec<_>, _>>()?;
+    let needed = variants.len() as u128 - 1;
+    let data = &variant_layouts[dataful];
+    if data.niches < needed {
+        return Ok(None);
+    }
+    let align = variant_layouts.iter().map(|it| it.align).fold(1, cmp::max);
+    let size = align_to(data.size, align)?;
+    let niches = data.niches - needed;
+    Ok(Some(Layout { size, align, field_offsets: Vec::new(), variants: variant_layouts, niches }))
+}
+
+/// The size of the smallest integer which can hold all values from `min` to
+/// `max`.
+fn discriminant_size(min: i128, max: i128) -> u64 {
+    let fits = |size: u64| {
+        if size == 16 {
+            return true;
+        }
+        let bits = 8 * size as u32;
+        if min >= 0 {
+            max < 1 << bits
+        } else {
+            -(1 << (bits - 1)) <= min && max < 1 << (bits - 1)
+        }
+    };
+    [1, 2, 4, 8, 16].iter().copied().find(|&size| fits(size)).unwrap_or(16)
+}
+
+fn align_to(offset: u64, align: u64) -> Result<u64, LayoutError> {
+    let padding = (align - offset % align) % align;
+    offset.checked_add(padding).ok_or(LayoutError::SizeOverflow)
+}
@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, AdtId, ModuleDefId};
+
+use crate::{db::HirDatabase, test_db::TestDB, TyBuilder};
+
+use super::{Layout, LayoutError};
+
+/// The layout of the `Goal` struct, enum or union of the fixture, with its
+/// generic parameters left as placeholders.
+fn eval_goal(ra_fixture: &str) -> Result<Arc<Layout>, LayoutError> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let adt_id = scope
+        .declarations()
+        .find_map(|it| match it {
+            ModuleDefId::AdtId(it) => {
+                let name = match it {
+                    AdtId::StructId(it) => db.struct_data(it).name.clone(),
+                    AdtId::UnionId(it) => db.union_data(it).name.clone(),
+                    AdtId::EnumId(it) => db.enum_data(it).name.clone(),
+                };
+                if name.to_string() == "Goal" {
+                    Some(it)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .expect("no `Goal` type in the fixture");
+    db.layout_of_adt(adt_id, TyBuilder::type_params_subst(&db, adt_id))
+}
+
+fn check_size_and_align(ra_fixture: &str, size: u64, align: u64) {
+    let layout = eval_goal(ra_fixture).unwrap();
+    assert_eq!((layout.size, layout.align), (size, align));
+}
+
+fn check_fields(ra_fixture: &str, size: u64, align: u64, field_offsets: &[u64]) {
+    let layout = eval_goal(ra_fixture).unwrap();
+    assert_eq!((layout.size, layout.align, &*layout.field_offsets), (size, align, field_offsets));
+}
+
+fn check_fail(ra_fixture: &str, e: LayoutError) {
+    assert_eq!(eval_goal(ra_fixture), Err(e));
+}
+
+#[test]
+fn reordered_struct() {
+    check_fields("struct Goal { a: u8, b: u32, c: u16 }", 8, 4, &[6, 0, 4]);
+    check_fields("struct Goal(u8, u64, u8);", 16, 8, &[8, 0, 9]);
+    check_fields("struct Goal;", 0, 1, &[]);
+    check_fields("struct Goal { a: (), b: u16, c: [u8; 0] }", 2, 2, &[0, 0, 0]);
+}
+
+#[test]
+fn repr_struct() {
+    check_fields("#[repr(C)] struct Goal { a: u8, b: u32, c: u16 }", 12, 4, &[0, 4, 8]);
+    check_fields("#[repr(packed)] struct Goal { a: u8, b: u32 }", 5, 1, &[0, 1]);
+    check_fields("#[repr(C, packed(2))] struct Goal { a: u8, b: u32 }", 6, 2, &[0, 2]);
+    check_fields("#[repr(C)] #[repr(align(16))] struct Goal { a: u8 }", 16, 16, &[0]);
+    check_fields("#[repr(transparent)] struct Goal { a: (), b: u64 }", 8, 8, &[0, 0]);
+}
+
+#[test]
+fn primitives_and_compounds() {
+    check_fields("struct Goal(bool, char, f32, f64, i128);", 40, 8, &[32, 24, 28, 0, 8]);
+    check_fields("struct Goal((u8, u64), [u16; 3], ());", 24, 8, &[0, 16, 0]);
+    check_fields(
+        "struct Goal<'a> { a: &'a u8, b: &'a [u8], c: *const str, d: fn(), e: &'a &'a str }",
+        56,
+        8,
+        &[0, 8, 24, 40, 48],
+    );
+    check_size_and_align("const N: usize = 2 * 3; struct Goal([u32; N]);", 24, 4);
+    check_size_and_align("struct Inner { a: u8, b: [u16] } struct Goal(&'static Inner);", 16, 8);
+}
+
+#[test]
+fn pointer_width() {
+    check_fields(
+        r#"
+//- /main.rs cfg:target_pointer_width=32
+struct Goal { a: usize, b: &'static [u8], c: u64 }
+"#,
+        24,
+        8,
+        &[8, 12, 0],
+    );
+}
+
+#[test]
+fn unions() {
+    check_fields("union Goal { a: u8, b: u32, c: [u8; 5] }", 8, 4, &[0, 0, 0]);
+    check_size_and_align("#[repr(packed)] union Goal { a: u8, b: u32, c: [u8; 5] }", 5, 1);
+}
+
+#[test]
+fn fieldless_enums() {
+    check_size_and_align("enum Goal {}", 0, 1);
+    check_size_and_align("enum Goal { A }", 0, 1);
+    check_size_and_align("enum Goal { A, B, C }", 1, 1);
+    check_size_and_align("enum Goal { A = -1, B = 200 }", 2, 2);
+    check_size_and_align("enum Goal { A = 1 << 16 }", 0, 1);
+    check_size_and_align("enum Goal { A = 1 << 16, B }", 4, 4);
+    check_size_and_align("#[repr(C)] enum Goal { A, B }", 4, 4);
+    check_size_and_align("#[repr(u16)] enum Goal { A }", 2, 2);
+    check_size_and_align("#[repr(i64)] enum Goal { A, B }", 8, 8);
+}
+
+#[test]
+fn tagged_enums() {
+    let layout = eval_goal("enum Goal { A(u8), B(u32), C }").unwrap();
+    assert_eq!((layout.size, layout.align), (8, 4));
+    let offsets = layout.variants.iter().map(|it| it.field_offsets.clone()).collect::<Vec<_>>();
+    assert_eq!(offsets, vec![vec![1], vec![4], vec![]]);
+
+    check_size_and_align("enum Goal { A(u32), B }", 8, 4);
+    check_size_and_align("enum Goal { A(u8, u16), B(u8) }", 4, 2);
+    check_size_and_align("enum Goal { A = 5, B(bool) }", 2, 1);
+    check_size_and_align("#[repr(u8)] enum Goal { A(bool), B }", 2, 1);
+
+    let layout = eval_goal("#[repr(C)] enum Goal { A(u8), B(u64) }").unwrap();
+    assert_eq!((layout.size, layout.align), (16, 8));
+    assert_eq!(layout.variants[0].field_offsets, vec![8]);
+}
+
+#[test]
+fn niche_filling_enums() {
+    check_size_and_align("enum Goal<'a> { None, Some(&'a u8) }", 8, 8);
+    check_size_and_align("enum Goal { A(bool), B, C }", 1, 1);
+    check_size_and_align("enum Goal { A(char), B }", 4, 4);
+    check_size_and_align("enum Goal { A(u16, fn()), B(()) }", 16, 8);
+    check_size_and_align("enum Inner { A, B } enum Goal { A(Inner), B }", 1, 1);
+    check_size_and_align(
+        r#"
+enum Option<T> { None, Some(T) }
+struct Goal(Option<Option<Option<bool>>>);
+"#,
+        1,
+        1,
+    );
+}
+
+#[test]
+fn unknown_layouts() {
+    check_fail("struct Goal<T> { a: u8, b: T }", LayoutError::HasPlaceholder);
+    check_fail("struct Goal<T> { a: &'static T }", LayoutError::HasPlaceholder);
+    check_fail("struct Goal { a: u8, b: [u8] }", LayoutError::Unsized);
+    check_fail("struct Goal { a: u8, b: Goal }", LayoutError::Recursive);
+    check_fail("struct Goal { a: Unresolved }", LayoutError::Unknown);
+}
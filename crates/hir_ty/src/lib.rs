@@ -21,6 +21,7 @@ mod walk;
 pub mod db;
 pub mod diagnostics;
 pub mod display;
+pub mod layout;
 pub mod method_resolution;
 pub mod primitive;
 pub mod traits;
@@ -48,11 +49,15 @@ use crate::{db::HirDatabase, utils::generics};
 pub use autoderef::autoderef;
 pub use builder::TyBuilder;
 pub use chalk_ext::*;
-pub use infer::{could_unify, InferenceDiagnostic, InferenceResult};
+pub use infer::{
+    could_unify, Adjust, Adjustment, AutoBorrow, ExprOrPatId, InferenceDiagnostic, InferenceResult,
+    OverloadedDeref, PointerCast, TypeMismatch, TypeRefSource,
+};
 pub use interner::Interner;
 pub use lower::{
-    associated_type_shorthand_candidates, callable_item_sig, CallableDefId, ImplTraitLoweringMode,
-    TyDefId, TyLoweringContext, ValueTyDefId,
+    associated_type_shorthand_candidates, callable_item_sig, CallableDefId,
+    GenericArgCountMismatch, ImplTraitLoweringMode, TyDefId, TyLoweringContext,
+    TyLoweringDiagnostic, ValueTyDefId,
 };
 pub use mapping::{
     const_from_placeholder_idx, from_assoc_type_id, from_chalk_trait_id, from_foreign_def_id,
@@ -154,6 +159,36 @@ pub(crate) fn make_only_type_binders<T: HasInterner<Interner = Interner>>(
     )
 }
 
+pub(crate) fn make_late_bound_lifetime_binders<T: HasInterner<Interner = Interner>>(
+    num_lifetimes: usize,
+    value: T,
+) -> Binders<T> {
+    Binders::new(
+        VariableKinds::from_iter(
+            &Interner,
+            std::iter::repeat(chalk_ir::VariableKind::Lifetime).take(num_lifetimes),
+        ),
+        value,
+    )
+}
+
+/// Instantiates the lifetimes bound by a `for<'a>` binder with `'static`, which is what all other
+/// lifetimes are lowered to. Used where we need a trait ref or signature outside of its binder.
+pub(crate) fn erase_late_bound_lifetimes<T>(binders: Binders<T>) -> T::Result
+where
+    T: HasInterner<Interner = Interner> + Fold<Interner>,
+{
+    let substitution = Substitution::from_iter(
+        &Interner,
+        binders.binders.iter(&Interner).map(|kind| match kind {
+            chalk_ir::VariableKind::Lifetime => static_lifetime().cast(&Interner),
+            chalk_ir::VariableKind::Ty(_) => TyKind::Error.intern(&Interner).cast(&Interner),
+            chalk_ir::VariableKind::Const(_) => dummy_usize_const().cast(&Interner),
+        }),
+    );
+    binders.substitute(&Interner, &substitution)
+}
+
 // FIXME: get rid of this
 pub fn make_canonical<T: HasInterner<Interner = Interner>>(
     value: T,
@@ -191,11 +226,7 @@ impl CallableSig {
     pub fn from_fn_ptr(fn_ptr: &FnPointer) -> CallableSig {
         CallableSig {
             // FIXME: what to do about lifetime params? -> return PolyFnSig
-            params_and_return: fn_ptr
-                .substitution
-                .clone()
-                .shifted_out_to(&Interner, DebruijnIndex::ONE)
-                .expect("unexpected lifetime vars in fn ptr")
+            params_and_return: erase_late_bound_lifetimes(fn_ptr.clone().into_binders(&Interner))
                 .0
                 .as_slice(&Interner)
                 .iter()
@@ -16,12 +16,12 @@ use hir_def::{
     body::{Expander, LowerCtx},
     builtin_type::BuiltinType,
     generics::{TypeParamProvenance, WherePredicate, WherePredicateTypeTarget},
-    path::{GenericArg, Path, PathSegment, PathSegments},
+    path::{GenericArg, GenericArgs, Path, PathKind, PathSegment, PathSegments},
     resolver::{HasResolver, Resolver, TypeNs},
-    type_ref::{TraitBoundModifier, TraitRef as HirTraitRef, TypeBound, TypeRef},
+    type_ref::{LifetimeRef, TraitBoundModifier, TraitRef as HirTraitRef, TypeBound, TypeRef},
     AdtId, AssocContainerId, AssocItemId, ConstId, ConstParamId, EnumId, EnumVariantId, FunctionId,
-    GenericDefId, HasModule, ImplId, LocalFieldId, Lookup, StaticId, StructId, TraitId,
-    TypeAliasId, TypeParamId, UnionId, VariantId,
+    GenericDefId, HasModule, ImplId, LocalFieldId, Lookup, ModuleDefId, StaticId, StructId,
+    TraitId, TypeAliasId, TypeParamId, UnionId, VariantId,
 };
 use hir_expand::{name::Name, ExpandResult};
 use la_arena::ArenaMap;
@@ -39,9 +39,9 @@ use crate::{
         all_super_trait_refs, associated_type_by_name_including_super_traits, generics, Generics,
     },
     AliasEq, AliasTy, Binders, BoundVar, CallableSig, DebruijnIndex, DynTy, FnPointer, FnSig,
-    FnSubst, ImplTraitId, Interner, PolyFnSig, ProjectionTy, QuantifiedWhereClause,
-    QuantifiedWhereClauses, ReturnTypeImplTrait, ReturnTypeImplTraits, Substitution,
-    TraitEnvironment, TraitRef, TraitRefExt, Ty, TyBuilder, TyKind, WhereClause,
+    FnSubst, ImplTraitId, Interner, Lifetime, LifetimeData, PolyFnSig, ProjectionTy,
+    QuantifiedWhereClause, QuantifiedWhereClauses, ReturnTypeImplTrait, ReturnTypeImplTraits,
+    Substitution, TraitEnvironment, TraitRef, TraitRefExt, Ty, TyBuilder, TyKind, WhereClause,
 };
 
 #[derive(Debug)]
@@ -68,6 +68,35 @@ pub struct TyLoweringContext<'a> {
     expander: RefCell<Option<Expander>>,
     /// Tracks types with explicit `?Sized` bounds.
     pub(crate) unsized_types: RefCell<FxHashSet<Ty>>,
+    /// The lifetimes of the `for<'a>` binders we're currently inside of, together
+    /// with the `in_binders` depth just inside each binder.
+    late_bound_lifetimes: RefCell<Vec<(DebruijnIndex, Box<[Name]>)>>,
+    /// The problems found in the lowered type references, `None` unless
+    /// requested with `with_diagnostics`, as checking for them isn't free.
+    diagnostics: RefCell<Option<Vec<TyLoweringDiagnostic>>>,
+}
+
+/// A problem in a lowered type reference. As type references don't have a
+/// source map, the offending path is identified by its value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TyLoweringDiagnostic {
+    /// `path` resolves to an item which isn't visible from where it is used.
+    PrivateItem { path: Path, item: ModuleDefId },
+    /// The segment at index `segment` of `path` has a different number of
+    /// generic arguments than the item it refers to has parameters.
+    MismatchedGenericArgCount { path: Path, segment: usize, mismatch: GenericArgCountMismatch },
+}
+
+/// A difference between the generic arguments given to an item and its
+/// generic parameters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GenericArgCountMismatch {
+    /// Lifetime arguments can be left out entirely, but if some are given,
+    /// all of them must be.
+    Lifetimes { expected: usize, found: usize },
+    /// Type and const arguments. `min` is less than `max` if some type
+    /// parameters have defaults.
+    TypesAndConsts { min: usize, max: usize, found: usize },
 }
 
 impl<'a> TyLoweringContext<'a> {
@@ -87,6 +116,8 @@ impl<'a> TyLoweringContext<'a> {
             opaque_type_data,
             expander: RefCell::new(None),
             unsized_types: RefCell::default(),
+            late_bound_lifetimes: RefCell::default(),
+            diagnostics: RefCell::default(),
         }
     }
 
@@ -98,12 +129,16 @@ impl<'a> TyLoweringContext<'a> {
         let opaque_ty_data_vec = self.opaque_type_data.replace(Vec::new());
         let expander = self.expander.replace(None);
         let unsized_types = self.unsized_types.replace(Default::default());
+        let late_bound_lifetimes = self.late_bound_lifetimes.replace(Vec::new());
+        let diagnostics = self.diagnostics.replace(None);
         let new_ctx = Self {
             in_binders: debruijn,
             impl_trait_counter: Cell::new(self.impl_trait_counter.get()),
             opaque_type_data: RefCell::new(opaque_ty_data_vec),
             expander: RefCell::new(expander),
             unsized_types: RefCell::new(unsized_types),
+            late_bound_lifetimes: RefCell::new(late_bound_lifetimes),
+            diagnostics: RefCell::new(diagnostics),
             ..*self
         };
         let result = f(&new_ctx);
@@ -111,6 +146,8 @@ impl<'a> TyLoweringContext<'a> {
         self.opaque_type_data.replace(new_ctx.opaque_type_data.into_inner());
         self.expander.replace(new_ctx.expander.into_inner());
         self.unsized_types.replace(new_ctx.unsized_types.into_inner());
+        self.late_bound_lifetimes.replace(new_ctx.late_bound_lifetimes.into_inner());
+        self.diagnostics.replace(new_ctx.diagnostics.into_inner());
         result
     }
 
@@ -122,6 +159,21 @@ impl<'a> TyLoweringContext<'a> {
         self.with_debruijn(self.in_binders.shifted_in_from(debruijn), f)
     }
 
+    /// Runs `f` inside a new binder for the given `for<'a>` lifetimes, so that
+    /// references to them are lowered to variables of that binder.
+    pub fn with_late_bound_lifetimes<T>(
+        &self,
+        lifetimes: &[Name],
+        f: impl FnOnce(&TyLoweringContext) -> T,
+    ) -> T {
+        self.with_shifted_in(DebruijnIndex::ONE, |ctx| {
+            ctx.late_bound_lifetimes.borrow_mut().push((ctx.in_binders, lifetimes.into()));
+            let result = f(ctx);
+            ctx.late_bound_lifetimes.borrow_mut().pop();
+            result
+        })
+    }
+
     pub fn with_impl_trait_mode(self, impl_trait_mode: ImplTraitLoweringMode) -> Self {
         Self { impl_trait_mode, ..self }
     }
@@ -129,6 +181,27 @@ impl<'a> TyLoweringContext<'a> {
     pub fn with_type_param_mode(self, type_param_mode: TypeParamLoweringMode) -> Self {
         Self { type_param_mode, ..self }
     }
+
+    /// Makes the context collect the problems in the lowered type references,
+    /// see `take_diagnostics`.
+    pub fn with_diagnostics(self) -> Self {
+        Self { diagnostics: RefCell::new(Some(Vec::new())), ..self }
+    }
+
+    /// Returns the problems found since the last call.
+    pub fn take_diagnostics(&self) -> Vec<TyLoweringDiagnostic> {
+        self.diagnostics.borrow_mut().as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn collects_diagnostics(&self) -> bool {
+        self.diagnostics.borrow().is_some()
+    }
+
+    fn push_diagnostic(&self, diagnostic: TyLoweringDiagnostic) {
+        if let Some(diagnostics) = &mut *self.diagnostics.borrow_mut() {
+            diagnostics.push(diagnostic);
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -183,7 +256,8 @@ impl<'a> TyLoweringContext<'a> {
             TypeRef::Array(inner, len) => {
                 let inner_ty = self.lower_ty(inner);
 
-                let const_len = consteval::usize_const(len.as_usize());
+                let len = consteval::eval_const_ref(self.db, self.resolver, len);
+                let const_len = consteval::usize_const(len.ok().and_then(|it| it.as_usize()));
 
                 TyKind::Array(inner_ty, const_len).intern(&Interner)
             }
@@ -191,24 +265,22 @@ impl<'a> TyLoweringContext<'a> {
                 let inner_ty = self.lower_ty(inner);
                 TyKind::Slice(inner_ty).intern(&Interner)
             }
-            TypeRef::Reference(inner, _, mutability) => {
+            TypeRef::Reference(inner, lifetime, mutability) => {
                 let inner_ty = self.lower_ty(inner);
-                let lifetime = static_lifetime();
+                let lifetime = self.lower_lifetime(lifetime.as_ref());
                 TyKind::Ref(lower_to_chalk_mutability(*mutability), lifetime, inner_ty)
                     .intern(&Interner)
             }
             TypeRef::Placeholder => TyKind::Error.intern(&Interner),
-            TypeRef::Fn(params, is_varargs) => {
-                let substs = self.with_shifted_in(DebruijnIndex::ONE, |ctx| {
-                    Substitution::from_iter(&Interner, params.iter().map(|tr| ctx.lower_ty(tr)))
-                });
-                TyKind::Function(FnPointer {
-                    num_binders: 0, // FIXME lower `for<'a> fn()` correctly
-                    sig: FnSig { abi: (), safety: Safety::Safe, variadic: *is_varargs },
-                    substitution: FnSubst(substs),
-                })
-                .intern(&Interner)
-            }
+            TypeRef::Fn(params, is_varargs) => self.lower_fn_ptr(&[], params, *is_varargs),
+            TypeRef::ForLifetime(lifetimes, inner) => match &**inner {
+                TypeRef::Fn(params, is_varargs) => {
+                    self.lower_fn_ptr(lifetimes, params, *is_varargs)
+                }
+                // FIXME: this is a bare trait object like `for<'a> Trait<'a>`, which we don't
+                // support without `dyn` anyway
+                _ => self.lower_ty(inner),
+            },
             TypeRef::DynTrait(bounds) => {
                 let self_ty =
                     TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0)).intern(&Interner);
@@ -354,6 +426,38 @@ impl<'a> TyLoweringContext<'a> {
         (ty, res)
     }
 
+    fn lower_fn_ptr(&self, lifetimes: &[Name], params: &[TypeRef], is_varargs: bool) -> Ty {
+        let substs = self.with_late_bound_lifetimes(lifetimes, |ctx| {
+            Substitution::from_iter(&Interner, params.iter().map(|tr| ctx.lower_ty(tr)))
+        });
+        TyKind::Function(FnPointer {
+            num_binders: lifetimes.len(),
+            sig: FnSig { abi: (), safety: Safety::Safe, variadic: is_varargs },
+            substitution: FnSubst(substs),
+        })
+        .intern(&Interner)
+    }
+
+    /// Lowers a lifetime bound by one of the `for<'a>` binders we're in to a
+    /// variable of that binder. All other lifetimes are lowered to `'static`.
+    fn lower_lifetime(&self, lifetime: Option<&LifetimeRef>) -> Lifetime {
+        let lifetime = match lifetime {
+            Some(it) => it,
+            None => return static_lifetime(),
+        };
+        let late_bound_lifetimes = self.late_bound_lifetimes.borrow();
+        let bound = late_bound_lifetimes.iter().rev().find_map(|(binder, lifetimes)| {
+            let idx = lifetimes.iter().position(|it| *it == lifetime.name)?;
+            // `impl Trait` is lowered outside of our binders, see `with_debruijn`
+            let debruijn = self.in_binders.depth().checked_sub(binder.depth())?;
+            Some(BoundVar::new(DebruijnIndex::new(debruijn), idx))
+        });
+        match bound {
+            Some(bound) => LifetimeData::BoundVar(bound).intern(&Interner),
+            None => static_lifetime(),
+        }
+    }
+
     /// This is only for `generic_predicates_for_param`, where we can't just
     /// lower the self types of the predicates since that could lead to cycles.
     /// So we just check here if the `type_ref` resolves to a generic param, and which.
@@ -514,16 +618,65 @@ impl<'a> TyLoweringContext<'a> {
                 Some(it) => it,
                 None => return (TyKind::Error.intern(&Interner), None),
             };
-        let (resolved_segment, remaining_segments) = match remaining_index {
-            None => (
-                path.segments().last().expect("resolved path has at least one element"),
-                PathSegments::EMPTY,
-            ),
-            Some(i) => (path.segments().get(i - 1).unwrap(), path.segments().skip(i)),
+        let resolved_index = match remaining_index {
+            None => {
+                self.check_path_visibility(path);
+                path.segments().len() - 1
+            }
+            Some(i) => i - 1,
         };
+        if let Some(def) = generic_def_for_type_ns(&resolution) {
+            self.check_generic_arg_count(path, resolved_index, def);
+        }
+        let resolved_segment =
+            path.segments().get(resolved_index).expect("resolved path has at least one element");
+        let remaining_segments = path.segments().skip(resolved_index + 1);
         self.lower_partly_resolved_path(resolution, resolved_segment, remaining_segments, false)
     }
 
+    /// Reports the segment at index `segment` of `path` if it doesn't give as
+    /// many generic arguments as `def` has parameters.
+    pub(crate) fn check_generic_arg_count(&self, path: &Path, segment: usize, def: GenericDefId) {
+        if !self.collects_diagnostics() {
+            return;
+        }
+        let args = match path.segments().get(segment).and_then(|it| it.args_and_bindings) {
+            Some(it) => it,
+            None => return,
+        };
+        for mismatch in generic_arg_count_mismatches(self.db, def, args) {
+            self.push_diagnostic(TyLoweringDiagnostic::MismatchedGenericArgCount {
+                path: path.clone(),
+                segment,
+                mismatch,
+            });
+        }
+    }
+
+    /// Reports a qualified path to an item which isn't visible from here.
+    fn check_path_visibility(&self, path: &Path) {
+        if !self.collects_diagnostics() {
+            return;
+        }
+        let mod_path = path.mod_path();
+        if mod_path.kind == PathKind::Plain && mod_path.segments().len() == 1 {
+            return;
+        }
+        let module = match self.resolver.module() {
+            Some(it) => it,
+            None => return,
+        };
+        let per_ns = self.resolver.resolve_module_path_in_items(self.db.upcast(), mod_path);
+        if let Some((item, vis)) = per_ns.types {
+            if !vis.is_visible_from(self.db.upcast(), module) {
+                self.push_diagnostic(TyLoweringDiagnostic::PrivateItem {
+                    path: path.clone(),
+                    item,
+                });
+            }
+        }
+    }
+
     fn select_associated_type(&self, res: Option<TypeNs>, segment: PathSegment<'_>) -> Ty {
         if let Some(res) = res {
             let ty = associated_type_shorthand_candidates(
@@ -595,7 +748,7 @@ impl<'a> TyLoweringContext<'a> {
         resolved: ValueTyDefId,
         infer_args: bool,
     ) -> Substitution {
-        let last = path.segments().last().expect("path should have at least one segment");
+        let last = path.segments().len() - 1;
         let (segment, generic_def) = match resolved {
             ValueTyDefId::FunctionId(it) => (last, Some(it.into())),
             ValueTyDefId::StructId(it) => (last, Some(it.into())),
@@ -608,15 +761,18 @@ impl<'a> TyLoweringContext<'a> {
                 // referring to the variant. So `Option::<T>::None` and
                 // `Option::None::<T>` are both allowed (though the former is
                 // preferred). See also `def_ids_for_path_segments` in rustc.
-                let len = path.segments().len();
-                let penultimate = if len >= 2 { path.segments().get(len - 2) } else { None };
+                let penultimate = last.checked_sub(1).and_then(|it| path.segments().get(it));
                 let segment = match penultimate {
-                    Some(segment) if segment.args_and_bindings.is_some() => segment,
+                    Some(segment) if segment.args_and_bindings.is_some() => last - 1,
                     _ => last,
                 };
                 (segment, Some(var.parent.into()))
             }
         };
+        if let Some(def) = generic_def {
+            self.check_generic_arg_count(path, segment, def);
+        }
+        let segment = path.segments().get(segment).expect("path should have at least one segment");
         self.substs_from_path_segment(segment, generic_def, infer_args, None)
     }
 
@@ -667,7 +823,7 @@ impl<'a> TyLoweringContext<'a> {
                         let ty = self.lower_ty(type_ref);
                         substs.push(ty);
                     }
-                    GenericArg::Lifetime(_) => {}
+                    GenericArg::Lifetime(_) | GenericArg::Const(_) => {}
                 }
             }
         } else {
@@ -710,7 +866,10 @@ impl<'a> TyLoweringContext<'a> {
                 TypeNs::TraitId(tr) => tr,
                 _ => return None,
             };
-        let segment = path.segments().last().expect("path should have at least one segment");
+        self.check_path_visibility(path);
+        let last = path.segments().len() - 1;
+        self.check_generic_arg_count(path, last, resolved.into());
+        let segment = path.segments().get(last).expect("path should have at least one segment");
         Some(self.lower_trait_ref_from_resolved_path(resolved, segment, explicit_self_ty))
     }
 
@@ -747,34 +906,63 @@ impl<'a> TyLoweringContext<'a> {
         ignore_bindings: bool,
     ) -> impl Iterator<Item = QuantifiedWhereClause> + 'a {
         match where_predicate {
-            WherePredicate::ForLifetime { target, bound, .. }
-            | WherePredicate::TypeBound { target, bound } => {
-                let self_ty = match target {
-                    WherePredicateTypeTarget::TypeRef(type_ref) => self.lower_ty(type_ref),
-                    WherePredicateTypeTarget::TypeParam(param_id) => {
-                        let generic_def = self.resolver.generic_def().expect("generics in scope");
-                        let generics = generics(self.db.upcast(), generic_def);
-                        let param_id =
-                            hir_def::TypeParamId { parent: generic_def, local_id: *param_id };
-                        let placeholder = to_placeholder_idx(self.db, param_id);
-                        match self.type_param_mode {
-                            TypeParamLoweringMode::Placeholder => TyKind::Placeholder(placeholder),
-                            TypeParamLoweringMode::Variable => {
-                                let idx = generics.param_idx(param_id).expect("matching generics");
-                                TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, idx))
-                            }
-                        }
-                        .intern(&Interner)
-                    }
-                };
+            WherePredicate::TypeBound { target, bound } => {
+                let self_ty = self.lower_where_predicate_target(target);
                 self.lower_type_bound(bound, self_ty, ignore_bindings)
                     .collect::<Vec<_>>()
                     .into_iter()
             }
+            WherePredicate::ForLifetime { lifetimes, target, bound } => self
+                .lower_higher_ranked(lifetimes, |ctx| {
+                    let self_ty = ctx.lower_where_predicate_target(target);
+                    ctx.lower_type_bound(bound, self_ty, ignore_bindings).collect()
+                })
+                .into_iter(),
             WherePredicate::Lifetime { .. } => vec![].into_iter(),
         }
     }
 
+    fn lower_where_predicate_target(&self, target: &WherePredicateTypeTarget) -> Ty {
+        match target {
+            WherePredicateTypeTarget::TypeRef(type_ref) => self.lower_ty(type_ref),
+            WherePredicateTypeTarget::TypeParam(param_id) => {
+                let generic_def = self.resolver.generic_def().expect("generics in scope");
+                let generics = generics(self.db.upcast(), generic_def);
+                let param_id = hir_def::TypeParamId { parent: generic_def, local_id: *param_id };
+                let placeholder = to_placeholder_idx(self.db, param_id);
+                match self.type_param_mode {
+                    TypeParamLoweringMode::Placeholder => TyKind::Placeholder(placeholder),
+                    TypeParamLoweringMode::Variable => {
+                        let idx = generics.param_idx(param_id).expect("matching generics");
+                        TyKind::BoundVar(BoundVar::new(self.in_binders, idx))
+                    }
+                }
+                .intern(&Interner)
+            }
+        }
+    }
+
+    /// Lowers the clauses produced by `f` inside the binder of a `for<'a>`, and
+    /// moves them under that binder.
+    fn lower_higher_ranked(
+        &self,
+        lifetimes: &[Name],
+        f: impl FnOnce(&TyLoweringContext) -> Vec<QuantifiedWhereClause>,
+    ) -> Vec<QuantifiedWhereClause> {
+        let clauses = self.with_late_bound_lifetimes(lifetimes, f);
+        clauses
+            .into_iter()
+            .filter_map(|clause| {
+                // The clauses only have binders of their own if they come from
+                // a `for<'b>` bound on an associated type binding.
+                // FIXME: merge those binders instead of dropping such bounds
+                let clause =
+                    clause.into_value_and_skipped_binders().0.shifted_out(&Interner).ok()?;
+                Some(crate::make_late_bound_lifetime_binders(lifetimes.len(), clause))
+            })
+            .collect()
+    }
+
     pub(crate) fn lower_type_bound(
         &'a self,
         bound: &'a TypeBound,
@@ -782,6 +970,7 @@ impl<'a> TyLoweringContext<'a> {
         ignore_bindings: bool,
     ) -> impl Iterator<Item = QuantifiedWhereClause> + 'a {
         let mut bindings = None;
+        let mut higher_ranked = Vec::new();
         let trait_ref = match bound {
             TypeBound::Path(path, TraitBoundModifier::None) => {
                 bindings = self.lower_trait_ref_from_path(path, Some(self_ty));
@@ -804,20 +993,36 @@ impl<'a> TyLoweringContext<'a> {
                 }
                 None
             }
-            TypeBound::ForLifetime(_, path) => {
-                // FIXME Don't silently drop the hrtb lifetimes here
-                bindings = self.lower_trait_ref_from_path(path, Some(self_ty));
-                bindings.clone().map(WhereClause::Implemented).map(crate::wrap_empty_binders)
+            TypeBound::ForLifetime(lifetimes, path) => {
+                higher_ranked = self.lower_higher_ranked(lifetimes, |ctx| {
+                    let self_ty = self_ty.shifted_in(&Interner);
+                    let trait_ref = match ctx.lower_trait_ref_from_path(path, Some(self_ty)) {
+                        Some(it) => it,
+                        None => return Vec::new(),
+                    };
+                    let bindings = if ignore_bindings {
+                        Vec::new()
+                    } else {
+                        ctx.assoc_type_bindings_from_type_bound(bound, trait_ref.clone()).collect()
+                    };
+                    iter::once(crate::wrap_empty_binders(WhereClause::Implemented(trait_ref)))
+                        .chain(bindings)
+                        .collect()
+                });
+                None
             }
             TypeBound::Lifetime(_) => None,
             TypeBound::Error => None,
         };
-        trait_ref.into_iter().chain(
-            bindings
-                .into_iter()
-                .filter(move |_| !ignore_bindings)
-                .flat_map(move |tr| self.assoc_type_bindings_from_type_bound(bound, tr)),
-        )
+        trait_ref
+            .into_iter()
+            .chain(
+                bindings
+                    .into_iter()
+                    .filter(move |_| !ignore_bindings)
+                    .flat_map(move |tr| self.assoc_type_bindings_from_type_bound(bound, tr)),
+            )
+            .chain(higher_ranked)
     }
 
     fn assoc_type_bindings_from_type_bound(
@@ -908,6 +1113,72 @@ impl<'a> TyLoweringContext<'a> {
     }
 }
 
+/// The item a path segment resolving to `res` gives generic arguments to.
+pub(crate) fn generic_def_for_type_ns(res: &TypeNs) -> Option<GenericDefId> {
+    match *res {
+        TypeNs::AdtId(it) => Some(it.into()),
+        TypeNs::TypeAliasId(it) => Some(it.into()),
+        TypeNs::TraitId(it) => Some(it.into()),
+        TypeNs::SelfType(_)
+        | TypeNs::GenericParam(_)
+        | TypeNs::AdtSelfType(_)
+        | TypeNs::EnumVariantId(_)
+        | TypeNs::BuiltinType(_) => None,
+    }
+}
+
+/// Compares the generic arguments `args` given to `def` with its generic
+/// parameters, as rustc does.
+pub(crate) fn generic_arg_count_mismatches(
+    db: &dyn HirDatabase,
+    def: GenericDefId,
+    args: &GenericArgs,
+) -> Vec<GenericArgCountMismatch> {
+    let params = db.generic_params(def);
+    let (mut min, mut max) = (0, 0);
+    for (_, param) in params.types.iter() {
+        match param.provenance {
+            TypeParamProvenance::TypeParamList => {
+                max += 1;
+                if param.default.is_none() {
+                    min += 1;
+                }
+            }
+            // The `Self` of a trait is never given in its arguments.
+            TypeParamProvenance::TraitSelf => {}
+            // Functions with `impl Trait` arguments can't be given any.
+            TypeParamProvenance::ArgumentImplTrait => return Vec::new(),
+        }
+    }
+    min += params.consts.len();
+    max += params.consts.len();
+
+    let (mut found_lifetimes, mut found) = (0, 0);
+    for arg in &args.args {
+        match arg {
+            GenericArg::Lifetime(_) => found_lifetimes += 1,
+            GenericArg::Type(_) | GenericArg::Const(_) => found += 1,
+        }
+    }
+    if args.has_self_type {
+        // `<T as Trait>`
+        found -= 1;
+    }
+
+    let mut res = Vec::new();
+    let lifetimes = params.lifetimes.len();
+    if found_lifetimes != 0 && found_lifetimes != lifetimes {
+        res.push(GenericArgCountMismatch::Lifetimes {
+            expected: lifetimes,
+            found: found_lifetimes,
+        });
+    }
+    if found < min || found > max {
+        res.push(GenericArgCountMismatch::TypesAndConsts { min, max, found });
+    }
+    res
+}
+
 fn count_impl_traits(type_ref: &TypeRef) -> usize {
     let mut count = 0;
     type_ref.walk(&mut |type_ref| {
@@ -955,14 +1226,11 @@ pub fn associated_type_shorthand_candidates<R>(
         ),
         TypeNs::GenericParam(param_id) => {
             let predicates = db.generic_predicates_for_param(param_id);
-            let res = predicates.iter().find_map(|pred| match pred.skip_binders().skip_binders() {
-                // FIXME: how to correctly handle higher-ranked bounds here?
-                WhereClause::Implemented(tr) => search(
-                    tr.clone()
-                        .shifted_out_to(&Interner, DebruijnIndex::ONE)
-                        .expect("FIXME unexpected higher-ranked trait bound"),
-                ),
-                _ => None,
+            let res = predicates.iter().find_map(|pred| {
+                match crate::erase_late_bound_lifetimes(pred.skip_binders().clone()) {
+                    WhereClause::Implemented(tr) => search(tr),
+                    _ => None,
+                }
             });
             if let res @ Some(_) = res {
                 return res;
@@ -1130,30 +1398,32 @@ pub(crate) fn generic_predicates_query(
 }
 
 /// Generate implicit `: Sized` predicates for all generics that has no `?Sized` bound.
-/// Exception is Self of a trait def.
-fn implicitly_sized_clauses<'a>(
+/// Exception is Self of a trait, also in the generics of its items.
+fn implicitly_sized_clauses(
     db: &dyn HirDatabase,
     def: GenericDefId,
-    explicitly_unsized_tys: &'a FxHashSet<Ty>,
-    substitution: &'a Substitution,
+    explicitly_unsized_tys: &FxHashSet<Ty>,
+    substitution: &Substitution,
     resolver: &Resolver,
-) -> impl Iterator<Item = WhereClause> + 'a {
-    let is_trait_def = matches!(def, GenericDefId::TraitId(..));
-    let generic_args = &substitution.as_slice(&Interner)[is_trait_def as usize..];
+) -> impl Iterator<Item = WhereClause> {
+    let implicitly_sized_tys: Vec<_> = generics(db.upcast(), def)
+        .iter()
+        .zip(substitution.iter(&Interner))
+        .filter(|((_, data), _)| data.provenance != TypeParamProvenance::TraitSelf)
+        .filter_map(|(_, generic_arg)| generic_arg.ty(&Interner))
+        .filter(|self_ty| !explicitly_unsized_tys.contains(self_ty))
+        .cloned()
+        .collect();
     let sized_trait = resolver
         .krate()
         .and_then(|krate| db.lang_item(krate, "sized".into()))
         .and_then(|lang_item| lang_item.as_trait().map(to_chalk_trait_id));
 
     sized_trait.into_iter().flat_map(move |sized_trait| {
-        let implicitly_sized_tys = generic_args
-            .iter()
-            .filter_map(|generic_arg| generic_arg.ty(&Interner))
-            .filter(move |&self_ty| !explicitly_unsized_tys.contains(self_ty));
-        implicitly_sized_tys.map(move |self_ty| {
+        implicitly_sized_tys.clone().into_iter().map(move |self_ty| {
             WhereClause::Implemented(TraitRef {
                 trait_id: sized_trait,
-                substitution: Substitution::from1(&Interner, self_ty.clone()),
+                substitution: Substitution::from1(&Interner, self_ty),
             })
         })
     })
@@ -1208,6 +1208,32 @@ mod b {
     )
 }
 
+#[test]
+fn private_field_and_method_are_still_inferred() {
+    check_types(
+        r#"
+mod a {
+    pub struct Foo(u32);
+    impl Foo {
+        pub fn new() -> Self {
+            Self(0)
+        }
+        fn value(&self) -> u64 {
+            0
+        }
+    }
+}
+fn foo() {
+    let foo = a::Foo::new();
+    let field = foo.0;
+     // ^^^^^ u32
+    let value = foo.value();
+     // ^^^^^ u64
+}
+"#,
+    )
+}
+
 #[test]
 fn trait_impl_in_unnamed_const() {
     check_types(
@@ -163,11 +163,15 @@ fn infer_range_pattern() {
             8..9 'x': &i32
             17..75 '{     ...2 {} }': ()
             23..45 'if let...u32 {}': ()
+            30..31 '1': u32
             30..35 '1..76': u32
+            33..35 '76': u32
             38..42 '2u32': u32
             43..45 '{}': ()
             50..73 'if let...u32 {}': ()
+            57..58 '1': u32
             57..63 '1..=76': u32
+            61..63 '76': u32
             66..70 '2u32': u32
             71..73 '{}': ()
         "#]],
@@ -1,6 +1,6 @@
 use expect_test::expect;
 
-use super::{check_infer, check_types};
+use super::{check_infer, check_no_mismatches, check_types};
 
 #[test]
 fn infer_box() {
@@ -1202,14 +1202,13 @@ fn infer_array() {
 
             let b = [a, ["b"]];
             let x: [u8; 0] = [];
-            // FIXME: requires const evaluation/taking type from rhs somehow
             let y: [u8; 2+2] = [1,2,3,4];
         }
         "#,
         expect![[r#"
             8..9 'x': &str
             17..18 'y': isize
-            27..395 '{     ...,4]; }': ()
+            27..326 '{     ...,4]; }': ()
             37..38 'a': [&str; 1]
             41..44 '[x]': [&str; 1]
             42..43 'x': &str
@@ -1259,16 +1258,50 @@ fn infer_array() {
             259..262 '"b"': &str
             274..275 'x': [u8; 0]
             287..289 '[]': [u8; 0]
-            368..369 'y': [u8; _]
-            383..392 '[1,2,3,4]': [u8; 4]
-            384..385 '1': u8
-            386..387 '2': u8
-            388..389 '3': u8
-            390..391 '4': u8
+            299..300 'y': [u8; 4]
+            314..323 '[1,2,3,4]': [u8; 4]
+            315..316 '1': u8
+            317..318 '2': u8
+            319..320 '3': u8
+            321..322 '4': u8
         "#]],
     );
 }
 
+#[test]
+fn array_lengths_are_evaluated() {
+    check_types(
+        r#"
+const SIZE: usize = 4 + 4;
+struct S;
+impl S {
+    const N: usize = 2;
+}
+fn test() {
+    let a = [0u8; S::N * SIZE];
+    a;
+  //^ [u8; 16]
+    let b: [u8; { 1 << 3 }] = [0; SIZE];
+    b;
+  //^ [u8; 8]
+    let c = [0u8; -1];
+    c;
+  //^ [u8; _]
+}
+"#,
+    );
+    check_no_mismatches(
+        r#"
+const SIZE: usize = 4 + 4;
+fn f(_: [u8; SIZE * 2]) {}
+fn test() {
+    f([0; 16]);
+    let a: [u8; (SIZE - 2) / 2] = [1, 2, 3];
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_struct_generics() {
     check_infer(
@@ -3544,3 +3544,119 @@ fn f<F: Foo>() {
         "#]],
     );
 }
+
+#[test]
+fn closure_passed_to_higher_ranked_bound() {
+    check_types(
+        r#"
+//- minicore: fn
+struct S;
+impl S {
+    fn inherent(&self) -> u8 { 0 }
+}
+fn apply<F: for<'a> Fn(&'a S) -> &'a S>(f: F) -> F { f }
+fn apply_where<F>(f: F) -> F where for<'a> F: FnOnce(&'a S) -> (&'a S, u8) { f }
+
+fn test() {
+    apply(|s| { s.inherent(); s });
+              //^^^^^^^^^^^^ u8
+    apply_where(|s| (s, s.inherent()));
+               //^ &S
+}
+"#,
+    );
+}
+
+#[test]
+fn call_higher_ranked_bound() {
+    check_types(
+        r#"
+//- minicore: fn
+struct S;
+fn call<F>(f: F, s: &S) where F: for<'a> Fn(&'a S) -> &'a u32 {
+    let x = f(s);
+      //^ &u32
+    let g: for<'a> fn(&'a S, &'a u32) -> &'a u32 = |_, x| x;
+    let y = g(s, x);
+      //^ &u32
+}
+"#,
+    );
+}
+
+#[test]
+fn higher_ranked_dyn_trait() {
+    check_types(
+        r#"
+//- minicore: fn, coerce_unsized
+struct S;
+impl S {
+    fn inherent(&self) -> u8 { 0 }
+}
+trait Tr<'a> {
+    type Out;
+    fn tr(&self, s: &'a S) -> Self::Out;
+}
+fn take_dyn_fn(f: &dyn for<'a> Fn(&'a S) -> &'a u8) {}
+fn take_dyn_tr(t: &dyn for<'a> Tr<'a, Out = u16>, s: &S) {
+    let x = t.tr(s);
+      //^ u16
+}
+
+fn f() {
+    take_dyn_fn(&|x| { x.inherent(); &0 });
+                     //^^^^^^^^^^^^ u8
+}
+"#,
+    );
+}
+
+#[test]
+fn higher_ranked_supertrait() {
+    check_types(
+        r#"
+struct S;
+trait Tr<'a> {
+    type Out;
+    fn tr(&self, s: &'a S) -> Self::Out;
+}
+trait Sub: for<'a> Tr<'a, Out = u16> {}
+fn f<T: Sub>(t: T, s: &S) {
+    let x = t.tr(s);
+      //^ u16
+}
+"#,
+    );
+}
+
+#[test]
+fn higher_ranked_fn_ptr_no_mismatches() {
+    check_infer_with_mismatches(
+        r#"
+//- minicore: fn
+struct S;
+fn apply<F>(f: F) where F: for<'a> Fn(&'a S) -> &'a S {}
+fn test(g: for<'a> fn(&'a S) -> &'a S) {
+    apply(g);
+    apply(|s| s);
+    let h: fn(&S) -> &S = g;
+}
+"#,
+        expect![[r#"
+            22..23 'f': F
+            64..66 '{}': ()
+            75..76 'g': fn(&S) -> &S
+            106..170 '{     ...= g; }': ()
+            112..117 'apply': fn apply<fn(&S) -> &S>(fn(&S) -> &S)
+            112..120 'apply(g)': ()
+            118..119 'g': fn(&S) -> &S
+            126..131 'apply': fn apply<|&S| -> &S>(|&S| -> &S)
+            126..138 'apply(|s| s)': ()
+            132..137 '|s| s': |&S| -> &S
+            133..134 's': &S
+            136..137 's': &S
+            148..149 'h': fn(&S) -> &S
+            166..167 'g': fn(&S) -> &S
+        "#]],
+    );
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use either::Either;
 use hir::{AsAssocItem, HasAttrs, HasSource, HirDisplay, Semantics, TypeInfo};
 use ide_db::{
@@ -32,6 +34,10 @@ use crate::{
 pub struct HoverConfig {
     pub links_in_hover: bool,
     pub documentation: Option<HoverDocFormat>,
+    /// Show the values of constants, statics and enum discriminants.
+    pub const_values: bool,
+    /// Show the size and alignment of types, and the offsets of fields.
+    pub memory_layout: bool,
 }
 
 impl HoverConfig {
@@ -520,14 +526,51 @@ fn hover_for_definition(
             },
             it.attrs(db).docs(),
         ),
-        Definition::Field(def) => label_and_docs(db, def),
+        Definition::Field(def) => {
+            let (mut label, docs) = label_and_docs(db, def);
+            if config.memory_layout {
+                push_layout(&mut label, def.ty(db).layout(db), Some(def.offset(db)));
+            }
+            (label, docs)
+        }
         Definition::ModuleDef(it) => match it {
             hir::ModuleDef::Module(it) => label_and_docs(db, it),
             hir::ModuleDef::Function(it) => label_and_docs(db, it),
-            hir::ModuleDef::Adt(it) => label_and_docs(db, it),
-            hir::ModuleDef::Variant(it) => label_and_docs(db, it),
-            hir::ModuleDef::Const(it) => label_and_docs(db, it),
-            hir::ModuleDef::Static(it) => label_and_docs(db, it),
+            hir::ModuleDef::Adt(it) => {
+                let (mut label, docs) = label_and_docs(db, it);
+                if config.memory_layout {
+                    push_layout(&mut label, it.layout(db), None);
+                }
+                (label, docs)
+            }
+            hir::ModuleDef::Variant(it) => {
+                let (mut label, docs) = label_and_docs(db, it);
+                // Only the discriminants of fieldless enums can be observed.
+                let is_fieldless = || {
+                    let variants = it.parent_enum(db).variants(db);
+                    variants.iter().all(|it| it.kind(db) == hir::StructKind::Unit)
+                };
+                if config.const_values && is_fieldless() {
+                    let ty = it.parent_enum(db).discriminant_ty(db);
+                    push_value(db, &mut label, it.discriminant(db), &ty);
+                }
+                (label, docs)
+            }
+            hir::ModuleDef::Const(it) => {
+                let (mut label, docs) = label_and_docs(db, it);
+                if config.const_values {
+                    push_value(db, &mut label, it.eval(db), &it.ty(db));
+                }
+                (label, docs)
+            }
+            hir::ModuleDef::Static(it) => {
+                let (mut label, docs) = label_and_docs(db, it);
+                // The initial value of a `static mut` says little about its value.
+                if config.const_values && !it.is_mut(db) {
+                    push_value(db, &mut label, it.eval(db), &it.ty(db));
+                }
+                (label, docs)
+            }
             hir::ModuleDef::Trait(it) => label_and_docs(db, it),
             hir::ModuleDef::TypeAlias(it) => label_and_docs(db, it),
             hir::ModuleDef::BuiltinType(it) => {
@@ -558,6 +601,57 @@ fn hover_for_definition(
         let docs = def.attrs(db).docs();
         (label, docs)
     }
+
+    fn push_layout(
+        label: &mut String,
+        layout: Result<Arc<hir::Layout>, hir::LayoutError>,
+        offset: Option<Result<u64, hir::LayoutError>>,
+    ) {
+        match layout {
+            Ok(layout) => {
+                format_to!(label, "\n// size = {}, align = {}", layout.size, layout.align);
+                match offset {
+                    Some(Ok(offset)) => format_to!(label, ", offset = {}", offset),
+                    Some(Err(hir::LayoutError::HasPlaceholder)) => {
+                        format_to!(label, ", offset = unknown")
+                    }
+                    _ => (),
+                }
+            }
+            Err(hir::LayoutError::HasPlaceholder) => {
+                format_to!(
+                    label,
+                    "\n// size = unknown, align = unknown: depends on generic parameters"
+                )
+            }
+            Err(_) => (),
+        }
+    }
+
+    /// Integers are shown both in decimal and in hexadecimal, with the bits of
+    /// `ty` for negative ones.
+    fn push_value(
+        db: &RootDatabase,
+        label: &mut String,
+        value: Result<hir::ComputedExpr, hir::ConstEvalError>,
+        ty: &hir::Type,
+    ) {
+        let bits = match value {
+            Ok(hir::ComputedExpr::Int(it)) if it >= 0 => Some(it as u128),
+            Ok(hir::ComputedExpr::Int(it)) => match ty.layout(db) {
+                Ok(layout) if layout.size < 16 => Some(it as u128 & ((1 << (layout.size * 8)) - 1)),
+                Ok(_) => Some(it as u128),
+                Err(_) => None,
+            },
+            Ok(hir::ComputedExpr::UInt(it)) => Some(it),
+            Ok(_) | Err(_) => None,
+        };
+        match (value, bits) {
+            (Ok(value), Some(bits)) => format_to!(label, " = {} ({:#X})", value, bits),
+            (Ok(value), None) => format_to!(label, " = {}", value),
+            (Err(_), _) => (),
+        }
+    }
 }
 
 fn hover_for_local(it: hir::Local, db: &RootDatabase) -> Option<Markup> {
@@ -637,6 +731,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::Markdown),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
             )
@@ -651,6 +747,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::Markdown),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
             )
@@ -671,6 +769,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: false,
                     documentation: Some(HoverDocFormat::Markdown),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
             )
@@ -691,6 +791,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::PlainText),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
             )
@@ -711,6 +813,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::Markdown),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 FileRange { file_id, range: position.range_or_empty() },
             )
@@ -726,6 +830,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: false,
                     documentation: Some(HoverDocFormat::Markdown),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 range,
             )
@@ -741,6 +847,8 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: false,
                     documentation: Some(HoverDocFormat::Markdown),
+                    const_values: true,
+                    memory_layout: true,
                 },
                 range,
             )
@@ -998,6 +1106,7 @@ fn main() {
 
                 ```rust
                 field_a: u32
+                // size = 4, align = 4, offset = 0
                 ```
             "#]],
         );
@@ -1020,6 +1129,7 @@ fn main() {
 
                 ```rust
                 field_a: u32
+                // size = 4, align = 4, offset = 0
                 ```
             "#]],
         );
@@ -1037,7 +1147,7 @@ fn main() {
                 ```
 
                 ```rust
-                const foo: u32
+                const foo: u32 = 123 (0x7B)
                 ```
             "#]],
         );
@@ -1051,10 +1161,371 @@ fn main() {
                 ```
 
                 ```rust
-                static foo: u32
+                static foo: u32 = 456 (0x1C8)
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_const_eval() {
+        check(
+            r#"
+const BITS: u32 = 4;
+const MASK$0: u32 = (1 << BITS) - 1;
+"#,
+            expect![[r#"
+                *MASK*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const MASK: u32 = 15 (0xF)
+                ```
+            "#]],
+        );
+        check(
+            r#"const NEG$0: i8 = -3 * 4;"#,
+            expect![[r#"
+                *NEG*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const NEG: i8 = -12 (0xF4)
+                ```
+            "#]],
+        );
+        check(
+            r#"
+const GREETING: &str = "hi\tthere";
+fn main() {
+    let _ = GREETING$0;
+}
+"#,
+            expect![[r#"
+                *GREETING*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const GREETING: &str = "hi\tthere"
+                ```
+            "#]],
+        );
+        check(
+            r#"static LETTER$0: char = 'x';"#,
+            expect![[r#"
+                *LETTER*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                static LETTER: char = 'x'
+                ```
+            "#]],
+        );
+        check(
+            r#"static mut COUNTER$0: u32 = 0;"#,
+            expect![[r#"
+                *COUNTER*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                static mut COUNTER: u32
+                ```
+            "#]],
+        );
+        check(
+            r#"const OVERFLOW$0: u8 = 255 + 1;"#,
+            expect![[r#"
+                *OVERFLOW*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const OVERFLOW: u8
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_assoc_const_eval() {
+        check(
+            r#"
+trait Tr { const SIZE: usize; }
+struct S;
+impl Tr for S { const SIZE$0: usize = 2 * 8; }
+"#,
+            expect![[r#"
+                *SIZE*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const SIZE: usize = 16 (0x10)
+                ```
+            "#]],
+        );
+        check(
+            r#"
+struct S;
+impl S { const HALF: u8 = 255 / 2; }
+impl S { const C: char = ((Self::HALF - 7) / 3) as char; }
+fn main() {
+    let _ = S::C$0;
+}
+"#,
+            expect![[r#"
+                *C*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const C: char = '('
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_const_u128() {
+        check(
+            r#"const A$0: u128 = !0;"#,
+            expect![[r#"
+                *A*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const A: u128 = 340282366920938463463374607431768211455 (0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF)
+                ```
+            "#]],
+        );
+        check(
+            r#"const E$0: u128 = 1 << 127;"#,
+            expect![[r#"
+                *E*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const E: u128 = 170141183460469231731687303715884105728 (0x80000000000000000000000000000000)
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_enum_discriminant() {
+        check(
+            r#"
+enum E { A = 10, B$0, C = 2 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B = 11 (0xB)
                 ```
             "#]],
         );
+        check(
+            r#"
+#[repr(i8)]
+enum E { A = -128, B$0 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B = -127 (0x81)
+                ```
+            "#]],
+        );
+        check(
+            r#"
+enum E { A$0 = -1, B }
+"#,
+            expect![[r#"
+                *A*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                A = -1 (0xFFFFFFFFFFFFFFFF)
+                ```
+            "#]],
+        );
+        check(
+            r#"
+enum E { A(u8), B$0 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_layout() {
+        check(
+            r#"
+struct Foo$0 { a: u8, b: u32, c: (u16, u8) }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                struct Foo
+                // size = 12, align = 4
+                ```
+            "#]],
+        );
+        check(
+            r#"
+#[repr(C)]
+struct Foo { a: u8, c$0: (u16, u8), b: u32 }
+"#,
+            expect![[r#"
+                *c*
+
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                c: (u16, u8)
+                // size = 4, align = 2, offset = 2
+                ```
+            "#]],
+        );
+        check(
+            r#"
+enum Foo { A(u8), B { b$0: u64 } }
+"#,
+            expect![[r#"
+                *b*
+
+                ```rust
+                test::B
+                ```
+
+                ```rust
+                b: u64
+                // size = 8, align = 8, offset = 8
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_layout_generic() {
+        check(
+            r#"
+struct Foo$0<T> { a: u8, b: T }
+"#,
+            expect![[r#"
+                *Foo*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                struct Foo<T>
+                // size = unknown, align = unknown: depends on generic parameters
+                ```
+            "#]],
+        );
+        check(
+            r#"
+struct Foo<T> { a$0: u8, b: T }
+"#,
+            expect![[r#"
+                *a*
+
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                a: u8
+                // size = 1, align = 1, offset = unknown
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_const_values_disabled() {
+        let (analysis, position) = fixture::position(r#"const FOO$0: u32 = 1 + 1;"#);
+        let hover = analysis
+            .hover(
+                &HoverConfig {
+                    links_in_hover: true,
+                    documentation: Some(HoverDocFormat::Markdown),
+                    const_values: false,
+                    memory_layout: true,
+                },
+                FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
+            )
+            .unwrap()
+            .unwrap();
+        expect![[r#"
+
+            ```rust
+            test
+            ```
+
+            ```rust
+            const FOO: u32
+            ```"#]]
+        .assert_eq(hover.info.markup.as_str());
     }
 
     #[test]
@@ -1132,7 +1603,7 @@ enum Option<T> {
                 ```
 
                 ```rust
-                None
+                None = 0 (0x0)
                 ```
 
                 ---
@@ -1322,7 +1793,7 @@ fn main() {
                 ```
 
                 ```rust
-                const C: u32
+                const C: u32 = 1 (0x1)
                 ```
             "#]],
         )
@@ -1829,6 +2300,7 @@ fn foo() { let bar = Ba$0r; }
 
                 ```rust
                 struct Bar
+                // size = 0, align = 1
                 ```
 
                 ---
@@ -1865,6 +2337,7 @@ fn foo() { let bar = Ba$0r; }
 
                 ```rust
                 struct Bar
+                // size = 0, align = 1
                 ```
 
                 ---
@@ -1894,6 +2367,7 @@ fn foo() { let bar = Ba$0r; }
 
                 ```rust
                 struct Bar
+                // size = 0, align = 1
                 ```
 
                 ---
@@ -1922,6 +2396,7 @@ pub struct B$0ar
 
                 ```rust
                 pub struct Bar
+                // size = 0, align = 1
                 ```
 
                 ---
@@ -1949,6 +2424,7 @@ pub struct B$0ar
 
                 ```rust
                 pub struct Bar
+                // size = 0, align = 1
                 ```
 
                 ---
@@ -3522,7 +3998,7 @@ fn foo() {
                 ```
 
                 ```rust
-                const FOO: usize
+                const FOO: usize = 3 (0x3)
                 ```
 
                 ---
@@ -3702,6 +4178,7 @@ pub fn gimme() -> theitem::TheItem {
 
                 ```rust
                 pub struct TheItem
+                // size = 0, align = 1
                 ```
 
                 ---
@@ -3807,6 +4284,7 @@ mod string {
 
                 ```rust
                 struct String
+                // size = 0, align = 1
                 ```
 
                 ---
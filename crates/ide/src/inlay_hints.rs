@@ -1,11 +1,12 @@
 use either::Either;
 use hir::{known, Callable, HasVisibility, HirDisplay, Semantics, TypeInfo};
 use ide_db::helpers::FamousDefs;
-use ide_db::RootDatabase;
+use ide_db::{line_index::LineIndex, LineIndexDatabase, RootDatabase};
 use stdx::to_lower_snake_case;
 use syntax::{
     ast::{self, ArgListOwner, AstNode, NameOwner},
-    match_ast, Direction, NodeOrToken, SmolStr, SyntaxKind, TextRange, T,
+    match_ast, Direction, NodeOrToken, SmolStr, SyntaxKind, SyntaxNode, SyntaxToken, TextRange,
+    WalkEvent, T,
 };
 
 use crate::FileId;
@@ -15,6 +16,12 @@ pub struct InlayHintsConfig {
     pub type_hints: bool,
     pub parameter_hints: bool,
     pub chaining_hints: bool,
+    pub closure_return_type_hints: bool,
+    pub lifetime_elision_hints: bool,
+    pub adjustment_hints: bool,
+    /// The minimum number of lines a block has to span to get a closing brace
+    /// hint, or `None` to not show them at all.
+    pub closing_brace_hints: Option<usize>,
     pub max_length: Option<usize>,
 }
 
@@ -23,6 +30,10 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    ClosureReturnTypeHint,
+    LifetimeHint,
+    AdjustmentHint,
+    ClosingBraceHint,
 }
 
 #[derive(Debug)]
@@ -42,6 +53,10 @@ pub struct InlayHint {
 // * types of local variables
 // * names of function arguments
 // * types of chained expressions
+// * return types of closures with a block body
+// * elided lifetimes in function signatures
+// * implicit reborrows, auto-derefs and auto-refs
+// * the item that a long block belongs to, after its closing brace
 //
 // **Note:** VS Code does not have native support for inlay hints https://github.com/microsoft/vscode/issues/16221[yet] and the hints are implemented using decorations.
 // This approach has limitations, the caret movement and bracket highlighting near the edges of the hint may be weird:
@@ -62,11 +77,13 @@ pub(crate) fn inlay_hints(
     let _p = profile::span("inlay_hints");
     let sema = Semantics::new(db);
     let file = sema.parse(file_id);
+    let line_index = db.line_index(file_id);
 
     let mut res = Vec::new();
     for node in file.syntax().descendants() {
         if let Some(expr) = ast::Expr::cast(node.clone()) {
-            get_chaining_hints(&mut res, &sema, config, expr);
+            get_chaining_hints(&mut res, &sema, config, expr.clone());
+            get_adjustment_hints(&mut res, &sema, config, expr);
         }
 
         match_ast! {
@@ -74,9 +91,12 @@ pub(crate) fn inlay_hints(
                 ast::CallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::MethodCallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::IdentPat(it) => { get_bind_pat_hints(&mut res, &sema, config, it); },
+                ast::ClosureExpr(it) => { get_closure_return_type_hints(&mut res, &sema, config, it); },
+                ast::Fn(it) => { get_lifetime_elision_hints(&mut res, config, it); },
                 _ => (),
             }
         }
+        get_closing_brace_hints(&mut res, config, &line_index, node);
     }
     res
 }
@@ -208,6 +228,230 @@ fn get_bind_pat_hints(
     Some(())
 }
 
+fn get_closure_return_type_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    closure: ast::ClosureExpr,
+) -> Option<()> {
+    if !config.closure_return_type_hints {
+        return None;
+    }
+
+    // Closures with an expression body make their return type obvious enough.
+    if closure.ret_type().is_some() || !matches!(closure.body()?, ast::Expr::BlockExpr(_)) {
+        return None;
+    }
+
+    let callable = sema.type_of_expr(&closure.clone().into())?.original.as_callable(sema.db)?;
+    let ty = callable.return_type();
+    if ty.is_unit() || ty.is_unknown() {
+        return None;
+    }
+
+    acc.push(InlayHint {
+        range: closure.param_list()?.syntax().text_range(),
+        kind: InlayKind::ClosureReturnTypeHint,
+        label: ty.display_truncated(sema.db, config.max_length).to_string().into(),
+    });
+    Some(())
+}
+
+fn get_lifetime_elision_hints(
+    acc: &mut Vec<InlayHint>,
+    config: &InlayHintsConfig,
+    func: ast::Fn,
+) -> Option<()> {
+    if !config.lifetime_elision_hints {
+        return None;
+    }
+
+    let param_list = func.param_list()?;
+    let mut gen_idx = 0;
+    let mut lifetime_of = |lifetime: Option<ast::Lifetime>, amp: SyntaxToken, acc: &mut Vec<_>| {
+        if let Some(lt) = lifetime {
+            return SmolStr::from(lt.text().as_str());
+        }
+        let name = SmolStr::from(format!("'{}", gen_idx));
+        gen_idx += 1;
+        acc.push(InlayHint {
+            range: amp.text_range(),
+            kind: InlayKind::LifetimeHint,
+            label: name.clone(),
+        });
+        name
+    };
+
+    let mut self_lifetime = None;
+    let mut input_lifetimes = Vec::new();
+    if let Some(self_param) = param_list.self_param() {
+        if let Some(amp) = self_param.amp_token() {
+            let name = lifetime_of(self_param.lifetime(), amp, acc);
+            self_lifetime = Some(name.clone());
+            input_lifetimes.push(name);
+        }
+        if let Some(ty) = self_param.ty() {
+            for ref_ty in ref_types(&ty) {
+                let name = lifetime_of(ref_ty.lifetime(), ref_ty.amp_token()?, acc);
+                if ref_ty.syntax() == ty.syntax() {
+                    self_lifetime = Some(name.clone());
+                }
+                input_lifetimes.push(name);
+            }
+        }
+    }
+    for ty in param_list.params().filter_map(|it| it.ty()) {
+        for ref_ty in ref_types(&ty) {
+            input_lifetimes.push(lifetime_of(ref_ty.lifetime(), ref_ty.amp_token()?, acc));
+        }
+    }
+
+    // Elided output lifetimes follow the elision rules: `&self` wins, otherwise
+    // there has to be exactly one input lifetime for them to be well-defined.
+    let output_lifetime = match self_lifetime {
+        Some(it) => it,
+        None => match input_lifetimes.as_slice() {
+            [it] => it.clone(),
+            _ => return None,
+        },
+    };
+    if output_lifetime == "'_" {
+        return None;
+    }
+    for ref_ty in ref_types(&func.ret_type()?.ty()?) {
+        if ref_ty.lifetime().is_none() {
+            acc.push(InlayHint {
+                range: ref_ty.amp_token()?.text_range(),
+                kind: InlayKind::LifetimeHint,
+                label: output_lifetime.clone(),
+            });
+        }
+    }
+    Some(())
+}
+
+/// Collects the reference types in `ty`, skipping over function pointer types
+/// and the parameters and return type of `Fn(&u8) -> &u8` sugar, as those
+/// introduce their own elision scope.
+fn ref_types(ty: &ast::Type) -> Vec<ast::RefType> {
+    let mut res = Vec::new();
+    let mut preorder = ty.syntax().preorder();
+    while let Some(event) = preorder.next() {
+        let node = match event {
+            WalkEvent::Enter(node) => node,
+            WalkEvent::Leave(_) => continue,
+        };
+        if matches!(node.kind(), SyntaxKind::PARAM_LIST | SyntaxKind::RET_TYPE)
+            && node.parent().is_some_and(|it| ast::PathSegment::can_cast(it.kind()))
+        {
+            preorder.skip_subtree();
+            continue;
+        }
+        match_ast! {
+            match node {
+                ast::FnPtrType(_it) => preorder.skip_subtree(),
+                ast::RefType(it) => res.push(it),
+                _ => (),
+            }
+        }
+    }
+    res
+}
+
+fn get_adjustment_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    expr: ast::Expr,
+) -> Option<()> {
+    if !config.adjustment_hints {
+        return None;
+    }
+
+    let adjustments = sema.expr_adjustments(&expr)?;
+    let kinds: Vec<_> = adjustments
+        .iter()
+        .map(|it| it.kind)
+        .filter(|it| !matches!(it, hir::Adjust::NeverToAny | hir::Adjust::Pointer))
+        .collect();
+    // A shared reborrow of a shared reference changes nothing worth showing.
+    if let [hir::Adjust::Deref(None), hir::Adjust::Borrow(hir::Mutability::Shared)] =
+        kinds.as_slice()
+    {
+        if !sema.type_of_expr(&expr)?.original.is_mutable_reference() {
+            return None;
+        }
+    }
+
+    // Adjustments are applied innermost first, so the outermost one comes first in the label.
+    let mut label = String::new();
+    for kind in kinds.iter().rev() {
+        label.push_str(match kind {
+            hir::Adjust::Deref(_) => "*",
+            hir::Adjust::Borrow(hir::Mutability::Shared) => "&",
+            hir::Adjust::Borrow(hir::Mutability::Mut) => "&mut ",
+            hir::Adjust::RawBorrow(hir::Mutability::Shared) => "&raw const ",
+            hir::Adjust::RawBorrow(hir::Mutability::Mut) => "&raw mut ",
+            hir::Adjust::NeverToAny | hir::Adjust::Pointer => continue,
+        });
+    }
+    if label.is_empty() {
+        return None;
+    }
+
+    acc.push(InlayHint {
+        range: expr.syntax().text_range(),
+        kind: InlayKind::AdjustmentHint,
+        label: label.into(),
+    });
+    Some(())
+}
+
+fn get_closing_brace_hints(
+    acc: &mut Vec<InlayHint>,
+    config: &InlayHintsConfig,
+    line_index: &LineIndex,
+    node: SyntaxNode,
+) -> Option<()> {
+    let min_lines = config.closing_brace_hints?;
+
+    let (label, block) = match_ast! {
+        match node {
+            ast::Fn(it) => (format!("fn {}", it.name()?), it.body()?.syntax().clone()),
+            ast::Impl(it) => {
+                let label = match it.trait_() {
+                    Some(trait_) => format!("impl {} for {}", trait_, it.self_ty()?),
+                    None => format!("impl {}", it.self_ty()?),
+                };
+                (label, it.assoc_item_list()?.syntax().clone())
+            },
+            ast::Trait(it) => (format!("trait {}", it.name()?), it.assoc_item_list()?.syntax().clone()),
+            ast::Module(it) => (format!("mod {}", it.name()?), it.item_list()?.syntax().clone()),
+            ast::Struct(it) => match it.field_list()? {
+                ast::FieldList::RecordFieldList(fields) => (format!("struct {}", it.name()?), fields.syntax().clone()),
+                ast::FieldList::TupleFieldList(_) => return None,
+            },
+            ast::Union(it) => (format!("union {}", it.name()?), it.record_field_list()?.syntax().clone()),
+            ast::Enum(it) => (format!("enum {}", it.name()?), it.variant_list()?.syntax().clone()),
+            _ => return None,
+        }
+    };
+
+    let r_curly = block.last_token().filter(|it| it.kind() == T!['}'])?;
+    let start = line_index.line_col(block.text_range().start()).line;
+    let end = line_index.line_col(r_curly.text_range().start()).line;
+    if ((end - start) as usize) < min_lines {
+        return None;
+    }
+
+    acc.push(InlayHint {
+        range: r_curly.text_range(),
+        kind: InlayKind::ClosingBraceHint,
+        label: label.into(),
+    });
+    Some(())
+}
+
 /// Checks if the type is an Iterator from std::iter and replaces its hint with an `impl Iterator<Item = Ty>`.
 fn hint_iterator(
     sema: &Semantics<RootDatabase>,
@@ -451,6 +695,20 @@ mod tests {
         type_hints: true,
         parameter_hints: true,
         chaining_hints: true,
+        closure_return_type_hints: false,
+        lifetime_elision_hints: false,
+        adjustment_hints: false,
+        closing_brace_hints: None,
+        max_length: None,
+    };
+    const DISABLED_CONFIG: InlayHintsConfig = InlayHintsConfig {
+        type_hints: false,
+        parameter_hints: false,
+        chaining_hints: false,
+        closure_return_type_hints: false,
+        lifetime_elision_hints: false,
+        adjustment_hints: false,
+        closing_brace_hints: None,
         max_length: None,
     };
 
@@ -464,6 +722,10 @@ mod tests {
                 parameter_hints: true,
                 type_hints: false,
                 chaining_hints: false,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             ra_fixture,
@@ -476,6 +738,10 @@ mod tests {
                 parameter_hints: false,
                 type_hints: true,
                 chaining_hints: false,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             ra_fixture,
@@ -488,6 +754,10 @@ mod tests {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             ra_fixture,
@@ -516,6 +786,10 @@ mod tests {
                 type_hints: false,
                 parameter_hints: false,
                 chaining_hints: false,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             r#"
@@ -1273,6 +1547,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             r#"
@@ -1329,6 +1607,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             r#"
@@ -1373,6 +1655,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             r#"
@@ -1418,6 +1704,10 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                closure_return_type_hints: false,
+                lifetime_elision_hints: false,
+                adjustment_hints: false,
+                closing_brace_hints: None,
                 max_length: None,
             },
             r#"
@@ -1467,4 +1757,140 @@ fn main() {
             "#]],
         );
     }
+
+    // Closure return type hint tests
+
+    #[test]
+    fn closure_return_type_hints() {
+        check_with_config(
+            InlayHintsConfig { closure_return_type_hints: true, ..DISABLED_CONFIG },
+            r#"
+fn main() {
+    let a = |x: i32| {
+          //^^^^^^^^ i32
+        x + 1
+    };
+    let b = |x: i32| x + 1;
+    let c = |x: i32| -> u64 { 0 };
+    let d = || {};
+}"#,
+        );
+    }
+
+    // Lifetime elision hint tests
+
+    #[test]
+    fn lifetime_elision_hints() {
+        check_with_config(
+            InlayHintsConfig { lifetime_elision_hints: true, ..DISABLED_CONFIG },
+            r#"
+fn single(x: &u8) -> &u8 { x }
+           //^ '0
+                   //^ '0
+fn named<'a>(x: &'a u8, y: &u16) {}
+                         //^ '0
+fn ambiguous(x: &u8, y: &u16) -> &u8 { x }
+              //^ '0
+                      //^ '1
+fn fn_ptr(f: fn(&u8) -> &u8) {}
+fn apply(f: impl Fn(&u8), x: &u8) -> &u8 { x }
+                           //^ '0
+                                   //^ '0
+struct S;
+impl S {
+    fn method(&self, x: &u8) -> &str { "" }
+            //^ '0
+                      //^ '1
+                              //^ '0
+    fn explicit<'a>(&'a self) -> &str { "" }
+                               //^ 'a
+}"#,
+        );
+    }
+
+    // Adjustment hint tests
+
+    #[test]
+    fn adjustment_hints() {
+        check_with_config(
+            InlayHintsConfig { adjustment_hints: true, ..DISABLED_CONFIG },
+            r#"
+struct S { field: u8 }
+fn takes_ref(_: &u8) {}
+fn takes_mut(_: &mut u8) {}
+fn main() {
+    let x = &mut 0u8;
+    takes_mut(x);
+            //^ &mut *
+    takes_ref(x);
+            //^ &*
+    takes_ref(&0);
+    let s = &S { field: 0 };
+    let _ = s.field;
+          //^ *
+}"#,
+        );
+    }
+
+    // Closing brace hint tests
+
+    #[test]
+    fn closing_brace_hints() {
+        check_expect(
+            InlayHintsConfig { closing_brace_hints: Some(3), ..DISABLED_CONFIG },
+            r#"
+mod m {
+    fn short() {}
+    fn long() {
+        let x = 0;
+        let y = 1;
+    }
+}
+struct S {
+    a: u8,
+    b: u8,
+}
+trait T {
+    fn f();
+}
+impl S {
+    fn g() {}
+    fn h() {}
+}
+impl T for S {
+    fn f() {}
+    fn x() {}
+}
+"#,
+            expect![[r#"
+                [
+                    InlayHint {
+                        range: 86..87,
+                        kind: ClosingBraceHint,
+                        label: "mod m",
+                    },
+                    InlayHint {
+                        range: 84..85,
+                        kind: ClosingBraceHint,
+                        label: "fn long",
+                    },
+                    InlayHint {
+                        range: 121..122,
+                        kind: ClosingBraceHint,
+                        label: "struct S",
+                    },
+                    InlayHint {
+                        range: 184..185,
+                        kind: ClosingBraceHint,
+                        label: "impl S",
+                    },
+                    InlayHint {
+                        range: 229..230,
+                        kind: ClosingBraceHint,
+                        label: "impl T for S",
+                    },
+                ]
+            "#]],
+        );
+    }
 }
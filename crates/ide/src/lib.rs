@@ -111,7 +111,9 @@ pub use ide_db::{
     symbol_index::Query,
     RootDatabase, SymbolKind,
 };
-pub use ide_diagnostics::{Diagnostic, DiagnosticsConfig, Severity};
+pub use ide_diagnostics::{
+    Diagnostic, DiagnosticCode, DiagnosticsConfig, FixAll, FixAllScope, Severity,
+};
 pub use ide_ssr::SsrError;
 pub use syntax::{TextRange, TextSize};
 pub use text_edit::{Indel, TextEdit};
@@ -574,6 +576,17 @@ impl Analysis {
         self.with_db(|db| ide_diagnostics::diagnostics(db, config, &resolve, file_id))
     }
 
+    /// Merges the fixes for the diagnostics with the given code, or for all
+    /// diagnostics, in a file or in the whole workspace into one source change.
+    pub fn fix_all(
+        &self,
+        config: &DiagnosticsConfig,
+        scope: FixAllScope,
+        diagnostic_code: Option<&str>,
+    ) -> Cancellable<FixAll> {
+        self.with_db(|db| ide_diagnostics::fix_all(db, config, scope, diagnostic_code))
+    }
+
     /// Convenience function to return assists + quick fixes for diagnostics
     pub fn assists_with_fixes(
         &self,
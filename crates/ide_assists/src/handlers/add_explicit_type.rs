@@ -177,6 +177,19 @@ fn main() {
 fn main() {
     let v: [f64; 2] = [0.0; 2];
 }
+"#,
+        );
+        check_assist(
+            add_explicit_type,
+            r#"
+fn main() {
+    let $0l = [0.0; 2+2];
+}
+"#,
+            r#"
+fn main() {
+    let l: [f64; 4] = [0.0; 2+2];
+}
 "#,
         );
         // note: this may break later if we add more consteval. it just needs to be something that our
@@ -184,8 +197,9 @@ fn main() {
         check_assist_not_applicable(
             add_explicit_type,
             r#"
+const fn len() -> usize { 4 }
 fn main() {
-    let $0l = [0.0; 2+2];
+    let $0l = [0.0; len()];
 }
 "#,
         );
@@ -1,3 +1,4 @@
+use ide_db::helpers::fix_visibility::vis_offset;
 use syntax::{
     ast::{self, NameOwner, VisibilityOwner},
     AstNode,
@@ -7,7 +8,7 @@ use syntax::{
     T,
 };
 
-use crate::{utils::vis_offset, AssistContext, AssistId, AssistKind, Assists};
+use crate::{AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: change_visibility
 //
@@ -114,7 +114,11 @@ pub(crate) fn fill_match_arms(acc: &mut Assists, ctx: &AssistContext) -> Option<
             .filter(|variant_pat| is_variant_missing(&top_lvl_pats, variant_pat));
         (Box::new(missing_pats) as Box<dyn Iterator<Item = _>>).peekable()
     } else {
-        return None;
+        // Fall back to the patterns the exhaustiveness checker could not find
+        // a match for, this covers integers, slices, references and the like.
+        let witnesses = ctx.sema.missing_match_arm_patterns(&match_expr)?;
+        let missing_pats = witnesses.into_iter().map(|it| make::pat_from_text(&it));
+        (Box::new(missing_pats) as Box<dyn Iterator<Item = _>>).peekable()
     };
 
     if missing_pats.peek().is_none() {
@@ -233,9 +237,11 @@ fn resolve_tuple_of_enum_def(
     sema: &Semantics<RootDatabase>,
     expr: &ast::Expr,
 ) -> Option<Vec<ExtendedEnum>> {
-    sema.type_of_expr(expr)?
-        .adjusted()
-        .tuple_fields(sema.db)
+    let fields = sema.type_of_expr(expr)?.adjusted().tuple_fields(sema.db);
+    if fields.is_empty() {
+        return None;
+    }
+    fields
         .iter()
         .map(|ty| {
             ty.autoderef(sema.db).find_map(|ty| match ty.as_adt() {
@@ -323,13 +329,87 @@ fn foo(a: bool) {
 
     #[test]
     fn tuple_of_non_enum() {
-        // for now this case is not handled, although it potentially could be
-        // in the future
-        check_assist_not_applicable(
+        check_assist(
             fill_match_arms,
             r#"
 fn main() {
     match (0, false)$0 {
+        (1..=9, _) => (),
+    }
+}
+"#,
+            r#"
+fn main() {
+    match (0, false) {
+        (1..=9, _) => (),
+        (i32::MIN..=0, ${0:_}) => todo!(),
+        (10..=i32::MAX, _) => todo!(),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fill_integer_ranges() {
+        check_assist(
+            fill_match_arms,
+            r#"
+fn main() {
+    match 0u8$0 {
+        0 => (),
+        10..=200 => (),
+    }
+}
+"#,
+            r#"
+fn main() {
+    match 0u8 {
+        0 => (),
+        10..=200 => (),
+        $01..=9 => todo!(),
+        201..=u8::MAX => todo!(),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fill_slice_patterns() {
+        check_assist(
+            fill_match_arms,
+            r#"
+fn main() {
+    let s: &[bool] = &[];
+    match s$0 {
+        [] => (),
+        [true, ..] => (),
+    }
+}
+"#,
+            r#"
+fn main() {
+    let s: &[bool] = &[];
+    match s {
+        [] => (),
+        [true, ..] => (),
+        $0&[false, ..] => todo!(),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn exhaustive_integer_match_not_applicable() {
+        check_assist_not_applicable(
+            fill_match_arms,
+            r#"
+fn main() {
+    match 0u8$0 {
+        0..=127 => (),
+        128.. => (),
     }
 }
 "#,
@@ -1,13 +1,10 @@
-use hir::{db::HirDatabase, HasSource, HasVisibility, PathResolution};
-use ide_db::base_db::FileId;
-use syntax::{
-    ast::{self, VisibilityOwner},
-    AstNode, TextRange, TextSize,
-};
+use hir::PathResolution;
+use ide_db::helpers::fix_visibility::{self, VisibilityFix};
+use syntax::{ast, AstNode};
 
-use crate::{utils::vis_offset, AssistContext, AssistId, AssistKind, Assists};
+use crate::{AssistContext, AssistId, AssistKind, Assists};
 
-// FIXME: this really should be a fix for diagnostic, rather than an assist.
+// This is also the quick fix of the private item diagnostics.
 
 // Assist: fix_visibility
 //
@@ -36,174 +33,55 @@ pub(crate) fn fix_visibility(acc: &mut Assists, ctx: &AssistContext) -> Option<(
 }
 
 fn add_vis_to_referenced_module_def(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
-    let path: ast::Path = ctx.find_node_at_offset()?;
-    let path_res = ctx.sema.resolve_path(&path)?;
-    let def = match path_res {
-        PathResolution::Def(def) => def,
-        _ => return None,
-    };
-
-    let current_module = ctx.sema.scope(path.syntax()).module()?;
-    let target_module = def.module(ctx.db())?;
-
-    if def.visibility(ctx.db()).is_visible_from(ctx.db(), current_module.into()) {
-        return None;
-    };
-
-    let (offset, current_visibility, target, target_file, target_name) =
-        target_data_for_def(ctx.db(), def)?;
-
-    let missing_visibility =
-        if current_module.krate() == target_module.krate() { "pub(crate)" } else { "pub" };
-
-    let assist_label = match target_name {
-        None => format!("Change visibility to {}", missing_visibility),
-        Some(name) => format!("Change visibility of {} to {}", name, missing_visibility),
-    };
-
-    acc.add(AssistId("fix_visibility", AssistKind::QuickFix), assist_label, target, |builder| {
-        builder.edit_file(target_file);
-        match ctx.config.snippet_cap {
-            Some(cap) => match current_visibility {
-                Some(current_visibility) => builder.replace_snippet(
-                    cap,
-                    current_visibility.syntax().text_range(),
-                    format!("$0{}", missing_visibility),
-                ),
-                None => builder.insert_snippet(cap, offset, format!("$0{} ", missing_visibility)),
-            },
-            None => match current_visibility {
-                Some(current_visibility) => {
-                    builder.replace(current_visibility.syntax().text_range(), missing_visibility)
-                }
-                None => builder.insert(offset, format!("{} ", missing_visibility)),
-            },
+    let (def, node) = match ctx.find_node_at_offset::<ast::Path>() {
+        Some(path) => {
+            let def = match ctx.sema.resolve_path(&path)? {
+                PathResolution::Def(def) => def,
+                PathResolution::AssocItem(it) => it.into(),
+                _ => return None,
+            };
+            (def, path.syntax().clone())
         }
-    })
+        None => {
+            let call: ast::MethodCallExpr = ctx.find_node_at_offset()?;
+            let func = ctx.sema.resolve_method_call(&call)?;
+            (hir::ModuleDef::Function(func), call.syntax().clone())
+        }
+    };
+    let current_module = ctx.sema.scope(&node).module()?;
+    let fix = fix_visibility::for_module_def(ctx.db(), def, current_module)?;
+    add_fix(acc, ctx, fix)
 }
 
 fn add_vis_to_referenced_record_field(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
-    let record_field: ast::RecordExprField = ctx.find_node_at_offset()?;
-    let (record_field_def, _, _) = ctx.sema.resolve_record_field(&record_field)?;
-
-    let current_module = ctx.sema.scope(record_field.syntax()).module()?;
-    let visibility = record_field_def.visibility(ctx.db());
-    if visibility.is_visible_from(ctx.db(), current_module.into()) {
-        return None;
-    }
-
-    let parent = record_field_def.parent_def(ctx.db());
-    let parent_name = parent.name(ctx.db());
-    let target_module = parent.module(ctx.db());
-
-    let in_file_source = record_field_def.source(ctx.db())?;
-    let (offset, current_visibility, target) = match in_file_source.value {
-        hir::FieldSource::Named(it) => {
-            let s = it.syntax();
-            (vis_offset(s), it.visibility(), s.text_range())
+    let (record_field_def, node) = match ctx.find_node_at_offset::<ast::RecordExprField>() {
+        Some(record_field) => {
+            let (field, _, _) = ctx.sema.resolve_record_field(&record_field)?;
+            (field, record_field.syntax().clone())
         }
-        hir::FieldSource::Pos(it) => {
-            let s = it.syntax();
-            (vis_offset(s), it.visibility(), s.text_range())
+        None => {
+            let field_expr: ast::FieldExpr = ctx.find_node_at_offset()?;
+            (ctx.sema.resolve_field(&field_expr)?, field_expr.syntax().clone())
         }
     };
-
-    let missing_visibility =
-        if current_module.krate() == target_module.krate() { "pub(crate)" } else { "pub" };
-    let target_file = in_file_source.file_id.original_file(ctx.db());
-
-    let target_name = record_field_def.name(ctx.db());
-    let assist_label =
-        format!("Change visibility of {}.{} to {}", parent_name, target_name, missing_visibility);
-
-    acc.add(AssistId("fix_visibility", AssistKind::QuickFix), assist_label, target, |builder| {
-        builder.edit_file(target_file);
-        match ctx.config.snippet_cap {
-            Some(cap) => match current_visibility {
-                Some(current_visibility) => builder.replace_snippet(
-                    cap,
-                    current_visibility.syntax().text_range(),
-                    format!("$0{}", missing_visibility),
-                ),
-                None => builder.insert_snippet(cap, offset, format!("$0{} ", missing_visibility)),
-            },
-            None => match current_visibility {
-                Some(current_visibility) => {
-                    builder.replace(current_visibility.syntax().text_range(), missing_visibility)
-                }
-                None => builder.insert(offset, format!("{} ", missing_visibility)),
-            },
-        }
-    })
+    let current_module = ctx.sema.scope(&node).module()?;
+    let fix = fix_visibility::for_field(ctx.db(), record_field_def, current_module)?;
+    add_fix(acc, ctx, fix)
 }
 
-fn target_data_for_def(
-    db: &dyn HirDatabase,
-    def: hir::ModuleDef,
-) -> Option<(TextSize, Option<ast::Visibility>, TextRange, FileId, Option<hir::Name>)> {
-    fn offset_target_and_file_id<S, Ast>(
-        db: &dyn HirDatabase,
-        x: S,
-    ) -> Option<(TextSize, Option<ast::Visibility>, TextRange, FileId)>
-    where
-        S: HasSource<Ast = Ast>,
-        Ast: AstNode + ast::VisibilityOwner,
-    {
-        let source = x.source(db)?;
-        let in_file_syntax = source.syntax();
-        let file_id = in_file_syntax.file_id;
-        let syntax = in_file_syntax.value;
-        let current_visibility = source.value.visibility();
-        Some((
-            vis_offset(syntax),
-            current_visibility,
-            syntax.text_range(),
-            file_id.original_file(db.upcast()),
-        ))
-    }
-
-    let target_name;
-    let (offset, current_visibility, target, target_file) = match def {
-        hir::ModuleDef::Function(f) => {
-            target_name = Some(f.name(db));
-            offset_target_and_file_id(db, f)?
-        }
-        hir::ModuleDef::Adt(adt) => {
-            target_name = Some(adt.name(db));
-            match adt {
-                hir::Adt::Struct(s) => offset_target_and_file_id(db, s)?,
-                hir::Adt::Union(u) => offset_target_and_file_id(db, u)?,
-                hir::Adt::Enum(e) => offset_target_and_file_id(db, e)?,
+fn add_fix(acc: &mut Assists, ctx: &AssistContext, fix: VisibilityFix) -> Option<()> {
+    let VisibilityFix { file_id, range, visibility, target, label } = fix;
+    acc.add(AssistId("fix_visibility", AssistKind::QuickFix), label, target, |builder| {
+        builder.edit_file(file_id);
+        match ctx.config.snippet_cap {
+            Some(cap) if range.is_empty() => {
+                builder.insert_snippet(cap, range.start(), format!("$0{} ", visibility))
             }
+            Some(cap) => builder.replace_snippet(cap, range, format!("$0{}", visibility)),
+            None if range.is_empty() => builder.insert(range.start(), format!("{} ", visibility)),
+            None => builder.replace(range, visibility),
         }
-        hir::ModuleDef::Const(c) => {
-            target_name = c.name(db);
-            offset_target_and_file_id(db, c)?
-        }
-        hir::ModuleDef::Static(s) => {
-            target_name = s.name(db);
-            offset_target_and_file_id(db, s)?
-        }
-        hir::ModuleDef::Trait(t) => {
-            target_name = Some(t.name(db));
-            offset_target_and_file_id(db, t)?
-        }
-        hir::ModuleDef::TypeAlias(t) => {
-            target_name = Some(t.name(db));
-            offset_target_and_file_id(db, t)?
-        }
-        hir::ModuleDef::Module(m) => {
-            target_name = m.name(db);
-            let in_file_source = m.declaration_source(db)?;
-            let file_id = in_file_source.file_id.original_file(db.upcast());
-            let syntax = in_file_source.value.syntax();
-            (vis_offset(syntax), in_file_source.value.visibility(), syntax.text_range(), file_id)
-        }
-        // Enum variants can't be private, we can't modify builtin types
-        hir::ModuleDef::Variant(_) | hir::ModuleDef::BuiltinType(_) => return None,
-    };
-
-    Some((offset, current_visibility, target, target_file, target_name))
+    })
 }
 
 #[cfg(test)]
@@ -323,6 +201,45 @@ pub struct Foo { pub bar: () }
         );
     }
 
+    #[test]
+    fn fix_visibility_of_field_access() {
+        check_assist(
+            fix_visibility,
+            r"mod foo { pub struct Foo { bar: () } }
+              fn main(foo: foo::Foo) { foo.$0bar; } ",
+            r"mod foo { pub struct Foo { $0pub(crate) bar: () } }
+              fn main(foo: foo::Foo) { foo.bar; } ",
+        );
+    }
+
+    #[test]
+    fn fix_visibility_of_method() {
+        check_assist(
+            fix_visibility,
+            r"mod foo { pub struct Foo; impl Foo { fn bar(&self) {} } }
+              fn main(foo: foo::Foo) { foo.$0bar(); } ",
+            r"mod foo { pub struct Foo; impl Foo { $0pub(crate) fn bar(&self) {} } }
+              fn main(foo: foo::Foo) { foo.bar(); } ",
+        );
+        check_assist_not_applicable(
+            fix_visibility,
+            r"mod foo { pub trait Bar { fn bar(&self); } pub struct Foo; impl Bar for Foo { fn bar(&self) {} } }
+              use foo::Bar;
+              fn main(foo: foo::Foo) { foo.$0bar(); } ",
+        );
+    }
+
+    #[test]
+    fn fix_visibility_of_assoc_item() {
+        check_assist(
+            fix_visibility,
+            r"mod foo { pub struct Foo; impl Foo { const BAR: () = (); } }
+              fn main() { foo::Foo::$0BAR } ",
+            r"mod foo { pub struct Foo; impl Foo { $0pub(crate) const BAR: () = (); } }
+              fn main() { foo::Foo::BAR } ",
+        );
+    }
+
     #[test]
     fn fix_visibility_of_enum_variant_field() {
         // Enum variants, as well as their fields, always get the enum's visibility. In fact, rustc
@@ -198,13 +198,6 @@ pub(crate) fn render_snippet(_cap: SnippetCap, node: &SyntaxNode, cursor: Cursor
     }
 }
 
-pub(crate) fn vis_offset(node: &SyntaxNode) -> TextSize {
-    node.children_with_tokens()
-        .find(|it| !matches!(it.kind(), WHITESPACE | COMMENT | ATTR))
-        .map(|it| it.text_range().start())
-        .unwrap_or_else(|| node.text_range().start())
-}
-
 pub(crate) fn invert_boolean_expression(expr: ast::Expr) -> ast::Expr {
     invert_special_case(&expr).unwrap_or_else(|| make::expr_prefix(T![!], expr))
 }
@@ -1,4 +1,5 @@
 //! A module with ide helpers for high-level ide features.
+pub mod fix_visibility;
 pub mod import_assets;
 pub mod insert_use;
 pub mod merge_imports;
@@ -12,7 +13,8 @@ use either::Either;
 use hir::{Crate, Enum, ItemInNs, MacroDef, Module, ModuleDef, Name, ScopeDef, Semantics, Trait};
 use syntax::{
     ast::{self, make, LoopBodyOwner},
-    AstNode, Direction, SyntaxElement, SyntaxKind, SyntaxToken, TokenAtOffset, WalkEvent, T,
+    AstNode, Direction, SyntaxElement, SyntaxKind, SyntaxToken, TextRange, TokenAtOffset,
+    WalkEvent, T,
 };
 
 use crate::RootDatabase;
@@ -57,6 +59,16 @@ pub fn try_resolve_derive_input_at(
     }
 }
 
+/// The text to delete to remove the `mut` keyword `mut_token`, with the
+/// whitespace after it.
+pub fn mut_removal_range(mut_token: &SyntaxToken) -> TextRange {
+    let delete_to = match mut_token.next_token() {
+        Some(it) if it.kind() == SyntaxKind::WHITESPACE => it.text_range().end(),
+        _ => mut_token.text_range().end(),
+    };
+    TextRange::new(mut_token.text_range().start(), delete_to)
+}
+
 /// Picks the token with the highest rank returned by the passed in function.
 pub fn pick_best_token(
     tokens: TokenAtOffset<SyntaxToken>,
@@ -142,6 +154,10 @@ impl FamousDefs<'_, '_> {
         self.find_trait("core:cmp:Ord")
     }
 
+    pub fn core_clone_Clone(&self) -> Option<Trait> {
+        self.find_trait("core:clone:Clone")
+    }
+
     pub fn core_convert_From(&self) -> Option<Trait> {
         self.find_trait("core:convert:From")
     }
@@ -0,0 +1,137 @@
+//! Making an item or a field visible from a module which uses it, shared by
+//! the `fix_visibility` assist and the fixes of the private item diagnostics.
+use base_db::FileId;
+use hir::{AsAssocItem, AssocItemContainer, Field, HasSource, HasVisibility, Module, ModuleDef};
+use syntax::{
+    ast::{self, VisibilityOwner},
+    AstNode, SyntaxKind, SyntaxNode, TextRange, TextSize,
+};
+use text_edit::TextEdit;
+
+use crate::RootDatabase;
+
+/// Where to put which visibility to make an item visible.
+#[derive(Debug)]
+pub struct VisibilityFix {
+    pub file_id: FileId,
+    /// The range of the current visibility of the item, or an empty range
+    /// where the missing one goes.
+    pub range: TextRange,
+    /// `pub(crate)`, or `pub` for uses from other crates.
+    pub visibility: &'static str,
+    /// The range of the item.
+    pub target: TextRange,
+    pub label: String,
+}
+
+impl VisibilityFix {
+    pub fn text_edit(&self) -> TextEdit {
+        if self.range.is_empty() {
+            TextEdit::insert(self.range.start(), format!("{} ", self.visibility))
+        } else {
+            TextEdit::replace(self.range, self.visibility.to_string())
+        }
+    }
+}
+
+/// How to make `def` visible from `from`, if it isn't and its visibility can
+/// be changed.
+pub fn for_module_def(db: &RootDatabase, def: ModuleDef, from: Module) -> Option<VisibilityFix> {
+    // Items of traits and their impls are as visible as the trait.
+    if let Some(assoc) = def.as_assoc_item(db) {
+        match assoc.container(db) {
+            AssocItemContainer::Impl(it) if it.trait_(db).is_none() => (),
+            _ => return None,
+        }
+    }
+    let target_module = def.module(db)?;
+    if def.visibility(db).is_visible_from(db, from.into()) {
+        return None;
+    }
+
+    let (name, source) = match def {
+        ModuleDef::Function(it) => (Some(it.name(db)), source(db, it)?),
+        ModuleDef::Adt(hir::Adt::Struct(it)) => (Some(it.name(db)), source(db, it)?),
+        ModuleDef::Adt(hir::Adt::Union(it)) => (Some(it.name(db)), source(db, it)?),
+        ModuleDef::Adt(hir::Adt::Enum(it)) => (Some(it.name(db)), source(db, it)?),
+        ModuleDef::Const(it) => (it.name(db), source(db, it)?),
+        ModuleDef::Static(it) => (it.name(db), source(db, it)?),
+        ModuleDef::Trait(it) => (Some(it.name(db)), source(db, it)?),
+        ModuleDef::TypeAlias(it) => (Some(it.name(db)), source(db, it)?),
+        ModuleDef::Module(it) => {
+            let source = it.declaration_source(db)?;
+            let file_id = source.file_id.original_file(db);
+            (it.name(db), (file_id, source.value.syntax().clone(), source.value.visibility()))
+        }
+        // Enum variants can't be private, we can't modify builtin types
+        ModuleDef::Variant(_) | ModuleDef::BuiltinType(_) => return None,
+    };
+    let visibility = missing_visibility(from, target_module);
+    let label = match name {
+        None => format!("Change visibility to {}", visibility),
+        Some(name) => format!("Change visibility of {} to {}", name, visibility),
+    };
+    Some(make_fix(source, visibility, label))
+}
+
+/// How to make `field` visible from `from`, if it isn't.
+pub fn for_field(db: &RootDatabase, field: Field, from: Module) -> Option<VisibilityFix> {
+    if field.visibility(db).is_visible_from(db, from.into()) {
+        return None;
+    }
+    let parent = field.parent_def(db);
+    let source = field.source(db)?;
+    let file_id = source.file_id.original_file(db);
+    let source = match source.value {
+        hir::FieldSource::Named(it) => (file_id, it.syntax().clone(), it.visibility()),
+        hir::FieldSource::Pos(it) => (file_id, it.syntax().clone(), it.visibility()),
+    };
+    let visibility = missing_visibility(from, parent.module(db));
+    let label =
+        format!("Change visibility of {}.{} to {}", parent.name(db), field.name(db), visibility);
+    Some(make_fix(source, visibility, label))
+}
+
+/// The offset of the visibility of the item or field `node`, after its
+/// comments and attributes.
+pub fn vis_offset(node: &SyntaxNode) -> TextSize {
+    node.children_with_tokens()
+        .find(|it| {
+            !matches!(it.kind(), SyntaxKind::WHITESPACE | SyntaxKind::COMMENT | SyntaxKind::ATTR)
+        })
+        .map(|it| it.text_range().start())
+        .unwrap_or_else(|| node.text_range().start())
+}
+
+fn source<S, Ast>(
+    db: &RootDatabase,
+    def: S,
+) -> Option<(FileId, SyntaxNode, Option<ast::Visibility>)>
+where
+    S: HasSource<Ast = Ast>,
+    Ast: AstNode + VisibilityOwner,
+{
+    let source = def.source(db)?;
+    let file_id = source.file_id.original_file(db);
+    Some((file_id, source.value.syntax().clone(), source.value.visibility()))
+}
+
+fn missing_visibility(from: Module, target: Module) -> &'static str {
+    if from.krate() == target.krate() {
+        "pub(crate)"
+    } else {
+        "pub"
+    }
+}
+
+fn make_fix(
+    (file_id, node, current_visibility): (FileId, SyntaxNode, Option<ast::Visibility>),
+    visibility: &'static str,
+    label: String,
+) -> VisibilityFix {
+    let range = match current_visibility {
+        Some(it) => it.syntax().text_range(),
+        None => TextRange::empty(vis_offset(&node)),
+    };
+    VisibilityFix { file_id, range, visibility, target: node.text_range(), label }
+}
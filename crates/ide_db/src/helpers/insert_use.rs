@@ -8,8 +8,10 @@ use hir::Semantics;
 use syntax::{
     algo,
     ast::{self, make, AstNode, AttrsOwner, ModuleItemOwner, PathSegmentKind, VisibilityOwner},
-    match_ast, ted, AstToken, Direction, NodeOrToken, SyntaxNode, SyntaxToken,
+    match_ast, ted, AstToken, Direction, NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken,
+    TextRange, TextSize,
 };
+use text_edit::TextEdit;
 
 use crate::{
     helpers::merge_imports::{
@@ -220,6 +222,49 @@ pub fn insert_use(scope: &ImportScope, path: ast::Path, cfg: &InsertUseConfig) {
     insert_use_(scope, &path, cfg.group, use_item);
 }
 
+/// Like `insert_use`, but returns the edit instead of changing `scope`, which
+/// doesn't have to be mutable.
+pub fn insert_use_edit(scope: &ImportScope, path: ast::Path, cfg: &InsertUseConfig) -> TextEdit {
+    let new_scope = scope.clone_for_update();
+    insert_use(&new_scope, path, cfg);
+    let mut builder = TextEdit::builder();
+    algo::diff(scope.as_syntax_node(), new_scope.as_syntax_node()).into_text_edit(&mut builder);
+    builder.finish()
+}
+
+/// The text to delete to remove `tree` from its `use` item: the tree and its
+/// separator from a neighbouring tree, or the whole item with its line if it
+/// imports nothing else.
+pub fn use_tree_removal_range(tree: &ast::UseTree) -> Option<TextRange> {
+    let mut tree = tree.clone();
+    loop {
+        let parent = tree.syntax().parent()?;
+        if let Some(use_item) = ast::Use::cast(parent.clone()) {
+            let range = use_item.syntax().text_range();
+            let newline =
+                use_item.syntax().next_sibling_or_token().and_then(|it| it.into_token()).filter(
+                    |it| it.kind() == SyntaxKind::WHITESPACE && it.text().starts_with('\n'),
+                );
+            return Some(match newline {
+                Some(_) => TextRange::new(range.start(), range.end() + TextSize::of('\n')),
+                None => range,
+            });
+        }
+        let list = ast::UseTreeList::cast(parent)?;
+        let mut trees = list.use_trees();
+        if list.use_trees().count() == 1 {
+            tree = list.parent_use_tree();
+            continue;
+        }
+        let range = tree.syntax().text_range();
+        let prev = trees.by_ref().take_while(|it| it != &tree).last();
+        return Some(match trees.next() {
+            Some(next) => TextRange::new(range.start(), next.syntax().text_range().start()),
+            None => TextRange::new(prev?.syntax().text_range().end(), range.end()),
+        });
+    }
+}
+
 #[derive(Eq, PartialEq, PartialOrd, Ord)]
 enum ImportGroup {
     // the order here defines the order of new group inserts
@@ -933,3 +933,43 @@ fn check_guess(ra_fixture: &str, expected: ImportGranularityGuess) {
     let file = super::ImportScope::from(syntax).unwrap();
     assert_eq!(file.guess_granularity_from_scope(), expected);
 }
+
+#[test]
+fn remove_use_tree_in_list() {
+    check_remove("Bar", "use foo::{Bar, Baz};", "use foo::{Baz};");
+    check_remove("Baz", "use foo::{Bar, Baz};", "use foo::{Bar};");
+    check_remove("Qux", "use foo::{Bar, baz::{Qux}};", "use foo::{Bar};");
+}
+
+#[test]
+fn remove_whole_use_item() {
+    check_remove(
+        "Bar",
+        r"
+use foo::Bar;
+use foo::Baz;",
+        r"
+use foo::Baz;",
+    );
+    check_remove(
+        "Qux",
+        r"
+use foo::{baz::{Qux}};
+use foo::Baz;",
+        r"
+use foo::Baz;",
+    );
+}
+
+fn check_remove(name: &str, ra_fixture_before: &str, ra_fixture_after: &str) {
+    let syntax = ast::SourceFile::parse(ra_fixture_before).tree().syntax().clone();
+    let tree = syntax
+        .descendants()
+        .filter_map(ast::UseTree::cast)
+        .find(|it| it.use_tree_list().is_none() && it.syntax().to_string().ends_with(name))
+        .unwrap();
+    let range = use_tree_removal_range(&tree).unwrap();
+    let mut actual = ra_fixture_before.to_string();
+    actual.replace_range(std::ops::Range::<usize>::from(range), "");
+    assert_eq_text!(ra_fixture_after, &actual);
+}
@@ -0,0 +1,252 @@
+//! Applies the fixes of many diagnostics at once, e.g. to fix every
+//! `unnecessary-braces` diagnostic of a file with a single edit.
+
+use ide_db::{
+    assists::AssistResolveStrategy,
+    base_db::{FileId, FileRange, SourceDatabaseExt},
+    source_change::SourceChange,
+    symbol_index::SymbolsDatabase,
+    RootDatabase,
+};
+
+use crate::{diagnostics, Diagnostic, DiagnosticsConfig};
+
+/// Where `fix_all` looks for diagnostics to fix.
+#[derive(Debug, Clone, Copy)]
+pub enum FixAllScope {
+    File(FileId),
+    /// All files of the workspace, but not those of the libraries it uses.
+    Workspace,
+}
+
+#[derive(Debug, Default)]
+pub struct FixAll {
+    pub source_change: SourceChange,
+    /// The number of diagnostics `source_change` fixes.
+    pub fixed: usize,
+    /// The diagnostics which were left alone because their fix overlaps with
+    /// the fix of another one, with their messages.
+    pub conflicts: Vec<(FileRange, String)>,
+}
+
+/// Merges the fixes for all diagnostics with the given code, or for all
+/// diagnostics if `code` is `None`, into a single source change.
+///
+/// Only diagnostics with exactly one fix are considered, choosing between
+/// several fixes is up to the user. Fixes are merged in the order of the
+/// diagnostics, the ones which overlap with a fix that is already merged are
+/// reported as conflicts.
+pub fn fix_all(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    scope: FixAllScope,
+    code: Option<&str>,
+) -> FixAll {
+    let _p = profile::span("fix_all");
+    let files = match scope {
+        FixAllScope::File(file_id) => vec![file_id],
+        FixAllScope::Workspace => db
+            .local_roots()
+            .iter()
+            .flat_map(|&root| db.source_root(root).iter().collect::<Vec<_>>())
+            .collect(),
+    };
+    let is_fixable = |d: &Diagnostic| {
+        let code_matches = match code {
+            Some(code) => d.code.as_str() == code,
+            None => true,
+        };
+        code_matches && matches!(&d.fixes, Some(fixes) if fixes.len() == 1)
+    };
+
+    let mut res = FixAll::default();
+    for file_id in files {
+        // Computing the edits can be expensive, e.g. for renames, so skip the
+        // files where there is nothing to fix.
+        let unresolved = diagnostics(db, config, &AssistResolveStrategy::None, file_id);
+        if !unresolved.iter().any(is_fixable) {
+            continue;
+        }
+        let mut diags = diagnostics(db, config, &AssistResolveStrategy::All, file_id);
+        diags.retain(is_fixable);
+        diags.sort_by_key(|d| d.range.start());
+        for d in diags {
+            let source_change =
+                match d.fixes.and_then(|mut fixes| fixes.pop()).and_then(|fix| fix.source_change) {
+                    Some(it) => it,
+                    None => continue,
+                };
+            let conflicts = source_change.source_file_edits.iter().any(|(file_id, edit)| match res
+                .source_change
+                .get_source_edit(*file_id)
+            {
+                Some(merged) => merged.intersects(edit),
+                None => false,
+            });
+            if conflicts {
+                res.conflicts.push((FileRange { file_id, range: d.range }, d.message));
+                continue;
+            }
+            res.source_change.extend(source_change.source_file_edits);
+            res.source_change.file_system_edits.extend(source_change.file_system_edits);
+            res.fixed += 1;
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use expect_test::{expect, Expect};
+    use ide_db::{
+        base_db::{fixture::WithFixture, salsa::Durability, SourceDatabaseExt},
+        symbol_index::SymbolsDatabase,
+        RootDatabase,
+    };
+    use rustc_hash::FxHashSet;
+    use stdx::format_to;
+
+    use crate::{tests::test_config, DiagnosticsConfig};
+
+    use super::{fix_all, FixAllScope};
+
+    fn check(workspace: bool, code: Option<&str>, ra_fixture: &str, expect: Expect) {
+        check_with_config(test_config(), workspace, code, ra_fixture, expect)
+    }
+
+    fn check_with_config(
+        config: DiagnosticsConfig,
+        workspace: bool,
+        code: Option<&str>,
+        ra_fixture: &str,
+        expect: Expect,
+    ) {
+        let (mut db, files) = RootDatabase::with_many_files(ra_fixture);
+        let mut local_roots = FxHashSet::default();
+        local_roots.insert(ide_db::base_db::fixture::WORKSPACE);
+        db.set_local_roots_with_durability(Arc::new(local_roots), Durability::HIGH);
+        let scope = if workspace { FixAllScope::Workspace } else { FixAllScope::File(files[0]) };
+        let fix_all = fix_all(&db, &config, scope, code);
+
+        let mut actual = String::new();
+        for file_id in files {
+            let mut text = db.file_text(file_id).to_string();
+            if let Some(edit) = fix_all.source_change.get_source_edit(file_id) {
+                edit.apply(&mut text);
+            }
+            actual.push_str(&text);
+        }
+        format_to!(actual, "fixed: {}\n", fix_all.fixed);
+        for (frange, message) in &fix_all.conflicts {
+            format_to!(actual, "conflict at {:?}: {}\n", frange.range, message);
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn fixes_all_diagnostics_with_code_in_file() {
+        check(
+            false,
+            Some("use-field-shorthand"),
+            r#"
+struct S { a: i32, b: i32 }
+fn main() {
+    let a = 1;
+    let b = 2;
+    let s = S { a: a, b: b };
+    let S { a: a, b: b } = s;
+}
+"#,
+            expect![[r#"
+                struct S { a: i32, b: i32 }
+                fn main() {
+                    let a = 1;
+                    let b = 2;
+                    let s = S { a, b };
+                    let S { a, b } = s;
+                }
+                fixed: 4
+            "#]],
+        );
+    }
+
+    #[test]
+    fn reports_overlapping_fixes() {
+        check(
+            false,
+            Some("unnecessary-braces"),
+            r#"
+mod a { pub mod b { pub struct C; } }
+use a::{b::{C}};
+"#,
+            expect![[r#"
+                mod a { pub mod b { pub struct C; } }
+                use a::b::{C};
+                fixed: 1
+                conflict at 49..52: Unnecessary braces in use statement
+            "#]],
+        );
+    }
+
+    #[test]
+    fn fixes_all_diagnostics_in_workspace() {
+        check(
+            true,
+            None,
+            r#"
+//- /main.rs
+mod foo;
+fn f() -> i32 {
+    0;
+}
+//- /foo.rs
+pub struct S { pub f: i32 }
+fn foo(f: i32) -> S {
+    S { f: f }
+}
+"#,
+            expect![[r#"
+                mod foo;
+                fn f() -> i32 {
+                    0
+                }
+                pub struct S { pub f: i32 }
+                fn foo(f: i32) -> S {
+                    S { f }
+                }
+                fixed: 2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn fixes_all_unused_imports_in_file() {
+        check_with_config(
+            DiagnosticsConfig::default(),
+            false,
+            Some("unused-imports"),
+            r#"
+mod m {
+    pub struct S;
+    pub struct T;
+    pub struct U;
+}
+use m::S;
+use m::{T, U};
+fn foo(_: U) {}
+"#,
+            expect![[r#"
+                mod m {
+                    pub struct S;
+                    pub struct T;
+                    pub struct U;
+                }
+                use m::{U};
+                fn foo(_: U) {}
+                fixed: 2
+            "#]],
+        );
+    }
+}
@@ -34,10 +34,10 @@ pub(crate) fn inactive_code(
 
 #[cfg(test)]
 mod tests {
-    use crate::{tests::check_diagnostics_with_config, DiagnosticsConfig};
+    use crate::tests::{check_diagnostics_with_config, test_config};
 
     pub(crate) fn check(ra_fixture: &str) {
-        let config = DiagnosticsConfig::default();
+        let config = test_config();
         check_diagnostics_with_config(config, ra_fixture)
     }
 
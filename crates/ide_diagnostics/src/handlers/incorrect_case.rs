@@ -1,4 +1,4 @@
-use hir::{db::AstDatabase, InFile};
+use hir::{db::AstDatabase, diagnostics::CaseType, InFile};
 use ide_db::{assists::Assist, defs::NameClass};
 use syntax::AstNode;
 
@@ -23,6 +23,11 @@ pub(crate) fn incorrect_case(ctx: &DiagnosticsContext<'_>, d: &hir::IncorrectCas
         ctx.sema.diagnostics_display_range(InFile::new(d.file, d.ident.clone().into())).range,
     )
     .severity(Severity::WeakWarning)
+    .lint(match d.expected_case {
+        CaseType::LowerSnakeCase => "non_snake_case",
+        CaseType::UpperSnakeCase => "non_upper_case_globals",
+        CaseType::UpperCamelCase => "non_camel_case_types",
+    })
     .with_fixes(fixes(ctx, d))
 }
 
@@ -46,7 +51,16 @@ fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::IncorrectCase) -> Option<Vec<Ass
 
 #[cfg(test)]
 mod change_case {
-    use crate::tests::{check_diagnostics, check_fix};
+    use crate::tests::{check_diagnostics, check_diagnostics_with_config, check_fix, test_config};
+
+    /// `None` and `Some` aren't in scope in these fixtures, so they bind the
+    /// scrutinee and the arms after them are unreachable.
+    fn check_diagnostics_ignoring_unreachable(ra_fixture: &str) {
+        let mut config = test_config();
+        config.disabled.insert("inactive-code".to_string());
+        config.disabled.insert("unreachable-pattern".to_string());
+        check_diagnostics_with_config(config, ra_fixture)
+    }
 
     #[test]
     fn test_rename_incorrect_case() {
@@ -296,7 +310,7 @@ impl someStruct {
 
     #[test]
     fn no_diagnostic_for_enum_varinats() {
-        check_diagnostics(
+        check_diagnostics_ignoring_unreachable(
             r#"
 enum Option { Some, None }
 
@@ -312,7 +326,7 @@ fn main() {
 
     #[test]
     fn non_let_bind() {
-        check_diagnostics(
+        check_diagnostics_ignoring_unreachable(
             r#"
 enum Option { Some, None }
 
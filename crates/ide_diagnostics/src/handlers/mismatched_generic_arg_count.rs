@@ -0,0 +1,144 @@
+use hir::GenericArgCountMismatch;
+
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: mismatched-generic-arg-count
+//
+// This diagnostic is triggered if a path or a method call has a different
+// number of lifetime, type or const arguments than the item it refers to has
+// parameters.
+pub(crate) fn mismatched_generic_arg_count(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::MismatchedGenericArgCount,
+) -> Diagnostic {
+    let message = match d.mismatch {
+        GenericArgCountMismatch::Lifetimes { expected, found } => {
+            format!("expected {} lifetime argument{}, found {}", expected, plural(expected), found)
+        }
+        GenericArgCountMismatch::TypesAndConsts { min, max, found } => {
+            let expected = if min == max {
+                format!("{} generic argument{}", min, plural(min))
+            } else if found < min {
+                format!("at least {} generic argument{}", min, plural(min))
+            } else {
+                format!("at most {} generic argument{}", max, plural(max))
+            };
+            format!("expected {}, found {}", expected, found)
+        }
+    };
+    Diagnostic::new(
+        "mismatched-generic-arg-count",
+        message,
+        ctx.sema.diagnostics_display_range(d.generic_args.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn type_paths() {
+        check_diagnostics(
+            r#"
+struct S<T>(T);
+struct D<T, U = u32>(T, U);
+struct L<'a>(&'a u32);
+trait Tr<T> {}
+type A<T> = S<T>;
+fn foo(
+    _: S<u32>,
+    _: S<u32, u8>,
+      //^^^^^^^^^ error: expected 1 generic argument, found 2
+    _: D<u8>,
+    _: D<u8, u8>,
+    _: D<u8, u8, u8>,
+      //^^^^^^^^^^^^ error: expected at most 2 generic arguments, found 3
+    _: L<'static>,
+    _: L<'static, 'static>,
+      //^^^^^^^^^^^^^^^^^^ error: expected 1 lifetime argument, found 2
+    _: L<'static, u32>,
+      //^^^^^^^^^^^^^^ error: expected 0 generic arguments, found 1
+    _: &dyn Tr<u32>,
+    _: &dyn Tr<u32, u8>,
+            //^^^^^^^^^ error: expected 1 generic argument, found 2
+    _: A<u32, u32>,
+      //^^^^^^^^^^ error: expected 1 generic argument, found 2
+) {
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn expression_paths_and_method_calls() {
+        check_diagnostics(
+            r#"
+struct S<T>(T);
+impl<T> S<T> {
+    fn new<U>(t: T, _: U) -> S<T> { S(t) }
+    fn method<const N: usize>(&self) {}
+}
+fn f<T, U>(_: T, _: U) {}
+fn g(_: impl Copy) {}
+fn foo() {
+    let s = S::<u32>::new::<u8>(0, 0);
+    S::<u32, u32>::new(0, 0);
+   //^^^^^^^^^^^^ error: expected 1 generic argument, found 2
+    S::new::<u8, u8>(0, 0);
+        //^^^^^^^^^^ error: expected 1 generic argument, found 2
+    f::<u8, u8>(0, 0);
+    f::<u8>(0, 0);
+   //^^^^^^ error: expected 2 generic arguments, found 1
+    g::<u8>(0);
+    s.method::<1>();
+    s.method::<1, 2>();
+          //^^^^^^^^ error: expected 1 generic argument, found 2
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn body_type_paths() {
+        check_diagnostics(
+            r#"
+struct Vec<T>(T);
+trait Tr<T> {
+    type Assoc;
+}
+fn foo() {
+    let x: Vec<u8, u8, u8>;
+            //^^^^^^^^^^^^ error: expected 1 generic argument, found 3
+    let _ = 0 as Vec<u8, u8>;
+                  //^^^^^^^^ error: expected 1 generic argument, found 2
+    let _: <u8 as Tr<u8, u8>>::Assoc;
+                  //^^^^^^^^ error: expected 1 generic argument, found 2
+    let _ = |_: Vec<u8, u8>| ();
+                 //^^^^^^^^ error: expected 1 generic argument, found 2
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn assoc_type_args_are_not_counted() {
+        check_diagnostics(
+            r#"
+trait Iter<T> {
+    type Item;
+}
+fn foo(_: &dyn Iter<u32, Item = u8>) {}
+"#,
+        );
+    }
+}
@@ -11,7 +11,7 @@ pub(crate) fn missing_match_arms(
 ) -> Diagnostic {
     Diagnostic::new(
         "missing-match-arm",
-        "missing match arm",
+        format!("missing match arm: {} not covered", d.uncovered_patterns),
         ctx.sema.diagnostics_display_range(InFile::new(d.file, d.match_expr.clone().into())).range,
     )
 }
@@ -31,9 +31,9 @@ mod tests {
             r#"
 fn main() {
     match () { }
-        //^^ error: missing match arm
+        //^^ error: missing match arm: `()` not covered
     match (()) { }
-        //^^^^ error: missing match arm
+        //^^^^ error: missing match arm: `()` not covered
 
     match () { _ => (), }
     match () { () => (), }
@@ -49,7 +49,7 @@ fn main() {
             r#"
 fn main() {
     match ((), ()) { }
-        //^^^^^^^^ error: missing match arm
+        //^^^^^^^^ error: missing match arm: `(_, _)` not covered
 
     match ((), ()) { ((), ()) => (), }
 }
@@ -63,21 +63,21 @@ fn main() {
             r#"
 fn test_main() {
     match false { }
-        //^^^^^ error: missing match arm
+        //^^^^^ error: missing match arm: `_` not covered
     match false { true => (), }
-        //^^^^^ error: missing match arm
+        //^^^^^ error: missing match arm: `false` not covered
     match (false, true) {}
-        //^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^ error: missing match arm: `(_, _)` not covered
     match (false, true) { (true, true) => (), }
-        //^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
     match (false, true) {
-        //^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^ error: missing match arm: `(true, true)` not covered
         (false, true) => (),
         (false, false) => (),
         (true, false) => (),
     }
     match (false, true) { (true, _x) => (), }
-        //^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
 
     match false { true => (), false => (), }
     match (false, true) {
@@ -116,11 +116,11 @@ fn test_main() {
             r#"
 fn main() {
     match (false, ((), false)) {}
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(_, _)` not covered
     match (false, ((), false)) { (true, ((), true)) => (), }
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
     match (false, ((), false)) { (true, _) => (), }
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
 
     match (false, ((), false)) {
         (true, ((), true)) => (),
@@ -146,12 +146,12 @@ enum Either { A, B, }
 
 fn main() {
     match Either::A { }
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `A` and `B` not covered
     match Either::B { Either::A => (), }
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `B` not covered
 
     match &Either::B {
-        //^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^ error: missing match arm: `&B` not covered
         Either::A => (),
     }
 
@@ -174,9 +174,9 @@ enum Either { A(bool), B }
 
 fn main() {
     match Either::B { }
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `A(_)` and `B` not covered
     match Either::B {
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `A(false)` not covered
         Either::A(true) => (), Either::B => ()
     }
 
@@ -207,7 +207,7 @@ enum Either { A(bool), B(bool, bool) }
 
 fn main() {
     match Either::A(false) {
-        //^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^ error: missing match arm: `B(true, _)` not covered
         Either::A(_) => (),
         Either::B(false, _) => (),
     }
@@ -278,6 +278,7 @@ fn main() {
     match (true, false) {
         (true, false, true) => (),
         (true) => (),
+       //^^^^ error: expected (bool, bool), found bool
     }
     match (true, false) { (true,) => {} }
     match (0) { () => () }
@@ -352,7 +353,7 @@ fn main() {
         Either::A => (),
     }
     match loop { break Foo::A } {
-        //^^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `B` not covered
         Either::A => (),
     }
     match loop { break Foo::A } {
@@ -390,9 +391,9 @@ enum Either { A { foo: bool }, B }
 fn main() {
     let a = Either::A { foo: true };
     match a { }
-        //^ error: missing match arm
+        //^ error: missing match arm: `A { .. }` and `B` not covered
     match a { Either::A { foo: true } => () }
-        //^ error: missing match arm
+        //^ error: missing match arm: `B` not covered
     match a {
         Either::A { } => (),
       //^^^^^^^^^ error: missing structure fields:
@@ -400,7 +401,7 @@ fn main() {
         Either::B => (),
     }
     match a {
-        //^ error: missing match arm
+        //^ error: missing match arm: `B` not covered
         Either::A { } => (),
     } //^^^^^^^^^ error: missing structure fields:
       //        | - foo
@@ -431,7 +432,7 @@ enum Either {
 fn main() {
     let a = Either::A { foo: true, bar: () };
     match a {
-        //^ error: missing match arm
+        //^ error: missing match arm: `B` not covered
         Either::A { bar: (), foo: false } => (),
         Either::A { foo: true, bar: () } => (),
     }
@@ -458,12 +459,12 @@ enum Either {
 fn main() {
     let a = Either::B;
     match a {
-        //^ error: missing match arm
+        //^ error: missing match arm: `A { foo: false, .. }` not covered
         Either::A { foo: true, .. } => (),
         Either::B => (),
     }
     match a {
-        //^ error: missing match arm
+        //^ error: missing match arm: `B` not covered
         Either::A { .. } => (),
     }
 
@@ -493,14 +494,14 @@ enum Either {
 
 fn main() {
     match Either::B {
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `A(false, _, _, true)` not covered
         Either::A(true, .., true) => (),
         Either::A(true, .., false) => (),
         Either::A(false, .., false) => (),
         Either::B => (),
     }
     match Either::B {
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `A(false, _, _, false)` not covered
         Either::A(true, .., true) => (),
         Either::A(true, .., false) => (),
         Either::A(.., true) => (),
@@ -537,7 +538,7 @@ fn enum_(never: Never) {
 }
 fn enum_ref(never: &Never) {
     match never {}
-        //^^^^^ error: missing match arm
+        //^^^^^ error: missing match arm: `&_` not covered
 }
 fn bang(never: !) {
     match never {}
@@ -561,7 +562,7 @@ fn main() {
         Some(never) => match never {},
     }
     match Option::<Never>::None {
-        //^^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `None` not covered
         Option::Some(_never) => {},
     }
 }
@@ -575,7 +576,7 @@ fn main() {
             r#"
 fn main() {
     match (false, true, false) {
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(true, _, _)` not covered
         (false, ..) => (),
     }
 }"#,
@@ -588,7 +589,7 @@ fn main() {
             r#"
 fn main() {
     match (false, true, false) {
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(_, _, true)` not covered
         (.., false) => (),
     }
 }"#,
@@ -601,7 +602,7 @@ fn main() {
             r#"
 fn main() {
     match (false, true, false) {
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(false, _, _)` not covered
         (true, .., false) => (),
     }
 }"#,
@@ -614,11 +615,11 @@ fn main() {
             r#"struct Foo { a: bool }
 fn main(f: Foo) {
     match f {}
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo { .. }` not covered
     match f { Foo { a: true } => () }
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo { a: false }` not covered
     match &f { Foo { a: true } => () }
-        //^^ error: missing match arm
+        //^^ error: missing match arm: `&Foo { a: false }` not covered
     match f { Foo { a: _ } => () }
     match f {
         Foo { a: true } => (),
@@ -639,9 +640,9 @@ fn main(f: Foo) {
             r#"struct Foo(bool);
 fn main(f: Foo) {
     match f {}
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo(_)` not covered
     match f { Foo(true) => () }
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo(false)` not covered
     match f {
         Foo(true) => (),
         Foo(false) => (),
@@ -657,7 +658,7 @@ fn main(f: Foo) {
             r#"struct Foo;
 fn main(f: Foo) {
     match f {}
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo` not covered
     match f { Foo => () }
 }
 "#,
@@ -670,9 +671,9 @@ fn main(f: Foo) {
             r#"struct Foo { foo: bool, bar: bool }
 fn main(f: Foo) {
     match f { Foo { foo: true, .. } => () }
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo { foo: false, .. }` not covered
     match f {
-        //^ error: missing match arm
+        //^ error: missing match arm: `Foo { foo: false, bar: true }` not covered
         Foo { foo: true, .. } => (),
         Foo { bar: false, .. } => ()
     }
@@ -693,7 +694,7 @@ fn main(f: Foo) {
 fn main() {
     enum Either { A(bool), B }
     match Either::B {
-        //^^^^^^^^^ error: missing match arm
+        //^^^^^^^^^ error: missing match arm: `B` not covered
         Either::A(true | false) => (),
     }
 }
@@ -702,20 +703,19 @@ fn main() {
     }
 
     #[test]
-    fn no_panic_at_unimplemented_subpattern_type() {
-        cov_mark::check_count!(validate_match_bailed_out, 1);
-
-        check_diagnostics(
+    fn char_field() {
+        check_diagnostics_no_bails(
             r#"
 struct S { a: char}
 fn main(v: S) {
     match v { S{ a }      => {} }
     match v { S{ a: _x }  => {} }
     match v { S{ a: 'a' } => {} }
+        //^ error: missing match arm: `S { a: '\0'..='`' }`, `S { a: 'b'..='\u{d7ff}' }` and `S { a: '\u{e000}'..='\u{10ffff}' }` not covered
     match v { S{..}       => {} }
     match v { _           => {} }
     match v { }
-        //^ error: missing match arm
+        //^ error: missing match arm: `S { .. }` not covered
 }
 "#,
         );
@@ -731,7 +731,7 @@ fn main() {
         false     => {}
     }
     match true { _x @ true => {} }
-        //^^^^ error: missing match arm
+        //^^^^ error: missing match arm: `false` not covered
 }
 "#,
         );
@@ -757,6 +757,7 @@ fn main() {
     match (true,) {
         (ref _x,) => {}
         (true,) => {}
+      //^^^^^^^ warn: unreachable pattern
     }
 }
 "#,
@@ -786,12 +787,12 @@ use lib::E;
 fn main() {
     match E::A { _ => {} }
     match E::A {
-        //^^^^ error: missing match arm
+        //^^^^ error: missing match arm: `_` not covered
         E::A => {}
         E::B => {}
     }
     match E::A {
-        //^^^^ error: missing match arm
+        //^^^^ error: missing match arm: `_` not covered
         E::A | E::B => {}
     }
 }
@@ -810,7 +811,7 @@ fn main() {
         false         => {}
     }
     match true {
-        //^^^^ error: missing match arm
+        //^^^^ error: missing match arm: `true` not covered
         true if false => {}
         false         => {}
     }
@@ -858,72 +859,238 @@ fn main() {
     match &Foo::A(true) {
         _ => {}
         Foo::A(_) => {}
+      //^^^^^^^^^ warn: unreachable pattern
     }
 }
 "#,
         );
     }
 
-    mod false_negatives {
-        //! The implementation of match checking here is a work in progress. As we roll this out, we
-        //! prefer false negatives to false positives (ideally there would be no false positives). This
-        //! test module should document known false negatives. Eventually we will have a complete
-        //! implementation of match checking and this module will be empty.
-        //!
-        //! The reasons for documenting known false negatives:
-        //!
-        //!   1. It acts as a backlog of work that can be done to improve the behavior of the system.
-        //!   2. It ensures the code doesn't panic when handling these cases.
-        use super::*;
-
-        #[test]
-        fn integers() {
-            cov_mark::check_count!(validate_match_bailed_out, 1);
-
-            // We don't currently check integer exhaustiveness.
-            check_diagnostics(
-                r#"
+    #[test]
+    fn integers() {
+        check_diagnostics_no_bails(
+            r#"
 fn main() {
     match 5 {
+        //^ error: missing match arm: `i32::MIN..=9` and `20..=i32::MAX` not covered
         10 => (),
         11..20 => (),
     }
+    match 5u8 {
+        //^^^ error: missing match arm: `1..=254` not covered
+        0 => (),
+        255 => (),
+    }
+    match 5u8 {
+        0..=127 => (),
+        128..=255 => (),
+    }
+    match 5i8 {
+        -128..=-1 => (),
+        0 => (),
+        1.. => (),
+    }
+    match 5i8 {
+        //^^^ error: missing match arm: `i8::MIN..=-2` and `0` not covered
+        -1 => (),
+        1..=127 => (),
+    }
+    match 5usize {
+        //^^^^^^ error: missing match arm: `_` not covered
+        0.. => (),
+    }
+    match (0u8, true) {
+        //^^^^^^^^^^^ error: missing match arm: `(128..=u8::MAX, false)` not covered
+        (0..=127, _) => (),
+        (_, true) => (),
+    }
 }
 "#,
-            );
-        }
+        );
+    }
 
-        #[test]
-        fn reference_patterns_at_top_level() {
-            cov_mark::check_count!(validate_match_bailed_out, 1);
+    #[test]
+    fn chars() {
+        check_diagnostics_no_bails(
+            r#"
+fn main() {
+    match 'x' {
+        '\0'..='\u{D7FF}' => (),
+        '\u{E000}'..='\u{10FFFF}' => (),
+    }
+    match 'x' {
+        //^^^ error: missing match arm: `'\0'..='@'`, `'['..='\u{d7ff}'` and `'\u{e000}'..='\u{10ffff}'` not covered
+        'A'..='Z' => (),
+    }
+}
+"#,
+        );
+    }
 
-            check_diagnostics(
-                r#"
+    #[test]
+    fn constants() {
+        check_diagnostics_no_bails(
+            r#"
+const LOW: u8 = 10;
+const HIGH: u8 = 20;
+const NEG: i32 = -5;
+struct S;
+impl S {
+    const LIMIT: u8 = 100;
+}
 fn main() {
-    match &false {
-        &true => {}
+    match 5u8 {
+        //^^^ error: missing match arm: `21..=99` not covered
+        0..LOW => (),
+        LOW..=HIGH => (),
+        S::LIMIT..=255 => (),
+    }
+    match 5 {
+        NEG => (),
+        -100..NEG => (),
+        _ => (),
+    }
+    match true {
+        //^^^^ error: missing match arm: `false` not covered
+        TRUE => (),
     }
 }
-            "#,
-            );
-        }
+const TRUE: bool = true;
+"#,
+        );
+    }
 
-        #[test]
-        fn reference_patterns_in_fields() {
-            cov_mark::check_count!(validate_match_bailed_out, 2);
+    #[test]
+    fn computed_constants() {
+        check_diagnostics_no_bails(
+            r#"
+const HI: u8 = 10 + 5;
+const MID: u8 = HI / 3;
+fn main() {
+    match 5u8 {
+        //^^^ error: missing match arm: `16..=u8::MAX` not covered
+        0..=HI => (),
+    }
+    match 5u8 {
+        0..MID => (),
+        MID..=255 => (),
+    }
+}
+"#,
+        );
+    }
 
-            check_diagnostics(
-                r#"
+    #[test]
+    fn strings_and_floats_are_never_exhaustive() {
+        check_diagnostics_no_bails(
+            r#"
 fn main() {
-    match (&false,) {
-        (true,) => {}
+    match "foo" {
+        //^^^^^ error: missing match arm: `&_` not covered
+        "foo" => (),
+        "bar" => (),
+    }
+    match 1.0 {
+        //^^^ error: missing match arm: `_` not covered
+        1.0 => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn slices() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(s: &[bool], a: [bool; 2]) {
+    match s {
+        //^ error: missing match arm: `&[_, _, ..]` not covered
+        [] => (),
+        [_] => (),
+    }
+    match s {
+        [] => (),
+        [_, ..] => (),
+    }
+    match s {
+        //^ error: missing match arm: `&[false, .., true]` not covered
+        [] => (),
+        [true, ..] => (),
+        [.., false] | [_] => (),
+    }
+    match a {
+        //^ error: missing match arm: `[false, true]` not covered
+        [true, _] => (),
+        [_, false] => (),
+    }
+    match a {
+        [true, ..] => (),
+        [.., true] => (),
+        [false, false] => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reference_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+fn main() {
+    match &false {
+        //^^^^^^ error: missing match arm: `&false` not covered
+        &true => {}
     }
     match (&false,) {
+        //^^^^^^^^^ error: missing match arm: `(&false,)` not covered
         (&true,) => {}
     }
+    match &&Some(true) {
+        //^^^^^^^^^^^^ error: missing match arm: `&&Some(false)` not covered
+        None => {}
+        &&Some(true) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn type_alias() {
+        check_diagnostics_no_bails(
+            r#"
+enum E { A, B }
+type Alias = E;
+fn main(x: &Alias) {
+    match x {
+        //^ error: missing match arm: `&B` not covered
+        Alias::A => {}
+    }
+    match x {
+        Alias::A => {}
+        E::B => {}
+    }
 }
-            "#,
-            );
-        }
+"#,
+        );
+    }
+
+    #[test]
+    fn box_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+#[lang = "owned_box"]
+struct Box<T>(T);
+fn main(b: Box<bool>) {
+    match b {
+        //^ error: missing match arm: `box false` not covered
+        box true => {}
+    }
+}
+"#,
+        );
     }
 }
@@ -181,6 +181,7 @@ fn div(x: i32, y: i32) -> MyResult<i32> {
             r#"
 //- minicore: option, result
 fn foo() -> Result<(), i32> { 0 }
+                            //^ error: expected Result<(), i32>, found i32
 "#,
         );
     }
@@ -193,6 +194,7 @@ fn foo() -> Result<(), i32> { 0 }
 enum SomeOtherEnum { Ok(i32), Err(String) }
 
 fn foo() -> SomeOtherEnum { 0 }
+                          //^ error: expected SomeOtherEnum, found i32
 "#,
         );
     }
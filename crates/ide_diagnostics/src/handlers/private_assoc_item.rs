@@ -0,0 +1,177 @@
+use hir::{db::AstDatabase, AssocItem, InFile, ModuleDef};
+use ide_db::{helpers::fix_visibility, source_change::SourceChange};
+use syntax::{ast, AstNode, SyntaxNodePtr, TextRange};
+
+use crate::{fix, Assist, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: private-assoc-item
+//
+// This diagnostic is triggered if a method call or a path refers to an
+// associated item of an inherent impl which isn't visible from the current
+// module.
+pub(crate) fn private_assoc_item(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::PrivateAssocItem,
+) -> Diagnostic {
+    let db = ctx.sema.db;
+    let (kind, name) = match d.item {
+        AssocItem::Function(it) if it.self_param(db).is_some() => ("method", Some(it.name(db))),
+        AssocItem::Function(it) => ("associated function", Some(it.name(db))),
+        AssocItem::Const(it) => ("associated constant", it.name(db)),
+        AssocItem::TypeAlias(it) => ("associated type", Some(it.name(db))),
+    };
+    let name = name.map_or_else(|| "_".to_string(), |it| it.to_string());
+    let node = d.expr_or_pat.clone().map(|it| it.either(Into::into, Into::into));
+    private_name_diagnostic(
+        ctx,
+        "private-assoc-item",
+        format!("{} `{}` is private", kind, name),
+        &node,
+        d.item.into(),
+    )
+}
+
+/// A diagnostic on the name of the private `item` `node` refers to, with the
+/// quick fix to make it visible. `node` is a method call, or an expression,
+/// pattern or type path.
+pub(crate) fn private_name_diagnostic(
+    ctx: &DiagnosticsContext<'_>,
+    code: &'static str,
+    message: String,
+    node: &InFile<SyntaxNodePtr>,
+    item: ModuleDef,
+) -> Diagnostic {
+    let name_ref = private_name_ref(ctx, node);
+    let range_node = match &name_ref {
+        Some(name_ref) => node.with_value(SyntaxNodePtr::new(name_ref.syntax())),
+        None => node.clone(),
+    };
+    Diagnostic::new(code, message, ctx.sema.diagnostics_display_range(range_node).range)
+        .experimental()
+        .with_fixes(fixes(ctx, node, name_ref, item))
+}
+
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    node: &InFile<SyntaxNodePtr>,
+    name_ref: Option<ast::NameRef>,
+    item: ModuleDef,
+) -> Option<Vec<Assist>> {
+    if node.file_id.is_macro() {
+        return None;
+    }
+    let name_ref = name_ref?;
+    let from = ctx.sema.scope(name_ref.syntax()).module()?;
+    let fix = fix_visibility::for_module_def(ctx.sema.db, item, from)?;
+    Some(vec![visibility_fix(fix, name_ref.syntax().text_range())])
+}
+
+/// Turns `fix` into a quick fix offered at `target`.
+pub(crate) fn visibility_fix(it: fix_visibility::VisibilityFix, target: TextRange) -> Assist {
+    let source_change = SourceChange::from_text_edit(it.file_id, it.text_edit());
+    fix("fix_visibility", &it.label, source_change, target)
+}
+
+/// The name of the method or the last segment of the path.
+fn private_name_ref(
+    ctx: &DiagnosticsContext<'_>,
+    node: &InFile<SyntaxNodePtr>,
+) -> Option<ast::NameRef> {
+    let root = ctx.sema.db.parse_or_expand(node.file_id)?;
+    let node = node.value.to_node(&root);
+    if let Some(call) = ast::MethodCallExpr::cast(node.clone()) {
+        return call.name_ref();
+    }
+    let path = node.descendants().find_map(ast::Path::cast)?;
+    path.segment()?.name_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn private_assoc_item() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S;
+    impl S {
+        const C: u32 = 0;
+        pub fn new() -> S {
+            S::create()
+        }
+        fn create() -> S {
+            S
+        }
+        fn get(&self) -> u32 {
+            S::C
+        }
+    }
+    pub trait Tr {
+        fn tr(&self) {}
+    }
+    impl Tr for S {}
+}
+use m::Tr;
+fn foo(s: m::S) {
+    m::S::new();
+    m::S::create();
+        //^^^^^^ 💡 error: associated function `create` is private
+    m::S::C;
+        //^ 💡 error: associated constant `C` is private
+    s.get();
+    //^^^ 💡 error: method `get` is private
+    s.tr();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn visible_through_pub_in() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S;
+    pub mod n {
+        impl super::S {
+            pub(in crate::m) fn get(&self) {}
+        }
+    }
+    fn foo(s: S) {
+        s.get();
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn make_method_visible() {
+        check_fix(
+            r#"
+mod m {
+    pub struct S;
+    impl S {
+        fn get(&self) {}
+    }
+}
+fn foo(s: m::S) {
+    s.get$0();
+}
+"#,
+            r#"
+mod m {
+    pub struct S;
+    impl S {
+        pub(crate) fn get(&self) {}
+    }
+}
+fn foo(s: m::S) {
+    s.get();
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use hir::{db::AstDatabase, VariantDef};
+use ide_db::helpers::fix_visibility;
+use syntax::{ast, AstNode, SyntaxNodePtr};
+
+use crate::{handlers::private_assoc_item::visibility_fix, Assist, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: private-field
+//
+// This diagnostic is triggered if a field access refers to a field which isn't
+// visible from the current module.
+pub(crate) fn private_field(ctx: &DiagnosticsContext<'_>, d: &hir::PrivateField) -> Diagnostic {
+    let name_ref = field_name_ref(ctx, d);
+    let node = match &name_ref {
+        Some(name_ref) => d.expr.with_value(SyntaxNodePtr::new(name_ref.syntax())),
+        None => d.expr.clone().map(|it| it.into()),
+    };
+    let db = ctx.sema.db;
+    let parent = match d.field.parent_def(db) {
+        VariantDef::Struct(it) => format!("struct `{}`", it.name(db)),
+        VariantDef::Union(it) => format!("union `{}`", it.name(db)),
+        VariantDef::Variant(it) => format!("variant `{}`", it.name(db)),
+    };
+    Diagnostic::new(
+        "private-field",
+        format!("field `{}` of {} is private", d.field.name(db), parent),
+        ctx.sema.diagnostics_display_range(node).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d, name_ref))
+}
+
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::PrivateField,
+    name_ref: Option<ast::NameRef>,
+) -> Option<Vec<Assist>> {
+    if d.expr.file_id.is_macro() {
+        return None;
+    }
+    let name_ref = name_ref?;
+    let from = ctx.sema.scope(name_ref.syntax()).module()?;
+    let fix = fix_visibility::for_field(ctx.sema.db, d.field, from)?;
+    Some(vec![visibility_fix(fix, name_ref.syntax().text_range())])
+}
+
+fn field_name_ref(ctx: &DiagnosticsContext<'_>, d: &hir::PrivateField) -> Option<ast::NameRef> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    match d.expr.value.to_node(&root) {
+        ast::Expr::FieldExpr(it) => it.name_ref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn private_field() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S {
+        pub a: u32,
+        b: u32,
+    }
+    pub union U {
+        c: u32,
+    }
+    pub fn new() -> S {
+        S { a: 0, b: 0 }
+    }
+    fn local(s: S) -> u32 {
+        s.b
+    }
+}
+fn foo(s: m::S, u: m::U) {
+    s.a;
+    s.b;
+    //^ 💡 error: field `b` of struct `S` is private
+    unsafe { u.c };
+             //^ 💡 error: field `c` of union `U` is private
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn private_field_through_deref() {
+        check_diagnostics(
+            r#"
+//- minicore: deref
+mod m {
+    pub struct Inner {
+        pub x: u32,
+    }
+    pub struct Outer {
+        x: u32,
+        inner: Inner,
+    }
+    impl core::ops::Deref for Outer {
+        type Target = Inner;
+        fn deref(&self) -> &Inner {
+            &self.inner
+        }
+    }
+}
+fn foo(o: m::Outer) -> u32 {
+    o.x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn make_field_visible() {
+        check_fix(
+            r#"
+mod m {
+    pub struct S {
+        b: u32,
+    }
+}
+fn foo(s: m::S) {
+    s.b$0;
+}
+"#,
+            r#"
+mod m {
+    pub struct S {
+        pub(crate) b: u32,
+    }
+}
+fn foo(s: m::S) {
+    s.b;
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use hir::{Adt, ModuleDef};
+
+use crate::{
+    handlers::private_assoc_item::private_name_diagnostic, Diagnostic, DiagnosticsContext,
+};
+
+// Diagnostic: private-item
+//
+// This diagnostic is triggered if a path, in an expression, a pattern or a type,
+// refers to an item in another module which isn't visible from the current one.
+pub(crate) fn private_item(ctx: &DiagnosticsContext<'_>, d: &hir::PrivateItem) -> Diagnostic {
+    let db = ctx.sema.db;
+    let (kind, name) = match d.item {
+        ModuleDef::Function(it) => ("function", Some(it.name(db))),
+        ModuleDef::Const(it) => ("constant", it.name(db)),
+        ModuleDef::Static(it) => ("static", it.name(db)),
+        ModuleDef::Adt(Adt::Struct(it)) => ("struct", Some(it.name(db))),
+        ModuleDef::Adt(Adt::Union(it)) => ("union", Some(it.name(db))),
+        ModuleDef::Adt(Adt::Enum(it)) => ("enum", Some(it.name(db))),
+        ModuleDef::Variant(it) => ("variant", Some(it.name(db))),
+        ModuleDef::Module(it) => ("module", it.name(db)),
+        ModuleDef::Trait(it) => ("trait", Some(it.name(db))),
+        ModuleDef::TypeAlias(it) => ("type alias", Some(it.name(db))),
+        ModuleDef::BuiltinType(it) => ("type", Some(it.name())),
+    };
+    let name = name.map_or_else(|| "_".to_string(), |it| it.to_string());
+    let message = format!("{} `{}` is private", kind, name);
+    private_name_diagnostic(ctx, "private-item", message, &d.node, d.item)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn private_item() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub fn public() {}
+    fn private() {}
+    const C: u32 = 0;
+    static S: u32 = 0;
+    struct Unit;
+    pub(crate) struct Tuple(u32);
+    pub mod n {
+        pub fn child() {
+            super::private();
+        }
+    }
+}
+use m::n;
+fn foo(x: u32) {
+    m::public();
+    m::private();
+     //^^^^^^^ 💡 error: function `private` is private
+    m::C;
+     //^ 💡 error: constant `C` is private
+    m::S;
+     //^ 💡 error: static `S` is private
+    m::Unit;
+     //^^^^ 💡 error: struct `Unit` is private
+    m::Tuple(0);
+    n::child();
+    match x {
+        m::C => {}
+         //^ 💡 error: constant `C` is private
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn type_paths() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct Public;
+    struct Private;
+    pub(super) struct Generic<T>(T);
+    trait Tr {}
+}
+fn foo(_: m::Private) -> m::Generic<m::Private> {
+           //^^^^^^^ 💡 error: struct `Private` is private
+                                     //^^^^^^^ 💡 error: struct `Private` is private
+    let _: m::Public;
+    let _: m::Private;
+            //^^^^^^^ 💡 error: struct `Private` is private
+    let _: (m::Generic<m::Private>, u8);
+                        //^^^^^^^ 💡 error: struct `Private` is private
+    let _ = 0 as m::Private;
+                  //^^^^^^^ 💡 error: struct `Private` is private
+    let _: &dyn m::Tr;
+                 //^^ 💡 error: trait `Tr` is private
+    loop {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn other_crate() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:dep
+fn foo() {
+    dep::public();
+    dep::private();
+       //^^^^^^^ 💡 error: function `private` is private
+}
+//- /dep.rs crate:dep
+pub fn public() {}
+fn private() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn make_item_visible() {
+        check_fix(
+            r#"
+mod m {
+    fn private() {}
+}
+fn foo() {
+    m::private$0();
+}
+"#,
+            r#"
+mod m {
+    pub(crate) fn private() {}
+}
+fn foo() {
+    m::private();
+}
+"#,
+        );
+    }
+}
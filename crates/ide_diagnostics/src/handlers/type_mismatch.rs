@@ -0,0 +1,349 @@
+use hir::{db::AstDatabase, known, HirDisplay, Type};
+use ide_db::{assists::Assist, helpers::FamousDefs, source_change::SourceChange};
+use syntax::{ast, AstNode};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: type-mismatch
+//
+// This diagnostic is triggered when the type of an expression does not match
+// the expected type.
+//
+// Example:
+//
+// ```rust
+// fn foo() -> i32 {
+//     "0"
+// }
+// ```
+pub(crate) fn type_mismatch(ctx: &DiagnosticsContext<'_>, d: &hir::TypeMismatch) -> Diagnostic {
+    Diagnostic::new(
+        "type-mismatch",
+        format!(
+            "expected {}, found {}",
+            d.expected.display(ctx.sema.db),
+            d.actual.display(ctx.sema.db)
+        ),
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::TypeMismatch) -> Option<Vec<Assist>> {
+    // Editing the expansion of a macro doesn't make much sense.
+    if d.expr.file_id.is_macro() {
+        return None;
+    }
+    let db = ctx.sema.db;
+    let root = db.parse_or_expand(d.expr.file_id)?;
+    let expr = d.expr.value.to_node(&root);
+    let range = expr.syntax().text_range();
+    let file_id = d.expr.file_id.original_file(db);
+    let scope = ctx.sema.scope(expr.syntax());
+    let krate = scope.krate()?;
+    let famous_defs = FamousDefs(&ctx.sema, Some(krate));
+
+    let mut fixes = Vec::new();
+    let mut push_fix = |id, label: &str, replacement: String| {
+        let edit = TextEdit::replace(range, replacement);
+        fixes.push(fix(id, label, SourceChange::from_text_edit(file_id, edit), range));
+    };
+
+    if let Some(referent) = d.expected.remove_ref() {
+        if d.actual.could_unify_with(db, &referent) {
+            let (id, label, prefix) = if d.expected.is_mutable_reference() {
+                ("add_mut_reference", "Add mutable reference", "&mut ")
+            } else {
+                ("add_reference", "Add reference", "&")
+            };
+            push_fix(id, label, format!("{}{}", prefix, operand(&expr)));
+        }
+    }
+
+    if let Some(referent) = d.actual.remove_ref() {
+        if referent.could_unify_with(db, &d.expected) {
+            if referent.is_copy(db) {
+                push_fix("add_deref", "Dereference", format!("*{}", operand(&expr)));
+            } else if matches!(famous_defs.core_clone_Clone(), Some(clone) if referent.impls_trait(db, clone, &[]))
+            {
+                push_fix("add_clone", "Clone", format!("{}.clone()", receiver(&expr)));
+            }
+        }
+    }
+
+    if has_to_string_method(ctx, &scope, krate, &d.actual, &d.expected) {
+        push_fix(
+            "add_to_string",
+            "Convert with `.to_string()`",
+            format!("{}.to_string()", receiver(&expr)),
+        );
+    }
+
+    if let Some(into) = famous_defs.core_convert_Into() {
+        if d.actual.impls_trait(db, into, std::slice::from_ref(&d.expected)) {
+            push_fix("add_into", "Convert with `.into()`", format!("{}.into()", receiver(&expr)));
+        }
+    }
+
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(fixes)
+    }
+}
+
+/// Whether `actual` has a `to_string` method which returns an `expected`.
+fn has_to_string_method(
+    ctx: &DiagnosticsContext<'_>,
+    scope: &hir::SemanticsScope<'_>,
+    krate: hir::Crate,
+    actual: &Type,
+    expected: &Type,
+) -> bool {
+    let db = ctx.sema.db;
+    let traits_in_scope = scope.traits_in_scope();
+    actual
+        .iterate_method_candidates(
+            db,
+            krate,
+            &traits_in_scope,
+            Some(&known::to_string),
+            |_, func| {
+                let takes_no_args =
+                    matches!(func.method_params(db), Some(params) if params.is_empty());
+                if takes_no_args && func.ret_type(db).could_unify_with(db, expected) {
+                    Some(())
+                } else {
+                    None
+                }
+            },
+        )
+        .is_some()
+}
+
+/// The text of `expr` as the operand of a prefix operator.
+fn operand(expr: &ast::Expr) -> String {
+    match expr {
+        ast::Expr::BinExpr(_) | ast::Expr::CastExpr(_) | ast::Expr::RangeExpr(_) => {
+            format!("({})", expr)
+        }
+        _ => expr.to_string(),
+    }
+}
+
+/// The text of `expr` as the receiver of a method call.
+fn receiver(expr: &ast::Expr) -> String {
+    match expr {
+        ast::Expr::ArrayExpr(_)
+        | ast::Expr::AwaitExpr(_)
+        | ast::Expr::CallExpr(_)
+        | ast::Expr::FieldExpr(_)
+        | ast::Expr::IndexExpr(_)
+        | ast::Expr::Literal(_)
+        | ast::Expr::MacroCall(_)
+        | ast::Expr::MethodCallExpr(_)
+        | ast::Expr::ParenExpr(_)
+        | ast::Expr::PathExpr(_)
+        | ast::Expr::TryExpr(_)
+        | ast::Expr::TupleExpr(_) => expr.to_string(),
+        _ => format!("({})", expr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix, check_fixes, check_no_fix};
+
+    #[test]
+    fn reports_expected_and_found_types() {
+        check_diagnostics(
+            r#"
+fn foo() -> i32 {
+    let x: u8 = true;
+              //^^^^ error: expected u8, found bool
+    0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_on_unknown_types() {
+        check_diagnostics(
+            r#"
+fn foo() {
+    let x: i32 = unresolved();
+    let y: Unresolved = 92;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_fix_without_conversion() {
+        check_no_fix(
+            r#"
+struct A;
+struct B;
+fn foo() {
+    let b: B = A$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_reference() {
+        // Plain references are added by `add-reference-here`.
+        check_fix(
+            r#"
+struct S;
+fn foo(r: &S) {
+    let rr: &&S = $0r;
+}
+"#,
+            r#"
+struct S;
+fn foo(r: &S) {
+    let rr: &&S = &r;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_cast_with_into() {
+        check_fix(
+            r#"
+//- minicore: from
+impl From<i32> for i64 {
+    fn from(it: i32) -> i64 { loop {} }
+}
+fn foo(a: u8) {
+    let x: i64 = a as$0 i32;
+}
+"#,
+            r#"
+impl From<i32> for i64 {
+    fn from(it: i32) -> i64 { loop {} }
+}
+fn foo(a: u8) {
+    let x: i64 = (a as i32).into();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn dereference_copy_type() {
+        check_fix(
+            r#"
+//- minicore: copy
+fn foo(r: &i32) {
+    let x: i32 = $0r;
+}
+"#,
+            r#"
+fn foo(r: &i32) {
+    let x: i32 = *r;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn clone_non_copy_type() {
+        check_fix(
+            r#"
+//- minicore: clone
+struct S;
+impl Clone for S {
+    fn clone(&self) -> S { S }
+}
+fn foo(r: &S) -> S {
+    let s: S = $0r;
+    s
+}
+"#,
+            r#"
+struct S;
+impl Clone for S {
+    fn clone(&self) -> S { S }
+}
+fn foo(r: &S) -> S {
+    let s: S = r.clone();
+    s
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_with_to_string_or_into() {
+        check_fixes(
+            r#"
+//- minicore: from
+struct Name;
+impl From<&str> for Name {
+    fn from(_: &str) -> Name { Name }
+}
+trait ToName {
+    fn to_string(&self) -> Name;
+}
+impl ToName for str {
+    fn to_string(&self) -> Name { Name }
+}
+fn foo() {
+    let n: Name = "x"$0;
+}
+"#,
+            vec![
+                r#"
+struct Name;
+impl From<&str> for Name {
+    fn from(_: &str) -> Name { Name }
+}
+trait ToName {
+    fn to_string(&self) -> Name;
+}
+impl ToName for str {
+    fn to_string(&self) -> Name { Name }
+}
+fn foo() {
+    let n: Name = "x".to_string();
+}
+"#,
+                r#"
+struct Name;
+impl From<&str> for Name {
+    fn from(_: &str) -> Name { Name }
+}
+trait ToName {
+    fn to_string(&self) -> Name;
+}
+impl ToName for str {
+    fn to_string(&self) -> Name { Name }
+}
+fn foo() {
+    let n: Name = "x".into();
+}
+"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn no_type_mismatch_with_more_specific_diagnostic() {
+        check_diagnostics(
+            r#"
+fn foo(r: &i32) {}
+fn bar() {
+    foo(1);
+      //^ 💡 error: add reference here
+}
+"#,
+        );
+    }
+}
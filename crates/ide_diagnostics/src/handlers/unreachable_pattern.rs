@@ -0,0 +1,131 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unreachable-pattern
+//
+// This diagnostic is triggered if the pattern of a match arm can never match,
+// because the arms above it already cover all of its values.
+pub(crate) fn unreachable_pattern(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnreachablePattern,
+) -> Diagnostic {
+    Diagnostic::new(
+        "unreachable-pattern",
+        "unreachable pattern",
+        ctx.sema.diagnostics_display_range(d.pat.clone().map(|it| it.into())).range,
+    )
+    .severity(Severity::Warning)
+    .lint("unreachable_patterns")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn unreachable_arms() {
+        check_diagnostics(
+            r#"
+enum E { A, B }
+fn foo(e: E, b: bool, n: u8) {
+    match e {
+        E::A => {}
+        _ => {}
+        E::B => {}
+      //^^^^ warn: unreachable pattern
+    }
+    match b {
+        true | false => {}
+        true => {}
+      //^^^^ warn: unreachable pattern
+    }
+    match n {
+        0..=100 => {}
+        50 => {}
+      //^^ warn: unreachable pattern
+        101.. => {}
+        _ => {}
+      //^ warn: unreachable pattern
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn guarded_arms_do_not_cover() {
+        check_diagnostics(
+            r#"
+fn foo(x: Option<u32>, cond: bool) {
+    match x {
+        Some(_) if cond => {}
+        Some(_) => {}
+        None => {}
+    }
+    match x {
+        None => {}
+        _ if cond => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn slices_and_references() {
+        check_diagnostics(
+            r#"
+fn foo(s: &[u8], a: [bool; 2]) {
+    match s {
+        [] => {}
+        [_, ..] => {}
+        [1, 2] => {}
+      //^^^^^^ warn: unreachable pattern
+    }
+    match &a {
+        &[true, _] => {}
+        [false, _] => {}
+        [_, true] => {}
+      //^^^^^^^^^ warn: unreachable pattern
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bare_path_to_tuple_variant() {
+        check_diagnostics(
+            r#"
+enum E { A(u8), B }
+use E::*;
+fn foo(e: E) {
+    match e {
+        B => {}
+        A => {}
+    }
+    match e {
+        E::B => {}
+        E::A => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed() {
+        check_diagnostics(
+            r#"
+fn foo(b: bool) {
+    #[allow(unreachable_patterns)]
+    match b {
+        _ => {}
+        true => {}
+    }
+}
+"#,
+        );
+    }
+}
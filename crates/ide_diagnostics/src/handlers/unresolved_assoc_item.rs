@@ -0,0 +1,136 @@
+use either::Either;
+use hir::{db::AstDatabase, HirDisplay};
+use ide_db::helpers::import_assets::ImportAssets;
+use syntax::{ast, AstNode, SyntaxNodePtr};
+
+use crate::{import_fixes, Assist, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: unresolved-assoc-item
+//
+// This diagnostic is triggered if a path refers to an associated function or
+// constant which doesn't exist on the type, or whose trait isn't in scope.
+pub(crate) fn unresolved_assoc_item(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedAssocItem,
+) -> Diagnostic {
+    let name_ref = assoc_item_name_ref(ctx, d);
+    let node = match &name_ref {
+        Some(name_ref) => d.expr_or_pat.with_value(SyntaxNodePtr::new(name_ref.syntax())),
+        None => d.expr_or_pat.clone().map(|it| it.either(Into::into, Into::into)),
+    };
+    Diagnostic::new(
+        "unresolved-assoc-item",
+        format!("no associated item `{}` on type `{}`", d.name, d.ty.display(ctx.sema.db)),
+        ctx.sema.diagnostics_display_range(node).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d, name_ref))
+}
+
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedAssocItem,
+    name_ref: Option<ast::NameRef>,
+) -> Option<Vec<Assist>> {
+    // Editing the expansion of a macro doesn't make much sense.
+    if d.expr_or_pat.file_id.is_macro() {
+        return None;
+    }
+    let name_ref = name_ref?;
+    let path = name_ref.syntax().ancestors().find_map(ast::Path::cast)?;
+    let import_assets = ImportAssets::for_exact_path(&path, &ctx.sema)?;
+    let file_id = d.expr_or_pat.file_id.original_file(ctx.sema.db);
+    import_fixes(ctx, import_assets, path.syntax(), file_id, name_ref.syntax().text_range())
+}
+
+/// The last segment of the path to the associated item.
+fn assoc_item_name_ref(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedAssocItem,
+) -> Option<ast::NameRef> {
+    let root = ctx.sema.db.parse_or_expand(d.expr_or_pat.file_id)?;
+    let node = match &d.expr_or_pat.value {
+        Either::Left(expr) => expr.to_node(&root).syntax().clone(),
+        Either::Right(pat) => pat.to_node(&root).syntax().clone(),
+    };
+    let path = node.descendants().find_map(ast::Path::cast)?;
+    path.segment()?.name_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn unresolved_assoc_item() {
+        check_diagnostics(
+            r#"
+struct S;
+impl S {
+    const C: u32 = 0;
+    fn new() -> S { S }
+}
+fn foo(x: u32) {
+    S::new();
+    S::create();
+     //^^^^^^ error: no associated item `create` on type `S`
+    match x {
+        S::C => {}
+        S::D => {}
+         //^ error: no associated item `D` on type `S`
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_on_enum_variant_or_unknown_type() {
+        check_diagnostics(
+            r#"
+enum E { A }
+fn foo() {
+    E::A;
+    Unresolved::new();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn import_trait() {
+        check_fix(
+            r#"
+mod m {
+    pub trait Create {
+        fn create() -> Self;
+    }
+    impl Create for super::S {
+        fn create() -> Self { super::S }
+    }
+}
+struct S;
+fn foo() {
+    S::create$0();
+}
+"#,
+            r#"
+use m::Create;
+
+mod m {
+    pub trait Create {
+        fn create() -> Self;
+    }
+    impl Create for super::S {
+        fn create() -> Self { super::S }
+    }
+}
+struct S;
+fn foo() {
+    S::create();
+}
+"#,
+        );
+    }
+}
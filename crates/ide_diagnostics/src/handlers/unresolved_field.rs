@@ -0,0 +1,84 @@
+use hir::{db::AstDatabase, HirDisplay};
+use syntax::{ast, AstNode, SyntaxNodePtr};
+
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: unresolved-field
+//
+// This diagnostic is triggered if a field access refers to a field which
+// doesn't exist on the receiver's type.
+pub(crate) fn unresolved_field(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedField,
+) -> Diagnostic {
+    let node = match field_name_ref(ctx, d) {
+        Some(name_ref) => d.expr.with_value(SyntaxNodePtr::new(name_ref.syntax())),
+        None => d.expr.clone().map(|it| it.into()),
+    };
+    Diagnostic::new(
+        "unresolved-field",
+        format!("no field `{}` on type `{}`", d.name, d.receiver.display(ctx.sema.db)),
+        ctx.sema.diagnostics_display_range(node).range,
+    )
+    .experimental()
+}
+
+fn field_name_ref(ctx: &DiagnosticsContext<'_>, d: &hir::UnresolvedField) -> Option<ast::NameRef> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    match d.expr.value.to_node(&root) {
+        ast::Expr::FieldExpr(field) => field.name_ref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn unresolved_field() {
+        check_diagnostics(
+            r#"
+struct S { foo: u32 }
+fn foo(s: S, t: (u32,)) {
+    s.bar;
+    //^^^ error: no field `bar` on type `S`
+    t.1;
+    //^ error: no field `1` on type `(u32,)`
+    s.foo;
+    t.0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unknown_receiver_or_private_field() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S { foo: u32 }
+}
+fn foo(s: m::S) {
+    s.foo;
+    //^^^ 💡 error: field `foo` of struct `S` is private
+    unresolved().foo;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn field_through_autoderef() {
+        check_diagnostics(
+            r#"
+struct S { foo: u32 }
+fn foo(s: &&S) {
+    s.foo;
+    s.bar;
+    //^^^ error: no field `bar` on type `&&S`
+}
+"#,
+        );
+    }
+}
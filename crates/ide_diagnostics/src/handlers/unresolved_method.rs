@@ -0,0 +1,220 @@
+use hir::{db::AstDatabase, HirDisplay};
+use ide_db::helpers::import_assets::ImportAssets;
+use syntax::{ast, AstNode, SyntaxNodePtr};
+
+use crate::{import_fixes, Assist, Diagnostic, DiagnosticsContext};
+
+// Diagnostic: unresolved-method
+//
+// This diagnostic is triggered if a method call refers to a method which
+// doesn't exist on the receiver's type, or whose trait isn't in scope.
+pub(crate) fn unresolved_method(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedMethodCall,
+) -> Diagnostic {
+    let name_ref = method_name_ref(ctx, d);
+    let node = match &name_ref {
+        Some(name_ref) => d.expr.with_value(SyntaxNodePtr::new(name_ref.syntax())),
+        None => d.expr.clone().map(|it| it.into()),
+    };
+    Diagnostic::new(
+        "unresolved-method",
+        format!("no method `{}` on type `{}`", d.name, d.receiver.display(ctx.sema.db)),
+        ctx.sema.diagnostics_display_range(node).range,
+    )
+    .experimental()
+    .with_fixes(fixes(ctx, d, name_ref))
+}
+
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedMethodCall,
+    name_ref: Option<ast::NameRef>,
+) -> Option<Vec<Assist>> {
+    // Editing the expansion of a macro doesn't make much sense.
+    if d.expr.file_id.is_macro() {
+        return None;
+    }
+    let name_ref = name_ref?;
+    let call = name_ref.syntax().parent().and_then(ast::MethodCallExpr::cast)?;
+    let import_assets = ImportAssets::for_method_call(&call, &ctx.sema)?;
+    let file_id = d.expr.file_id.original_file(ctx.sema.db);
+    import_fixes(ctx, import_assets, call.syntax(), file_id, name_ref.syntax().text_range())
+}
+
+fn method_name_ref(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedMethodCall,
+) -> Option<ast::NameRef> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    let expr = d.expr.value.to_node(&root);
+    match expr {
+        ast::Expr::MethodCallExpr(call) => call.name_ref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix, check_fixes, check_no_fix};
+
+    #[test]
+    fn unresolved_method() {
+        check_diagnostics(
+            r#"
+struct S;
+fn foo(s: S) {
+    s.bar();
+    //^^^ error: no method `bar` on type `S`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_on_unknown_receiver() {
+        check_diagnostics(
+            r#"
+fn foo() {
+    unresolved().bar();
+    let x = Default::default();
+    x.bar();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn private_method_is_not_unresolved() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S;
+    impl S {
+        fn bar(&self) {}
+    }
+}
+fn foo(s: m::S) {
+    s.bar();
+    //^^^ 💡 error: method `bar` is private
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn import_trait() {
+        check_fix(
+            r#"
+mod m {
+    pub trait Bar {
+        fn bar(&self) {}
+    }
+    impl Bar for u32 {}
+}
+fn foo(x: u32) {
+    x.bar$0();
+}
+"#,
+            r#"
+use m::Bar;
+
+mod m {
+    pub trait Bar {
+        fn bar(&self) {}
+    }
+    impl Bar for u32 {}
+}
+fn foo(x: u32) {
+    x.bar();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_fix_without_trait() {
+        check_no_fix(
+            r#"
+struct S;
+impl S {
+    fn foo(&self) {
+        self.bar$0(92);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn import_one_of_the_traits() {
+        check_fixes(
+            r#"
+mod m {
+    pub trait Bar {
+        fn bar(&self) {}
+    }
+    impl Bar for super::S {}
+}
+mod n {
+    pub trait Baz {
+        fn bar(&self) {}
+    }
+    impl Baz for super::S {}
+}
+struct S;
+impl S {
+    fn foo(&self) {
+        self.bar$0();
+    }
+}
+"#,
+            vec![
+                r#"
+use m::Bar;
+
+mod m {
+    pub trait Bar {
+        fn bar(&self) {}
+    }
+    impl Bar for super::S {}
+}
+mod n {
+    pub trait Baz {
+        fn bar(&self) {}
+    }
+    impl Baz for super::S {}
+}
+struct S;
+impl S {
+    fn foo(&self) {
+        self.bar();
+    }
+}
+"#,
+                r#"
+use n::Baz;
+
+mod m {
+    pub trait Bar {
+        fn bar(&self) {}
+    }
+    impl Bar for super::S {}
+}
+mod n {
+    pub trait Baz {
+        fn bar(&self) {}
+    }
+    impl Baz for super::S {}
+}
+struct S;
+impl S {
+    fn foo(&self) {
+        self.bar();
+    }
+}
+"#,
+            ],
+        );
+    }
+}
@@ -72,6 +72,7 @@ mod baz {}
                         severity: Error,
                         unused: false,
                         experimental: false,
+                        lint: None,
                         fixes: Some(
                             [
                                 Assist {
@@ -102,6 +103,7 @@ mod baz {}
                                 },
                             ],
                         ),
+                        related: [],
                     },
                 ]
             "#]],
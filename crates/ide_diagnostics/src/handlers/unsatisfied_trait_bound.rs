@@ -0,0 +1,138 @@
+use hir::{db::AstDatabase, HirDisplay};
+use syntax::{ast, AstNode, SyntaxNodePtr};
+
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: unsatisfied-trait-bound
+//
+// This diagnostic is triggered if a function or method is called with types
+// which don't satisfy the trait bounds of its where clauses.
+pub(crate) fn unsatisfied_trait_bound(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnsatisfiedTraitBound,
+) -> Diagnostic {
+    let node = match callee(ctx, d) {
+        Some(callee) => d.expr.with_value(callee),
+        None => d.expr.clone().map(|it| it.into()),
+    };
+    Diagnostic::new(
+        "unsatisfied-trait-bound",
+        format!("the trait bound `{}` is not satisfied", d.trait_ref.display(ctx.sema.db)),
+        ctx.sema.diagnostics_display_range(node).range,
+    )
+    .experimental()
+}
+
+/// The name of the called function or method, which is what the bounds
+/// belong to.
+fn callee(ctx: &DiagnosticsContext<'_>, d: &hir::UnsatisfiedTraitBound) -> Option<SyntaxNodePtr> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id)?;
+    let node = match d.expr.value.to_node(&root) {
+        ast::Expr::MethodCallExpr(call) => call.name_ref()?.syntax().clone(),
+        ast::Expr::CallExpr(call) => call.expr()?.syntax().clone(),
+        _ => return None,
+    };
+    Some(SyntaxNodePtr::new(&node))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn unsatisfied_bound_on_function() {
+        check_diagnostics(
+            r#"
+trait Tr {}
+impl Tr for u32 {}
+fn take<T: Tr>(_: T) {}
+fn foo() {
+    take(0u8);
+  //^^^^ error: the trait bound `u8: Tr` is not satisfied
+    take(0u32);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsatisfied_bound_on_method() {
+        check_diagnostics(
+            r#"
+trait Tr<T> {}
+impl Tr<u32> for u32 {}
+struct S;
+impl S {
+    fn f<T>(&self, _: T) where T: Tr<u32> {}
+    fn g<T>(&self, _: T) where u8: Tr<T> {}
+}
+fn foo(s: S) {
+    s.f(0u32);
+    s.f(0u8);
+    //^ error: the trait bound `u8: Tr<u32>` is not satisfied
+    s.g(0u32);
+    //^ error: the trait bound `u8: Tr<u32>` is not satisfied
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bounds_from_the_environment_hold() {
+        check_diagnostics(
+            r#"
+trait Tr {}
+impl Tr for i32 {}
+fn take<T: Tr>(_: T) {}
+fn foo<U: Tr>(x: U, y: impl Tr) {
+    take(x);
+    take(y);
+    take(1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsatisfied_bound_on_trait_function() {
+        check_diagnostics(
+            r#"
+//- minicore: clone
+struct NotClone;
+fn foo(x: &NotClone) {
+    Clone::clone(x);
+  //^^^^^^^^^^^^ error: the trait bound `NotClone: Clone` is not satisfied
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsized_types() {
+        check_diagnostics(
+            r#"
+//- minicore: sized
+trait Display {}
+impl Display for str {}
+trait ToS {
+    fn to_s(&self) -> u32;
+}
+impl<T: ?Sized + Display> ToS for T {
+    fn to_s(&self) -> u32 {
+        0
+    }
+}
+fn take<T: ?Sized>(_: &T) {}
+fn take_sized<T>(_: &T) {}
+fn foo() {
+    "a".to_s();
+    ToS::to_s("a");
+    <str as ToS>::to_s("a");
+    take("a");
+    take_sized("a");
+  //^^^^^^^^^^ error: the trait bound `str: Sized` is not satisfied
+}
+"#,
+        );
+    }
+}
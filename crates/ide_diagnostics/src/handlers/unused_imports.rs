@@ -0,0 +1,534 @@
+use hir::{AsAssocItem, ModuleDef, Trait};
+use ide_db::{
+    base_db::FileId,
+    defs::{Definition, NameRefClass},
+    helpers::insert_use::use_tree_removal_range,
+    source_change::SourceChange,
+};
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, NameOwner, VisibilityOwner},
+    AstNode, SmolStr, SyntaxKind, SyntaxNode, WalkEvent,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, is_lint_allowed, Assist, Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unused-imports
+//
+// This diagnostic is triggered if an item imported with a private `use` is
+// never used.
+pub(crate) fn unused_imports(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<()> {
+    // Look at all imports of an item list or block at once, so that the code
+    // they are visible in only has to be searched once.
+    let imports: Vec<(ast::UseTree, Definition, Option<SmolStr>)> = node
+        .children()
+        .filter_map(ast::Use::cast)
+        // Re-exports are used elsewhere.
+        .filter(|it| {
+            it.visibility().is_none() && !is_lint_allowed(&ctx.sema, it.syntax(), "unused_imports")
+        })
+        .flat_map(|it| it.syntax().descendants().filter_map(ast::UseTree::cast))
+        .filter(|it| it.is_simple_path())
+        .filter_map(|tree| {
+            let name_ref = tree.path()?.segment()?.name_ref()?;
+            let def = match NameRefClass::classify(&ctx.sema, &name_ref)? {
+                NameRefClass::Definition(def) => def,
+                _ => return None,
+            };
+            let name = match tree.rename() {
+                Some(rename) => rename.name().map(|it| SmolStr::from(it.text().as_str())),
+                None => Some(SmolStr::from(name_ref.text().as_str())),
+            };
+            Some((tree, def, name))
+        })
+        .collect();
+    if imports.is_empty() {
+        return None;
+    }
+
+    let scope = UseScope::new(ctx, node, &imports);
+    for (tree, def, name) in imports {
+        if scope.uses(def, name.as_deref()) {
+            continue;
+        }
+
+        acc.push(
+            Diagnostic::new(
+                "unused-imports",
+                format!("unused import: `{}`", tree),
+                tree.syntax().text_range(),
+            )
+            .severity(Severity::Warning)
+            .lint("unused_imports")
+            .with_unused(true)
+            .experimental()
+            .with_fixes(fixes(file_id, &tree)),
+        );
+    }
+    Some(())
+}
+
+fn fixes(file_id: FileId, tree: &ast::UseTree) -> Option<Vec<Assist>> {
+    let edit = TextEdit::delete(use_tree_removal_range(tree)?);
+    let source_change = SourceChange::from_text_edit(file_id, edit);
+    Some(vec![fix(
+        "remove_unused_import",
+        "Remove unused import",
+        source_change,
+        tree.syntax().text_range(),
+    )])
+}
+
+/// How deep macro calls in the scope of imports are expanded, to not get
+/// stuck in recursive macros.
+const MACRO_EXPANSION_DEPTH_LIMIT: usize = 32;
+
+/// What the code an import is visible in refers to: the items or statements
+/// next to it, the expansions of the macro calls among them, and the child
+/// modules which import from `super`.
+///
+/// This is worked out from the syntax instead of recording which imports name
+/// resolution goes through, as most uses are only resolved when inferring
+/// function bodies. To keep that cheap, every name in scope is classified at
+/// most once, and only if it could refer to one of the imports.
+#[derive(Default)]
+struct UseScope {
+    /// The identifiers in macro calls and attributes, which can use anything.
+    macro_idents: FxHashSet<SmolStr>,
+    /// Whether there is a macro call. Its expansion is searched as well, but
+    /// the methods called in it might not be resolved, so this keeps all
+    /// trait imports.
+    macro_call: bool,
+    defs: FxHashSet<Definition>,
+    /// The traits whose associated items are used.
+    traits: FxHashSet<Trait>,
+    /// The names which couldn't be resolved, and might refer to an import.
+    unresolved: FxHashSet<SmolStr>,
+    /// Whether there is an unresolved method call, which might go through an
+    /// imported trait.
+    unresolved_method_call: bool,
+}
+
+impl UseScope {
+    fn new(
+        ctx: &DiagnosticsContext<'_>,
+        root: &SyntaxNode,
+        imports: &[(ast::UseTree, Definition, Option<SmolStr>)],
+    ) -> UseScope {
+        let names: FxHashSet<&str> =
+            imports.iter().filter_map(|(_, _, it)| it.as_deref()).collect();
+        let any_trait = imports
+            .iter()
+            .any(|(_, def, _)| matches!(def, Definition::ModuleDef(ModuleDef::Trait(_))));
+        let mut res = UseScope::default();
+        res.collect(ctx, root, true, &names, any_trait, 0);
+        res
+    }
+
+    /// Whether `def`, imported as `name`, is used in this scope. A `name` of
+    /// `None` is an `as _` import, which can only be used by calling trait
+    /// methods.
+    fn uses(&self, def: Definition, name: Option<&str>) -> bool {
+        if let Some(name) = name {
+            if self.macro_idents.contains(name) || self.unresolved.contains(name) {
+                return true;
+            }
+        }
+        match def {
+            Definition::ModuleDef(ModuleDef::Trait(it)) => {
+                self.macro_call
+                    || self.unresolved_method_call
+                    || self.traits.contains(&it)
+                    || self.defs.contains(&def)
+            }
+            _ => self.defs.contains(&def),
+        }
+    }
+
+    /// Records what the code under `root` refers to, leaving out child modules
+    /// which can't see the imports.
+    fn collect(
+        &mut self,
+        ctx: &DiagnosticsContext<'_>,
+        root: &SyntaxNode,
+        in_use_scope: bool,
+        names: &FxHashSet<&str>,
+        any_trait: bool,
+        macro_depth: usize,
+    ) {
+        let db = ctx.sema.db;
+        let mut preorder = root.preorder();
+        while let Some(event) = preorder.next() {
+            let node = match event {
+                WalkEvent::Enter(it) => it,
+                WalkEvent::Leave(_) => continue,
+            };
+            // Other imports of the item don't use this one, unless they are in
+            // a child module and go through `super`.
+            if in_use_scope && ast::Use::can_cast(node.kind()) {
+                preorder.skip_subtree();
+                continue;
+            }
+            if let Some(module) = ast::Module::cast(node.clone()) {
+                preorder.skip_subtree();
+                let items = match module.item_list() {
+                    Some(it) => it.syntax().clone(),
+                    None => {
+                        let module = match ctx.sema.to_def(&module) {
+                            Some(it) => it,
+                            None => continue,
+                        };
+                        let file_id = module.definition_source(db).file_id;
+                        ctx.sema.parse(file_id.original_file(db)).syntax().clone()
+                    }
+                };
+                if imports_from_super(&items) {
+                    self.collect(ctx, &items, false, names, any_trait, macro_depth);
+                }
+                continue;
+            }
+            if let Some(macro_call) = ast::MacroCall::cast(node.clone()) {
+                self.macro_call = true;
+                // Its token tree is still walked into, as for attributes.
+                if macro_depth < MACRO_EXPANSION_DEPTH_LIMIT {
+                    if let Some(expansion) = ctx.sema.expand(&macro_call) {
+                        self.collect(
+                            ctx,
+                            &expansion,
+                            in_use_scope,
+                            names,
+                            any_trait,
+                            macro_depth + 1,
+                        );
+                    }
+                }
+                continue;
+            }
+            if ast::TokenTree::can_cast(node.kind()) {
+                let idents = node
+                    .children_with_tokens()
+                    .filter_map(|it| it.into_token())
+                    .filter(|it| it.kind() == SyntaxKind::IDENT && names.contains(it.text()))
+                    .map(|it| SmolStr::from(it.text()));
+                self.macro_idents.extend(idents);
+                continue;
+            }
+            if let Some(ident_pat) = ast::IdentPat::cast(node.clone()) {
+                // A pattern like `C` refers to a constant or a unit struct.
+                let is_name =
+                    matches!(ident_pat.name(), Some(it) if names.contains(it.text().as_str()));
+                if is_name {
+                    if let Some(it) = ctx.sema.resolve_bind_pat_to_const(&ident_pat) {
+                        self.defs.insert(Definition::ModuleDef(it));
+                    }
+                }
+                continue;
+            }
+            let name_ref = match ast::NameRef::cast(node) {
+                Some(it) => it,
+                None => continue,
+            };
+            let is_name = names.contains(name_ref.text().as_str());
+            let is_method_call = name_ref
+                .syntax()
+                .parent()
+                .is_some_and(|it| ast::MethodCallExpr::can_cast(it.kind()));
+            let is_qualified = name_ref
+                .syntax()
+                .ancestors()
+                .find_map(ast::Path::cast)
+                .is_some_and(|it| it.qualifier().is_some());
+            if !is_name && !(any_trait && (is_method_call || is_qualified)) {
+                continue;
+            }
+            match NameRefClass::classify(&ctx.sema, &name_ref) {
+                Some(NameRefClass::Definition(def)) => {
+                    self.defs.insert(def);
+                    if let Definition::ModuleDef(it) = def {
+                        let trait_ = it
+                            .as_assoc_item(db)
+                            .and_then(|it| it.containing_trait_or_trait_impl(db));
+                        self.traits.extend(trait_);
+                    }
+                }
+                Some(_) => (),
+                // If we don't know what it refers to, it might be the import
+                // or a method of the imported trait.
+                None => {
+                    if is_name {
+                        self.unresolved.insert(SmolStr::from(name_ref.text().as_str()));
+                    }
+                    self.unresolved_method_call |= is_method_call;
+                }
+            }
+        }
+    }
+}
+
+fn imports_from_super(items: &SyntaxNode) -> bool {
+    items.children().filter_map(ast::Use::cast).any(|it| {
+        let first_segment =
+            it.use_tree().and_then(|it| it.path()).and_then(|it| it.first_segment());
+        matches!(first_segment.and_then(|it| it.kind()), Some(ast::PathSegmentKind::SuperKw))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::{
+        assists::AssistResolveStrategy,
+        base_db::{fixture::WithFixture, SourceDatabaseExt},
+        RootDatabase,
+    };
+
+    use crate::{
+        tests::{check_diagnostics_with_config, check_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn check_diagnostics(ra_fixture: &str) {
+        let mut config = DiagnosticsConfig::default();
+        config.disabled.insert("unused-variables".to_string());
+        check_diagnostics_with_config(config, ra_fixture)
+    }
+
+    #[test]
+    fn unused_imports() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S;
+    pub struct T;
+    pub fn f() {}
+    pub mod n {
+        pub fn g() {}
+    }
+}
+use m::{S, T};
+         //^ 💡 warn: unused import: `T`
+use m::f;
+  //^^^^ 💡 warn: unused import: `m::f`
+use m::n as renamed;
+pub use m::T as Reexported;
+fn foo(_: S) {
+    let f = 92;
+}
+mod tests {
+    use super::renamed;
+    fn bar() {
+        renamed::g();
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_traits() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub trait A { fn a(&self) {} }
+    pub trait B { fn b(&self) {} }
+    pub trait C { fn c(&self) {} }
+    impl A for u32 {}
+    impl B for u32 {}
+    impl C for u32 {}
+}
+use m::A;
+use m::B as _;
+use m::C;
+  //^^^^ 💡 warn: unused import: `m::C`
+fn foo(x: u32) {
+    x.a();
+    x.b();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_in_macros_patterns_and_child_modules() {
+        check_diagnostics(
+            r#"
+//- /lib.rs
+mod m {
+    pub const C: u32 = 0;
+    pub struct S;
+    pub struct T;
+}
+macro_rules! id { ($($t:tt)*) => { $($t)* } }
+use m::C;
+use m::S;
+use m::T;
+mod tests;
+fn foo(x: u32) {
+    match x {
+        C => {}
+        _ => {}
+    }
+    id!(S);
+}
+//- /tests.rs
+use super::*;
+fn bar(_: T) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_in_macro_expansions() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub struct S;
+    pub trait W { fn w(&self) {} }
+    impl W for () {}
+}
+macro_rules! call { ($e:expr) => { $e.w() } }
+macro_rules! make { () => { S } }
+use m::S;
+use m::W;
+fn foo() {
+    call!(());
+    let _ = make!();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn trait_used_by_std_macro() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:std
+use std::fmt::Write;
+fn foo(s: &mut String) {
+    std::write!(s, "").unwrap();
+}
+//- /std.rs crate:std
+pub mod fmt {
+    pub struct Error;
+    pub trait Write {
+        fn write_fmt(&mut self, s: &str) -> Result<(), Error>;
+    }
+}
+#[macro_export]
+macro_rules! write {
+    ($dst:expr, $($arg:tt)*) => { $dst.write_fmt($($arg)*) };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_traits_if_macros_are_called() {
+        check_diagnostics(
+            r#"
+mod m {
+    pub trait W { fn w(&self) {} }
+    pub struct S;
+}
+macro_rules! unknown { () => { unresolved() } }
+use m::W;
+use m::S;
+  //^^^^ 💡 warn: unused import: `m::S`
+fn foo() {
+    unknown!();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn respects_allow() {
+        check_diagnostics(
+            r#"
+#![allow(unused_imports)]
+mod m {
+    pub struct S;
+}
+use m::S;
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_from_list() {
+        check_fix_with_config(
+            DiagnosticsConfig::default(),
+            r#"
+mod m {
+    pub struct S;
+    pub struct T;
+}
+use m::{S, T$0};
+fn foo(_: S) {}
+"#,
+            r#"
+mod m {
+    pub struct S;
+    pub struct T;
+}
+use m::{S};
+fn foo(_: S) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_use_item() {
+        check_fix_with_config(
+            DiagnosticsConfig::default(),
+            r#"
+mod m {
+    pub struct S;
+}
+use m::S$0;
+fn foo() {}
+"#,
+            r#"
+mod m {
+    pub struct S;
+}
+fn foo() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_only_touches_the_import() {
+        let (db, file_id) = RootDatabase::with_single_file(
+            r#"
+mod m {
+    pub struct S;
+}
+use m::S;
+fn foo() {}
+fn bar() {}
+"#,
+        );
+        let diagnostic = crate::diagnostics(
+            &db,
+            &DiagnosticsConfig::default(),
+            &AssistResolveStrategy::All,
+            file_id,
+        )
+        .pop()
+        .unwrap();
+        let fix = &diagnostic.fixes.unwrap()[0];
+        let edit = fix.source_change.as_ref().unwrap().get_source_edit(file_id).unwrap();
+        let indels: Vec<_> = edit.iter().map(|it| (it.delete, it.insert.as_str())).collect();
+        // The `use m::S;` line, with its newline.
+        let line = "use m::S;\n";
+        let start = db.file_text(file_id).find(line).unwrap();
+        let range = text_edit::TextRange::at((start as u32).into(), text_edit::TextSize::of(line));
+        assert_eq!(indels, vec![(range, "")]);
+    }
+}
@@ -0,0 +1,255 @@
+use hir::Access;
+use ide_db::{
+    base_db::FileId, defs::Definition, helpers::mut_removal_range, search::FileReference,
+    source_change::SourceChange,
+};
+use syntax::{
+    ast::{self, NameOwner},
+    match_ast, AstNode, SyntaxNode, TextRange,
+};
+use text_edit::TextEdit;
+
+use crate::{
+    fix, handlers::unused_variables::local_binding, is_lint_allowed, Diagnostic,
+    DiagnosticsContext, Severity,
+};
+
+// Diagnostic: unused-mut
+//
+// This diagnostic is triggered if a variable is declared `mut` but is never
+// mutated.
+pub(crate) fn unused_mut(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<()> {
+    let ident_pat = ast::IdentPat::cast(node.clone())?;
+    let mut_token = ident_pat.mut_token()?;
+    // `ref mut x` binds a mutable reference, not a mutable variable.
+    if ident_pat.ref_token().is_some() {
+        return None;
+    }
+    let name = ident_pat.name()?;
+    let local = local_binding(ctx, &ident_pat)?;
+    let usages = Definition::Local(local).usages(&ctx.sema).all();
+    let source_file = ctx.sema.parse(file_id);
+    if usages.references.values().flatten().any(|it| needs_mut(ctx, source_file.syntax(), it))
+        || is_lint_allowed(&ctx.sema, node, "unused_mut")
+    {
+        return None;
+    }
+
+    let edit = TextEdit::delete(mut_removal_range(&mut_token));
+    let fix = fix(
+        "remove_mut",
+        "Remove `mut` keyword",
+        SourceChange::from_text_edit(file_id, edit),
+        mut_token.text_range(),
+    );
+    acc.push(
+        Diagnostic::new(
+            "unused-mut",
+            "variable does not need to be mutable",
+            TextRange::new(mut_token.text_range().start(), name.syntax().text_range().end()),
+        )
+        .severity(Severity::Warning)
+        .lint("unused_mut")
+        .experimental()
+        .with_fixes(Some(vec![fix])),
+    );
+    Some(())
+}
+
+/// Whether `reference` might mutate the variable, directly or through one of
+/// its fields or elements. When in doubt, it does.
+fn needs_mut(ctx: &DiagnosticsContext<'_>, file: &SyntaxNode, reference: &FileReference) -> bool {
+    let db = ctx.sema.db;
+    let in_macro_call = file
+        .covering_element(reference.range)
+        .ancestors()
+        .any(|it| ast::TokenTree::can_cast(it.kind()));
+    if in_macro_call {
+        return true;
+    }
+    let path_expr = match &reference.name {
+        ast::NameLike::NameRef(name_ref) => {
+            match name_ref.syntax().ancestors().find_map(ast::PathExpr::cast) {
+                Some(it) => it,
+                // A field shorthand, which moves or copies the variable.
+                None => return false,
+            }
+        }
+        _ => return true,
+    };
+
+    // The place expression which is used, like `x.field[0]` for `x`.
+    let mut place = ast::Expr::from(path_expr);
+    while let Some(parent) = place.syntax().parent() {
+        let parent = match_ast! {
+            match parent {
+                ast::FieldExpr(it) => ast::Expr::from(it),
+                ast::IndexExpr(it) => {
+                    if it.base().as_ref() != Some(&place) {
+                        break;
+                    }
+                    ast::Expr::from(it)
+                },
+                ast::ParenExpr(it) => ast::Expr::from(it),
+                ast::PrefixExpr(it) => {
+                    if it.op_kind() != Some(ast::UnaryOp::Deref) {
+                        break;
+                    }
+                    ast::Expr::from(it)
+                },
+                _ => break,
+            }
+        };
+        place = parent;
+    }
+
+    let parent = match place.syntax().parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    match_ast! {
+        match parent {
+            ast::BinExpr(it) => {
+                matches!(it.op_kind(), Some(ast::BinaryOp::Assignment { .. }))
+                    && it.lhs().as_ref() == Some(&place)
+            },
+            ast::RefExpr(it) => it.mut_token().is_some(),
+            ast::MethodCallExpr(it) => {
+                if it.receiver().as_ref() != Some(&place) {
+                    return false;
+                }
+                match ctx.sema.resolve_method_call(&it) {
+                    Some(func) => {
+                        matches!(func.self_param(db), Some(param) if matches!(param.access(db), Access::Exclusive))
+                    }
+                    None => true,
+                }
+            },
+            // A `FnMut` closure.
+            ast::CallExpr(it) => it.expr().as_ref() == Some(&place),
+            ast::MatchExpr(it) => binds_ref_mut(it.match_arm_list().map(|it| it.syntax().clone())),
+            ast::LetStmt(it) => binds_ref_mut(it.pat().map(|it| it.syntax().clone())),
+            ast::Condition(it) => binds_ref_mut(it.pat().map(|it| it.syntax().clone())),
+            _ => false,
+        }
+    }
+}
+
+/// Whether the patterns in `node` take a mutable reference into the value.
+fn binds_ref_mut(node: Option<SyntaxNode>) -> bool {
+    node.into_iter()
+        .flat_map(|it| it.descendants())
+        .filter_map(ast::IdentPat::cast)
+        .any(|it| it.ref_token().is_some() && it.mut_token().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_diagnostics_with_config, check_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn check_diagnostics(ra_fixture: &str) {
+        let mut config = DiagnosticsConfig::default();
+        config.disabled.insert("unused-variables".to_string());
+        check_diagnostics_with_config(config, ra_fixture)
+    }
+
+    #[test]
+    fn unused_mut() {
+        check_diagnostics(
+            r#"
+fn foo(mut a: u32) -> u32 {
+     //^^^^^ 💡 warn: variable does not need to be mutable
+    let mut x = 92;
+      //^^^^^ 💡 warn: variable does not need to be mutable
+    a + x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn mutations() {
+        check_diagnostics(
+            r#"
+struct S { f: [u32; 1] }
+impl S {
+    fn set(&mut self) {}
+    fn get(&self) {}
+}
+fn take(_: &mut u32) {}
+fn foo() {
+    let mut a = 0;
+    a = 1;
+    let mut b = 0;
+    b += 1;
+    let mut c = S { f: [0] };
+    c.f[0] = 1;
+    let mut d = 0;
+    take(&mut d);
+    let mut e = S { f: [0] };
+    e.set();
+    let mut f = S { f: [0] };
+      //^^^^^ 💡 warn: variable does not need to be mutable
+    f.get();
+    let mut g = Some(0);
+    if let Some(ref mut it) = g {}
+    let mut h = 0;
+    let mut inc = || h += 1;
+    inc();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_in_macro() {
+        check_diagnostics(
+            r#"
+macro_rules! set { ($e:expr) => { $e = 1 } }
+fn foo() {
+    let mut x = 0;
+    set!(x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn respects_allow() {
+        check_diagnostics(
+            r#"
+#[allow(unused_mut)]
+fn foo() {
+    let mut x = 92;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_mut() {
+        check_fix_with_config(
+            DiagnosticsConfig::default(),
+            r#"
+fn foo() -> u32 {
+    let mut$0 x = 92;
+    x
+}
+"#,
+            r#"
+fn foo() -> u32 {
+    let x = 92;
+    x
+}
+"#,
+        );
+    }
+}
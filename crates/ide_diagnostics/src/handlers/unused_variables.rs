@@ -0,0 +1,227 @@
+use ide_db::{base_db::FileId, defs::Definition, source_change::SourceChange};
+use syntax::{
+    ast::{self, NameOwner},
+    AstNode, SyntaxKind, SyntaxNode,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, is_lint_allowed, Assist, Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: unused-variables
+//
+// This diagnostic is triggered if a local variable or a parameter is never
+// used. Names starting with an underscore are exempt.
+pub(crate) fn unused_variables(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<()> {
+    let ident_pat = ast::IdentPat::cast(node.clone())?;
+    let name = ident_pat.name()?;
+    if name.text().starts_with('_') {
+        return None;
+    }
+    let local = local_binding(ctx, &ident_pat)?;
+    if !Definition::Local(local).usages(&ctx.sema).all().is_empty()
+        || is_lint_allowed(&ctx.sema, node, "unused_variables")
+    {
+        return None;
+    }
+
+    acc.push(
+        Diagnostic::new(
+            "unused-variables",
+            format!("unused variable: `{}`", name),
+            name.syntax().text_range(),
+        )
+        .severity(Severity::Warning)
+        .lint("unused_variables")
+        .with_unused(true)
+        .with_fixes(fixes(file_id, &ident_pat, &name)),
+    );
+    Some(())
+}
+
+fn fixes(file_id: FileId, ident_pat: &ast::IdentPat, name: &ast::Name) -> Option<Vec<Assist>> {
+    let new_name = format!("_{}", name);
+    let mut edit = TextEdit::builder();
+    // `S { x }` becomes `S { x: _x }`.
+    if ast::RecordPatField::for_field_name(name).is_some() {
+        edit.insert(ident_pat.syntax().text_range().start(), format!("{}: ", name));
+    }
+    edit.replace(name.syntax().text_range(), new_name.clone());
+    let source_change = SourceChange::from_text_edit(file_id, edit.finish());
+    Some(vec![fix(
+        "prefix_with_underscore",
+        &format!("Rename to `{}`", new_name),
+        source_change,
+        name.syntax().text_range(),
+    )])
+}
+
+/// The local variable bound by `ident_pat`, unless it is a binding we don't
+/// check: a constant pattern, an alternative of an or-pattern, or a parameter
+/// of a function without body.
+pub(crate) fn local_binding(
+    ctx: &DiagnosticsContext<'_>,
+    ident_pat: &ast::IdentPat,
+) -> Option<hir::Local> {
+    if ctx.sema.resolve_bind_pat_to_const(ident_pat).is_some() {
+        return None;
+    }
+    for ancestor in ident_pat.syntax().ancestors().skip(1) {
+        match ancestor.kind() {
+            // Every alternative binds the variable, but usages only refer to
+            // the first one.
+            SyntaxKind::OR_PAT => return None,
+            SyntaxKind::PARAM => {
+                let f = ancestor.parent().and_then(|it| it.parent()).and_then(ast::Fn::cast);
+                if matches!(f, Some(f) if f.body().is_none()) {
+                    return None;
+                }
+                break;
+            }
+            SyntaxKind::RECORD_PAT_FIELD | SyntaxKind::RECORD_PAT_FIELD_LIST => (),
+            kind if ast::Pat::can_cast(kind) => (),
+            _ => break,
+        }
+    }
+    ctx.sema.to_def(ident_pat)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_diagnostics_with_config, check_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn check_diagnostics(ra_fixture: &str) {
+        let mut config = DiagnosticsConfig::default();
+        config.disabled.insert("unused-mut".to_string());
+        check_diagnostics_with_config(config, ra_fixture)
+    }
+
+    #[test]
+    fn unused_locals_and_params() {
+        check_diagnostics(
+            r#"
+struct S { f: u32 }
+fn foo(a: u32, b: u32, _c: u32) -> u32 {
+             //^ 💡 warn: unused variable: `b`
+    let x = a;
+      //^ 💡 warn: unused variable: `x`
+    let S { f } = S { f: 0 };
+          //^ 💡 warn: unused variable: `f`
+    let _y = 92;
+    let z = 1;
+    let closure = |it: u32| z;
+                 //^^ 💡 warn: unused variable: `it`
+    closure(0)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_diagnostic_on_or_patterns_consts_and_declarations() {
+        check_diagnostics(
+            r#"
+enum E { A(u32), B(u32) }
+const C: u32 = 0;
+trait T {
+    fn required(x: u32);
+}
+fn foo(e: E, v: u32) -> u32 {
+    match v {
+        C => {}
+        _ => {}
+    }
+    match e {
+        E::A(x) | E::B(x) => x,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_in_macro() {
+        check_diagnostics(
+            r#"
+macro_rules! id { ($e:expr) => { $e } }
+fn foo() -> u32 {
+    let x = 92;
+    id!(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn respects_allow() {
+        check_diagnostics(
+            r#"
+//- /lib.rs
+#![allow(unused_variables)]
+mod m;
+fn foo() {
+    let x = 92;
+}
+//- /m.rs
+fn bar() {
+    let y = 92;
+}
+"#,
+        );
+        check_diagnostics(
+            r#"
+#[allow(unused)]
+fn foo() {
+    let x = 92;
+}
+fn bar() {
+    #[allow(unused_variables)]
+    let y = 92;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn prefix_with_underscore() {
+        check_fix_with_config(
+            DiagnosticsConfig::default(),
+            r#"
+fn foo() {
+    let x$0 = 92;
+}
+"#,
+            r#"
+fn foo() {
+    let _x = 92;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn prefix_shorthand_field_with_underscore() {
+        check_fix_with_config(
+            DiagnosticsConfig::default(),
+            r#"
+struct S { f: u32 }
+fn foo(s: S) {
+    let S { ref f$0 } = s;
+}
+"#,
+            r#"
+struct S { f: u32 }
+fn foo(s: S) {
+    let S { f: ref _f } = s;
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: use-after-move
+//
+// This diagnostic is triggered if a local variable is used after its value
+// was moved out of it, on some path through the function.
+pub(crate) fn use_after_move(ctx: &DiagnosticsContext<'_>, d: &hir::UseAfterMove) -> Diagnostic {
+    let moved_at = ctx.sema.diagnostics_display_range(d.moved_at.clone().map(|it| it.into()));
+    let message = match d.local.name(ctx.sema.db) {
+        Some(name) => format!("use of moved value: `{}`", name),
+        None => "use of moved value".to_string(),
+    };
+    Diagnostic::new(
+        "use-after-move",
+        message,
+        ctx.sema.diagnostics_display_range(d.expr.clone().map(|it| it.into())).range,
+    )
+    .experimental()
+    .with_related(moved_at, "value moved here")
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use crate::tests::{check_diagnostics, check_expect};
+
+    #[test]
+    fn use_after_move() {
+        check_diagnostics(
+            r#"
+//- minicore: copy
+struct S;
+fn take(_: S) {}
+fn foo(s: S) {
+    take(s);
+    take(s);
+       //^ error: use of moved value: `s`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn copy_types_and_borrows_are_not_moved() {
+        check_diagnostics(
+            r#"
+//- minicore: copy, derive
+#[derive(Clone, Copy)]
+struct C;
+struct S { f: u32 }
+impl S {
+    fn by_ref(&self) {}
+    fn by_value(self) {}
+}
+fn take<T>(_: T) {}
+fn foo<T: Copy>(c: C, n: u32, t: T, s: S, r: &S) {
+    take(c);
+    take(c);
+    take(n);
+    take(n);
+    take(t);
+    take(t);
+    take(r);
+    take(r);
+    take(&s);
+    take(s.f);
+    s.by_ref();
+    let _ = s;
+    let ref _x = s;
+    s.by_value();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_in_method_calls_and_let() {
+        check_diagnostics(
+            r#"
+//- minicore: copy
+struct S;
+impl S {
+    fn by_ref(&self) {}
+    fn by_value(self) {}
+}
+fn foo(s: S, t: S) {
+    s.by_value();
+    s.by_ref();
+  //^ error: use of moved value: `s`
+    let u = t;
+    &t;
+   //^ error: use of moved value: `t`
+    u.by_ref();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_in_branches() {
+        check_diagnostics(
+            r#"
+//- minicore: copy
+struct S;
+fn take(_: S) {}
+fn foo(a: S, b: S, c: S, cond: bool) {
+    if cond {
+        take(a);
+    }
+    take(a);
+       //^ error: use of moved value: `a`
+    if cond {
+        take(b);
+    } else {
+        take(b);
+    }
+    match cond {
+        true => take(c),
+        false => return,
+    }
+    take(c);
+       //^ error: use of moved value: `c`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_in_loops() {
+        check_diagnostics(
+            r#"
+//- minicore: copy
+struct S;
+fn take(_: S) {}
+fn foo(a: S) {
+    loop {
+        take(a);
+           //^ error: use of moved value: `a`
+    }
+}
+fn bar(b: S, c: S, mut d: S, cond: bool) {
+    while cond {
+        take(b);
+        break;
+    }
+    'outer: loop {
+        loop {
+            take(c);
+            break 'outer;
+        }
+    }
+    while cond {
+        take(d);
+        d = S;
+    }
+    let mut n = 0;
+    while n < 10 {
+        let e = S;
+        take(e);
+        n += 1;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reassignment_and_diverging_code() {
+        check_diagnostics(
+            r#"
+//- minicore: copy
+struct S;
+fn take(_: S) {}
+fn foo(mut s: S, t: S, cond: bool) {
+    take(s);
+    s = S;
+    take(s);
+    if cond {
+        take(t);
+        return;
+    }
+    take(t);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closures_check_uses_only() {
+        check_diagnostics(
+            r#"
+//- minicore: copy
+struct S;
+fn take(_: S) {}
+fn foo(s: S, t: S) {
+    let f = || take(t);
+    take(s);
+    let g = || &s;
+              //^ error: use of moved value: `s`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reports_move_site() {
+        check_expect(
+            r#"
+//- minicore: copy
+struct S;
+fn take(_: S) {}
+fn foo(s: S) {
+    take(s);
+    take(s);
+}
+"#,
+            expect![[r#"
+                [
+                    Diagnostic {
+                        code: DiagnosticCode(
+                            "use-after-move",
+                        ),
+                        message: "use of moved value: `s`",
+                        range: 64..65,
+                        severity: Error,
+                        unused: false,
+                        experimental: true,
+                        lint: None,
+                        fixes: None,
+                        related: [
+                            (
+                                FileRange {
+                                    file_id: FileId(
+                                        0,
+                                    ),
+                                    range: 51..52,
+                                },
+                                "value moved here",
+                            ),
+                        ],
+                    },
+                ]
+            "#]],
+        );
+    }
+}
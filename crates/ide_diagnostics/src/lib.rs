@@ -30,39 +30,66 @@ mod handlers {
     pub(crate) mod incorrect_case;
     pub(crate) mod macro_error;
     pub(crate) mod mismatched_arg_count;
+    pub(crate) mod mismatched_generic_arg_count;
     pub(crate) mod missing_fields;
     pub(crate) mod missing_match_arms;
     pub(crate) mod missing_ok_or_some_in_tail_expr;
     pub(crate) mod missing_unsafe;
     pub(crate) mod no_such_field;
+    pub(crate) mod private_assoc_item;
+    pub(crate) mod private_field;
+    pub(crate) mod private_item;
     pub(crate) mod remove_this_semicolon;
     pub(crate) mod replace_filter_map_next_with_find_map;
+    pub(crate) mod type_mismatch;
     pub(crate) mod unimplemented_builtin_macro;
+    pub(crate) mod unreachable_pattern;
+    pub(crate) mod unresolved_assoc_item;
     pub(crate) mod unresolved_extern_crate;
+    pub(crate) mod unresolved_field;
     pub(crate) mod unresolved_import;
     pub(crate) mod unresolved_macro_call;
+    pub(crate) mod unresolved_method;
     pub(crate) mod unresolved_module;
     pub(crate) mod unresolved_proc_macro;
+    pub(crate) mod unsatisfied_trait_bound;
+    pub(crate) mod use_after_move;
 
     // The handlers below are unusual, the implement the diagnostics as well.
     pub(crate) mod field_shorthand;
     pub(crate) mod useless_braces;
     pub(crate) mod unlinked_file;
+    pub(crate) mod unused_imports;
+    pub(crate) mod unused_mut;
+    pub(crate) mod unused_variables;
 }
 
+mod fix_all;
 #[cfg(test)]
 mod tests;
 
-use hir::{diagnostics::AnyDiagnostic, Semantics};
+use std::iter;
+
+use hir::{diagnostics::AnyDiagnostic, LintLevel, Semantics};
 use ide_db::{
     assists::{Assist, AssistId, AssistKind, AssistResolveStrategy},
-    base_db::{FileId, SourceDatabase},
+    base_db::{FileId, FileRange, SourceDatabase},
+    helpers::{
+        import_assets::ImportAssets,
+        insert_use::{insert_use_edit, ImportScope, InsertUseConfig},
+        mod_path_to_ast,
+    },
     label::Label,
     source_change::SourceChange,
     RootDatabase,
 };
-use rustc_hash::FxHashSet;
-use syntax::{ast::AstNode, TextRange};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken, SyntaxKind, SyntaxNode, TextRange,
+};
+
+pub use crate::fix_all::{fix_all, FixAll, FixAllScope};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct DiagnosticCode(pub &'static str);
@@ -81,7 +108,12 @@ pub struct Diagnostic {
     pub severity: Severity,
     pub unused: bool,
     pub experimental: bool,
+    /// The rustc lint this diagnostic corresponds to, lint attributes in the
+    /// source can change its severity or silence it.
+    pub lint: Option<&'static str>,
     pub fixes: Option<Vec<Assist>>,
+    /// Other places involved in the problem, each with a message.
+    pub related: Vec<(FileRange, String)>,
 }
 
 impl Diagnostic {
@@ -94,7 +126,9 @@ impl Diagnostic {
             severity: Severity::Error,
             unused: false,
             experimental: false,
+            lint: None,
             fixes: None,
+            related: Vec::new(),
         }
     }
 
@@ -108,6 +142,11 @@ impl Diagnostic {
         self
     }
 
+    fn lint(mut self, lint: &'static str) -> Diagnostic {
+        self.lint = Some(lint);
+        self
+    }
+
     fn with_fixes(mut self, fixes: Option<Vec<Assist>>) -> Diagnostic {
         self.fixes = fixes;
         self
@@ -117,20 +156,30 @@ impl Diagnostic {
         self.unused = unused;
         self
     }
+
+    fn with_related(mut self, range: FileRange, message: impl Into<String>) -> Diagnostic {
+        self.related.push((range, message.into()));
+        self
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
 pub enum Severity {
     Error,
-    // We don't actually emit this one yet, but we should at some point.
-    // Warning,
+    Warning,
     WeakWarning,
+    Hint,
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct DiagnosticsConfig {
     pub disable_experimental: bool,
     pub disabled: FxHashSet<String>,
+    /// Severities to use instead of the default ones, by diagnostic code.
+    pub severity: FxHashMap<String, Severity>,
+    /// How the fixes which import an item insert the `use`. Without it, no
+    /// such fixes are offered.
+    pub insert_use: Option<InsertUseConfig>,
 }
 
 struct DiagnosticsContext<'a> {
@@ -167,6 +216,26 @@ pub fn diagnostics(
     let ctx = DiagnosticsContext { config, sema, resolve };
     if module.is_none() {
         handlers::unlinked_file::unlinked_file(&ctx, &mut res, file_id);
+    } else {
+        // These look at every node or search for usages, so skip them if they
+        // would be filtered out anyway.
+        let disabled = |code: &str, experimental: bool| {
+            ctx.config.disabled.contains(code) || (ctx.config.disable_experimental && experimental)
+        };
+        let unused_imports = !disabled("unused-imports", true);
+        let unused_mut = !disabled("unused-mut", true);
+        let unused_variables = !disabled("unused-variables", false);
+        for node in ctx.sema.parse(file_id).syntax().descendants() {
+            if unused_imports {
+                handlers::unused_imports::unused_imports(&ctx, &mut res, file_id, &node);
+            }
+            if unused_mut {
+                handlers::unused_mut::unused_mut(&ctx, &mut res, file_id, &node);
+            }
+            if unused_variables {
+                handlers::unused_variables::unused_variables(&ctx, &mut res, file_id, &node);
+            }
+        }
     }
 
     let mut diags = Vec::new();
@@ -182,19 +251,30 @@ pub fn diagnostics(
             AnyDiagnostic::IncorrectCase(d) => handlers::incorrect_case::incorrect_case(&ctx, &d),
             AnyDiagnostic::MacroError(d) => handlers::macro_error::macro_error(&ctx, &d),
             AnyDiagnostic::MismatchedArgCount(d) => handlers::mismatched_arg_count::mismatched_arg_count(&ctx, &d),
+            AnyDiagnostic::MismatchedGenericArgCount(d) => handlers::mismatched_generic_arg_count::mismatched_generic_arg_count(&ctx, &d),
             AnyDiagnostic::MissingFields(d) => handlers::missing_fields::missing_fields(&ctx, &d),
             AnyDiagnostic::MissingMatchArms(d) => handlers::missing_match_arms::missing_match_arms(&ctx, &d),
             AnyDiagnostic::MissingOkOrSomeInTailExpr(d) => handlers::missing_ok_or_some_in_tail_expr::missing_ok_or_some_in_tail_expr(&ctx, &d),
             AnyDiagnostic::MissingUnsafe(d) => handlers::missing_unsafe::missing_unsafe(&ctx, &d),
             AnyDiagnostic::NoSuchField(d) => handlers::no_such_field::no_such_field(&ctx, &d),
+            AnyDiagnostic::PrivateAssocItem(d) => handlers::private_assoc_item::private_assoc_item(&ctx, &d),
+            AnyDiagnostic::PrivateField(d) => handlers::private_field::private_field(&ctx, &d),
+            AnyDiagnostic::PrivateItem(d) => handlers::private_item::private_item(&ctx, &d),
             AnyDiagnostic::RemoveThisSemicolon(d) => handlers::remove_this_semicolon::remove_this_semicolon(&ctx, &d),
             AnyDiagnostic::ReplaceFilterMapNextWithFindMap(d) => handlers::replace_filter_map_next_with_find_map::replace_filter_map_next_with_find_map(&ctx, &d),
+            AnyDiagnostic::TypeMismatch(d) => handlers::type_mismatch::type_mismatch(&ctx, &d),
             AnyDiagnostic::UnimplementedBuiltinMacro(d) => handlers::unimplemented_builtin_macro::unimplemented_builtin_macro(&ctx, &d),
+            AnyDiagnostic::UnreachablePattern(d) => handlers::unreachable_pattern::unreachable_pattern(&ctx, &d),
+            AnyDiagnostic::UnresolvedAssocItem(d) => handlers::unresolved_assoc_item::unresolved_assoc_item(&ctx, &d),
             AnyDiagnostic::UnresolvedExternCrate(d) => handlers::unresolved_extern_crate::unresolved_extern_crate(&ctx, &d),
+            AnyDiagnostic::UnresolvedField(d) => handlers::unresolved_field::unresolved_field(&ctx, &d),
             AnyDiagnostic::UnresolvedImport(d) => handlers::unresolved_import::unresolved_import(&ctx, &d),
             AnyDiagnostic::UnresolvedMacroCall(d) => handlers::unresolved_macro_call::unresolved_macro_call(&ctx, &d),
+            AnyDiagnostic::UnresolvedMethodCall(d) => handlers::unresolved_method::unresolved_method(&ctx, &d),
             AnyDiagnostic::UnresolvedModule(d) => handlers::unresolved_module::unresolved_module(&ctx, &d),
             AnyDiagnostic::UnresolvedProcMacro(d) => handlers::unresolved_proc_macro::unresolved_proc_macro(&ctx, &d),
+            AnyDiagnostic::UnsatisfiedTraitBound(d) => handlers::unsatisfied_trait_bound::unsatisfied_trait_bound(&ctx, &d),
+            AnyDiagnostic::UseAfterMove(d) => handlers::use_after_move::use_after_move(&ctx, &d),
 
             AnyDiagnostic::InactiveCode(d) => match handlers::inactive_code::inactive_code(&ctx, &d) {
                 Some(it) => it,
@@ -204,12 +284,30 @@ pub fn diagnostics(
         res.push(d)
     }
 
-    res.retain(|d| {
-        !ctx.config.disabled.contains(d.code.as_str())
-            && !(ctx.config.disable_experimental && d.experimental)
-    });
-
-    res
+    let root = ctx.sema.parse(file_id);
+    res.into_iter()
+        .filter(|d| !ctx.config.disabled.contains(d.code.as_str()))
+        .filter(|d| !(ctx.config.disable_experimental && d.experimental))
+        .filter_map(|mut d| {
+            if let Some(&severity) = ctx.config.severity.get(d.code.as_str()) {
+                d.severity = severity;
+            }
+            if let Some(lint) = d.lint {
+                // Lint attributes in the source take precedence over the config.
+                let node = match root.syntax().covering_element(d.range) {
+                    NodeOrToken::Node(it) => it,
+                    NodeOrToken::Token(it) => it.parent()?,
+                };
+                match lint_level(&ctx.sema, &node, lint) {
+                    Some(LintLevel::Allow) => return None,
+                    Some(LintLevel::Warn) => d.severity = Severity::Warning,
+                    Some(LintLevel::Deny | LintLevel::Forbid) => d.severity = Severity::Error,
+                    None => (),
+                }
+            }
+            Some(d)
+        })
+        .collect()
 }
 
 fn fix(id: &'static str, label: &str, source_change: SourceChange, target: TextRange) -> Assist {
@@ -218,6 +316,90 @@ fn fix(id: &'static str, label: &str, source_change: SourceChange, target: TextR
     res
 }
 
+/// The fixes importing the item or the trait `import_assets` is looking for,
+/// offered at `target`. `node` is the unresolved path or method call.
+fn import_fixes(
+    ctx: &DiagnosticsContext<'_>,
+    import_assets: ImportAssets,
+    node: &SyntaxNode,
+    file_id: FileId,
+    target: TextRange,
+) -> Option<Vec<Assist>> {
+    let cfg = ctx.config.insert_use.as_ref()?;
+    let mut imports = import_assets.search_for_imports(&ctx.sema, cfg.prefix_kind);
+    // We aren't interested in different namespaces.
+    imports.dedup_by(|a, b| a.import_path == b.import_path);
+    let scope = ImportScope::find_insert_use_container_with_macros(node, &ctx.sema)?;
+    let fixes: Vec<_> = imports
+        .into_iter()
+        .map(|import| {
+            let edit = insert_use_edit(&scope, mod_path_to_ast(&import.import_path), cfg);
+            fix(
+                "auto_import",
+                &format!("Import `{}`", import.import_path),
+                SourceChange::from_text_edit(file_id, edit),
+                target,
+            )
+        })
+        .collect();
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(fixes)
+    }
+}
+
+/// Whether `lint` is allowed with an `#[allow]` attribute on `node` or on one
+/// of the items or modules around it.
+fn is_lint_allowed(sema: &Semantics<RootDatabase>, node: &SyntaxNode, lint: &str) -> bool {
+    lint_level(sema, node, lint) == Some(LintLevel::Allow)
+}
+
+/// The level set for `lint` by the innermost lint attribute which mentions it
+/// or one of its groups, on `node` or on the items and modules around it.
+fn lint_level(sema: &Semantics<RootDatabase>, node: &SyntaxNode, lint: &str) -> Option<LintLevel> {
+    let names = lint_names(lint);
+    let level_in_attrs = |node: &SyntaxNode| {
+        node.children()
+            .filter_map(ast::Attr::cast)
+            .filter_map(|attr| {
+                let (name, tt) = attr.as_simple_call()?;
+                let level = LintLevel::from_attr_name(&name)?;
+                let applies = tt
+                    .syntax()
+                    .children_with_tokens()
+                    .filter_map(|it| it.into_token())
+                    .any(|it| it.kind() == SyntaxKind::IDENT && names.contains(&it.text()));
+                if applies {
+                    Some(level)
+                } else {
+                    None
+                }
+            })
+            .last()
+    };
+    if let Some(level) = node.ancestors().find_map(|it| level_in_attrs(&it)) {
+        return Some(level);
+    }
+    // The attributes of modules declared in other files, and of the crate.
+    sema.scope(node).module()?.lint_level(sema.db, &names)
+}
+
+/// The names `lint` can be referred to by in lint attributes: its own, the
+/// names of the groups it belongs to and `warnings`.
+fn lint_names(lint: &str) -> Vec<&str> {
+    const GROUPS: &[(&str, &[&str])] = &[
+        ("unused", &["unused_imports", "unused_mut", "unused_variables", "unreachable_patterns"]),
+        (
+            "nonstandard_style",
+            &["non_camel_case_types", "non_snake_case", "non_upper_case_globals"],
+        ),
+        ("bad_style", &["non_camel_case_types", "non_snake_case", "non_upper_case_globals"]),
+    ];
+    let groups = GROUPS.iter().filter(|(_, lints)| lints.contains(&lint)).map(|&(group, _)| group);
+    iter::once(lint).chain(groups).chain(iter::once("warnings")).collect()
+}
+
 fn unresolved_fix(id: &'static str, label: &str, target: TextRange) -> Assist {
     assert!(!id.contains(' '));
     Assist {
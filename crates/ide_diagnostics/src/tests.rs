@@ -4,6 +4,7 @@ use expect_test::Expect;
 use ide_db::{
     assists::AssistResolveStrategy,
     base_db::{fixture::WithFixture, SourceDatabaseExt},
+    helpers::insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
     RootDatabase,
 };
 use stdx::trim_indent;
@@ -18,7 +19,16 @@ use crate::{DiagnosticsConfig, Severity};
 ///  * that the contents of the file containing the cursor match `after` after the diagnostic fix is applied
 #[track_caller]
 pub(crate) fn check_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
-    check_nth_fix(0, ra_fixture_before, ra_fixture_after);
+    check_nth_fix(test_config(), 0, ra_fixture_before, ra_fixture_after);
+}
+
+#[track_caller]
+pub(crate) fn check_fix_with_config(
+    config: DiagnosticsConfig,
+    ra_fixture_before: &str,
+    ra_fixture_after: &str,
+) {
+    check_nth_fix(config, 0, ra_fixture_before, ra_fixture_after);
 }
 /// Takes a multi-file input fixture with annotated cursor positions,
 /// and checks that:
@@ -27,23 +37,24 @@ pub(crate) fn check_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
 ///  * that the contents of the file containing the cursor match `after` after each diagnostic fix is applied
 pub(crate) fn check_fixes(ra_fixture_before: &str, ra_fixtures_after: Vec<&str>) {
     for (i, ra_fixture_after) in ra_fixtures_after.iter().enumerate() {
-        check_nth_fix(i, ra_fixture_before, ra_fixture_after)
+        check_nth_fix(test_config(), i, ra_fixture_before, ra_fixture_after)
     }
 }
 
 #[track_caller]
-fn check_nth_fix(nth: usize, ra_fixture_before: &str, ra_fixture_after: &str) {
+fn check_nth_fix(
+    config: DiagnosticsConfig,
+    nth: usize,
+    ra_fixture_before: &str,
+    ra_fixture_after: &str,
+) {
     let after = trim_indent(ra_fixture_after);
 
     let (db, file_position) = RootDatabase::with_position(ra_fixture_before);
-    let diagnostic = super::diagnostics(
-        &db,
-        &DiagnosticsConfig::default(),
-        &AssistResolveStrategy::All,
-        file_position.file_id,
-    )
-    .pop()
-    .expect("no diagnostics");
+    let diagnostic =
+        super::diagnostics(&db, &config, &AssistResolveStrategy::All, file_position.file_id)
+            .pop()
+            .expect("no diagnostics");
     let fix = &diagnostic.fixes.expect("diagnostic misses fixes")[nth];
     let actual = {
         let source_change = fix.source_change.as_ref().unwrap();
@@ -68,35 +79,45 @@ fn check_nth_fix(nth: usize, ra_fixture_before: &str, ra_fixture_after: &str) {
 /// Checks that there's a diagnostic *without* fix at `$0`.
 pub(crate) fn check_no_fix(ra_fixture: &str) {
     let (db, file_position) = RootDatabase::with_position(ra_fixture);
-    let diagnostic = super::diagnostics(
-        &db,
-        &DiagnosticsConfig::default(),
-        &AssistResolveStrategy::All,
-        file_position.file_id,
-    )
-    .pop()
-    .unwrap();
+    let diagnostic =
+        super::diagnostics(&db, &test_config(), &AssistResolveStrategy::All, file_position.file_id)
+            .pop()
+            .unwrap();
     assert!(diagnostic.fixes.is_none(), "got a fix when none was expected: {:?}", diagnostic);
 }
 
 pub(crate) fn check_expect(ra_fixture: &str, expect: Expect) {
     let (db, file_id) = RootDatabase::with_single_file(ra_fixture);
-    let diagnostics = super::diagnostics(
-        &db,
-        &DiagnosticsConfig::default(),
-        &AssistResolveStrategy::All,
-        file_id,
-    );
+    let diagnostics = super::diagnostics(&db, &test_config(), &AssistResolveStrategy::All, file_id);
     expect.assert_debug_eq(&diagnostics)
 }
 
 #[track_caller]
 pub(crate) fn check_diagnostics(ra_fixture: &str) {
-    let mut config = DiagnosticsConfig::default();
+    let mut config = test_config();
     config.disabled.insert("inactive-code".to_string());
     check_diagnostics_with_config(config, ra_fixture)
 }
 
+/// Most fixtures are full of unused code, so the `unused-*` diagnostics are
+/// only checked by their own tests.
+pub(crate) fn test_config() -> DiagnosticsConfig {
+    let mut config = DiagnosticsConfig {
+        insert_use: Some(InsertUseConfig {
+            granularity: ImportGranularity::Crate,
+            enforce_granularity: false,
+            prefix_kind: PrefixKind::Plain,
+            group: true,
+            skip_glob_imports: false,
+        }),
+        ..DiagnosticsConfig::default()
+    };
+    for code in ["unused-imports", "unused-mut", "unused-variables"] {
+        config.disabled.insert(code.to_string());
+    }
+    config
+}
+
 #[track_caller]
 pub(crate) fn check_diagnostics_with_config(config: DiagnosticsConfig, ra_fixture: &str) {
     let (db, files) = RootDatabase::with_many_files(ra_fixture);
@@ -114,7 +135,9 @@ pub(crate) fn check_diagnostics_with_config(config: DiagnosticsConfig, ra_fixtur
                 }
                 annotation.push_str(match d.severity {
                     Severity::Error => "error",
+                    Severity::Warning => "warn",
                     Severity::WeakWarning => "weak",
+                    Severity::Hint => "hint",
                 });
                 annotation.push_str(": ");
                 annotation.push_str(&d.message);
@@ -144,3 +167,62 @@ fn test_disabled_diagnostics() {
     );
     assert!(!diagnostics.is_empty());
 }
+
+#[test]
+fn severity_overrides() {
+    let mut config = test_config();
+    config.severity.insert("incorrect-ident-case".to_string(), Severity::Error);
+    config.severity.insert("unreachable-pattern".to_string(), Severity::Hint);
+    check_diagnostics_with_config(
+        config,
+        r#"
+struct foo;
+     //^^^ 💡 error: Structure `foo` should have CamelCase name, e.g. `Foo`
+fn f(b: bool) {
+    match b {
+        _ => (),
+        true => (),
+      //^^^^ hint: unreachable pattern
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn lint_attributes_set_severity() {
+    let mut config = test_config();
+    config.severity.insert("unreachable-pattern".to_string(), Severity::Error);
+    check_diagnostics_with_config(
+        config,
+        r#"
+//- /lib.rs
+#![deny(nonstandard_style)]
+mod m;
+
+struct foo;
+     //^^^ 💡 error: Structure `foo` should have CamelCase name, e.g. `Foo`
+#[warn(non_camel_case_types)]
+struct bar;
+     //^^^ 💡 warn: Structure `bar` should have CamelCase name, e.g. `Bar`
+#[warn(unused)]
+fn f(b: bool) {
+    match b {
+        _ => (),
+        true => (),
+      //^^^^ warn: unreachable pattern
+    }
+    #[allow(unreachable_patterns)]
+    match b {
+        _ => (),
+        true => (),
+    }
+}
+//- /m.rs
+const x: u8 = 0;
+    //^ 💡 error: Constant `x` should have UPPER_SNAKE_CASE name, e.g. `X`
+#[allow(warnings)]
+fn F() {}
+"#,
+    );
+}
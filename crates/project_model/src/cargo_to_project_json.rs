@@ -1,48 +1,281 @@
-use std::{ops::Index, path::PathBuf};
+//! Converts a Cargo workspace, together with the results of its build scripts,
+//! into `rust-project.json` data, so that the project can later be loaded
+//! without running cargo.
 
-use base_db::{CrateName, FileId, ProcMacro};
-use cfg::CfgDiff;
-use paths::AbsPath;
-use rustc_hash::FxHashMap;
+use std::{collections::VecDeque, path::PathBuf};
 
-use crate::{CargoWorkspace, Sysroot, WorkspaceBuildScripts, cfg_flag::CfgFlag, project_json::{CrateData, DepData, EditionData, ProjectJsonData}, sysroot::SysrootCrate};
+use base_db::{CrateName, Env};
+use cfg::CfgOptions;
+use rustc_hash::FxHashMap;
 
-pub type CfgOverrides = FxHashMap<String, CfgDiff>;
+use crate::{
+    cargo_workspace::DepKind,
+    cfg_flag::CfgFlag,
+    crate_graph_json::cfg_options_to_json,
+    project_json::{CrateData, CrateSource, DepData, EditionData, ProjectJsonData},
+    workspace::inject_cargo_env,
+    CargoWorkspace, CfgOverrides, Package, Sysroot, Target, TargetKind, WorkspaceBuildScripts,
+};
 
 pub fn cargo_to_json(
     rustc_cfg: Vec<CfgFlag>,
     override_cfg: &CfgOverrides,
-    load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
-    load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
     cargo: &CargoWorkspace,
     build_scripts: &WorkspaceBuildScripts,
     sysroot: Option<&Sysroot>,
     rustc: &Option<CargoWorkspace>,
 ) -> ProjectJsonData {
-    let _p = profile::span("cargo_to_crate_graph");
-    let crates = cargo
-        .packages()
-        .map(|id| &cargo[id])
-        .map(|pkg| {
-            let _deps = 
-                &pkg.dependencies
-                    .iter()
-                    .collect::<Vec<_>>();
-            CrateData {
-                display_name: Some(pkg.name.to_owned()),
-                root_module: PathBuf::new(),
-                edition: EditionData::from(pkg.edition),
-                deps: Vec::new(),
-                cfg: Vec::new(),
-                target: None,
-                env: FxHashMap::default(),
-                proc_macro_dylib_path: None,
-                is_workspace_member: None,
-                source: None,
-                is_proc_macro: false,
-            }
-        })
-        .collect();
-    let json = ProjectJsonData { sysroot_src: None, crates: crates };
-    json
+    let _p = profile::span("cargo_to_json");
+    let mut crates: Vec<CrateData> = Vec::new();
+
+    let mut cfg_options = CfgOptions::default();
+    cfg_options.extend(rustc_cfg);
+
+    let mut pkg_to_lib_crate = FxHashMap::default();
+
+    // Add test cfg for non-sysroot crates
+    cfg_options.insert_atom("test".into());
+    cfg_options.insert_atom("debug_assertions".into());
+
+    let mut pkg_crates = FxHashMap::default();
+    // Does any crate signal to rust-analyzer that they need the rustc_private crates?
+    let mut has_private = false;
+    // Next, create crates for each package, target pair
+    for pkg in cargo.packages() {
+        let mut cfg_options = &cfg_options;
+        let mut replaced_cfg_options;
+        if let Some(overrides) = override_cfg.get(&cargo[pkg].name) {
+            replaced_cfg_options = cfg_options.clone();
+            replaced_cfg_options.apply_diff(overrides.clone());
+            cfg_options = &replaced_cfg_options;
+        };
+
+        has_private |= cargo[pkg].metadata.rustc_private;
+        let mut lib_tgt = None;
+        for &tgt in cargo[pkg].targets.iter() {
+            let crate_id = crates.len();
+            crates.push(target_crate_data(cargo, pkg, tgt, build_scripts, cfg_options));
+            if cargo[tgt].kind == TargetKind::Lib {
+                lib_tgt = Some((crate_id, cargo[tgt].name.clone()));
+                pkg_to_lib_crate.insert(pkg, crate_id);
+            }
+            pkg_crates.entry(pkg).or_insert_with(Vec::new).push((crate_id, cargo[tgt].kind));
+        }
+
+        // Set deps to the lib target of the current package. Deps on the
+        // sysroot are added when the project is loaded.
+        for &(from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
+            if let Some((to, name)) = lib_tgt.clone() {
+                if to != from && kind != TargetKind::BuildScript {
+                    // (build script can not depend on its library target)
+
+                    // For root projects with dashes in their name,
+                    // cargo metadata does not do any normalization,
+                    // so we do it ourselves currently
+                    let name = CrateName::normalize_dashes(&name);
+                    crates[from].deps.push(DepData { krate: to, name });
+                }
+            }
+        }
+    }
+
+    // Now add a dep edge from all targets of upstream to the lib
+    // target of downstream.
+    for pkg in cargo.packages() {
+        for dep in cargo[pkg].dependencies.iter() {
+            let name = CrateName::new(&dep.name).unwrap();
+            if let Some(&to) = pkg_to_lib_crate.get(&dep.pkg) {
+                for &(from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
+                    if dep.kind == DepKind::Build && kind != TargetKind::BuildScript {
+                        // Only build scripts may depend on build dependencies.
+                        continue;
+                    }
+                    if dep.kind != DepKind::Build && kind == TargetKind::BuildScript {
+                        // Build scripts may only depend on build dependencies.
+                        continue;
+                    }
+                    add_dep(&mut crates, from, name.clone(), to);
+                }
+            }
+        }
+    }
+
+    if has_private {
+        // If the user provided a path to rustc sources, we add all the rustc_private crates
+        // and create dependencies on them for the crates which opt-in to that
+        if let Some(rustc_workspace) = rustc {
+            handle_rustc_crates(rustc_workspace, &mut crates, &cfg_options, cargo, &pkg_crates);
+        }
+    }
+
+    ProjectJsonData { sysroot_src: sysroot.map(|it| it.root().to_path_buf().into()), crates }
+}
+
+fn handle_rustc_crates(
+    rustc_workspace: &CargoWorkspace,
+    crates: &mut Vec<CrateData>,
+    cfg_options: &CfgOptions,
+    cargo: &CargoWorkspace,
+    pkg_crates: &FxHashMap<Package, Vec<(usize, TargetKind)>>,
+) {
+    let mut rustc_pkg_crates = FxHashMap::default();
+    let mut pkg_to_lib_crate = FxHashMap::default();
+    // The root package of the rustc-dev component is rustc_driver, so we match that
+    let root_pkg =
+        rustc_workspace.packages().find(|package| rustc_workspace[*package].name == "rustc_driver");
+    // The rustc workspace might be incomplete (such as if rustc-dev is not
+    // installed for the current toolchain) and `rustcSource` is set to discover.
+    if let Some(root_pkg) = root_pkg {
+        // Iterate through every crate in the dependency subtree of rustc_driver using BFS
+        let mut queue = VecDeque::new();
+        queue.push_back(root_pkg);
+        while let Some(pkg) = queue.pop_front() {
+            // Don't duplicate packages if they are dependended on a diamond pattern
+            if rustc_pkg_crates.contains_key(&pkg) {
+                continue;
+            }
+            for dep in &rustc_workspace[pkg].dependencies {
+                queue.push_back(dep.pkg);
+            }
+            for &tgt in rustc_workspace[pkg].targets.iter() {
+                if rustc_workspace[tgt].kind != TargetKind::Lib {
+                    continue;
+                }
+                let crate_id = crates.len();
+                let mut krate = target_crate_data(
+                    rustc_workspace,
+                    pkg,
+                    tgt,
+                    &WorkspaceBuildScripts::default(),
+                    cfg_options,
+                );
+                krate.is_workspace_member = Some(false);
+                crates.push(krate);
+                pkg_to_lib_crate.insert(pkg, crate_id);
+                rustc_pkg_crates.entry(pkg).or_insert_with(Vec::new).push(crate_id);
+            }
+        }
+    }
+    // Now add a dep edge from all targets of upstream to the lib
+    // target of downstream.
+    for pkg in rustc_pkg_crates.keys().copied() {
+        for dep in rustc_workspace[pkg].dependencies.iter() {
+            let name = CrateName::new(&dep.name).unwrap();
+            if let Some(&to) = pkg_to_lib_crate.get(&dep.pkg) {
+                for &from in rustc_pkg_crates.get(&pkg).into_iter().flatten() {
+                    add_dep(crates, from, name.clone(), to);
+                }
+            }
+        }
+    }
+    // Add a dependency on the rustc_private crates for all targets of each package
+    // which opts in
+    for dep in rustc_workspace.packages() {
+        let name = CrateName::normalize_dashes(&rustc_workspace[dep].name);
+
+        if let Some(&to) = pkg_to_lib_crate.get(&dep) {
+            for pkg in cargo.packages() {
+                if !cargo[pkg].metadata.rustc_private {
+                    continue;
+                }
+                for &(from, _) in pkg_crates.get(&pkg).into_iter().flatten() {
+                    // Avoid creating duplicate dependencies, a crate from
+                    // crates.io wins over the one from `rustcSource`.
+                    if !crates[from].deps.iter().any(|d| d.name == name) {
+                        add_dep(crates, from, name.clone(), to);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn target_crate_data(
+    cargo: &CargoWorkspace,
+    pkg: Package,
+    tgt: Target,
+    build_scripts: &WorkspaceBuildScripts,
+    cfg_options: &CfgOptions,
+) -> CrateData {
+    let build_data = build_scripts.outputs.get(pkg);
+    let package = &cargo[pkg];
+    let target = &cargo[tgt];
+
+    let cfg = {
+        let mut opts = cfg_options.clone();
+        for feature in package.active_features.iter() {
+            opts.insert_key_value("feature".into(), feature.into());
+        }
+        if let Some(cfgs) = build_data.map(|it| &it.cfgs) {
+            opts.extend(cfgs.iter().cloned());
+        }
+        cfg_options_to_json(&opts)
+            .into_iter()
+            .flat_map(|(key, values)| {
+                if values.is_empty() {
+                    return vec![CfgFlag::Atom(key)];
+                }
+                values
+                    .into_iter()
+                    .map(|value| CfgFlag::KeyValue { key: key.clone(), value })
+                    .collect()
+            })
+            .collect()
+    };
+
+    let mut env = Env::default();
+    inject_cargo_env(package, &mut env);
+    let mut env: FxHashMap<String, String> =
+        env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    if let Some(envs) = build_data.map(|it| &it.envs) {
+        env.extend(envs.iter().cloned());
+    }
+
+    let proc_macro_dylib_path = if target.is_proc_macro {
+        build_data.and_then(|it| it.proc_macro_dylib_path.clone()).map(PathBuf::from)
+    } else {
+        None
+    };
+
+    // Mirrors the roots computed by `ProjectWorkspace::to_roots` for cargo.
+    let pkg_root = package.manifest.parent().to_path_buf();
+    let mut include_dirs = vec![pkg_root.clone()];
+    include_dirs.extend(build_data.and_then(|it| it.out_dir.clone()));
+    if target.kind == TargetKind::Lib {
+        if let Some(dir) = target.root.parent() {
+            let dir = dir.normalize();
+            if !dir.starts_with(&pkg_root) {
+                include_dirs.push(dir);
+            }
+        }
+    }
+    let mut exclude_dirs = vec![pkg_root.join(".git")];
+    if package.is_member {
+        exclude_dirs.push(pkg_root.join("target"));
+    } else {
+        exclude_dirs.push(pkg_root.join("tests"));
+        exclude_dirs.push(pkg_root.join("examples"));
+        exclude_dirs.push(pkg_root.join("benches"));
+    }
+
+    CrateData {
+        display_name: Some(target.name.clone()),
+        root_module: target.root.to_path_buf().into(),
+        edition: EditionData::from(package.edition),
+        deps: Vec::new(),
+        cfg,
+        target: None,
+        env,
+        proc_macro_dylib_path,
+        is_workspace_member: Some(package.is_member),
+        source: Some(CrateSource {
+            include_dirs: include_dirs.into_iter().map(PathBuf::from).collect(),
+            exclude_dirs: exclude_dirs.into_iter().map(PathBuf::from).collect(),
+        }),
+        is_proc_macro: target.is_proc_macro,
+    }
+}
+
+fn add_dep(crates: &mut [CrateData], from: usize, name: CrateName, to: usize) {
+    crates[from].deps.push(DepData { krate: to, name });
 }
@@ -1,7 +1,7 @@
 //! Parsing of CfgFlags as command line arguments, as in
 //!
 //! rustc main.rs --cfg foo --cfg 'feature="bar"'
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use cfg::CfgOptions;
 
@@ -29,6 +29,24 @@ impl FromStr for CfgFlag {
     }
 }
 
+impl fmt::Display for CfgFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfgFlag::Atom(atom) => f.write_str(atom),
+            CfgFlag::KeyValue { key, value } => write!(f, "{}=\"{}\"", key, value),
+        }
+    }
+}
+
+impl serde::Serialize for CfgFlag {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for CfgFlag {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
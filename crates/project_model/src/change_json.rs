@@ -1,18 +1,25 @@
+//! A serializable snapshot of a `Change`: the crate graph, the source roots
+//! and the file contents, keyed by `FileId`.
+
 use std::sync::Arc;
 
-use base_db::{Change, FileId, FileSet, SourceRoot, VfsPath};
+use base_db::{Change, FileId, FileSet, ProcMacro, SourceRoot, VfsPath};
+use paths::AbsPath;
 
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 
-use crate::CrateGraphJson;
+use crate::{
+    json_format::{FormatError, JsonFormat, ValidationError, VersionedJson},
+    CrateGraphJson,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct ChangeJson {
-    crate_graph: CrateGraphJson,
-    local_roots: SourceRootJson,
-    library_roots: SourceRootJson,
-    files: FxHashMap<u32, Option<String>>,
+    pub(crate) crate_graph: CrateGraphJson,
+    pub(crate) local_roots: SourceRootJson,
+    pub(crate) library_roots: SourceRootJson,
+    pub(crate) files: FxHashMap<u32, Option<String>>,
 }
 
 impl ChangeJson {
@@ -32,13 +39,15 @@ impl ChangeJson {
         self.crate_graph = crate_graph;
     }
 
-    pub fn to_change(&self) -> Change {
-        let crate_graph = self.crate_graph.to_crate_graph().clone();
+    pub fn to_change(
+        &self,
+        load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
+    ) -> Result<Change, FormatError> {
+        self.validate()?;
+        let crate_graph = self.crate_graph.to_crate_graph(load_proc_macro)?;
         let mut change = Change::default();
         change.set_crate_graph(crate_graph);
-        let mut roots = self.local_roots.to_roots(false);
-        roots.append(&mut self.library_roots.to_roots(true));
-        change.set_roots(roots);
+        change.set_roots(self.roots());
         self.files.iter().for_each(|(id, text)| {
             let id = FileId(*id);
             let text = match text {
@@ -47,13 +56,202 @@ impl ChangeJson {
             };
             change.change_file(id, text)
         });
-        change
+        Ok(change)
+    }
+
+    /// Applies `delta` to this snapshot and returns the `Change` which brings a
+    /// database loaded from the old snapshot up to date.
+    ///
+    /// Only what the delta touches ends up in the `Change`: the roots are only
+    /// set if files were added or removed, and the crate graph only if the
+    /// delta carries a new one, so that salsa can reuse as much as possible.
+    ///
+    /// An invalid delta is rejected as a whole, leaving the snapshot as is.
+    pub fn apply_delta(
+        &mut self,
+        delta: ChangeJsonDelta,
+        load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
+    ) -> Result<Change, FormatError> {
+        let mut errors = Vec::new();
+        let mut added = FxHashSet::default();
+        let (mut n_local_roots, mut n_library_roots) =
+            (self.local_roots.roots.len(), self.library_roots.roots.len());
+        for file in delta.added_files.iter() {
+            if !added.insert(file.file_id) {
+                errors.push(ValidationError::DuplicateFileId { file_id: file.file_id });
+            }
+            let n_roots = if file.library { &mut n_library_roots } else { &mut n_local_roots };
+            if file.root > *n_roots {
+                errors.push(ValidationError::BadSourceRoot {
+                    file_id: file.file_id,
+                    root: file.root,
+                });
+            } else if file.root == *n_roots {
+                *n_roots += 1;
+            }
+        }
+        let removed = delta.removed_files.iter().copied().collect::<FxHashSet<_>>();
+        let in_roots: FxHashSet<u32> =
+            self.root_file_ids().filter(|it| !removed.contains(it)).collect();
+        let crate_graph = delta.crate_graph.as_ref().unwrap_or(&self.crate_graph);
+        crate_graph.check_root_files(&(&in_roots | &added), &mut errors);
+        FormatError::from_errors(errors)?;
+
+        let mut change = Change::default();
+        if let Some(crate_graph) = delta.crate_graph {
+            change.set_crate_graph(crate_graph.to_crate_graph(load_proc_macro)?);
+            self.crate_graph = crate_graph;
+        }
+
+        let roots_changed = !delta.removed_files.is_empty() || !delta.added_files.is_empty();
+        for file_id in delta.removed_files {
+            let removed =
+                self.local_roots.remove_file(file_id) | self.library_roots.remove_file(file_id);
+            self.files.remove(&file_id);
+            if removed && !delta.files.contains_key(&file_id) {
+                // Salsa can't forget a file, so we reset its text, like the
+                // LSP path does for deleted files.
+                change.change_file(FileId(file_id), None);
+            }
+        }
+        for added in delta.added_files {
+            self.local_roots.remove_file(added.file_id);
+            self.library_roots.remove_file(added.file_id);
+            let roots = if added.library { &mut self.library_roots } else { &mut self.local_roots };
+            roots.add_file(added.root, added.file_id, added.path);
+        }
+        if roots_changed {
+            change.set_roots(self.roots());
+        }
+
+        let mut files = delta.files.into_iter().collect::<Vec<_>>();
+        files.sort_by_key(|(file_id, _)| *file_id);
+        for (file_id, text) in files {
+            change.change_file(FileId(file_id), text.clone().map(Arc::new));
+            self.files.insert(file_id, text);
+        }
+        Ok(change)
+    }
+
+    fn root_file_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.local_roots
+            .roots
+            .iter()
+            .chain(self.library_roots.roots.iter())
+            .flatten()
+            .map(|(file_id, _)| *file_id)
+    }
+
+    fn roots(&self) -> Vec<SourceRoot> {
+        let mut roots = self.local_roots.to_roots(false);
+        roots.append(&mut self.library_roots.to_roots(true));
+        roots
+    }
+
+    /// Whether `file_id` can be queried once this snapshot is loaded, that is
+    /// whether it belongs to a source root and has a text.
+    pub fn contains_file(&self, file_id: FileId) -> bool {
+        self.files.contains_key(&file_id.0) && self.root_file_ids().any(|id| id == file_id.0)
+    }
+
+    /// Returns the path and text of every file in the source roots, ordered
+    /// by `FileId`.
+    pub fn files(&self) -> Vec<(FileId, VfsPath, Option<&str>)> {
+        let mut files = self
+            .local_roots
+            .roots
+            .iter()
+            .chain(self.library_roots.roots.iter())
+            .flatten()
+            .filter_map(|(file_id, path)| {
+                let path = VfsPath::new_virtual_path(path.clone()?);
+                let text = self.files.get(file_id).and_then(|text| text.as_deref());
+                Some((FileId(*file_id), path, text))
+            })
+            .collect::<Vec<_>>();
+        files.sort_by_key(|(file_id, ..)| *file_id);
+        files
+    }
+}
+
+impl VersionedJson for ChangeJson {
+    const FORMAT: JsonFormat = JsonFormat::Change;
+
+    fn validate(&self) -> Result<(), FormatError> {
+        let mut errors = Vec::new();
+        self.crate_graph.check(&mut errors);
+        let mut seen = FxHashSet::default();
+        let mut duplicates = FxHashSet::default();
+        for file_id in self.root_file_ids() {
+            if !seen.insert(file_id) && duplicates.insert(file_id) {
+                errors.push(ValidationError::DuplicateFileId { file_id });
+            }
+        }
+        self.crate_graph.check_root_files(&seen, &mut errors);
+        FormatError::from_errors(errors)
     }
 }
 
+/// An incremental update to a [`ChangeJson`], small enough to be sent with
+/// every edit.
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
-struct SourceRootJson {
-    roots: Vec<Vec<(u32, Option<String>)>>,
+pub struct ChangeJsonDelta {
+    /// The new crate graph, `None` if the crate graph is unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crate_graph: Option<CrateGraphJson>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    added_files: Vec<AddedFileJson>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed_files: Vec<u32>,
+    #[serde(default, skip_serializing_if = "FxHashMap::is_empty")]
+    files: FxHashMap<u32, Option<String>>,
+}
+
+/// A file added to the `root`th local or library source root. The index one
+/// past the last root creates a new root, larger ones are rejected.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AddedFileJson {
+    library: bool,
+    root: usize,
+    file_id: u32,
+    path: String,
+}
+
+impl ChangeJsonDelta {
+    pub fn change_file(&mut self, file_id: FileId, new_text: Option<Arc<String>>) {
+        self.files.insert(file_id.0, new_text.map(|text| text.to_string()));
+    }
+    /// Adds `file_id` to a source root, moving it out of the root it was in.
+    pub fn add_file(&mut self, library: bool, root: usize, file_id: FileId, path: &VfsPath) {
+        self.removed_files.retain(|&it| it != file_id.0);
+        self.added_files.retain(|it| it.file_id != file_id.0);
+        self.added_files.push(AddedFileJson {
+            library,
+            root,
+            file_id: file_id.0,
+            path: path.to_string(),
+        });
+    }
+    /// Removes `file_id` from its source root.
+    pub fn remove_file(&mut self, file_id: FileId) {
+        self.added_files.retain(|it| it.file_id != file_id.0);
+        self.files.remove(&file_id.0);
+        self.removed_files.push(file_id.0);
+    }
+    pub fn set_crate_graph(&mut self, crate_graph: CrateGraphJson) {
+        self.crate_graph = Some(crate_graph);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.crate_graph.is_none()
+            && self.added_files.is_empty()
+            && self.removed_files.is_empty()
+            && self.files.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub(crate) struct SourceRootJson {
+    pub(crate) roots: Vec<Vec<(u32, Option<String>)>>,
 }
 
 impl SourceRootJson {
@@ -77,6 +275,21 @@ impl SourceRootJson {
             .collect::<Vec<Vec<(u32, Option<String>)>>>();
         SourceRootJson { roots }
     }
+    fn add_file(&mut self, root: usize, file_id: u32, path: String) {
+        if self.roots.len() <= root {
+            self.roots.resize_with(root + 1, Vec::new);
+        }
+        self.roots[root].push((file_id, Some(path)));
+    }
+    fn remove_file(&mut self, file_id: u32) -> bool {
+        let mut removed = false;
+        for root in self.roots.iter_mut() {
+            let len = root.len();
+            root.retain(|(it, _)| *it != file_id);
+            removed |= root.len() != len;
+        }
+        removed
+    }
     pub fn to_roots(&self, library: bool) -> Vec<SourceRoot> {
         let result = self
             .roots
@@ -1,40 +1,85 @@
-use std::{collections::VecDeque, fmt, str::FromStr};
+//! A serializable form of the `CrateGraph`.
 
-use base_db::{CrateDisplayName, CrateGraph, CrateId, CrateName, Edition, Env, FileId, ProcMacro};
-use cfg::{CfgDiff, CfgOptions};
+use std::{collections::VecDeque, fmt};
+
+use base_db::{
+    CrateDisplayName, CrateGraph, CrateId, CrateName, Edition, Env, FileId, ProcMacro,
+    ProcMacroKind,
+};
+use cfg::{CfgAtom, CfgDiff, CfgExpr, CfgOptions};
 use paths::{AbsPath, AbsPathBuf};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    build_scripts::BuildScriptOutput, cargo_workspace::DepKind, cfg_flag::CfgFlag,
-    sysroot::SysrootCrate, CargoWorkspace, PackageData, Sysroot, TargetKind, WorkspaceBuildScripts,
+    build_scripts::BuildScriptOutput,
+    cargo_workspace::DepKind,
+    cfg_flag::CfgFlag,
+    json_format::{check_deps, FormatError, ValidationError},
+    sysroot::SysrootCrate,
+    CargoWorkspace, PackageData, Sysroot, TargetKind, WorkspaceBuildScripts,
 };
 
 pub type CfgOverrides = FxHashMap<String, CfgDiff>;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CrateRoot {
-    file_id: u32,
+    pub(crate) file_id: u32,
+    #[serde(flatten)]
+    pub(crate) data: CrateRootData,
+}
+
+/// Everything about a crate except its root file and its dependencies.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CrateRootData {
     edition: String,
     display_name: Option<String>,
     cfg_options: Vec<(String, Vec<String>)>,
     potential_cfg_options: Vec<(String, Vec<String>)>,
     env: Vec<(String, String)>,
-    proc_macro: Vec<AbsPathBuf>,
+    proc_macro_dylib_path: Option<AbsPathBuf>,
+    proc_macro: Vec<ProcMacroJson>,
+}
+
+/// A proc macro exported by the dylib at `CrateRootData::proc_macro_dylib_path`.
+///
+/// The expander itself can't be serialized, so on load the dylib is loaded
+/// again and the macros are matched up by name and kind.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProcMacroJson {
+    name: String,
+    kind: ProcMacroKindJson,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum ProcMacroKindJson {
+    CustomDerive,
+    FuncLike,
+    Attr,
+}
+
+impl From<ProcMacroKind> for ProcMacroKindJson {
+    fn from(kind: ProcMacroKind) -> Self {
+        match kind {
+            ProcMacroKind::CustomDerive => ProcMacroKindJson::CustomDerive,
+            ProcMacroKind::FuncLike => ProcMacroKindJson::FuncLike,
+            ProcMacroKind::Attr => ProcMacroKindJson::Attr,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Dep {
-    from: u32,
-    name: String,
-    to: u32,
+    pub(crate) from: u32,
+    pub(crate) name: String,
+    pub(crate) to: u32,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct CrateGraphJson {
-    roots: Vec<CrateRoot>,
-    deps: Vec<Dep>,
+    pub(crate) roots: Vec<CrateRoot>,
+    pub(crate) deps: Vec<Dep>,
 }
 
 impl Default for CrateGraphJson {
@@ -53,11 +98,11 @@ impl fmt::Debug for CrateGraphJson {
 }
 
 impl CrateGraphJson {
-    fn add_dep(&mut self, dep: Dep) {
+    pub(crate) fn add_dep(&mut self, dep: Dep) {
         self.deps.push(dep);
     }
 
-    fn add_crate_root(
+    pub(crate) fn add_crate_root(
         &mut self,
         file_id: FileId,
         edition: Edition,
@@ -65,98 +110,162 @@ impl CrateGraphJson {
         cfg_options: CfgOptions,
         potential_cfg_options: CfgOptions,
         env: Env,
-        _proc_macro: Vec<ProcMacro>,
+        proc_macro: Option<(AbsPathBuf, Vec<ProcMacro>)>,
     ) -> u32 {
-        let env = env
+        let mut env = env
             .iter()
             .map(|(a, b)| (String::from(a), String::from(b)))
             .collect::<Vec<(String, String)>>();
-        let display_name = match display_name {
-            Some(name) => Some(name.to_string()),
-            None => None,
+        env.sort();
+        let display_name = display_name.map(|name| name.canonical_name().to_string());
+        let cfg_options = cfg_options_to_json(&cfg_options);
+        let potential_cfg_options = cfg_options_to_json(&potential_cfg_options);
+        let (proc_macro_dylib_path, proc_macro) = match proc_macro {
+            Some((dylib_path, proc_macro)) => {
+                let proc_macro = proc_macro
+                    .iter()
+                    .map(|it| ProcMacroJson { name: it.name.to_string(), kind: it.kind.into() })
+                    .collect();
+                (Some(dylib_path), proc_macro)
+            }
+            None => (None, Vec::new()),
         };
-        let cfg_options = cfg_options
-            .get_cfg_keys()
-            .iter()
-            .map(|key| {
-                (
-                    String::from(key.as_str()),
-                    cfg_options
-                        .get_cfg_values(key)
-                        .iter()
-                        .map(|val| String::from(val.as_str()))
-                        .collect::<Vec<_>>(),
-                )
-            })
-            .collect::<Vec<_>>();
-        let potential_cfg_options = potential_cfg_options
-            .get_cfg_keys()
-            .iter()
-            .map(|key| {
-                (
-                    String::from(key.as_str()),
-                    potential_cfg_options
-                        .get_cfg_values(key)
-                        .iter()
-                        .map(|val| String::from(val.as_str()))
-                        .collect::<Vec<_>>(),
-                )
-            })
-            .collect::<Vec<_>>();
         self.roots.push(CrateRoot {
             file_id: file_id.0,
-            edition: edition.to_string(),
-            display_name,
-            cfg_options,
-            potential_cfg_options,
-            env,
-            proc_macro: Vec::new(),
+            data: CrateRootData {
+                edition: edition.to_string(),
+                display_name,
+                cfg_options,
+                potential_cfg_options,
+                env,
+                proc_macro_dylib_path,
+                proc_macro,
+            },
         });
         self.roots.len() as u32 - 1
     }
 
-    fn contains_dep(&self, from: u32, name: String) -> bool {
+    pub(crate) fn contains_dep(&self, from: u32, name: String) -> bool {
         self.deps.iter().any(|dep| dep.from == from && dep.name == name)
     }
 
-    pub fn to_crate_graph(&self) -> CrateGraph {
+    /// Checks that editions and dependency names parse, and that dependencies
+    /// point to existing crates without forming cycles.
+    pub fn validate(&self) -> Result<(), FormatError> {
+        let mut errors = Vec::new();
+        self.check(&mut errors);
+        FormatError::from_errors(errors)
+    }
+
+    pub(crate) fn check(&self, errors: &mut Vec<ValidationError>) {
+        for (krate, root) in self.roots.iter().enumerate() {
+            if let Err(err) = root.data.edition(krate as u32) {
+                errors.push(err);
+            }
+        }
+        for dep in self.deps.iter() {
+            if CrateName::new(&dep.name).is_err() {
+                errors.push(ValidationError::BadDepName { from: dep.from, name: dep.name.clone() });
+            }
+        }
+        check_deps(self.roots.len(), self.deps.iter().map(|dep| (dep.from, dep.to)), errors);
+    }
+
+    /// Reports the crates whose root file is not in `root_files`.
+    pub(crate) fn check_root_files(
+        &self,
+        root_files: &FxHashSet<u32>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (krate, root) in self.roots.iter().enumerate() {
+            if !root_files.contains(&root.file_id) {
+                errors.push(ValidationError::MissingCrateRoot {
+                    krate: krate as u32,
+                    file_id: root.file_id,
+                });
+            }
+        }
+    }
+
+    /// Drops the dependencies which would close a cycle, like
+    /// `CrateGraph::add_dep` would, so that the graph passes validation.
+    pub(crate) fn remove_cyclic_deps(&mut self) {
+        let mut adjacency: Vec<Vec<u32>> = vec![Vec::new(); self.roots.len()];
+        let roots = &self.roots;
+        self.deps.retain(|dep| {
+            if reaches(&adjacency, dep.to, dep.from) {
+                let name = |krate: u32| roots[krate as usize].data.display_name.clone();
+                log::error!(
+                    "cyclic deps: dropping the dependency of {:?}({}) on {:?}({})",
+                    name(dep.from),
+                    dep.from,
+                    name(dep.to),
+                    dep.to
+                );
+                return false;
+            }
+            adjacency[dep.from as usize].push(dep.to);
+            true
+        });
+    }
+
+    pub fn to_crate_graph(
+        &self,
+        load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
+    ) -> Result<CrateGraph, FormatError> {
+        self.validate()?;
         let mut crate_graph = CrateGraph::default();
-        self.roots.iter().enumerate().for_each(|(id, root)| {
-            let file_id = FileId(root.file_id);
-            let edition = root.edition.parse::<Edition>().unwrap_or_else(|err| {
-                log::error!("Failed to parse edition {}", err);
-                Edition::CURRENT
-            });
-            let display_name = match &root.display_name {
-                Some(name) => Some(CrateDisplayName::from_canonical_name(name.to_string())),
-                None => None,
-            };
-            let cfg_options = parse_cfg_options(&root.cfg_options);
-            let potential_cfg_options = parse_cfg_options(&root.potential_cfg_options);
-            let mut env = Env::default();
-            root.env
-                .iter()
-                .map(|(key, value)| (key, value))
-                .for_each(|(a, b)| env.set(a, b.to_string()));
-            let crate_id = crate_graph.add_crate_root(
-                file_id,
-                edition,
-                display_name,
-                cfg_options,
-                potential_cfg_options,
-                env,
-                Vec::new(),
-            );
+        for (id, root) in self.roots.iter().enumerate() {
+            let crate_id = root
+                .data
+                .add_to(&mut crate_graph, id as u32, FileId(root.file_id), load_proc_macro)
+                .map_err(|err| FormatError::Invalid(vec![err]))?;
             assert_eq!(id as u32, crate_id.0, "Id from CrateGraph should match denpendency Id's!");
-        });
-        self.deps.iter().for_each(|dep| {
-            let from = CrateId(dep.from);
-            let to = CrateId(dep.to);
-            if let Ok(name) = CrateName::new(&dep.name) {
-                let _ = crate_graph.add_dep(from, name, to);
-            };
-        });
-        crate_graph
+        }
+        for dep in self.deps.iter() {
+            let name = CrateName::new(&dep.name).expect("dependency names are validated");
+            crate_graph
+                .add_dep(CrateId(dep.from), name, CrateId(dep.to))
+                .expect("dependency cycles are validated");
+        }
+        if crate_graph.patch_cfg_if() {
+            log::debug!("Patched std to depend on cfg-if")
+        } else {
+            log::debug!("Did not patch std to depend on cfg-if")
+        }
+        Ok(crate_graph)
+    }
+
+    /// The serialized form of `crate_graph`, whose crates are numbered from
+    /// zero as `CrateGraph::add_crate_root` does. The proc macro crates are
+    /// looked up in `proc_macro_dylib_paths` by their root file.
+    pub(crate) fn from_crate_graph(
+        crate_graph: &CrateGraph,
+        proc_macro_dylib_paths: &FxHashMap<FileId, AbsPathBuf>,
+    ) -> CrateGraphJson {
+        let mut crate_graph_json = CrateGraphJson::default();
+        let mut crates = crate_graph.iter().collect::<Vec<_>>();
+        crates.sort();
+        for krate in crates {
+            let data = &crate_graph[krate];
+            let proc_macro = proc_macro_dylib_paths
+                .get(&data.root_file_id)
+                .map(|path| (path.clone(), data.proc_macro.clone()));
+            let json_id = crate_graph_json.add_crate_root(
+                data.root_file_id,
+                data.edition,
+                data.display_name.clone(),
+                data.cfg_options.clone(),
+                data.potential_cfg_options.clone(),
+                data.env.clone(),
+                proc_macro,
+            );
+            assert_eq!(json_id, krate.0, "crates should be numbered from zero");
+            for dep in data.dependencies.iter() {
+                add_dep(&mut crate_graph_json, krate, dep.name.clone(), dep.crate_id);
+            }
+        }
+        crate_graph_json
     }
 
     pub fn cargo_to_json(
@@ -301,14 +410,121 @@ impl CrateGraphJson {
     }
 }
 
-fn parse_cfg_options(options: &Vec<(String, Vec<String>)>) -> CfgOptions {
+impl CrateRootData {
+    pub(crate) fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
+    /// The enabled cfgs, as they would be written in a `cfg` attribute.
+    pub(crate) fn cfgs(&self) -> impl Iterator<Item = String> + '_ {
+        self.cfg_options.iter().flat_map(|(key, values)| {
+            let atom = if values.is_empty() { Some(key.clone()) } else { None };
+            atom.into_iter().chain(values.iter().map(move |value| format!("{}={:?}", key, value)))
+        })
+    }
+
+    /// `krate` is the index of the crate, for the error.
+    pub(crate) fn edition(&self, krate: u32) -> Result<Edition, ValidationError> {
+        self.edition
+            .parse()
+            .map_err(|_| ValidationError::BadEdition { krate, edition: self.edition.clone() })
+    }
+
+    pub(crate) fn add_to(
+        &self,
+        crate_graph: &mut CrateGraph,
+        krate: u32,
+        file_id: FileId,
+        load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
+    ) -> Result<CrateId, ValidationError> {
+        let edition = self.edition(krate)?;
+        let display_name = self
+            .display_name
+            .as_ref()
+            .map(|name| CrateDisplayName::from_canonical_name(name.to_string()));
+        let cfg_options = parse_cfg_options(&self.cfg_options);
+        let potential_cfg_options = parse_cfg_options(&self.potential_cfg_options);
+        let mut env = Env::default();
+        self.env.iter().for_each(|(key, value)| env.set(key, value.to_string()));
+        let proc_macro = self.load_proc_macros(load_proc_macro);
+        Ok(crate_graph.add_crate_root(
+            file_id,
+            edition,
+            display_name,
+            cfg_options,
+            potential_cfg_options,
+            env,
+            proc_macro,
+        ))
+    }
+
+    fn load_proc_macros(
+        &self,
+        load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
+    ) -> Vec<ProcMacro> {
+        let dylib_path = match &self.proc_macro_dylib_path {
+            Some(it) => it,
+            None => return Vec::new(),
+        };
+        let loaded = load_proc_macro(dylib_path);
+        if self.proc_macro.is_empty() {
+            // The graph was exported without a proc macro server, so we don't
+            // know which macros to expect. Take whatever the dylib provides.
+            return loaded;
+        }
+        self.proc_macro
+            .iter()
+            .filter_map(|expected| {
+                let res = loaded
+                    .iter()
+                    .find(|it| it.name == expected.name && expected.kind == it.kind.into())
+                    .cloned();
+                if res.is_none() {
+                    log::error!(
+                        "proc macro `{}` not found in {}",
+                        expected.name,
+                        dylib_path.display()
+                    );
+                }
+                res
+            })
+            .collect()
+    }
+}
+
+/// Atoms like `test` are stored as a key without values, key-value options as
+/// a key with all its values, e.g. `("feature", ["default", "std"])`.
+pub(crate) fn cfg_options_to_json(cfg_options: &CfgOptions) -> Vec<(String, Vec<String>)> {
+    let mut keys = cfg_options.get_cfg_keys();
+    keys.sort();
+    keys.dedup();
+    let mut res = Vec::new();
+    for key in keys {
+        if cfg_options.check(&CfgExpr::Atom(CfgAtom::Flag(key.clone()))) == Some(true) {
+            res.push((key.to_string(), Vec::new()));
+        }
+        let mut values = cfg_options
+            .get_cfg_values(key)
+            .into_iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        if !values.is_empty() {
+            values.sort();
+            res.push((key.to_string(), values));
+        }
+    }
+    res
+}
+
+fn parse_cfg_options(options: &[(String, Vec<String>)]) -> CfgOptions {
     let mut cfg_options = CfgOptions::default();
     options.iter().for_each(|(key, values)| {
-        let options = values.iter().map(|value| {
-            let str = format!("{}=\"{}\"", key.as_str(), value.as_str());
-            CfgFlag::from_str(&str).unwrap()
-        });
-        cfg_options.extend(options);
+        if values.is_empty() {
+            cfg_options.insert_atom(key.into());
+        }
+        for value in values {
+            cfg_options.insert_key_value(key.into(), value.into());
+        }
     });
     cfg_options
 }
@@ -435,11 +651,10 @@ fn add_target_crate_root(
         }
     }
 
-    let proc_macro = build_data
-        .as_ref()
-        .and_then(|it| it.proc_macro_dylib_path.as_ref())
-        .map(|it| load_proc_macro(it))
-        .unwrap_or_default();
+    let proc_macro = build_data.and_then(|it| it.proc_macro_dylib_path.clone()).map(|it| {
+        let proc_macro = load_proc_macro(&it);
+        (it, proc_macro)
+    });
 
     let display_name = CrateDisplayName::from_canonical_name(cargo_name.to_string());
     let mut potential_cfg_options = cfg_options.clone();
@@ -477,7 +692,6 @@ fn sysroot_to_crate_graph(
             let file_id = load(&sysroot[krate].root)?;
 
             let env = Env::default();
-            let proc_macro = vec![];
             let display_name = CrateDisplayName::from_canonical_name(sysroot[krate].name.clone());
             let crate_id = crate_graph_json.add_crate_root(
                 file_id,
@@ -486,7 +700,7 @@ fn sysroot_to_crate_graph(
                 cfg_options.clone(),
                 cfg_options.clone(),
                 env,
-                proc_macro,
+                None,
             );
             Some((krate, CrateId(crate_id)))
         })
@@ -510,6 +724,21 @@ fn sysroot_to_crate_graph(
     (public_deps, libproc_macro)
 }
 
+/// Whether `to` can be reached from `from`, following the edges in `adjacency`.
+fn reaches(adjacency: &[Vec<u32>], from: u32, to: u32) -> bool {
+    let mut visited = FxHashSet::default();
+    let mut stack = vec![from];
+    while let Some(krate) = stack.pop() {
+        if krate == to {
+            return true;
+        }
+        if visited.insert(krate) {
+            stack.extend(adjacency[krate as usize].iter().copied());
+        }
+    }
+    false
+}
+
 fn add_dep(graph: &mut CrateGraphJson, from: CrateId, name: CrateName, to: CrateId) {
     graph.add_dep(Dep { from: from.0, name: name.to_string(), to: to.0 });
 }
@@ -0,0 +1,579 @@
+//! Versioning, validation and the JSON schema of the serialized project
+//! formats.
+//!
+//! Snapshots are written inside an envelope which names their format and its
+//! version, so that a reader rejects data it doesn't understand up front
+//! instead of misreading it. After parsing, the data is checked as a whole:
+//! a snapshot with a bad edition or a dangling dependency is rejected with a
+//! list of [`ValidationError`]s, rather than loaded with the bad bits
+//! replaced or dropped.
+
+use std::{error::Error, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Bumped whenever one of the formats in an envelope changes.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// The formats which are written inside an envelope.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonFormat {
+    /// A [`crate::ChangeJson`].
+    Change,
+    /// A [`crate::PathChangeJson`].
+    PathChange,
+    /// A [`crate::SysrootBundle`].
+    SysrootBundle,
+}
+
+impl JsonFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            JsonFormat::Change => "change",
+            JsonFormat::PathChange => "path_change",
+            JsonFormat::SysrootBundle => "sysroot_bundle",
+        }
+    }
+}
+
+impl fmt::Display for JsonFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A format which is serialized as `{ "format": .., "version": .., "data": .. }`.
+pub trait VersionedJson: Serialize + DeserializeOwned {
+    const FORMAT: JsonFormat;
+
+    /// Checks the invariants serde can't check, like dependencies pointing to
+    /// existing crates.
+    fn validate(&self) -> Result<(), FormatError>;
+
+    fn to_versioned_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&Envelope {
+            format: Self::FORMAT,
+            version: JSON_FORMAT_VERSION,
+            data: self,
+        })
+    }
+
+    fn from_versioned_json(json: &str) -> Result<Self, FormatError> {
+        // Check the envelope first, data of another version would likely fail
+        // to parse with a less helpful error.
+        #[derive(Deserialize)]
+        struct Header {
+            format: Option<String>,
+            version: Option<u32>,
+        }
+        let header: Header = serde_json::from_str(json)?;
+        check_header::<Self>(header.format.as_deref(), header.version)?;
+
+        #[derive(Deserialize)]
+        #[serde(bound = "T: DeserializeOwned")]
+        struct Data<T> {
+            data: T,
+        }
+        let Data { data } = serde_json::from_str::<Data<Self>>(json)?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    fn from_versioned_value(mut value: Value) -> Result<Self, FormatError> {
+        let version = value.get("version").and_then(Value::as_u64).map(|it| it as u32);
+        check_header::<Self>(value.get("format").and_then(Value::as_str), version)?;
+        let data = serde_json::from_value::<Self>(value["data"].take())?;
+        data.validate()?;
+        Ok(data)
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    format: JsonFormat,
+    version: u32,
+    data: &'a T,
+}
+
+fn check_header<T: VersionedJson>(
+    format: Option<&str>,
+    version: Option<u32>,
+) -> Result<(), FormatError> {
+    let (format, version) = match (format, version) {
+        (Some(format), Some(version)) => (format, version),
+        _ => return Err(FormatError::MissingEnvelope),
+    };
+    if format != T::FORMAT.as_str() {
+        return Err(FormatError::WrongFormat { expected: T::FORMAT, found: format.to_string() });
+    }
+    if version != JSON_FORMAT_VERSION {
+        return Err(FormatError::UnsupportedVersion {
+            found: version,
+            supported: JSON_FORMAT_VERSION,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Json(serde_json::Error),
+    /// The data has no `format` and `version`, it was likely written before
+    /// snapshots were versioned.
+    MissingEnvelope,
+    WrongFormat {
+        expected: JsonFormat,
+        found: String,
+    },
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+    Invalid(Vec<ValidationError>),
+}
+
+impl FormatError {
+    pub(crate) fn from_errors(errors: Vec<ValidationError>) -> Result<(), FormatError> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(FormatError::Invalid(errors))
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Json(err) => write!(f, "{}", err),
+            FormatError::MissingEnvelope => {
+                f.write_str("missing `format` and `version`, the snapshot may be too old")
+            }
+            FormatError::WrongFormat { expected, found } => {
+                write!(f, "expected format `{}`, found `{}`", expected, found)
+            }
+            FormatError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "snapshot has version {}, but this rust-analyzer reads version {}",
+                found, supported
+            ),
+            FormatError::Invalid(errors) => {
+                for (idx, err) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("\n")?;
+                    }
+                    write!(f, "{}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FormatError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for FormatError {
+    fn from(err: serde_json::Error) -> FormatError {
+        FormatError::Json(err)
+    }
+}
+
+/// Crates are identified by their index, in the order they are listed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    BadEdition {
+        krate: u32,
+        edition: String,
+    },
+    BadDepName {
+        from: u32,
+        name: String,
+    },
+    /// `from` or `to` is not the index of a crate.
+    DanglingDep {
+        from: u32,
+        to: u32,
+    },
+    /// A dependency of a `PathChangeJson` or `SysrootBundle` crate on a key or
+    /// name which is not a crate.
+    UnknownCrate {
+        from: u32,
+        key: String,
+    },
+    DuplicateFileId {
+        file_id: u32,
+    },
+    /// The root file of a crate is in none of the source roots.
+    MissingCrateRoot {
+        krate: u32,
+        file_id: u32,
+    },
+    /// A file added to a source root more than one past the last one.
+    BadSourceRoot {
+        file_id: u32,
+        root: usize,
+    },
+    /// Starts and ends with the same crate.
+    CyclicDeps {
+        path: Vec<u32>,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::BadEdition { krate, edition } => {
+                write!(f, "crate {} has an invalid edition {:?}", krate, edition)
+            }
+            ValidationError::BadDepName { from, name } => {
+                write!(f, "crate {} has a dependency with an invalid name {:?}", from, name)
+            }
+            ValidationError::DanglingDep { from, to } => {
+                write!(f, "dependency of crate {} on crate {} refers to a missing crate", from, to)
+            }
+            ValidationError::UnknownCrate { from, key } => {
+                write!(f, "crate {} depends on an unknown crate {:?}", from, key)
+            }
+            ValidationError::DuplicateFileId { file_id } => {
+                write!(f, "file {} is listed more than once in the source roots", file_id)
+            }
+            ValidationError::MissingCrateRoot { krate, file_id } => {
+                write!(f, "the root file {} of crate {} is not in a source root", file_id, krate)
+            }
+            ValidationError::BadSourceRoot { file_id, root } => {
+                write!(f, "file {} is added to source root {}, which doesn't exist", file_id, root)
+            }
+            ValidationError::CyclicDeps { path } => {
+                let path = path.iter().map(|it| it.to_string()).collect::<Vec<_>>().join(" -> ");
+                write!(f, "cyclic dependencies: {}", path)
+            }
+        }
+    }
+}
+
+/// Reports dependencies on missing crates, and the first dependency cycle
+/// found among the others.
+pub(crate) fn check_deps(
+    n_crates: usize,
+    deps: impl Iterator<Item = (u32, u32)>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut adjacency = vec![Vec::new(); n_crates];
+    for (from, to) in deps {
+        if from as usize >= n_crates || to as usize >= n_crates {
+            errors.push(ValidationError::DanglingDep { from, to });
+            continue;
+        }
+        adjacency[from as usize].push(to);
+    }
+    if let Some(path) = find_cycle(&adjacency) {
+        errors.push(ValidationError::CyclicDeps { path });
+    }
+}
+
+fn find_cycle(adjacency: &[Vec<u32>]) -> Option<Vec<u32>> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        New,
+        OnStack,
+        Done,
+    }
+    let mut state = vec![State::New; adjacency.len()];
+    for start in 0..adjacency.len() {
+        if state[start] != State::New {
+            continue;
+        }
+        state[start] = State::OnStack;
+        // Crates on the current path, with the index of their next dependency.
+        let mut stack = vec![(start as u32, 0)];
+        while let Some(&(krate, next_dep)) = stack.last() {
+            let dep = match adjacency[krate as usize].get(next_dep) {
+                Some(&dep) => dep,
+                None => {
+                    state[krate as usize] = State::Done;
+                    stack.pop();
+                    continue;
+                }
+            };
+            stack.last_mut().unwrap().1 += 1;
+            match state[dep as usize] {
+                State::New => {
+                    state[dep as usize] = State::OnStack;
+                    stack.push((dep, 0));
+                }
+                State::OnStack => {
+                    let cycle_start = stack.iter().position(|&(it, _)| it == dep).unwrap();
+                    let mut path: Vec<u32> =
+                        stack[cycle_start..].iter().map(|&(it, _)| it).collect();
+                    path.push(dep);
+                    return Some(path);
+                }
+                State::Done => (),
+            }
+        }
+    }
+    None
+}
+
+/// The JSON schema of the envelope and of all the formats: the ones in an
+/// envelope, `ChangeJsonDelta` and `rust-project.json`.
+pub fn json_schema() -> Value {
+    let envelope = |format: JsonFormat, definition: &str| {
+        json!({
+            "type": "object",
+            "properties": {
+                "format": { "const": format.as_str() },
+                "version": { "const": JSON_FORMAT_VERSION },
+                "data": { "$ref": format!("#/definitions/{}", definition) },
+            },
+            "required": ["format", "version", "data"],
+            "additionalProperties": false,
+        })
+    };
+    let file_id = json!({ "type": "integer", "minimum": 0 });
+    let texts = json!({
+        "type": "object",
+        "additionalProperties": { "type": ["string", "null"] },
+    });
+    let path_crate = {
+        let mut properties = crate_root_data_properties();
+        properties.insert("root_file".into(), json!({ "type": "string" }));
+        properties.insert(
+            "deps".into(),
+            json!({
+                "description": "`[name, crate]` pairs, `crate` being a key of `crates`.",
+                "type": "array",
+                "items": pair(json!({ "type": "string" }), json!({ "type": "string" })),
+            }),
+        );
+        object(properties, &["root_file", "edition", "deps"])
+    };
+    let crate_root = {
+        let mut properties = crate_root_data_properties();
+        properties.insert("file_id".into(), file_id.clone());
+        object(properties, &["file_id", "edition"])
+    };
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "rust-analyzer project snapshot",
+        "oneOf": [
+            envelope(JsonFormat::Change, "ChangeJson"),
+            envelope(JsonFormat::PathChange, "PathChangeJson"),
+            envelope(JsonFormat::SysrootBundle, "SysrootBundle"),
+        ],
+        "definitions": {
+            "ChangeJson": object(
+                properties(json!({
+                    "crate_graph": { "$ref": "#/definitions/CrateGraphJson" },
+                    "local_roots": { "$ref": "#/definitions/SourceRootJson" },
+                    "library_roots": { "$ref": "#/definitions/SourceRootJson" },
+                    "files": texts.clone(),
+                })),
+                &["crate_graph", "local_roots", "library_roots", "files"],
+            ),
+            "ChangeJsonDelta": object(
+                properties(json!({
+                    "crate_graph": { "$ref": "#/definitions/CrateGraphJson" },
+                    "added_files": {
+                        "type": "array",
+                        "items": object(
+                            properties(json!({
+                                "library": { "type": "boolean" },
+                                "root": { "type": "integer", "minimum": 0 },
+                                "file_id": file_id.clone(),
+                                "path": { "type": "string" },
+                            })),
+                            &["library", "root", "file_id", "path"],
+                        ),
+                    },
+                    "removed_files": { "type": "array", "items": file_id.clone() },
+                    "files": texts.clone(),
+                })),
+                &[],
+            ),
+            "SourceRootJson": object(
+                properties(json!({
+                    "roots": {
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "items": pair(file_id.clone(), json!({ "type": ["string", "null"] })),
+                        },
+                    },
+                })),
+                &["roots"],
+            ),
+            "CrateGraphJson": object(
+                properties(json!({
+                    "roots": { "type": "array", "items": { "$ref": "#/definitions/CrateRoot" } },
+                    "deps": { "type": "array", "items": { "$ref": "#/definitions/Dep" } },
+                })),
+                &["roots", "deps"],
+            ),
+            "CrateRoot": crate_root,
+            "Dep": object(
+                properties(json!({
+                    "from": { "type": "integer", "minimum": 0 },
+                    "name": { "type": "string" },
+                    "to": { "type": "integer", "minimum": 0 },
+                })),
+                &["from", "name", "to"],
+            ),
+            "PathChangeJson": object(
+                properties(json!({
+                    "crates": { "type": "object", "additionalProperties": path_crate },
+                    "local_roots": path_roots(),
+                    "library_roots": path_roots(),
+                    "files": texts,
+                    "removed_crates": { "type": "array", "items": { "type": "string" } },
+                    "removed_files": { "type": "array", "items": { "type": "string" } },
+                })),
+                &["crates", "local_roots", "library_roots", "files"],
+            ),
+            "SysrootBundle": object(
+                properties(json!({
+                    "cfg": { "type": "array", "items": { "type": "string" } },
+                    "crates": {
+                        "type": "array",
+                        "items": object(
+                            properties(json!({
+                                "name": { "type": "string" },
+                                "root_file": { "type": "string" },
+                                "edition": { "$ref": "#/definitions/Edition" },
+                                "deps": { "type": "array", "items": { "type": "string" } },
+                            })),
+                            &["name", "root_file", "edition", "deps"],
+                        ),
+                    },
+                    "public_deps": { "type": "array", "items": { "type": "string" } },
+                    "files": { "type": "object", "additionalProperties": { "type": "string" } },
+                })),
+                &["cfg", "crates", "public_deps", "files"],
+            ),
+            "ProjectJsonData": project_json_data(),
+            "Edition": { "type": "string", "enum": ["2015", "2018", "2021"] },
+        },
+    })
+}
+
+fn crate_root_data_properties() -> Map<String, Value> {
+    let cfg_options = json!({
+        "description": "`[key, values]` pairs, atoms have no values.",
+        "type": "array",
+        "items": pair(
+            json!({ "type": "string" }),
+            json!({ "type": "array", "items": { "type": "string" } }),
+        ),
+    });
+    properties(json!({
+        "edition": { "$ref": "#/definitions/Edition" },
+        "display_name": { "type": ["string", "null"] },
+        "cfg_options": cfg_options.clone(),
+        "potential_cfg_options": cfg_options,
+        "env": {
+            "type": "array",
+            "items": pair(json!({ "type": "string" }), json!({ "type": "string" })),
+        },
+        "proc_macro_dylib_path": { "type": ["string", "null"] },
+        "proc_macro": {
+            "type": "array",
+            "items": object(
+                properties(json!({
+                    "name": { "type": "string" },
+                    "kind": { "type": "string", "enum": ["custom_derive", "func_like", "attr"] },
+                })),
+                &["name", "kind"],
+            ),
+        },
+    }))
+}
+
+fn project_json_data() -> Value {
+    let paths = json!({ "type": "array", "items": { "type": "string" } });
+    let krate = object(
+        properties(json!({
+            "display_name": { "type": ["string", "null"] },
+            "root_module": { "type": "string" },
+            "edition": { "$ref": "#/definitions/Edition" },
+            "deps": {
+                "type": "array",
+                "items": object(
+                    properties(json!({
+                        "crate": { "type": "integer", "minimum": 0 },
+                        "name": { "type": "string" },
+                    })),
+                    &["crate", "name"],
+                ),
+            },
+            "cfg": { "type": "array", "items": { "type": "string" } },
+            "target": { "type": ["string", "null"] },
+            "env": { "type": "object", "additionalProperties": { "type": "string" } },
+            "proc_macro_dylib_path": { "type": ["string", "null"] },
+            "is_workspace_member": { "type": ["boolean", "null"] },
+            "source": {
+                "oneOf": [
+                    object(
+                        properties(json!({
+                            "include_dirs": paths.clone(),
+                            "exclude_dirs": paths,
+                        })),
+                        &["include_dirs", "exclude_dirs"],
+                    ),
+                    { "type": "null" },
+                ],
+            },
+            "is_proc_macro": { "type": "boolean" },
+        })),
+        &["root_module", "edition", "deps"],
+    );
+    let mut res = object(
+        properties(json!({
+            "sysroot_src": { "type": ["string", "null"] },
+            "crates": { "type": "array", "items": krate },
+        })),
+        &["crates"],
+    );
+    // Unlike the snapshots, `rust-project.json` is written by other tools,
+    // which may add their own fields.
+    res["additionalProperties"] = true.into();
+    res["properties"]["crates"]["items"]["additionalProperties"] = true.into();
+    res["description"] = "The contents of `rust-project.json`.".into();
+    res
+}
+
+fn path_roots() -> Value {
+    json!({
+        "type": "array",
+        "items": { "type": "array", "items": { "type": "string" }, "uniqueItems": true },
+    })
+}
+
+fn pair(first: Value, second: Value) -> Value {
+    json!({ "type": "array", "items": [first, second], "minItems": 2, "maxItems": 2 })
+}
+
+fn properties(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(it) => it,
+        _ => unreachable!(),
+    }
+}
+
+fn object(properties: Map<String, Value>, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
@@ -24,8 +24,12 @@ mod workspace;
 mod rustc_cfg;
 mod build_scripts;
 mod meta_to_project_json;
+mod cargo_to_project_json;
 mod crate_graph_json;
 mod change_json;
+mod path_change_json;
+mod sysroot_bundle;
+mod json_format;
 
 #[cfg(test)]
 mod tests;
@@ -43,16 +47,22 @@ use rustc_hash::FxHashSet;
 
 pub use crate::{
     build_scripts::WorkspaceBuildScripts,
+    cargo_to_project_json::cargo_to_json,
     cargo_workspace::{
         CargoConfig, CargoWorkspace, Package, PackageData, PackageDependency, RustcSource, Target,
         TargetData, TargetKind,
     },
-    change_json::ChangeJson,
+    change_json::{ChangeJson, ChangeJsonDelta},
     crate_graph_json::CrateGraphJson,
+    json_format::{
+        json_schema, FormatError, JsonFormat, ValidationError, VersionedJson, JSON_FORMAT_VERSION,
+    },
     manifest_path::ManifestPath,
     meta_to_project_json::meta_to_json,
+    path_change_json::PathChangeJson,
     project_json::{ProjectJson, ProjectJsonData},
     sysroot::Sysroot,
+    sysroot_bundle::SysrootBundle,
     workspace::{CfgOverrides, PackageRoot, ProjectWorkspace},
 };
 
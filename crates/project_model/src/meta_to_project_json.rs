@@ -1,88 +1,87 @@
-use std::path::PathBuf;
+//! Converts `cargo metadata` output into `rust-project.json` data.
+//!
+//! Unlike `cargo_to_json`, this needs neither a toolchain nor the results of
+//! build scripts: only what `cargo metadata` knows ends up in the data, so the
+//! cfgs are limited to `debug_assertions`, `test` and the enabled features.
 
 use base_db::CrateName;
-use cargo_metadata::PackageId;
+use cfg::CfgOptions;
 use rustc_hash::FxHashMap;
 
 use crate::{
+    cargo_to_project_json::target_crate_data,
     cargo_workspace::DepKind,
-    project_json::{CrateData, DepData, EditionData, ProjectJsonData},
+    project_json::{DepData, ProjectJsonData},
+    CargoWorkspace, TargetKind, WorkspaceBuildScripts,
 };
 
-pub fn meta_to_json(mut meta: cargo_metadata::Metadata) -> ProjectJsonData {
-    let mut pkg_by_id: FxHashMap<PackageId, usize> = FxHashMap::default();
+pub fn meta_to_json(meta: cargo_metadata::Metadata) -> ProjectJsonData {
     let _p = profile::span("meta_to_json");
-    meta.packages.sort_by(|a, b| a.id.cmp(&b.id));
-    let mut crates: Vec<CrateData> = Vec::new();
-    for pkg in &mut meta.packages.into_iter() {
-        let root_id = crates.len();
-        pkg_by_id.insert(pkg.id, root_id);
-        for tgt in &mut pkg.targets.iter() {
-            let edition = tgt.edition.parse::<EditionData>().unwrap_or_else(|err| {
-                log::error!("Failed to parse edition {:?}", err);
-                EditionData::Edition2018
-            });
-            let target = tgt.kind.first();
-            let target = match target {
-                Some(target) => Some(String::from(target.as_str())),
-                None => None,
-            };
-            let data = CrateData {
-                display_name: Some(tgt.name.to_owned()),
-                root_module: PathBuf::from(&tgt.src_path),
-                edition,
-                deps: Vec::new(), // resolved further down
-                cfg: Vec::new(),
-                target,
-                env: FxHashMap::default(),
-                proc_macro_dylib_path: None,
-                is_workspace_member: None,
-                source: None,
-                is_proc_macro: false,
-            };
-            crates.push(data);
-            if crates.len() + 1 > root_id {
-                // Should not push root to its own deps!
-                let name = CrateName::normalize_dashes(&tgt.name);
-                let dep = DepData { krate: crates.len(), name };
-                crates[root_id].deps.push(dep);
+    let cargo = CargoWorkspace::new(meta);
+    let build_scripts = WorkspaceBuildScripts::default();
+    let mut crates = Vec::new();
+
+    let mut cfg_options = CfgOptions::default();
+    cfg_options.insert_atom("debug_assertions".into());
+    let mut test_cfg_options = cfg_options.clone();
+    test_cfg_options.insert_atom("test".into());
+
+    let mut pkg_to_lib_crate = FxHashMap::default();
+    let mut pkg_crates = FxHashMap::default();
+    for pkg in cargo.packages() {
+        let mut lib_tgt = None;
+        for &tgt in cargo[pkg].targets.iter() {
+            let kind = cargo[tgt].kind;
+            // Only tests and benches of members are compiled with `cfg(test)`,
+            // they also get the dev-dependencies, so that `#[cfg(test)]` code
+            // doesn't end up with unresolved imports. Build scripts and
+            // dependencies are never tested.
+            let has_test_cfg =
+                cargo[pkg].is_member && matches!(kind, TargetKind::Test | TargetKind::Bench);
+            let cfg_options = if has_test_cfg { &test_cfg_options } else { &cfg_options };
+            let crate_id = crates.len();
+            crates.push(target_crate_data(&cargo, pkg, tgt, &build_scripts, cfg_options));
+            if kind == TargetKind::Lib {
+                lib_tgt = Some((crate_id, cargo[tgt].name.clone()));
+                pkg_to_lib_crate.insert(pkg, crate_id);
             }
+            pkg_crates.entry(pkg).or_insert_with(Vec::new).push((crate_id, kind));
         }
-    }
-    let resolve = meta.resolve.expect("metadata executed with deps");
-    for mut node in resolve.nodes {
-        let source = match pkg_by_id.get(&node.id) {
-            Some(&src) => src,
-            // FIXME: replace this and a similar branch below with `.unwrap`, once
-            // https://github.com/rust-lang/cargo/issues/7841
-            // is fixed and hits stable (around 1.43-is probably?).
-            None => {
-                log::error!("Node id do not match in cargo metadata, ignoring {}", node.id);
-                continue;
-            }
-        };
-        node.deps.sort_by(|a, b| a.pkg.cmp(&b.pkg));
-        for (dep_node, _kind) in node
-            .deps
-            .iter()
-            .flat_map(|dep| DepKind::iter(&dep.dep_kinds).map(move |kind| (dep, kind)))
-        {
-            let pkg = match pkg_by_id.get(&dep_node.pkg) {
-                Some(&pkg) => pkg,
-                None => {
-                    log::error!(
-                        "Dep node id do not match in cargo metadata, ignoring {}",
-                        dep_node.pkg
-                    );
-                    continue;
+
+        // Every target but the build script depends on the lib target of its
+        // own package.
+        if let Some((to, name)) = lib_tgt {
+            let name = CrateName::normalize_dashes(&name);
+            for &(from, kind) in pkg_crates[&pkg].iter() {
+                if from != to && kind != TargetKind::BuildScript {
+                    crates[from].deps.push(DepData { krate: to, name: name.clone() });
                 }
+            }
+        }
+    }
+
+    for pkg in cargo.packages() {
+        for dep in cargo[pkg].dependencies.iter() {
+            let to = match pkg_to_lib_crate.get(&dep.pkg) {
+                Some(&to) => to,
+                None => continue,
             };
-            let name = CrateName::new(&dep_node.name).unwrap();
-            let dep = DepData { krate: pkg, name };
-            crates[source].deps.push(dep);
+            let name = CrateName::new(&dep.name).unwrap();
+            for &(from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
+                let wanted = match dep.kind {
+                    DepKind::Normal => kind != TargetKind::BuildScript,
+                    DepKind::Dev => {
+                        matches!(kind, TargetKind::Test | TargetKind::Bench | TargetKind::Example)
+                    }
+                    DepKind::Build => kind == TargetKind::BuildScript,
+                };
+                // A dependency can be both normal and dev, only add it once.
+                if wanted && !crates[from].deps.iter().any(|it| it.krate == to) {
+                    crates[from].deps.push(DepData { krate: to, name: name.clone() });
+                }
+            }
         }
-        //  crates[source].active_features.extend(node.features);
     }
-    let json = ProjectJsonData { sysroot_src: None, crates: crates.to_vec() };
-    json
+
+    ProjectJsonData { sysroot_src: None, crates }
 }
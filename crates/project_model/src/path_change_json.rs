@@ -0,0 +1,312 @@
+//! A variant of `ChangeJson` which identifies files and crates by path, rather
+//! than by `FileId` and crate index.
+//!
+//! `FileId`s and crate indices depend on the order in which things happened to
+//! be loaded, so two `ChangeJson`s of the same workspace rarely line up. Here,
+//! everything is keyed by path and kept sorted, and `FileId`s are only handed
+//! out on load, by a `Vfs`. Two snapshots of the same workspace are thus equal
+//! if nothing changed, can be diffed and merged, and can be loaded one after
+//! the other into the same `Vfs`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use base_db::{CrateName, VfsPath};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use stdx::format_to;
+use vfs::Vfs;
+
+use crate::{
+    change_json::SourceRootJson,
+    crate_graph_json::{CrateRoot, CrateRootData, Dep},
+    json_format::{check_deps, FormatError, JsonFormat, ValidationError, VersionedJson},
+    ChangeJson, CrateGraphJson,
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct PathChangeJson {
+    /// Crates, keyed by the path of their root file, their display name and
+    /// their sorted cfgs, see `crate_key`.
+    crates: BTreeMap<String, PathCrateJson>,
+    local_roots: Vec<BTreeSet<String>>,
+    library_roots: Vec<BTreeSet<String>>,
+    files: BTreeMap<String, Option<String>>,
+    /// Crates which `merge` removes, only set by `diff`.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    removed_crates: BTreeSet<String>,
+    /// Files which `merge` removes from the roots, only set by `diff`.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    removed_files: BTreeSet<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct PathCrateJson {
+    root_file: String,
+    #[serde(flatten)]
+    data: CrateRootData,
+    /// `(name, crate)` pairs, where `crate` is a key of `PathChangeJson::crates`.
+    deps: Vec<(String, String)>,
+}
+
+impl PathChangeJson {
+    pub fn from_change_json(change_json: &ChangeJson) -> PathChangeJson {
+        let paths: FxHashMap<u32, &String> = change_json
+            .local_roots
+            .roots
+            .iter()
+            .chain(change_json.library_roots.roots.iter())
+            .flatten()
+            .filter_map(|(file_id, path)| Some((*file_id, path.as_ref()?)))
+            .collect();
+
+        let mut crates = BTreeMap::new();
+        let mut crate_keys = Vec::new();
+        for root in change_json.crate_graph.roots.iter() {
+            let path = match paths.get(&root.file_id) {
+                Some(&path) => path,
+                None => {
+                    log::error!("crate root {} is not part of any source root", root.file_id);
+                    crate_keys.push(None);
+                    continue;
+                }
+            };
+            let key = crate_key(path, &root.data);
+            // Crates which only differ in what the key leaves out, like their
+            // env, are told apart by their order.
+            let mut unique_key = key.clone();
+            let mut n = 1;
+            while crates.contains_key(&unique_key) {
+                unique_key = format!("{}#{}", key, n);
+                n += 1;
+            }
+            let key = unique_key;
+            crates.insert(
+                key.clone(),
+                PathCrateJson {
+                    root_file: path.clone(),
+                    data: root.data.clone(),
+                    deps: Vec::new(),
+                },
+            );
+            crate_keys.push(Some(key));
+        }
+        for dep in change_json.crate_graph.deps.iter() {
+            let key = |idx: u32| crate_keys.get(idx as usize).cloned().flatten();
+            if let (Some(from), Some(to)) = (key(dep.from), key(dep.to)) {
+                crates.get_mut(&from).unwrap().deps.push((dep.name.clone(), to));
+            }
+        }
+        crates.values_mut().for_each(|krate| krate.deps.sort());
+
+        let files = change_json
+            .files
+            .iter()
+            .filter_map(|(file_id, text)| Some(((*paths.get(file_id)?).clone(), text.clone())))
+            .collect();
+
+        PathChangeJson {
+            crates,
+            local_roots: path_roots(&change_json.local_roots),
+            library_roots: path_roots(&change_json.library_roots),
+            files,
+            ..PathChangeJson::default()
+        }
+    }
+
+    /// Converts back to a `ChangeJson`, allocating `FileId`s in `vfs`. Paths
+    /// already known to `vfs` keep their `FileId`.
+    ///
+    /// `Vfs` only hands out ids of existing files, so files which have no text
+    /// in the snapshot and are unknown to `vfs` are left out, as are the crates
+    /// rooted in them.
+    pub fn to_change_json(&self, vfs: &mut Vfs) -> ChangeJson {
+        let mut file_ids: FxHashMap<String, Option<u32>> = FxHashMap::default();
+        let mut file_id = |path: &str| -> Option<u32> {
+            *file_ids.entry(path.to_string()).or_insert_with(|| {
+                let vfs_path = VfsPath::new_virtual_path(path.to_string());
+                if let Some(Some(text)) = self.files.get(path) {
+                    vfs.set_file_contents(vfs_path.clone(), Some(text.clone().into_bytes()));
+                }
+                let file_id = vfs.file_id(&vfs_path).map(|it| it.0);
+                if file_id.is_none() {
+                    log::error!("{} has no text and is not loaded", path);
+                }
+                file_id
+            })
+        };
+
+        let mut change_json = ChangeJson::default();
+        for (roots, path_roots) in [
+            (&mut change_json.local_roots, &self.local_roots),
+            (&mut change_json.library_roots, &self.library_roots),
+        ] {
+            roots.roots = path_roots
+                .iter()
+                .map(|root| {
+                    root.iter()
+                        .filter_map(|path| Some((file_id(path)?, Some(path.clone()))))
+                        .collect()
+                })
+                .collect();
+        }
+        for (path, text) in self.files.iter() {
+            if let Some(file_id) = file_id(path) {
+                change_json.files.insert(file_id, text.clone());
+            }
+        }
+        for path in self.removed_files.iter() {
+            if let Some(file_id) = file_id(path) {
+                change_json.files.insert(file_id, None);
+            }
+        }
+
+        let mut crate_graph = CrateGraphJson::default();
+        let mut crate_ids: FxHashMap<&String, u32> = FxHashMap::default();
+        for (key, krate) in self.crates.iter() {
+            match file_id(&krate.root_file) {
+                Some(file_id) => {
+                    crate_ids.insert(key, crate_graph.roots.len() as u32);
+                    crate_graph.roots.push(CrateRoot { file_id, data: krate.data.clone() });
+                }
+                None => log::error!("root file of crate {} is missing", key),
+            }
+        }
+        for (key, krate) in self.crates.iter() {
+            let from = match crate_ids.get(key) {
+                Some(&from) => from,
+                None => continue,
+            };
+            for (name, to) in krate.deps.iter() {
+                match crate_ids.get(to) {
+                    Some(&to) => crate_graph.deps.push(Dep { from, name: name.clone(), to }),
+                    None => log::error!("dependency `{}` on unknown crate {}", name, to),
+                }
+            }
+        }
+        change_json.crate_graph = crate_graph;
+        change_json
+    }
+
+    /// Merges `other` into `self`. Where the two overlap, `other` wins: its
+    /// crates and file texts replace ours, and its files move to its roots.
+    pub fn merge(&mut self, other: PathChangeJson) {
+        self.crates.retain(|key, _| !other.removed_crates.contains(key));
+        for roots in [&mut self.local_roots, &mut self.library_roots] {
+            merge_roots(roots, Vec::new(), |path| other.removed_files.contains(path));
+        }
+        self.files.retain(|path, _| !other.removed_files.contains(path));
+
+        let moved: FxHashSet<String> =
+            other.local_roots.iter().chain(other.library_roots.iter()).flatten().cloned().collect();
+        merge_roots(&mut self.local_roots, other.local_roots, |path| moved.contains(path));
+        merge_roots(&mut self.library_roots, other.library_roots, |path| moved.contains(path));
+        self.crates.extend(other.crates);
+        self.files.extend(other.files);
+    }
+
+    /// Returns the changes from `self` to `new`, such that merging them into
+    /// `self` gives `new`.
+    ///
+    /// Like `ChangeJsonDelta`, the diff carries either no crates or all of the
+    /// new ones, so that it is valid on its own, and the roots only if files
+    /// were added to or moved between them.
+    pub fn diff(&self, new: &PathChangeJson) -> PathChangeJson {
+        let mut res = PathChangeJson::default();
+        if self.crates != new.crates {
+            res.crates = new.crates.clone();
+            res.removed_crates =
+                self.crates.keys().filter(|key| !new.crates.contains_key(*key)).cloned().collect();
+        }
+
+        let new_paths = new.paths();
+        res.removed_files =
+            self.paths().into_iter().filter(|path| !new_paths.contains(path)).collect();
+        res.files = new
+            .files
+            .iter()
+            .filter(|(path, text)| self.files.get(*path) != Some(*text))
+            .map(|(path, text)| (path.clone(), text.clone()))
+            .collect();
+
+        let (mut local_roots, mut library_roots) =
+            (self.local_roots.clone(), self.library_roots.clone());
+        merge_roots(&mut local_roots, Vec::new(), |path| res.removed_files.contains(path));
+        merge_roots(&mut library_roots, Vec::new(), |path| res.removed_files.contains(path));
+        if local_roots != new.local_roots || library_roots != new.library_roots {
+            res.local_roots = new.local_roots.clone();
+            res.library_roots = new.library_roots.clone();
+        }
+        res
+    }
+
+    fn paths(&self) -> FxHashSet<String> {
+        let roots = self.local_roots.iter().chain(self.library_roots.iter()).flatten();
+        roots.chain(self.files.keys()).cloned().collect()
+    }
+}
+
+impl VersionedJson for PathChangeJson {
+    const FORMAT: JsonFormat = JsonFormat::PathChange;
+
+    /// Crates are identified by their index in `crates`, which is the index
+    /// they get in the `ChangeJson`.
+    fn validate(&self) -> Result<(), FormatError> {
+        let crate_ids: FxHashMap<&String, u32> =
+            self.crates.keys().enumerate().map(|(idx, key)| (key, idx as u32)).collect();
+        let mut errors = Vec::new();
+        let mut deps = Vec::new();
+        for (from, krate) in self.crates.values().enumerate() {
+            let from = from as u32;
+            if let Err(err) = krate.data.edition(from) {
+                errors.push(err);
+            }
+            for (name, to) in krate.deps.iter() {
+                if CrateName::new(name).is_err() {
+                    errors.push(ValidationError::BadDepName { from, name: name.clone() });
+                }
+                match crate_ids.get(to) {
+                    Some(&to) => deps.push((from, to)),
+                    None => errors.push(ValidationError::UnknownCrate { from, key: to.clone() }),
+                }
+            }
+        }
+        check_deps(self.crates.len(), deps.into_iter(), &mut errors);
+        FormatError::from_errors(errors)
+    }
+}
+
+/// Identifies a crate across snapshots, regardless of the order of the crates.
+fn crate_key(root_file: &str, data: &CrateRootData) -> String {
+    let mut key = root_file.to_string();
+    if let Some(name) = data.display_name() {
+        format_to!(key, " {}", name);
+    }
+    let mut cfgs = data.cfgs().collect::<Vec<_>>();
+    if !cfgs.is_empty() {
+        cfgs.sort();
+        format_to!(key, " cfg({})", cfgs.join(", "));
+    }
+    key
+}
+
+fn path_roots(roots: &SourceRootJson) -> Vec<BTreeSet<String>> {
+    let mut res: Vec<BTreeSet<String>> = roots
+        .roots
+        .iter()
+        .map(|root| root.iter().filter_map(|(_, path)| path.clone()).collect())
+        .filter(|root: &BTreeSet<String>| !root.is_empty())
+        .collect();
+    res.sort();
+    res
+}
+
+fn merge_roots(
+    ours: &mut Vec<BTreeSet<String>>,
+    theirs: Vec<BTreeSet<String>>,
+    moved: impl Fn(&String) -> bool,
+) {
+    ours.iter_mut().for_each(|root| root.retain(|path| !moved(path)));
+    ours.retain(|root| !root.is_empty());
+    ours.extend(theirs);
+    ours.sort();
+}
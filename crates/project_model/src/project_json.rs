@@ -10,9 +10,12 @@ use std::{path::PathBuf, str::FromStr};
 use base_db::{CrateDisplayName, CrateId, CrateName, Dependency, Edition};
 use paths::{AbsPath, AbsPathBuf};
 use rustc_hash::FxHashMap;
-use serde::{de, Deserialize};
+use serde::{de, ser, Deserialize, Serialize};
 
-use crate::cfg_flag::CfgFlag;
+use crate::{
+    cfg_flag::CfgFlag,
+    json_format::{check_deps, FormatError},
+};
 
 /// Roots and crates that compose this Rust project.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -118,13 +121,26 @@ impl ProjectJson {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ProjectJsonData {
     pub(crate) sysroot_src: Option<PathBuf>,
     pub(crate) crates: Vec<CrateData>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl ProjectJsonData {
+    /// Checks that dependencies point to existing crates without forming
+    /// cycles. Editions and dependency names are already checked by serde.
+    pub fn validate(&self) -> Result<(), FormatError> {
+        let mut errors = Vec::new();
+        let deps = self.crates.iter().enumerate().flat_map(|(from, krate)| {
+            krate.deps.iter().map(move |dep| (from as u32, dep.krate as u32))
+        });
+        check_deps(self.crates.len(), deps, &mut errors);
+        FormatError::from_errors(errors)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CrateData {
     pub(crate) display_name: Option<String>,
     pub(crate) root_module: PathBuf,
@@ -147,7 +163,7 @@ pub struct ParseEditionError {
     invalid_input: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename = "edition")]
 pub enum EditionData {
     #[serde(rename = "2015")]
@@ -192,19 +208,20 @@ impl FromStr for EditionData {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DepData {
     /// Identifies a crate by position in the crates array.
     #[serde(rename = "crate")]
     pub(crate) krate: usize,
     #[serde(deserialize_with = "deserialize_crate_name")]
+    #[serde(serialize_with = "serialize_crate_name")]
     pub(crate) name: CrateName,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CrateSource {
-    include_dirs: Vec<PathBuf>,
-    exclude_dirs: Vec<PathBuf>,
+    pub(crate) include_dirs: Vec<PathBuf>,
+    pub(crate) exclude_dirs: Vec<PathBuf>,
 }
 
 fn deserialize_crate_name<'de, D>(de: D) -> Result<CrateName, D::Error>
@@ -214,3 +231,10 @@ where
     let name = String::deserialize(de)?;
     CrateName::new(&name).map_err(|err| de::Error::custom(format!("invalid crate name: {:?}", err)))
 }
+
+fn serialize_crate_name<S>(name: &CrateName, se: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    se.serialize_str(name)
+}
@@ -0,0 +1,246 @@
+//! A self-contained copy of the sysroot sources.
+//!
+//! Loading the sysroot normally means finding the toolchain with
+//! `Sysroot::discover` and reading `core`, `alloc` and `std` from disk. A
+//! `SysrootBundle` packages the sources and the crate graph of these crates
+//! once, so that a `ChangeJson` can later be completed with a sysroot on a
+//! machine without any toolchain. Like the snapshots, bundles are written as
+//! [`VersionedJson`].
+
+use std::{collections::BTreeMap, fs};
+
+use anyhow::Result;
+use base_db::{CrateDisplayName, CrateId, Edition, Env, FileId};
+use cfg::CfgOptions;
+use paths::AbsPath;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cfg_flag::CfgFlag,
+    crate_graph_json::Dep,
+    json_format::{check_deps, FormatError, JsonFormat, ValidationError, VersionedJson},
+    rustc_cfg,
+    sysroot::SysrootCrate,
+    ChangeJson, Sysroot,
+};
+
+/// Where the files of a bundle end up in the VFS.
+const MOUNT_POINT: &str = "/sysroot";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SysrootBundle {
+    cfg: Vec<CfgFlag>,
+    crates: Vec<BundleCrate>,
+    /// The crates every crate of the workspace depends on.
+    public_deps: Vec<String>,
+    /// File texts, keyed by their path relative to the sysroot source dir.
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct BundleCrate {
+    name: String,
+    root_file: String,
+    edition: String,
+    deps: Vec<String>,
+}
+
+impl SysrootBundle {
+    /// Bundles the public sysroot crates (`core`, `alloc` and `std`) and
+    /// `proc_macro`, together with everything they depend on.
+    pub fn new(sysroot: &Sysroot) -> Result<SysrootBundle> {
+        let _p = profile::span("SysrootBundle::new");
+        let mut queue: Vec<SysrootCrate> = sysroot.public_deps().map(|(_, krate)| krate).collect();
+        queue.extend(sysroot.proc_macro());
+        let mut seen = FxHashSet::default();
+        let mut crates = Vec::new();
+        while let Some(krate) = queue.pop() {
+            if seen.insert(krate) {
+                crates.push(krate);
+                queue.extend(sysroot[krate].deps.iter().copied());
+            }
+        }
+        crates.sort_by(|&a, &b| sysroot[a].name.cmp(&sysroot[b].name));
+
+        let mut files = BTreeMap::new();
+        for &krate in crates.iter() {
+            // Same as the source root `ProjectWorkspace::to_roots` uses.
+            collect_rs_files(sysroot.root(), sysroot[krate].root.parent(), &mut files)?;
+        }
+        let crates = crates
+            .iter()
+            .map(|&krate| {
+                let root = &sysroot[krate].root;
+                BundleCrate {
+                    name: sysroot[krate].name.clone(),
+                    root_file: relative_path(sysroot.root(), root),
+                    edition: crate_edition(root).to_string(),
+                    deps: sysroot[krate]
+                        .deps
+                        .iter()
+                        .map(|&dep| sysroot[dep].name.clone())
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(SysrootBundle {
+            cfg: rustc_cfg::get(None, None),
+            crates,
+            public_deps: sysroot.public_deps().map(|(name, _)| name.to_string()).collect(),
+            files,
+        })
+    }
+
+    /// Adds the bundled files as a library source root and the bundled crates
+    /// to the crate graph of `change_json`. Every crate already in the graph
+    /// gets a dependency on `core`, `alloc` and `std`, unless it already has a
+    /// dependency of that name.
+    pub fn add_to(&self, change_json: &mut ChangeJson) {
+        let _p = profile::span("SysrootBundle::add_to");
+        let first_file_id = change_json
+            .local_roots
+            .roots
+            .iter()
+            .chain(change_json.library_roots.roots.iter())
+            .flatten()
+            .map(|(file_id, _)| *file_id)
+            .chain(change_json.files.keys().copied())
+            .chain(change_json.crate_graph.roots.iter().map(|root| root.file_id))
+            .max()
+            .map_or(0, |it| it + 1);
+
+        let mut file_ids = FxHashMap::default();
+        let mut source_root = Vec::new();
+        for (file_id, (path, text)) in (first_file_id..).zip(self.files.iter()) {
+            file_ids.insert(path.as_str(), FileId(file_id));
+            source_root.push((file_id, Some(format!("{}/{}", MOUNT_POINT, path))));
+            change_json.files.insert(file_id, Some(text.clone()));
+        }
+        change_json.library_roots.roots.push(source_root);
+
+        let crate_graph = &mut change_json.crate_graph;
+        let workspace_crates = crate_graph.roots.len() as u32;
+        let mut cfg_options = CfgOptions::default();
+        cfg_options.extend(self.cfg.iter().cloned());
+        let mut crate_ids = FxHashMap::default();
+        for krate in self.crates.iter() {
+            let file_id = match file_ids.get(krate.root_file.as_str()) {
+                Some(&file_id) => file_id,
+                None => {
+                    log::error!("root file of sysroot crate `{}` is not bundled", krate.name);
+                    continue;
+                }
+            };
+            let crate_id = crate_graph.add_crate_root(
+                file_id,
+                krate.edition.parse().unwrap_or(Edition::CURRENT),
+                Some(CrateDisplayName::from_canonical_name(krate.name.clone())),
+                cfg_options.clone(),
+                cfg_options.clone(),
+                Env::default(),
+                None,
+            );
+            crate_ids.insert(krate.name.as_str(), CrateId(crate_id));
+        }
+        for krate in self.crates.iter() {
+            for dep in krate.deps.iter() {
+                if let (Some(from), Some(to)) =
+                    (crate_ids.get(krate.name.as_str()), crate_ids.get(dep.as_str()))
+                {
+                    crate_graph.add_dep(Dep { from: from.0, name: dep.clone(), to: to.0 });
+                }
+            }
+        }
+        for from in 0..workspace_crates {
+            for name in self.public_deps.iter() {
+                if let Some(to) = crate_ids.get(name.as_str()) {
+                    if !crate_graph.contains_dep(from, name.clone()) {
+                        crate_graph.add_dep(Dep { from, name: name.clone(), to: to.0 });
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl VersionedJson for SysrootBundle {
+    const FORMAT: JsonFormat = JsonFormat::SysrootBundle;
+
+    /// Crates are identified by their index in `crates`.
+    fn validate(&self) -> Result<(), FormatError> {
+        let crate_ids: FxHashMap<&str, u32> = self
+            .crates
+            .iter()
+            .enumerate()
+            .map(|(idx, krate)| (krate.name.as_str(), idx as u32))
+            .collect();
+        let mut errors = Vec::new();
+        let mut deps = Vec::new();
+        for (from, krate) in self.crates.iter().enumerate() {
+            let from = from as u32;
+            if krate.edition.parse::<Edition>().is_err() {
+                errors.push(ValidationError::BadEdition {
+                    krate: from,
+                    edition: krate.edition.clone(),
+                });
+            }
+            for dep in krate.deps.iter() {
+                match crate_ids.get(dep.as_str()) {
+                    Some(&to) => deps.push((from, to)),
+                    None => errors.push(ValidationError::UnknownCrate { from, key: dep.clone() }),
+                }
+            }
+        }
+        check_deps(self.crates.len(), deps.into_iter(), &mut errors);
+        FormatError::from_errors(errors)
+    }
+}
+
+/// Reads the edition from the `Cargo.toml` next to the `src` dir of the crate,
+/// as the `rust-src` component ships them. Sysroots without manifests get the
+/// edition the crate graph uses for sysroot crates.
+fn crate_edition(root_file: &AbsPath) -> Edition {
+    let manifest = match root_file.parent().and_then(|src| src.parent()) {
+        Some(dir) => dir.join("Cargo.toml"),
+        None => return Edition::CURRENT,
+    };
+    let text = match fs::read_to_string(&manifest) {
+        Ok(it) => it,
+        Err(_) => return Edition::CURRENT,
+    };
+    text.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            if key.trim() != "edition" {
+                return None;
+            }
+            value.trim().trim_matches('"').parse().ok()
+        })
+        .next()
+        .unwrap_or(Edition::CURRENT)
+}
+
+fn collect_rs_files(
+    sysroot_src: &AbsPath,
+    dir: &AbsPath,
+    files: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = dir.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_rs_files(sysroot_src, &path, files)?;
+        } else if path.extension() == Some("rs".as_ref()) {
+            files.insert(relative_path(sysroot_src, &path), fs::read_to_string(&path)?);
+        }
+    }
+    Ok(())
+}
+
+/// Uses `/` as separator on all platforms, so that bundles are portable.
+fn relative_path(sysroot_src: &AbsPath, path: &AbsPath) -> String {
+    let relative = path.strip_prefix(sysroot_src).expect("sysroot file outside of the sysroot");
+    relative.as_ref().iter().map(|it| it.to_string_lossy()).collect::<Vec<_>>().join("/")
+}
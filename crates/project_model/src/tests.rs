@@ -1,38 +1,55 @@
 use std::{
     ops::Deref,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
-use base_db::{CrateGraph, FileId};
+use base_db::{
+    CrateGraph, Edition, Env, FileId, FileSet, ProcMacro, ProcMacroExpander, ProcMacroKind,
+    SourceRoot, VfsPath,
+};
 use expect_test::{expect, Expect};
+use hir::Semantics;
+use ide_db::RootDatabase;
 use paths::{AbsPath, AbsPathBuf};
 use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use stdx::format_to;
+use syntax::{ast, AstNode};
 
 use crate::{
-    CargoWorkspace, CfgOverrides, ProjectJson, ProjectJsonData, ProjectWorkspace, Sysroot,
-    WorkspaceBuildScripts,
+    build_scripts::BuildScriptOutput, cargo_to_json, json_schema, meta_to_json, CargoWorkspace,
+    CfgOverrides, ChangeJson, ChangeJsonDelta, CrateGraphJson, FormatError, JsonFormat,
+    PathChangeJson, ProjectJson, ProjectJsonData, ProjectWorkspace, Sysroot, SysrootBundle,
+    ValidationError, VersionedJson, WorkspaceBuildScripts, JSON_FORMAT_VERSION,
 };
 
 fn load_cargo(file: &str) -> CrateGraph {
+    to_crate_graph(cargo_project_workspace(file))
+}
+
+fn load_rust_project(file: &str) -> CrateGraph {
+    to_crate_graph(rust_project_workspace(file))
+}
+
+fn cargo_project_workspace(file: &str) -> ProjectWorkspace {
     let meta = get_test_json_file(file);
     let cargo_workspace = CargoWorkspace::new(meta);
-    let project_workspace = ProjectWorkspace::Cargo {
+    ProjectWorkspace::Cargo {
         cargo: cargo_workspace,
         build_scripts: WorkspaceBuildScripts::default(),
         sysroot: None,
         rustc: None,
         rustc_cfg: Vec::new(),
         cfg_overrides: CfgOverrides::default(),
-    };
-    to_crate_graph(project_workspace)
+    }
 }
 
-fn load_rust_project(file: &str) -> CrateGraph {
+fn rust_project_workspace(file: &str) -> ProjectWorkspace {
     let data = get_test_json_file(file);
     let project = rooted_project_json(data);
     let sysroot = Some(get_fake_sysroot());
-    let project_workspace = ProjectWorkspace::Json { project, sysroot, rustc_cfg: Vec::new() };
-    to_crate_graph(project_workspace)
+    ProjectWorkspace::Json { project, sysroot, rustc_cfg: Vec::new() }
 }
 
 fn get_test_json_file<T: DeserializeOwned>(file: &str) -> T {
@@ -93,6 +110,95 @@ fn to_crate_graph(project_workspace: ProjectWorkspace) -> CrateGraph {
     })
 }
 
+/// Checks that exporting `project_workspace` to JSON and loading it back gives
+/// the same crate graph as lowering the workspace directly.
+fn check_crate_graph_json_round_trip(project_workspace: ProjectWorkspace) {
+    let mut load = {
+        let mut counter = 0;
+        move |_: &AbsPath| {
+            counter += 1;
+            Some(FileId(counter))
+        }
+    };
+    let direct = project_workspace.to_crate_graph(&mut |_| Vec::new(), &mut load.clone());
+    let crate_graph_json =
+        project_workspace.to_crate_graph_json(&mut |_| Vec::new(), &mut load).unwrap();
+
+    let json = serde_json::to_string(&crate_graph_json).unwrap();
+    let crate_graph_json: CrateGraphJson = serde_json::from_str(&json).unwrap();
+    let via_json = crate_graph_json.to_crate_graph(&mut |_| Vec::new()).unwrap();
+
+    assert_eq!(direct.iter().count(), via_json.iter().count());
+    for krate in direct.iter() {
+        let (direct, via_json) = (&direct[krate], &via_json[krate]);
+        assert_eq!(direct.root_file_id, via_json.root_file_id);
+        assert_eq!(direct.edition, via_json.edition);
+        assert_eq!(direct.display_name, via_json.display_name);
+        assert_eq!(direct.cfg_options, via_json.cfg_options);
+        assert_eq!(direct.potential_cfg_options, via_json.potential_cfg_options);
+        assert_eq!(direct.env, via_json.env);
+        assert_eq!(direct.dependencies, via_json.dependencies);
+    }
+}
+
+/// Exports `project_workspace` to a `ChangeJson` with a single source root,
+/// numbering the files from `first_file_id` on.
+fn change_json(project_workspace: &ProjectWorkspace, first_file_id: u32) -> ChangeJson {
+    let mut change_json = ChangeJson::default();
+    let mut file_set = FileSet::default();
+    let mut next_file_id = first_file_id;
+    let crate_graph_json = project_workspace
+        .to_crate_graph_json(&mut |_| Vec::new(), &mut |path| {
+            let file_id = FileId(next_file_id);
+            next_file_id += 1;
+            file_set.insert(file_id, VfsPath::new_virtual_path(path.display().to_string()));
+            change_json.change_file(file_id, Some(Arc::new(format!("// {}", path.display()))));
+            Some(file_id)
+        })
+        .unwrap();
+    change_json.set_crate_graph(crate_graph_json);
+    change_json.set_roots(vec![SourceRoot::new_local(file_set)]);
+    change_json
+}
+
+/// Stands in for the proc macro server: every dylib exports a derive and an
+/// attribute macro, which expand to an identifier naming the macro and dylib.
+fn load_fake_proc_macros(dylib_path: &AbsPath) -> Vec<ProcMacro> {
+    return [("Derive", ProcMacroKind::CustomDerive), ("attr", ProcMacroKind::Attr)]
+        .iter()
+        .map(|&(name, kind)| ProcMacro {
+            name: name.into(),
+            kind,
+            expander: Arc::new(FakeExpander {
+                dylib_path: dylib_path.to_path_buf(),
+                name: name.to_string(),
+            }),
+        })
+        .collect();
+
+    #[derive(Debug)]
+    struct FakeExpander {
+        dylib_path: AbsPathBuf,
+        name: String,
+    }
+
+    impl ProcMacroExpander for FakeExpander {
+        fn expand(
+            &self,
+            _subtree: &tt::Subtree,
+            _attrs: Option<&tt::Subtree>,
+            _env: &Env,
+        ) -> Result<tt::Subtree, tt::ExpansionError> {
+            let text = format!("{}_{}", self.name, self.dylib_path.display());
+            let ident = tt::Ident { text: text.into(), id: tt::TokenId::unspecified() };
+            Ok(tt::Subtree {
+                delimiter: None,
+                token_trees: vec![tt::TokenTree::Leaf(tt::Leaf::Ident(ident))],
+            })
+        }
+    }
+}
+
 fn check_crate_graph(crate_graph: CrateGraph, expect: Expect) {
     let mut crate_graph = format!("{:#?}", crate_graph);
     replace_root(&mut crate_graph, false);
@@ -1015,3 +1121,686 @@ fn rust_project_is_proc_macro_has_proc_macro_dep() {
     // on the proc_macro sysroot crate.
     crate_data.dependencies.iter().find(|&dep| dep.name.deref() == "proc_macro").unwrap();
 }
+
+#[test]
+fn cargo_crate_graph_json_round_trips_proc_macros() {
+    let meta = get_test_json_file("hello-world-metadata.json");
+    let cargo = CargoWorkspace::new(meta);
+    let mut build_scripts = WorkspaceBuildScripts::default();
+    for pkg in cargo.packages() {
+        let mut dylib = format!("$ROOT$target/debug/deps/lib{}.so", cargo[pkg].name);
+        replace_root(&mut dylib, true);
+        let output = BuildScriptOutput {
+            proc_macro_dylib_path: Some(AbsPathBuf::assert(PathBuf::from(dylib))),
+            ..BuildScriptOutput::default()
+        };
+        build_scripts.outputs.insert(pkg, output);
+    }
+    let project_workspace = ProjectWorkspace::Cargo {
+        cargo,
+        build_scripts,
+        sysroot: None,
+        rustc: None,
+        rustc_cfg: Vec::new(),
+        cfg_overrides: CfgOverrides::default(),
+    };
+
+    let mut load = {
+        let mut counter = 0;
+        move |_: &AbsPath| {
+            counter += 1;
+            Some(FileId(counter))
+        }
+    };
+    let direct = project_workspace.to_crate_graph(&mut load_fake_proc_macros, &mut load.clone());
+    let crate_graph_json =
+        project_workspace.to_crate_graph_json(&mut load_fake_proc_macros, &mut load).unwrap();
+
+    let json = serde_json::to_string(&crate_graph_json).unwrap();
+    let crate_graph_json: CrateGraphJson = serde_json::from_str(&json).unwrap();
+    let via_json = crate_graph_json.to_crate_graph(&mut load_fake_proc_macros).unwrap();
+
+    let input = tt::Subtree::default();
+    let env = Env::default();
+    assert_eq!(direct.iter().count(), via_json.iter().count());
+    for krate in direct.iter() {
+        let (direct, via_json) = (&direct[krate].proc_macro, &via_json[krate].proc_macro);
+        assert_eq!(direct.len(), 2);
+        assert_eq!(format!("{:?}", direct), format!("{:?}", via_json));
+        for (direct, via_json) in direct.iter().zip(via_json) {
+            assert_eq!(
+                direct.expander.expand(&input, None, &env),
+                via_json.expander.expand(&input, None, &env)
+            );
+        }
+    }
+}
+
+#[test]
+fn cargo_crate_graph_json_round_trip() {
+    check_crate_graph_json_round_trip(cargo_project_workspace("hello-world-metadata.json"));
+}
+
+#[test]
+fn rust_project_crate_graph_json_round_trip() {
+    check_crate_graph_json_round_trip(rust_project_workspace("hello-world-project.json"));
+    check_crate_graph_json_round_trip(rust_project_workspace("is-proc-macro-project.json"));
+}
+
+#[test]
+fn detached_files_crate_graph_json_round_trip() {
+    let mut root = "$ROOT$".to_string();
+    replace_root(&mut root, true);
+    let files = ["main.rs", "other-file.rs"]
+        .iter()
+        .map(|it| AbsPathBuf::assert(Path::new(&root).join(it)))
+        .collect();
+    let project_workspace = ProjectWorkspace::DetachedFiles {
+        files,
+        sysroot: get_fake_sysroot(),
+        rustc_cfg: Vec::new(),
+    };
+    check_crate_graph_json_round_trip(project_workspace);
+}
+
+#[test]
+fn cargo_to_project_json_matches_cargo_crate_graph() {
+    let meta = get_test_json_file("hello-world-metadata.json");
+    let cargo = CargoWorkspace::new(meta);
+    let mut build_scripts = WorkspaceBuildScripts::default();
+    let libc = cargo.packages().find(|&pkg| cargo[pkg].name == "libc").unwrap();
+    let mut out_dir = "$ROOT$target/debug/build/libc-out".to_string();
+    replace_root(&mut out_dir, true);
+    let output = BuildScriptOutput {
+        cfgs: vec!["libc_priv_mod_use".parse().unwrap(), "freebsd=\"11\"".parse().unwrap()],
+        envs: vec![("OUT_DIR".to_string(), out_dir.clone())],
+        out_dir: Some(AbsPathBuf::assert(PathBuf::from(&out_dir))),
+        proc_macro_dylib_path: None,
+    };
+    build_scripts.outputs.insert(libc, output);
+    let project_workspace = ProjectWorkspace::Cargo {
+        cargo,
+        build_scripts,
+        sysroot: None,
+        rustc: None,
+        rustc_cfg: vec!["unix".parse().unwrap()],
+        cfg_overrides: CfgOverrides::default(),
+    };
+
+    let data = match &project_workspace {
+        ProjectWorkspace::Cargo {
+            cargo,
+            build_scripts,
+            sysroot,
+            rustc,
+            rustc_cfg,
+            cfg_overrides,
+        } => cargo_to_json(
+            rustc_cfg.clone(),
+            cfg_overrides,
+            cargo,
+            build_scripts,
+            sysroot.as_ref(),
+            rustc,
+        ),
+        _ => unreachable!(),
+    };
+    // Go through serialization, as if the data was written to `rust-project.json`.
+    let json = serde_json::to_string(&data).unwrap();
+    let data: ProjectJsonData = serde_json::from_str(&json).unwrap();
+    let project = rooted_project_json(data);
+    assert!(project.crates().all(|(_, krate)| {
+        let is_libc = krate.root_module.as_os_str().to_string_lossy().contains("libc");
+        krate.is_workspace_member != is_libc
+            && is_libc == (krate.env.get("OUT_DIR") == Some(&out_dir))
+    }));
+
+    let cargo_crate_graph = to_crate_graph(project_workspace);
+    let json_crate_graph =
+        to_crate_graph(ProjectWorkspace::Json { project, sysroot: None, rustc_cfg: Vec::new() });
+
+    assert_eq!(cargo_crate_graph.iter().count(), json_crate_graph.iter().count());
+    for krate in cargo_crate_graph.iter() {
+        let (cargo, json) = (&cargo_crate_graph[krate], &json_crate_graph[krate]);
+        assert_eq!(cargo.root_file_id, json.root_file_id);
+        assert_eq!(cargo.edition, json.edition);
+        assert_eq!(cargo.display_name, json.display_name);
+        assert_eq!(cargo.cfg_options, json.cfg_options);
+        assert_eq!(cargo.env, json.env);
+        assert_eq!(cargo.dependencies, json.dependencies);
+    }
+}
+
+#[test]
+fn path_change_json_does_not_depend_on_file_ids() {
+    let project_workspace = rust_project_workspace("hello-world-project.json");
+    let snapshot = PathChangeJson::from_change_json(&change_json(&project_workspace, 0));
+    let other = PathChangeJson::from_change_json(&change_json(&project_workspace, 100));
+    assert_eq!(snapshot, other);
+    assert_eq!(serde_json::to_string(&snapshot).unwrap(), serde_json::to_string(&other).unwrap());
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let snapshot: PathChangeJson = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot, other);
+}
+
+#[test]
+fn path_change_json_reloads_with_same_file_ids() {
+    let project_workspace = rust_project_workspace("hello-world-project.json");
+    let snapshot = PathChangeJson::from_change_json(&change_json(&project_workspace, 0));
+    let mut vfs = vfs::Vfs::default();
+
+    let change_json = snapshot.to_change_json(&mut vfs);
+    assert_eq!(PathChangeJson::from_change_json(&change_json), snapshot);
+    let n_files = vfs.len();
+    let crate_graph = change_json.to_change(&mut |_| Vec::new()).unwrap().crate_graph.unwrap();
+    assert!(crate_graph.iter().count() > 1);
+
+    let reloaded = snapshot.to_change_json(&mut vfs).to_change(&mut |_| Vec::new()).unwrap();
+    let reloaded = reloaded.crate_graph.unwrap();
+    assert_eq!(vfs.len(), n_files);
+    for krate in crate_graph.iter() {
+        assert_eq!(crate_graph[krate].root_file_id, reloaded[krate].root_file_id);
+        assert_eq!(crate_graph[krate].dependencies, reloaded[krate].dependencies);
+    }
+}
+
+#[test]
+fn path_change_json_merge() {
+    let project_workspace = rust_project_workspace("hello-world-project.json");
+    let old = change_json(&project_workspace, 0);
+    let mut new = change_json(&project_workspace, 100);
+    new.change_file(FileId(100), Some(Arc::new("fn changed() {}".to_string())));
+    let (old, new) =
+        (PathChangeJson::from_change_json(&old), PathChangeJson::from_change_json(&new));
+    assert_ne!(old, new);
+
+    let mut merged = old.clone();
+    merged.merge(old.clone());
+    assert_eq!(merged, old);
+    merged.merge(new.clone());
+    assert_eq!(merged, new);
+}
+
+/// Two crates rooted in `a/lib.rs`, with and without `cfg(test)`, listed in
+/// the given order, and a crate depending on the one without `cfg(test)`.
+fn shared_root_workspace(test_first: bool) -> ProjectWorkspace {
+    let test = json!({ "root_module": "a/lib.rs", "edition": "2018", "cfg": ["test"], "deps": [] });
+    let lib = json!({ "root_module": "a/lib.rs", "edition": "2018", "deps": [] });
+    let (crates, lib_idx) = if test_first { ([test, lib], 1) } else { ([lib, test], 0) };
+    let mut crates = crates.to_vec();
+    crates.push(json!({
+        "root_module": "b/lib.rs",
+        "edition": "2018",
+        "deps": [{ "crate": lib_idx, "name": "a" }],
+    }));
+    let data: ProjectJsonData = serde_json::from_value(json!({ "crates": crates })).unwrap();
+    let project = rooted_project_json(data);
+    ProjectWorkspace::Json { project, sysroot: None, rustc_cfg: Vec::new() }
+}
+
+#[test]
+fn path_change_json_keys_do_not_depend_on_crate_order() {
+    let snapshot = PathChangeJson::from_change_json(&change_json(&shared_root_workspace(true), 0));
+    let other = PathChangeJson::from_change_json(&change_json(&shared_root_workspace(false), 0));
+    assert_eq!(snapshot, other);
+}
+
+#[test]
+fn path_change_json_diff() {
+    let old = PathChangeJson::from_change_json(&change_json(&shared_root_workspace(true), 0));
+    assert_eq!(old.diff(&old), PathChangeJson::default());
+
+    let mut new = change_json(&rust_project_workspace("hello-world-project.json"), 100);
+    new.change_file(FileId(100), Some(Arc::new("fn changed() {}".to_string())));
+    let new = PathChangeJson::from_change_json(&new);
+    let diff = old.diff(&new);
+    let diff = PathChangeJson::from_versioned_json(&diff.to_versioned_json().unwrap()).unwrap();
+    let mut merged = old.clone();
+    merged.merge(diff);
+    assert_eq!(merged, new);
+
+    // Only the changed text is carried when the crates and roots stay the same.
+    let mut changed = change_json(&shared_root_workspace(false), 0);
+    changed.change_file(FileId(2), Some(Arc::new("fn changed() {}".to_string())));
+    let changed = PathChangeJson::from_change_json(&changed);
+    let diff = old.diff(&changed);
+    assert_eq!(serde_json::to_value(&diff).unwrap()["files"].as_object().unwrap().len(), 1);
+    assert!(serde_json::to_value(&diff).unwrap()["crates"].as_object().unwrap().is_empty());
+    let mut merged = old.clone();
+    merged.merge(diff);
+    assert_eq!(merged, changed);
+}
+
+#[test]
+fn path_change_json_skips_missing_files() {
+    let mut change_json = change_json(&shared_root_workspace(true), 0);
+    // `b/lib.rs` has no text and was never loaded.
+    change_json.files.remove(&2);
+    let snapshot = PathChangeJson::from_change_json(&change_json);
+    let change_json = snapshot.to_change_json(&mut vfs::Vfs::default());
+    change_json.validate().unwrap();
+    assert_eq!(change_json.crate_graph.roots.len(), 2);
+    assert!(change_json.crate_graph.deps.is_empty());
+    assert_eq!(change_json.local_roots.roots.iter().flatten().count(), 1);
+}
+
+#[test]
+fn sysroot_bundle_completes_change_json() {
+    let mut change_json = change_json(&cargo_project_workspace("hello-world-metadata.json"), 0);
+    let n_workspace_crates =
+        change_json.to_change(&mut |_| Vec::new()).unwrap().crate_graph.unwrap().iter().count();
+
+    let bundle = SysrootBundle::new(&get_fake_sysroot()).unwrap();
+    let json = bundle.to_versioned_json().unwrap();
+    let bundle = SysrootBundle::from_versioned_json(&json).unwrap();
+    bundle.add_to(&mut change_json);
+    assert!(matches!(
+        ChangeJson::from_versioned_json(&json),
+        Err(FormatError::WrongFormat { expected: JsonFormat::Change, .. })
+    ));
+
+    // A workspace file using the bundled `Option`, `Vec` and `Iterator`.
+    let lib_rs = change_json.crate_graph.roots[0].file_id;
+    change_json.change_file(
+        FileId(lib_rs),
+        Some(Arc::new(
+            "fn f(o: Option<u32>, mut v: Vec<u32>) { o.is_some(); v.push(1); v.iter().map(|it| it); }"
+                .to_string(),
+        )),
+    );
+
+    let change = change_json.to_change(&mut |_| Vec::new()).unwrap();
+    let crate_graph = change.crate_graph.unwrap();
+    let name = |krate| crate_graph[krate].display_name.as_deref().unwrap_or_default().to_string();
+    let mut sysroot_crates = crate_graph
+        .iter()
+        .filter(|krate| krate.0 as usize >= n_workspace_crates)
+        .map(name)
+        .collect::<Vec<_>>();
+    sysroot_crates.sort();
+    assert_eq!(
+        sysroot_crates,
+        [
+            "alloc",
+            "core",
+            "panic_abort",
+            "panic_unwind",
+            "proc_macro",
+            "profiler_builtins",
+            "std",
+            "std_detect",
+            "term",
+            "test",
+            "unwind"
+        ]
+    );
+    for krate in crate_graph.iter().filter(|krate| (krate.0 as usize) < n_workspace_crates) {
+        let deps = crate_graph[krate]
+            .dependencies
+            .iter()
+            .map(|dep| dep.name.to_string())
+            .filter(|it| ["core", "alloc", "std"].contains(&it.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(deps, ["core", "alloc", "std"]);
+    }
+
+    // The bundled files make up one more library source root.
+    let roots = change.roots.unwrap();
+    assert_eq!(roots.iter().filter(|root| root.is_library).count(), 1);
+    let std_root = crate_graph
+        .iter()
+        .find(|&krate| name(krate) == "std")
+        .map(|krate| crate_graph[krate].root_file_id)
+        .unwrap();
+    assert!(roots.iter().any(|root| root.is_library && root.path_for_file(&std_root).is_some()));
+
+    // Editions come from the manifests of the sysroot crates, if any.
+    let edition = |krate_name: &str| {
+        let krate = crate_graph.iter().find(|&krate| name(krate) == krate_name).unwrap();
+        crate_graph[krate].edition
+    };
+    assert_eq!(edition("core"), Edition::Edition2021);
+    assert_eq!(edition("std"), Edition::CURRENT);
+
+    let mut db = RootDatabase::new(None);
+    db.apply_change(change_json.to_change(&mut |_| Vec::new()).unwrap());
+    let sema = Semantics::new(&db);
+    let methods = sema
+        .parse(FileId(lib_rs))
+        .syntax()
+        .descendants()
+        .filter_map(ast::MethodCallExpr::cast)
+        .map(|call| {
+            let method = sema.resolve_method_call(&call)?;
+            let krate = method.module(&db).krate().display_name(&db)?;
+            Some(format!("{}::{}", krate, method.name(&db)))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        methods,
+        [
+            Some("core::is_some".to_string()),
+            Some("alloc::push".to_string()),
+            Some("core::map".to_string()),
+            Some("alloc::iter".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn json_schema_matches_serialized_formats() {
+    let schema = json_schema();
+    let check = |definition: &str, value: Value| {
+        let definition = match definition {
+            "" => &schema,
+            name => &schema["definitions"][name],
+        };
+        if let Err(err) = check_schema(&schema, definition, &value, "") {
+            panic!("{}", err);
+        }
+    };
+
+    let project_workspace = cargo_project_workspace("hello-world-metadata.json");
+    let change_json = change_json(&project_workspace, 0);
+    let path_change_json = PathChangeJson::from_change_json(&change_json);
+    check("", serde_json::from_str(&change_json.to_versioned_json().unwrap()).unwrap());
+    check("", serde_json::from_str(&path_change_json.to_versioned_json().unwrap()).unwrap());
+    let bundle = SysrootBundle::new(&get_fake_sysroot()).unwrap();
+    check("", serde_json::from_str(&bundle.to_versioned_json().unwrap()).unwrap());
+
+    let mut delta = ChangeJsonDelta::default();
+    delta.change_file(FileId(0), None);
+    delta.change_file(FileId(1), Some(Arc::new("fn main() {}".to_string())));
+    delta.add_file(true, 1, FileId(2), &VfsPath::new_virtual_path("/foo/lib.rs".to_string()));
+    delta.remove_file(FileId(3));
+    delta.set_crate_graph(change_json.crate_graph.clone());
+    check("ChangeJsonDelta", serde_json::to_value(&delta).unwrap());
+    check("ChangeJsonDelta", serde_json::to_value(ChangeJsonDelta::default()).unwrap());
+
+    check("ProjectJsonData", get_test_json_file("hello-world-project.json"));
+    check("ProjectJsonData", get_test_json_file("is-proc-macro-project.json"));
+}
+
+/// Checks `value` against the subset of JSON schema used by `json_schema`.
+fn check_schema(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.strip_prefix("#/definitions/").unwrap();
+        return check_schema(root, &root["definitions"][name], value, path);
+    }
+    if let Some(branches) = schema.get("oneOf").and_then(Value::as_array) {
+        let matching = branches
+            .iter()
+            .filter(|branch| check_schema(root, branch, value, path).is_ok())
+            .count();
+        if matching != 1 {
+            return Err(format!("{}: {} branches of `oneOf` match", path, matching));
+        }
+        return Ok(());
+    }
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(format!("{}: expected {}, found {}", path, expected, value));
+        }
+    }
+    if let Some(variants) = schema.get("enum").and_then(Value::as_array) {
+        if !variants.contains(value) {
+            return Err(format!("{}: {} is not one of {:?}", path, value, variants));
+        }
+    }
+    if let Some(ty) = schema.get("type") {
+        let types = match ty {
+            Value::Array(types) => types.iter().map(|it| it.as_str().unwrap()).collect(),
+            ty => vec![ty.as_str().unwrap()],
+        };
+        let actual = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_u64() || n.is_i64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        if !types.contains(&actual) {
+            return Err(format!("{}: expected {:?}, found {}", path, types, value));
+        }
+    }
+    match value {
+        Value::Object(map) => {
+            for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+                if !map.contains_key(required.as_str().unwrap()) {
+                    return Err(format!("{}: missing {}", path, required));
+                }
+            }
+            for (key, value) in map {
+                let path = format!("{}/{}", path, key);
+                match schema.get("properties").and_then(|it| it.get(key)) {
+                    Some(property) => check_schema(root, property, value, &path)?,
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{}: unexpected property", path))
+                        }
+                        Some(Value::Bool(true)) | None => (),
+                        Some(schema) => check_schema(root, schema, value, &path)?,
+                    },
+                }
+            }
+        }
+        Value::Array(values) => match schema.get("items") {
+            Some(Value::Array(items)) => {
+                if items.len() != values.len() {
+                    return Err(format!("{}: expected {} items", path, items.len()));
+                }
+                for (idx, (item, value)) in items.iter().zip(values).enumerate() {
+                    check_schema(root, item, value, &format!("{}/{}", path, idx))?;
+                }
+            }
+            Some(item) => {
+                for (idx, value) in values.iter().enumerate() {
+                    check_schema(root, item, value, &format!("{}/{}", path, idx))?;
+                }
+            }
+            None => (),
+        },
+        _ => (),
+    }
+    Ok(())
+}
+
+#[test]
+fn versioned_json_rejects_invalid_snapshots() {
+    let crate_root = |file_id: u32, edition: &str| {
+        json!({
+            "file_id": file_id,
+            "edition": edition,
+            "display_name": null,
+            "cfg_options": [],
+            "potential_cfg_options": [],
+            "env": [],
+            "proc_macro_dylib_path": null,
+            "proc_macro": [],
+        })
+    };
+    let snapshot = json!({
+        "format": "change",
+        "version": JSON_FORMAT_VERSION,
+        "data": {
+            "crate_graph": {
+                "roots": [crate_root(0, "2018"), crate_root(1, "2077"), crate_root(3, "2021")],
+                "deps": [
+                    { "from": 0, "name": "b", "to": 1 },
+                    { "from": 1, "name": "c", "to": 2 },
+                    { "from": 2, "name": "a", "to": 0 },
+                    { "from": 0, "name": "d", "to": 3 },
+                    { "from": 1, "name": "not-a-name", "to": 0 },
+                ],
+            },
+            "local_roots": { "roots": [[[0, "/a.rs"], [1, "/b.rs"]], [[1, "/b.rs"]]] },
+            "library_roots": { "roots": [[[2, "/c.rs"], [1, "/b.rs"]]] },
+            "files": { "0": "", "1": "", "2": "" },
+        },
+    });
+    let errors = match ChangeJson::from_versioned_value(snapshot.clone()) {
+        Err(FormatError::Invalid(errors)) => errors,
+        res => panic!("expected validation errors, got {:?}", res),
+    };
+    assert_eq!(
+        errors,
+        [
+            ValidationError::BadEdition { krate: 1, edition: "2077".to_string() },
+            ValidationError::BadDepName { from: 1, name: "not-a-name".to_string() },
+            ValidationError::DanglingDep { from: 0, to: 3 },
+            ValidationError::CyclicDeps { path: vec![0, 1, 2, 0] },
+            ValidationError::DuplicateFileId { file_id: 1 },
+            ValidationError::MissingCrateRoot { krate: 2, file_id: 3 },
+        ]
+    );
+    // The same checks apply when loading from a string.
+    assert!(matches!(
+        ChangeJson::from_versioned_json(&snapshot.to_string()),
+        Err(FormatError::Invalid(it)) if it == errors
+    ));
+
+    let mut envelope = snapshot.clone();
+    envelope["version"] = json!(JSON_FORMAT_VERSION + 1);
+    assert!(matches!(
+        ChangeJson::from_versioned_json(&envelope.to_string()),
+        Err(FormatError::UnsupportedVersion { .. })
+    ));
+    assert!(matches!(
+        PathChangeJson::from_versioned_json(&snapshot.to_string()),
+        Err(FormatError::WrongFormat { expected: JsonFormat::PathChange, .. })
+    ));
+    assert!(matches!(
+        ChangeJson::from_versioned_json(&snapshot["data"].to_string()),
+        Err(FormatError::MissingEnvelope)
+    ));
+}
+
+#[test]
+fn change_json_delta_rejects_missing_source_roots() {
+    let path = |path: &str| VfsPath::new_virtual_path(path.to_string());
+    let mut snapshot = ChangeJson::default();
+    let mut delta = ChangeJsonDelta::default();
+    delta.add_file(false, 0, FileId(0), &path("/a.rs"));
+    delta.add_file(false, 1, FileId(1), &path("/b.rs"));
+    delta.add_file(true, 1, FileId(2), &path("/c.rs"));
+    delta.add_file(false, 3, FileId(3), &path("/d.rs"));
+    let res = snapshot.apply_delta(delta, &mut |_| Vec::new());
+    assert!(matches!(
+        res,
+        Err(FormatError::Invalid(it)) if it == [
+            ValidationError::BadSourceRoot { file_id: 2, root: 1 },
+            ValidationError::BadSourceRoot { file_id: 3, root: 3 },
+        ]
+    ));
+    // The rejected delta leaves the snapshot untouched.
+    assert!(snapshot.files().is_empty());
+}
+
+#[test]
+fn exported_crate_graph_has_no_cycles() {
+    let data: ProjectJsonData = serde_json::from_value(json!({
+        "crates": [
+            {
+                "root_module": "a/lib.rs",
+                "edition": "2018",
+                "deps": [{ "crate": 1, "name": "b" }],
+            },
+            {
+                "root_module": "b/lib.rs",
+                "edition": "2018",
+                "deps": [{ "crate": 0, "name": "a" }],
+            },
+        ],
+    }))
+    .unwrap();
+    assert!(matches!(
+        data.validate(),
+        Err(FormatError::Invalid(it)) if it == [ValidationError::CyclicDeps { path: vec![0, 1, 0] }]
+    ));
+
+    // Like `CrateGraph`, the export drops the dependency closing the cycle.
+    let project = rooted_project_json(data);
+    let project_workspace =
+        ProjectWorkspace::Json { project, sysroot: None, rustc_cfg: Vec::new() };
+    let change_json = change_json(&project_workspace, 0);
+    change_json.validate().unwrap();
+    assert_eq!(change_json.crate_graph.deps.len(), 1);
+}
+
+#[test]
+fn meta_to_json_snapshot() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../meta.json");
+    let meta: cargo_metadata::Metadata =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let workspace_root = meta.workspace_root.clone().into_std_path_buf();
+    let data = meta_to_json(meta);
+    data.validate().unwrap();
+
+    let mut actual = String::new();
+    for krate in data.crates.iter() {
+        // Members are shown by root module, as all build scripts share the
+        // same name.
+        let name = match krate.root_module.strip_prefix(&workspace_root) {
+            Ok(path)
+                if ["crates/rust-analyzer", "crates/proc_macro_test", "crates/cfg"]
+                    .iter()
+                    .any(|it| path.starts_with(it)) =>
+            {
+                path.display().to_string()
+            }
+            Err(_)
+                if krate.root_module.ends_with("src/lib.rs")
+                    && matches!(krate.display_name.as_deref(), Some("serde" | "serde_derive")) =>
+            {
+                krate.display_name.clone().unwrap()
+            }
+            _ => continue,
+        };
+        let cfg = krate.cfg.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+        let deps = krate.deps.iter().map(|it| it.name.to_string()).collect::<Vec<_>>();
+        format_to!(
+            actual,
+            "{} ({}{})\n    cfg: [{}]\n    deps: [{}]\n",
+            name,
+            if krate.is_workspace_member == Some(true) { "member" } else { "dependency" },
+            if krate.is_proc_macro { ", proc-macro" } else { "" },
+            cfg.join(", "),
+            deps.join(", "),
+        );
+    }
+    expect![[r#"
+        crates/cfg/src/lib.rs (member)
+            cfg: [debug_assertions]
+            deps: [rustc_hash, tt]
+        crates/proc_macro_test/src/lib.rs (member)
+            cfg: [debug_assertions]
+            deps: []
+        crates/proc_macro_test/build.rs (member)
+            cfg: [debug_assertions]
+            deps: [cargo_metadata, proc_macro_test_impl, toolchain]
+        crates/proc_macro_test/imp/src/lib.rs (dependency, proc-macro)
+            cfg: [debug_assertions]
+            deps: []
+        crates/rust-analyzer/src/lib.rs (member)
+            cfg: [debug_assertions]
+            deps: [always_assert, anyhow, cfg, crossbeam_channel, dissimilar, env_logger, flycheck, hir, hir_def, hir_ty, ide, ide_db, ide_ssr, itertools, jod_thread, log, lsp_server, lsp_types, oorandom, parking_lot, proc_macro_api, proc_macro_srv, profile, project_model, rayon, rustc_hash, serde, serde_json, stdx, syntax, text_edit, threadpool, toolchain, tracing, tracing_subscriber, tracing_tree, tt, vfs, vfs_notify, xflags]
+        crates/rust-analyzer/src/bin/main.rs (member)
+            cfg: [debug_assertions]
+            deps: [rust_analyzer, always_assert, anyhow, cfg, crossbeam_channel, dissimilar, env_logger, flycheck, hir, hir_def, hir_ty, ide, ide_db, ide_ssr, itertools, jod_thread, log, lsp_server, lsp_types, oorandom, parking_lot, proc_macro_api, proc_macro_srv, profile, project_model, rayon, rustc_hash, serde, serde_json, stdx, syntax, text_edit, threadpool, toolchain, tracing, tracing_subscriber, tracing_tree, tt, vfs, vfs_notify, xflags]
+        crates/rust-analyzer/tests/slow-tests/main.rs (member)
+            cfg: [debug_assertions, test]
+            deps: [rust_analyzer, always_assert, anyhow, cfg, crossbeam_channel, dissimilar, env_logger, expect_test, flycheck, hir, hir_def, hir_ty, ide, ide_db, ide_ssr, itertools, jod_thread, log, lsp_server, lsp_types, mbe, oorandom, parking_lot, proc_macro_api, proc_macro_srv, profile, project_model, rayon, rustc_hash, serde, serde_json, sourcegen, stdx, syntax, test_utils, text_edit, threadpool, toolchain, tracing, tracing_subscriber, tracing_tree, tt, vfs, vfs_notify, xflags, xshell]
+        crates/rust-analyzer/build.rs (member)
+            cfg: [debug_assertions]
+            deps: []
+        serde (dependency)
+            cfg: [debug_assertions, feature="default", feature="derive", feature="serde_derive", feature="std"]
+            deps: [serde_derive]
+        serde_derive (dependency, proc-macro)
+            cfg: [debug_assertions, feature="default"]
+            deps: [proc_macro2, quote, syn]
+    "#]].assert_eq(&actual);
+}
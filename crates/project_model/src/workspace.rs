@@ -18,8 +18,8 @@ use crate::{
     crate_graph_json::CrateGraphJson,
     rustc_cfg,
     sysroot::SysrootCrate,
-    utf8_stdout, CargoConfig, CargoWorkspace, ManifestPath, ProjectJson, ProjectManifest, Sysroot,
-    TargetKind, WorkspaceBuildScripts,
+    utf8_stdout, CargoConfig, CargoWorkspace, ManifestPath, ProjectJson, ProjectJsonData,
+    ProjectManifest, Sysroot, TargetKind, WorkspaceBuildScripts,
 };
 
 pub type CfgOverrides = FxHashMap<String, CfgDiff>;
@@ -120,9 +120,11 @@ impl ProjectWorkspace {
                 let file = fs::read_to_string(&project_json).with_context(|| {
                     format!("Failed to read json file {}", project_json.display())
                 })?;
-                let data = serde_json::from_str(&file).with_context(|| {
+                let data: ProjectJsonData = serde_json::from_str(&file).with_context(|| {
                     format!("Failed to deserialize json file {}", project_json.display())
                 })?;
+                data.validate()
+                    .with_context(|| format!("Invalid json file {}", project_json.display()))?;
                 let project_location = project_json.parent().to_path_buf();
                 let project_json = ProjectJson::new(&project_location, data);
                 ProjectWorkspace::load_inline(project_json, config.target.as_deref())?
@@ -397,11 +399,25 @@ impl ProjectWorkspace {
         load_proc_macro: &mut dyn FnMut(&AbsPath) -> Vec<ProcMacro>,
         load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
     ) -> Result<CrateGraphJson> {
-        let _p = profile::span("ProjectWorkspace::to_crate_graph");
+        let _p = profile::span("ProjectWorkspace::to_crate_graph_json");
 
-        let crate_graph_json = match self {
-            ProjectWorkspace::Json { project: _, sysroot: _, rustc_cfg: _ } => {
-                CrateGraphJson::default()
+        let mut crate_graph_json = match self {
+            ProjectWorkspace::Json { project, sysroot, rustc_cfg } => {
+                let crate_graph = project_json_to_crate_graph(
+                    rustc_cfg.clone(),
+                    load_proc_macro,
+                    load,
+                    project,
+                    sysroot,
+                );
+                let proc_macro_dylib_paths = project
+                    .crates()
+                    .filter_map(|(_, krate)| {
+                        let dylib_path = krate.proc_macro_dylib_path.clone()?;
+                        Some((load(&krate.root_module)?, dylib_path))
+                    })
+                    .collect();
+                CrateGraphJson::from_crate_graph(&crate_graph, &proc_macro_dylib_paths)
             }
             ProjectWorkspace::Cargo {
                 cargo,
@@ -420,10 +436,13 @@ impl ProjectWorkspace {
                 sysroot.as_ref(),
                 rustc,
             ),
-            ProjectWorkspace::DetachedFiles { files: _, sysroot: _, rustc_cfg: _ } => {
-                CrateGraphJson::default()
+            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg } => {
+                let crate_graph =
+                    detached_files_to_crate_graph(rustc_cfg.clone(), load, files, sysroot);
+                CrateGraphJson::from_crate_graph(&crate_graph, &FxHashMap::default())
             }
         };
+        crate_graph_json.remove_cyclic_deps();
         Ok(crate_graph_json)
     }
 }
@@ -893,7 +912,7 @@ fn add_dep(graph: &mut CrateGraph, from: CrateId, name: CrateName, to: CrateId)
 /// <https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-sets-for-crates>
 ///
 /// FIXME: ask Cargo to provide this data instead of re-deriving.
-fn inject_cargo_env(package: &PackageData, env: &mut Env) {
+pub(crate) fn inject_cargo_env(package: &PackageData, env: &mut Env) {
     // FIXME: Missing variables:
     // CARGO_BIN_NAME, CARGO_BIN_EXE_<name>
 
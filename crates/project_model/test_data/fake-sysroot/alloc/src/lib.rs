@@ -0,0 +1,25 @@
+pub mod vec {
+    pub struct Vec<T> {
+        ptr: *mut T,
+    }
+
+    impl<T> Vec<T> {
+        pub fn push(&mut self, value: T) {}
+
+        pub fn iter(&self) -> Iter<'_, T> {
+            loop {}
+        }
+    }
+
+    pub struct Iter<'a, T> {
+        vec: &'a Vec<T>,
+    }
+
+    impl<'a, T> Iterator for Iter<'a, T> {
+        type Item = &'a T;
+
+        fn next(&mut self) -> Option<&'a T> {
+            loop {}
+        }
+    }
+}
@@ -0,0 +1,41 @@
+pub mod option {
+    pub enum Option<T> {
+        None,
+        Some(T),
+    }
+
+    impl<T> Option<T> {
+        pub fn is_some(&self) -> bool {
+            loop {}
+        }
+    }
+}
+
+pub mod iter {
+    use crate::option::Option;
+
+    pub trait Iterator {
+        type Item;
+
+        fn next(&mut self) -> Option<Self::Item>;
+
+        fn map<B, F: FnMut(Self::Item) -> B>(self, f: F) -> Map<Self, F>
+        where
+            Self: Sized,
+        {
+            loop {}
+        }
+    }
+
+    pub struct Map<I, F> {
+        iter: I,
+        f: F,
+    }
+}
+
+pub mod prelude {
+    pub mod rust_2018 {
+        pub use crate::iter::Iterator;
+        pub use crate::option::Option::{self, None, Some};
+    }
+}
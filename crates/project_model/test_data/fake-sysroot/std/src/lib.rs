@@ -0,0 +1,9 @@
+pub use alloc::vec;
+pub use core::{iter, option};
+
+pub mod prelude {
+    pub mod rust_2018 {
+        pub use alloc::vec::Vec;
+        pub use core::prelude::rust_2018::*;
+    }
+}
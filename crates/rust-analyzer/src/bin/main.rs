@@ -87,7 +87,8 @@ fn try_main() -> Result<()> {
         flags::RustAnalyzerCmd::Diagnostics(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::Ssr(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::Search(cmd) => cmd.run()?,
-        flags::RustAnalyzerCmd::Json(cmd) => cmd.run()?,
+        flags::RustAnalyzerCmd::Json(cmd) => cmd.run(verbosity)?,
+        flags::RustAnalyzerCmd::AnalysisServer(cmd) => cmd.run()?,
     }
     Ok(())
 }
@@ -14,7 +14,7 @@ use lsp_types::{
 use serde_json::json;
 
 use crate::config::{Config, RustfmtConfig};
-use crate::semantic_tokens;
+use crate::{lsp_ext, semantic_tokens};
 
 pub fn server_capabilities(config: &Config) -> ServerCapabilities {
     ServerCapabilities {
@@ -173,6 +173,7 @@ fn code_action_capabilities(client_caps: &ClientCapabilities) -> CodeActionProvi
                     CodeActionKind::REFACTOR_EXTRACT,
                     CodeActionKind::REFACTOR_INLINE,
                     CodeActionKind::REFACTOR_REWRITE,
+                    lsp_ext::FIX_ALL_CODE_ACTION_KIND,
                 ]),
                 resolve_provider: Some(true),
                 work_done_progress_options: Default::default(),
@@ -8,7 +8,8 @@ mod highlight;
 mod analysis_stats;
 mod diagnostics;
 mod ssr;
-mod create_json;
+mod json;
+mod analysis_server;
 
 mod progress_report;
 
@@ -0,0 +1,664 @@
+//! Serves analysis requests on `ChangeJson` snapshots over stdio.
+//!
+//! This is meant for hosts which are not editors, like web playgrounds or
+//! batch tools. The protocol reuses the JSON-RPC framing of LSP, but none of
+//! its vocabulary: the host sends a whole `ChangeJson`, in the same versioned
+//! envelope as `rust-analyzer json export` writes, with `load`, keeps it
+//! up to date with `applyDelta`, and queries it with requests that map
+//! directly onto `ide::Analysis`. Positions are `FileId`s and byte offsets, so
+//! no line index is needed on either side.
+//!
+//! The server stops on `shutdown` followed by `exit`, like a language server.
+
+use std::panic;
+
+use ide::{
+    AnalysisHost, AssistResolveStrategy, CompletionItemKind, FileId, FilePosition, FileRange,
+    InlayKind, Severity, TextRange, TextSize,
+};
+use lsp_server::{Connection, ErrorCode, Message};
+use lsp_types::request::Request;
+use proc_macro_api::ProcMacroClient;
+use project_model::{ChangeJson, ChangeJsonDelta, VersionedJson};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use vfs::{AbsPath, AbsPathBuf};
+
+use crate::{
+    cli::{
+        flags,
+        load_cargo::{load_change_json, LoadCargoConfig},
+    },
+    config::Config,
+    dispatch::result_to_response,
+    reload::load_proc_macro,
+    LspError, Result,
+};
+
+impl flags::AnalysisServer {
+    pub fn run(self) -> Result<()> {
+        let (connection, io_threads) = Connection::stdio();
+        let root = AbsPathBuf::assert(std::env::current_dir()?);
+        let load_config = LoadCargoConfig {
+            load_out_dirs_from_check: false,
+            with_proc_macro: !self.disable_proc_macros,
+            prefill_caches: false,
+        };
+        AnalysisServer::new(root, load_config).serve(&connection)?;
+        io_threads.join()?;
+        Ok(())
+    }
+}
+
+/// Takes a `ChangeJson` in its versioned envelope.
+pub(crate) enum Load {}
+
+impl Request for Load {
+    type Params = serde_json::Value;
+    type Result = ();
+    const METHOD: &'static str = "load";
+}
+
+pub(crate) enum ApplyDelta {}
+
+impl Request for ApplyDelta {
+    type Params = ChangeJsonDelta;
+    type Result = ();
+    const METHOD: &'static str = "applyDelta";
+}
+
+/// Takes the same JSON as the `rust-analyzer` section of the LSP settings.
+pub(crate) enum UpdateConfig {}
+
+impl Request for UpdateConfig {
+    type Params = serde_json::Value;
+    type Result = ();
+    const METHOD: &'static str = "updateConfig";
+}
+
+pub(crate) enum Hover {}
+
+impl Request for Hover {
+    type Params = OffsetParams;
+    type Result = Option<HoverResult>;
+    const METHOD: &'static str = "hover";
+}
+
+pub(crate) enum Completions {}
+
+impl Request for Completions {
+    type Params = OffsetParams;
+    type Result = Option<Vec<CompletionItem>>;
+    const METHOD: &'static str = "completions";
+}
+
+pub(crate) enum GotoDefinition {}
+
+impl Request for GotoDefinition {
+    type Params = OffsetParams;
+    type Result = Option<Vec<NavigationTarget>>;
+    const METHOD: &'static str = "gotoDefinition";
+}
+
+pub(crate) enum Diagnostics {}
+
+impl Request for Diagnostics {
+    type Params = FileParams;
+    type Result = Vec<Diagnostic>;
+    const METHOD: &'static str = "diagnostics";
+}
+
+pub(crate) enum Highlight {}
+
+impl Request for Highlight {
+    type Params = FileParams;
+    type Result = Vec<HighlightRange>;
+    const METHOD: &'static str = "highlight";
+}
+
+pub(crate) enum InlayHints {}
+
+impl Request for InlayHints {
+    type Params = FileParams;
+    type Result = Vec<InlayHint>;
+    const METHOD: &'static str = "inlayHints";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileParams {
+    pub(crate) file_id: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OffsetParams {
+    pub(crate) file_id: u32,
+    /// Byte offset into the text of the file.
+    pub(crate) offset: u32,
+}
+
+/// A byte range, `end` is exclusive.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Range {
+    pub(crate) start: u32,
+    pub(crate) end: u32,
+}
+
+impl From<TextRange> for Range {
+    fn from(range: TextRange) -> Range {
+        Range { start: range.start().into(), end: range.end().into() }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct HoverResult {
+    pub(crate) range: Range,
+    pub(crate) markup: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompletionItem {
+    pub(crate) label: String,
+    pub(crate) kind: Option<String>,
+    pub(crate) detail: Option<String>,
+    /// The range the completion replaces.
+    pub(crate) source_range: Range,
+    pub(crate) edits: Vec<Edit>,
+    pub(crate) deprecated: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct Edit {
+    pub(crate) range: Range,
+    pub(crate) insert: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NavigationTarget {
+    pub(crate) file_id: u32,
+    pub(crate) full_range: Range,
+    pub(crate) focus_range: Option<Range>,
+    pub(crate) name: String,
+    pub(crate) kind: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct Diagnostic {
+    pub(crate) code: String,
+    pub(crate) message: String,
+    pub(crate) range: Range,
+    /// `error`, `warning`, `weak_warning` or `hint`.
+    pub(crate) severity: String,
+    pub(crate) unused: bool,
+    pub(crate) experimental: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HighlightRange {
+    pub(crate) range: Range,
+    /// The tag and modifiers, like `function.declaration`.
+    pub(crate) highlight: String,
+    pub(crate) binding_hash: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct InlayHint {
+    pub(crate) range: Range,
+    /// `type`, `parameter`, `chaining`, `closure_return_type`, `lifetime`,
+    /// `adjustment` or `closing_brace`.
+    pub(crate) kind: String,
+    pub(crate) label: String,
+}
+
+struct AnalysisServer {
+    config: Config,
+    load_config: LoadCargoConfig,
+    host: AnalysisHost,
+    /// What `host` currently holds, to apply deltas to.
+    change_json: ChangeJson,
+    proc_macro_client: Option<ProcMacroClient>,
+}
+
+impl AnalysisServer {
+    fn new(root: AbsPathBuf, load_config: LoadCargoConfig) -> AnalysisServer {
+        AnalysisServer {
+            config: Config::new(root, lsp_types::ClientCapabilities::default()),
+            load_config,
+            host: AnalysisHost::default(),
+            change_json: ChangeJson::default(),
+            proc_macro_client: None,
+        }
+    }
+
+    fn serve(mut self, connection: &Connection) -> Result<()> {
+        for msg in &connection.receiver {
+            match msg {
+                Message::Request(req) => {
+                    if connection.handle_shutdown(&req)? {
+                        return Ok(());
+                    }
+                    let response = self.handle(req);
+                    connection.sender.send(response.into())?;
+                }
+                Message::Notification(not) if not.method == "exit" => return Ok(()),
+                Message::Notification(not) => log::warn!("unexpected notification: {:?}", not),
+                Message::Response(resp) => log::warn!("unexpected response: {:?}", resp),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, req: lsp_server::Request) -> lsp_server::Response {
+        let mut dispatcher = Dispatcher { req: Some(req), server: self, response: None };
+        dispatcher
+            .on::<Load>(AnalysisServer::load)
+            .on::<ApplyDelta>(AnalysisServer::apply_delta)
+            .on::<UpdateConfig>(AnalysisServer::update_config)
+            .on::<Hover>(AnalysisServer::hover)
+            .on::<Completions>(AnalysisServer::completions)
+            .on::<GotoDefinition>(AnalysisServer::goto_definition)
+            .on::<Diagnostics>(AnalysisServer::diagnostics)
+            .on::<Highlight>(AnalysisServer::highlight)
+            .on::<InlayHints>(AnalysisServer::inlay_hints)
+            .finish()
+    }
+
+    fn load(&mut self, params: serde_json::Value) -> Result<()> {
+        let _p = profile::span("analysis_server::load");
+        let change_json = ChangeJson::from_versioned_value(params)
+            .map_err(|err| invalid_params(err.to_string()))?;
+        let (host, _vfs, proc_macro_client) = load_change_json(&change_json, &self.load_config)?;
+        self.host = host;
+        self.change_json = change_json;
+        self.proc_macro_client = proc_macro_client;
+        Ok(())
+    }
+
+    fn apply_delta(&mut self, delta: ChangeJsonDelta) -> Result<()> {
+        let _p = profile::span("analysis_server::apply_delta");
+        let proc_macro_client = self.proc_macro_client.as_ref();
+        let change = self
+            .change_json
+            .apply_delta(delta, &mut |path: &AbsPath| load_proc_macro(proc_macro_client, path))
+            .map_err(|err| invalid_params(err.to_string()))?;
+        self.host.apply_change(change);
+        Ok(())
+    }
+
+    fn update_config(&mut self, config: serde_json::Value) -> Result<()> {
+        self.config.update(config);
+        Ok(())
+    }
+
+    fn hover(&mut self, params: OffsetParams) -> Result<Option<HoverResult>> {
+        let position = self.position(&params)?;
+        let range =
+            FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) };
+        let res = self.host.analysis().hover(&self.config.hover(), range)?;
+        Ok(res.map(|info| HoverResult {
+            range: info.range.into(),
+            markup: info.info.markup.to_string(),
+        }))
+    }
+
+    fn completions(&mut self, params: OffsetParams) -> Result<Option<Vec<CompletionItem>>> {
+        let position = self.position(&params)?;
+        let items = match self.host.analysis().completions(&self.config.completion(), position)? {
+            Some(it) => it,
+            None => return Ok(None),
+        };
+        let items = items
+            .iter()
+            .map(|item| CompletionItem {
+                label: item.label().to_string(),
+                kind: item.kind().map(|kind| match kind {
+                    CompletionItemKind::SymbolKind(kind) => format!("{:?}", kind),
+                    kind => format!("{:?}", kind),
+                }),
+                detail: item.detail().map(ToString::to_string),
+                source_range: item.source_range().into(),
+                edits: item
+                    .text_edit()
+                    .iter()
+                    .map(|indel| Edit { range: indel.delete.into(), insert: indel.insert.clone() })
+                    .collect(),
+                deprecated: item.deprecated(),
+            })
+            .collect();
+        Ok(Some(items))
+    }
+
+    fn goto_definition(&mut self, params: OffsetParams) -> Result<Option<Vec<NavigationTarget>>> {
+        let position = self.position(&params)?;
+        let targets = match self.host.analysis().goto_definition(position)? {
+            Some(it) => it.info,
+            None => return Ok(None),
+        };
+        let targets = targets
+            .into_iter()
+            .map(|target| NavigationTarget {
+                file_id: target.file_id.0,
+                full_range: target.full_range.into(),
+                focus_range: target.focus_range.map(Into::into),
+                name: target.name.to_string(),
+                kind: target.kind.map(|kind| format!("{:?}", kind)),
+            })
+            .collect();
+        Ok(Some(targets))
+    }
+
+    fn diagnostics(&mut self, params: FileParams) -> Result<Vec<Diagnostic>> {
+        let file_id = self.file_id(params.file_id)?;
+        let diagnostics = self.host.analysis().diagnostics(
+            &self.config.diagnostics(),
+            AssistResolveStrategy::None,
+            file_id,
+        )?;
+        let res = diagnostics
+            .into_iter()
+            .map(|d| Diagnostic {
+                code: d.code.as_str().to_string(),
+                message: d.message,
+                range: d.range.into(),
+                severity: match d.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::WeakWarning => "weak_warning",
+                    Severity::Hint => "hint",
+                }
+                .to_string(),
+                unused: d.unused,
+                experimental: d.experimental,
+            })
+            .collect();
+        Ok(res)
+    }
+
+    fn highlight(&mut self, params: FileParams) -> Result<Vec<HighlightRange>> {
+        let file_id = self.file_id(params.file_id)?;
+        let res = self
+            .host
+            .analysis()
+            .highlight(file_id)?
+            .into_iter()
+            .filter(|hl| !hl.highlight.is_empty())
+            .map(|hl| HighlightRange {
+                range: hl.range.into(),
+                highlight: hl.highlight.to_string(),
+                binding_hash: hl.binding_hash,
+            })
+            .collect();
+        Ok(res)
+    }
+
+    fn inlay_hints(&mut self, params: FileParams) -> Result<Vec<InlayHint>> {
+        let file_id = self.file_id(params.file_id)?;
+        let res = self
+            .host
+            .analysis()
+            .inlay_hints(&self.config.inlay_hints(), file_id)?
+            .into_iter()
+            .map(|hint| InlayHint {
+                range: hint.range.into(),
+                kind: match hint.kind {
+                    InlayKind::TypeHint => "type",
+                    InlayKind::ParameterHint => "parameter",
+                    InlayKind::ChainingHint => "chaining",
+                    InlayKind::ClosureReturnTypeHint => "closure_return_type",
+                    InlayKind::LifetimeHint => "lifetime",
+                    InlayKind::AdjustmentHint => "adjustment",
+                    InlayKind::ClosingBraceHint => "closing_brace",
+                }
+                .to_string(),
+                label: hint.label.to_string(),
+            })
+            .collect();
+        Ok(res)
+    }
+
+    fn file_id(&self, file_id: u32) -> Result<FileId> {
+        let file_id = FileId(file_id);
+        if !self.change_json.contains_file(file_id) {
+            return Err(invalid_params(format!("unknown file {}", file_id.0)));
+        }
+        Ok(file_id)
+    }
+
+    fn position(&self, params: &OffsetParams) -> Result<FilePosition> {
+        let file_id = self.file_id(params.file_id)?;
+        let text = self.host.analysis().file_text(file_id)?;
+        if !text.is_char_boundary(params.offset as usize) {
+            return Err(invalid_params(format!(
+                "offset {} is not a char boundary of file {}",
+                params.offset, params.file_id
+            )));
+        }
+        Ok(FilePosition { file_id, offset: TextSize::from(params.offset) })
+    }
+}
+
+fn invalid_params(message: String) -> crate::Error {
+    LspError::new(ErrorCode::InvalidParams as i32, message).into()
+}
+
+/// Like `RequestDispatcher::on_sync`, but without a `GlobalState`.
+struct Dispatcher<'a> {
+    req: Option<lsp_server::Request>,
+    server: &'a mut AnalysisServer,
+    response: Option<lsp_server::Response>,
+}
+
+impl<'a> Dispatcher<'a> {
+    fn on<R>(&mut self, f: fn(&mut AnalysisServer, R::Params) -> Result<R::Result>) -> &mut Self
+    where
+        R: Request + 'static,
+        R::Params: DeserializeOwned + 'static,
+        R::Result: Serialize + 'static,
+    {
+        let req = match &self.req {
+            Some(req) if req.method == R::METHOD => self.req.take().unwrap(),
+            _ => return self,
+        };
+        let params = match crate::from_json::<R::Params>(R::METHOD, req.params) {
+            Ok(it) => it,
+            Err(err) => {
+                self.response = Some(lsp_server::Response::new_err(
+                    req.id,
+                    ErrorCode::InvalidParams as i32,
+                    err.to_string(),
+                ));
+                return self;
+            }
+        };
+        let server = panic::AssertUnwindSafe(&mut *self.server);
+        let params = panic::AssertUnwindSafe(params);
+        let result = panic::catch_unwind(move || {
+            let (panic::AssertUnwindSafe(server), panic::AssertUnwindSafe(params)) =
+                (server, params);
+            f(server, params)
+        });
+        self.response = Some(result_to_response::<R>(req.id, result));
+        self
+    }
+
+    fn finish(&mut self) -> lsp_server::Response {
+        if let Some(req) = self.req.take() {
+            log::error!("unknown request: {:?}", req);
+            return lsp_server::Response::new_err(
+                req.id,
+                ErrorCode::MethodNotFound as i32,
+                "unknown request".to_string(),
+            );
+        }
+        self.response.take().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use project_model::JSON_FORMAT_VERSION;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    struct Client {
+        connection: Connection,
+        next_id: i32,
+    }
+
+    impl Client {
+        fn request<R: Request>(&mut self, params: Value) -> std::result::Result<Value, String> {
+            self.next_id += 1;
+            let req = lsp_server::Request::new(self.next_id.into(), R::METHOD.to_string(), params);
+            self.connection.sender.send(req.into()).unwrap();
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(resp) => match resp.error {
+                    Some(err) => Err(err.message),
+                    None => Ok(resp.result.unwrap_or(Value::Null)),
+                },
+                msg => panic!("expected a response, got {:?}", msg),
+            }
+        }
+    }
+
+    fn change_json(files: &[(&str, &str)]) -> Value {
+        let roots: Vec<Value> =
+            files.iter().enumerate().map(|(id, (path, _))| json!([id, path])).collect();
+        let texts: serde_json::Map<String, Value> =
+            files.iter().enumerate().map(|(id, (_, text))| (id.to_string(), json!(text))).collect();
+        let change_json = json!({
+            "crate_graph": {
+                "roots": [{
+                    "file_id": 0,
+                    "edition": "2018",
+                    "display_name": "foo",
+                    "cfg_options": [],
+                    "potential_cfg_options": [],
+                    "env": [],
+                    "proc_macro_dylib_path": null,
+                    "proc_macro": [],
+                }],
+                "deps": [],
+            },
+            "local_roots": { "roots": [roots] },
+            "library_roots": { "roots": [] },
+            "files": texts,
+        });
+        json!({ "format": "change", "version": JSON_FORMAT_VERSION, "data": change_json })
+    }
+
+    fn with_server(f: impl FnOnce(&mut Client)) {
+        let (server, client) = Connection::memory();
+        let load_config = LoadCargoConfig {
+            load_out_dirs_from_check: false,
+            with_proc_macro: false,
+            prefill_caches: false,
+        };
+        let root = AbsPathBuf::assert(std::env::current_dir().unwrap());
+        let handle = thread::spawn(move || AnalysisServer::new(root, load_config).serve(&server));
+        let mut client = Client { connection: client, next_id: 0 };
+        f(&mut client);
+        let exit = lsp_server::Notification::new("exit".to_string(), ());
+        client.connection.sender.send(exit.into()).unwrap();
+        handle.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn analysis_server_answers_requests() {
+        let text = "fn foo(x: i32) -> i32 { x }\nfn main() { let y = foo(92); y; }\n";
+        let call = text.find("foo(92)").unwrap() as u32;
+        with_server(|client| {
+            client.request::<Load>(change_json(&[("/foo/lib.rs", text)])).unwrap();
+
+            let hover = client.request::<Hover>(json!({ "fileId": 0, "offset": call })).unwrap();
+            assert!(hover["markup"].as_str().unwrap().contains("fn foo(x: i32) -> i32"));
+
+            let targets =
+                client.request::<GotoDefinition>(json!({ "fileId": 0, "offset": call })).unwrap();
+            assert_eq!(targets[0]["name"], "foo");
+            assert_eq!(targets[0]["focusRange"], json!({ "start": 3, "end": 6 }));
+            assert_eq!(targets[0]["kind"], "Function");
+
+            let completions =
+                client.request::<Completions>(json!({ "fileId": 0, "offset": call + 1 })).unwrap();
+            assert!(completions.as_array().unwrap().iter().any(|item| item["label"] == "foo"));
+
+            let hints = client.request::<InlayHints>(json!({ "fileId": 0 })).unwrap();
+            assert!(hints
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|hint| hint["kind"] == "type" && hint["label"] == "i32"));
+
+            let highlight = client.request::<Highlight>(json!({ "fileId": 0 })).unwrap();
+            assert!(highlight
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|hl| hl["highlight"] == "function.declaration"));
+        });
+    }
+
+    #[test]
+    fn analysis_server_applies_deltas() {
+        with_server(|client| {
+            client.request::<Load>(change_json(&[("/foo/lib.rs", "fn main() {}\n")])).unwrap();
+            let diagnostics = client.request::<Diagnostics>(json!({ "fileId": 0 })).unwrap();
+            assert_eq!(diagnostics, json!([]));
+
+            client
+                .request::<ApplyDelta>(json!({ "files": { "0": "mod bar;\nfn main() {}\n" } }))
+                .unwrap();
+            let diagnostics = client.request::<Diagnostics>(json!({ "fileId": 0 })).unwrap();
+            assert_eq!(diagnostics[0]["code"], "unresolved-module");
+
+            client
+                .request::<ApplyDelta>(json!({
+                    "added_files": [{ "library": false, "root": 0, "file_id": 1, "path": "/foo/bar.rs" }],
+                    "files": { "1": "" },
+                }))
+                .unwrap();
+            let diagnostics = client.request::<Diagnostics>(json!({ "fileId": 0 })).unwrap();
+            assert_eq!(diagnostics, json!([]));
+        });
+    }
+
+    #[test]
+    fn analysis_server_rejects_bad_requests() {
+        with_server(|client| {
+            client.request::<Load>(change_json(&[("/foo/lib.rs", "fn ü() {}\n")])).unwrap();
+
+            let err = client.request::<Hover>(json!({ "fileId": 1, "offset": 0 })).unwrap_err();
+            assert_eq!(err, "unknown file 1");
+            let err = client.request::<Hover>(json!({ "fileId": 0, "offset": 4 })).unwrap_err();
+            assert_eq!(err, "offset 4 is not a char boundary of file 0");
+            let err = client.request::<Hover>(json!({ "fileId": 0, "offset": 100 })).unwrap_err();
+            assert_eq!(err, "offset 100 is not a char boundary of file 0");
+            assert!(client.request::<Hover>(json!({ "fileId": 0 })).is_err());
+
+            let mut cyclic = change_json(&[("/foo/lib.rs", "")]);
+            cyclic["data"]["crate_graph"]["deps"] = json!([{ "from": 0, "name": "foo", "to": 0 }]);
+            let err = client.request::<Load>(cyclic).unwrap_err();
+            assert_eq!(err, "cyclic dependencies: 0 -> 0");
+            let err =
+                client.request::<Load>(json!({ "format": "change", "version": 0 })).unwrap_err();
+            assert_eq!(err, "snapshot has version 0, but this rust-analyzer reads version 1");
+            // The previous snapshot is still loaded.
+            assert!(client.request::<Hover>(json!({ "fileId": 0, "offset": 0 })).is_ok());
+
+            let unknown = lsp_server::Request::new(0.into(), "references".to_string(), json!({}));
+            client.connection.sender.send(unknown.into()).unwrap();
+            match client.connection.receiver.recv().unwrap() {
+                Message::Response(resp) => {
+                    assert_eq!(resp.error.unwrap().code, ErrorCode::MethodNotFound as i32)
+                }
+                msg => panic!("expected a response, got {:?}", msg),
+            }
+        });
+    }
+}
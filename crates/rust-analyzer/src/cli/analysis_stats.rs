@@ -45,11 +45,6 @@ impl<DB: ParallelDatabase> Clone for Snap<salsa::Snapshot<DB>> {
 
 impl flags::AnalysisStats {
     pub fn run(self, verbosity: Verbosity) -> Result<()> {
-        let mut rng = {
-            let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
-            Rand32::new(seed)
-        };
-
         let mut db_load_sw = self.stop_watch();
         let mut cargo_config = CargoConfig::default();
         cargo_config.no_sysroot = self.no_sysroot;
@@ -60,9 +55,19 @@ impl flags::AnalysisStats {
         };
         let (host, vfs, _proc_macro) =
             load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
-        let db = host.raw_database();
         eprintln!("{:<20} {}", "Database loaded:", db_load_sw.elapsed());
 
+        self.run_on(host, vfs, verbosity)
+    }
+
+    /// Runs the analysis on an already loaded database.
+    pub(crate) fn run_on(self, host: AnalysisHost, vfs: Vfs, verbosity: Verbosity) -> Result<()> {
+        let mut rng = {
+            let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+            Rand32::new(seed)
+        };
+        let db = host.raw_database();
+
         let mut analysis_sw = self.stop_watch();
         let mut num_crates = 0;
         let mut visited_modules = FxHashSet::default();
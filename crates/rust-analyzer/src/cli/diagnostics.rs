@@ -1,75 +1,241 @@
-//! Analyze all modules in a project for diagnostics. Exits with a non-zero
-//! status code if any errors are found.
+//! Analyze all modules in a project for diagnostics, and print them as text or
+//! in a machine-readable format. Exits with a non-zero status code if any
+//! errors are found or, with `--severity`, if any diagnostics are reported.
+
+use std::{
+    env, fmt, fs,
+    io::{self, BufRead},
+    path::PathBuf,
+    str::FromStr,
+};
 
 use rustc_hash::FxHashSet;
 
 use hir::{db::HirDatabase, Crate, Module};
-use ide::{AssistResolveStrategy, DiagnosticsConfig, Severity};
+use ide::{AnalysisHost, AssistResolveStrategy, Diagnostic, DiagnosticsConfig, FileId, Severity};
 use ide_db::base_db::SourceDatabaseExt;
+use serde_json::json;
+use syntax::TextSize;
+use text_edit::TextEdit;
+use vfs::{AbsPathBuf, Vfs};
 
-use crate::cli::{
-    flags,
-    load_cargo::{load_workspace_at, LoadCargoConfig},
+use crate::{
+    cli::{
+        flags,
+        load_cargo::{load_workspace_at, LoadCargoConfig},
+    },
+    config::Config,
 };
 
 impl flags::Diagnostics {
     pub fn run(self) -> anyhow::Result<()> {
+        let cwd = env::current_dir()?;
+        let mut config = Config::new(AbsPathBuf::assert(cwd.join(&self.path)), Default::default());
+        if let Some(path) = &self.config {
+            config.update(serde_json::from_str(&fs::read_to_string(path)?)?);
+        }
+        let config = config.diagnostics();
+        let files = if self.changed_files {
+            let mut files = FxHashSet::default();
+            for line in io::stdin().lock().lines() {
+                let line = line?;
+                let line = line.trim();
+                if !line.is_empty() {
+                    files.insert(AbsPathBuf::assert(cwd.join(line)).normalize());
+                }
+            }
+            Some(files)
+        } else {
+            None
+        };
+        let options = DiagnosticsOptions {
+            config,
+            format: self.format.unwrap_or(DiagnosticsFormat::Human),
+            severity: self.severity.as_deref().map(parse_severity).transpose()?,
+            codes: self.code.into_iter().collect(),
+            files,
+            fixes: self.fixes,
+        };
+
         let cargo_config = Default::default();
         let load_cargo_config = LoadCargoConfig {
             load_out_dirs_from_check: !self.disable_build_scripts,
             with_proc_macro: !self.disable_proc_macros,
             prefill_caches: false,
         };
-        let (host, _vfs, _proc_macro) =
+        let (host, vfs, _proc_macro) =
             load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
-        let db = host.raw_database();
-        let analysis = host.analysis();
-
-        let mut found_error = false;
-        let mut visited_files = FxHashSet::default();
-
-        let work = all_modules(db).into_iter().filter(|module| {
-            let file_id = module.definition_source(db).file_id.original_file(db);
-            let source_root = db.file_source_root(file_id);
-            let source_root = db.source_root(source_root);
-            !source_root.is_library
-        });
-
-        for module in work {
-            let file_id = module.definition_source(db).file_id.original_file(db);
-            if !visited_files.contains(&file_id) {
-                let crate_name =
-                    module.krate().display_name(db).as_deref().unwrap_or("unknown").to_string();
-                println!("processing crate: {}, module: {}", crate_name, _vfs.file_path(file_id));
-                for diagnostic in analysis
-                    .diagnostics(
-                        &DiagnosticsConfig::default(),
-                        AssistResolveStrategy::None,
-                        file_id,
-                    )
-                    .unwrap()
-                {
-                    if matches!(diagnostic.severity, Severity::Error) {
-                        found_error = true;
-                    }
-
-                    println!("{:?}", diagnostic);
-                }
+        run_diagnostics(&host, &vfs, &options)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticsFormat {
+    Human,
+    Json,
+    Sarif,
+    Checkstyle,
+}
+
+impl FromStr for DiagnosticsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DiagnosticsFormat, String> {
+        match s {
+            "human" => Ok(DiagnosticsFormat::Human),
+            "json" => Ok(DiagnosticsFormat::Json),
+            "sarif" => Ok(DiagnosticsFormat::Sarif),
+            "checkstyle" => Ok(DiagnosticsFormat::Checkstyle),
+            _ => Err(format!(
+                "unknown format `{}`, expected `human`, `json`, `sarif` or `checkstyle`",
+                s
+            )),
+        }
+    }
+}
+
+/// Which diagnostics `run_diagnostics` reports, and how.
+pub(crate) struct DiagnosticsOptions {
+    pub(crate) config: DiagnosticsConfig,
+    pub(crate) format: DiagnosticsFormat,
+    /// Only report diagnostics at least this severe, and fail if there are any.
+    pub(crate) severity: Option<Severity>,
+    /// Only report diagnostics with these codes, all of them if empty.
+    pub(crate) codes: FxHashSet<String>,
+    /// Only check these files.
+    pub(crate) files: Option<FxHashSet<AbsPathBuf>>,
+    /// Also print the fixes of the diagnostics, as patches.
+    pub(crate) fixes: bool,
+}
+
+impl Default for DiagnosticsOptions {
+    fn default() -> Self {
+        DiagnosticsOptions {
+            config: DiagnosticsConfig::default(),
+            format: DiagnosticsFormat::Human,
+            severity: None,
+            codes: FxHashSet::default(),
+            files: None,
+            fixes: false,
+        }
+    }
+}
+
+/// A diagnostic with its location and fixes resolved for printing.
+struct Reported {
+    path: String,
+    /// One-based line and column, in characters.
+    start: (usize, usize),
+    end: (usize, usize),
+    diagnostic: Diagnostic,
+    /// The label and patch of each fix.
+    fixes: Vec<(String, String)>,
+}
+
+/// Prints the diagnostics of all local modules of an already loaded database.
+pub(crate) fn run_diagnostics(
+    host: &AnalysisHost,
+    vfs: &Vfs,
+    options: &DiagnosticsOptions,
+) -> anyhow::Result<()> {
+    let db = host.raw_database();
+    let analysis = host.analysis();
+    let cwd = env::current_dir()?;
+    let human = options.format == DiagnosticsFormat::Human;
+    let resolve =
+        if options.fixes { AssistResolveStrategy::All } else { AssistResolveStrategy::None };
+
+    let mut failed = false;
+    let mut reported = Vec::new();
+    let mut visited_files = FxHashSet::default();
+
+    let work = all_modules(db).into_iter().filter(|module| {
+        let file_id = module.definition_source(db).file_id.original_file(db);
+        let source_root = db.file_source_root(file_id);
+        let source_root = db.source_root(source_root);
+        !source_root.is_library
+    });
 
-                visited_files.insert(file_id);
+    for module in work {
+        let file_id = module.definition_source(db).file_id.original_file(db);
+        if !visited_files.insert(file_id) {
+            continue;
+        }
+        if let Some(files) = &options.files {
+            match vfs.file_path(file_id).as_path() {
+                Some(path) if files.contains(&path.normalize()) => (),
+                _ => continue,
             }
         }
+        if human {
+            let crate_name =
+                module.krate().display_name(db).as_deref().unwrap_or("unknown").to_string();
+            println!("processing crate: {}, module: {}", crate_name, vfs.file_path(file_id));
+        }
+        let text = analysis.file_text(file_id)?;
+        let path = display_path(vfs, file_id, &cwd);
+        for diagnostic in analysis.diagnostics(&options.config, resolve.clone(), file_id)? {
+            if let Some(severity) = options.severity {
+                if severity_rank(diagnostic.severity) < severity_rank(severity) {
+                    continue;
+                }
+            }
+            if !options.codes.is_empty() && !options.codes.contains(diagnostic.code.as_str()) {
+                continue;
+            }
+            if options.severity.is_some() || matches!(diagnostic.severity, Severity::Error) {
+                failed = true;
+            }
 
-        println!();
-        println!("diagnostic scan complete");
+            let mut fixes = Vec::new();
+            for fix in diagnostic.fixes.iter().flatten() {
+                let source_change = match &fix.source_change {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let mut patch = String::new();
+                for (&file_id, edit) in &source_change.source_file_edits {
+                    let text = analysis.file_text(file_id)?;
+                    patch.push_str(&unified_diff(&display_path(vfs, file_id, &cwd), &text, edit));
+                }
+                fixes.push((fix.label.to_string(), patch));
+            }
 
-        if found_error {
+            if human {
+                println!("{:?}", diagnostic);
+                for (_, patch) in &fixes {
+                    print!("{}", patch);
+                }
+                continue;
+            }
+            reported.push(Reported {
+                start: line_column(&text, diagnostic.range.start()),
+                end: line_column(&text, diagnostic.range.end()),
+                path: path.clone(),
+                diagnostic,
+                fixes,
+            });
+        }
+    }
+
+    match options.format {
+        DiagnosticsFormat::Human => {
             println!();
-            anyhow::bail!("diagnostic error detected")
+            println!("diagnostic scan complete");
         }
+        DiagnosticsFormat::Json => println!("{}", to_json(&reported)),
+        DiagnosticsFormat::Sarif => println!("{}", to_sarif(&reported)),
+        DiagnosticsFormat::Checkstyle => print!("{}", to_checkstyle(&reported)),
+    }
 
-        Ok(())
+    if failed {
+        if human {
+            println!();
+        }
+        anyhow::bail!("diagnostic error detected")
     }
+
+    Ok(())
 }
 
 fn all_modules(db: &dyn HirDatabase) -> Vec<Module> {
@@ -84,3 +250,324 @@ fn all_modules(db: &dyn HirDatabase) -> Vec<Module> {
 
     modules
 }
+
+fn parse_severity(s: &str) -> anyhow::Result<Severity> {
+    let severity = match s {
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        "weak-warning" => Severity::WeakWarning,
+        "hint" => Severity::Hint,
+        _ => anyhow::bail!(
+            "unknown severity `{}`, expected `error`, `warning`, `weak-warning` or `hint`",
+            s
+        ),
+    };
+    Ok(severity)
+}
+
+fn severity_rank(severity: Severity) -> u8 {
+    match severity {
+        Severity::Hint => 0,
+        Severity::WeakWarning => 1,
+        Severity::Warning => 2,
+        Severity::Error => 3,
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::WeakWarning => "weak-warning",
+        Severity::Hint => "hint",
+    }
+}
+
+/// The path of the file relative to the current directory if it is inside of
+/// it, so that patches can be applied from there.
+fn display_path(vfs: &Vfs, file_id: FileId, cwd: &std::path::Path) -> String {
+    let path = vfs.file_path(file_id);
+    match path.as_path() {
+        Some(path) => {
+            let path = PathBuf::from(path.as_ref());
+            match path.strip_prefix(cwd) {
+                Ok(relative) => relative.display().to_string(),
+                Err(_) => path.display().to_string(),
+            }
+        }
+        None => path.to_string(),
+    }
+}
+
+fn line_column(text: &str, offset: TextSize) -> (usize, usize) {
+    let before = &text[..usize::from(offset)];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |it| it + 1);
+    (line + 1, before[line_start..].chars().count() + 1)
+}
+
+/// Renders `edit` of `text` as a unified diff, which `git apply` or `patch -p1`
+/// can apply.
+fn unified_diff(path: &str, text: &str, edit: &TextEdit) -> String {
+    const CONTEXT: usize = 3;
+
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut line_starts: Vec<usize> = lines
+        .iter()
+        .scan(0, |start, line| {
+            let it = *start;
+            *start += line.len();
+            Some(it)
+        })
+        .collect();
+    line_starts.push(text.len());
+    let line_of = |offset: usize| {
+        let line = line_starts.partition_point(|&it| it <= offset).saturating_sub(1);
+        line.min(lines.len().saturating_sub(1))
+    };
+
+    // Group the edits into hunks of the lines they change, merging those
+    // whose context would overlap.
+    let mut hunks: Vec<(usize, usize, Vec<&text_edit::Indel>)> = Vec::new();
+    for indel in edit.iter() {
+        let start = usize::from(indel.delete.start());
+        let end = usize::from(indel.delete.end());
+        let first = line_of(start);
+        let last = if end > start { line_of(end - 1) } else { first };
+        let end_line = (last + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, prev_end, indels)) if first <= *prev_end + 2 * CONTEXT => {
+                *prev_end = (*prev_end).max(end_line);
+                indels.push(indel);
+            }
+            _ => hunks.push((first, end_line, vec![indel])),
+        }
+    }
+
+    let mut res = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut delta = 0isize;
+    for (first, end, indels) in hunks {
+        let context_start = first.saturating_sub(CONTEXT);
+        let context_end = (end + CONTEXT).min(lines.len());
+        let base = line_starts[first];
+        let mut new_text = text[base..line_starts[end]].to_string();
+        for indel in indels.iter().rev() {
+            let range =
+                usize::from(indel.delete.start()) - base..usize::from(indel.delete.end()) - base;
+            new_text.replace_range(range, &indel.insert);
+        }
+        let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+
+        let old_len = context_end - context_start;
+        let new_len = old_len - (end - first) + new_lines.len();
+        let old_start = if old_len == 0 { context_start } else { context_start + 1 };
+        let new_start = (old_start as isize + delta) as usize;
+        let new_start = if new_len == 0 { new_start.saturating_sub(1) } else { new_start.max(1) };
+        res.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+        delta += new_lines.len() as isize - (end - first) as isize;
+
+        let mut push_line = |prefix: char, line: &str| {
+            res.push(prefix);
+            res.push_str(line);
+            if !line.ends_with('\n') {
+                res.push_str("\n\\ No newline at end of file\n");
+            }
+        };
+        lines[context_start..first].iter().for_each(|line| push_line(' ', line));
+        lines[first..end].iter().for_each(|line| push_line('-', line));
+        new_lines.iter().for_each(|line| push_line('+', line));
+        lines[end..context_end].iter().for_each(|line| push_line(' ', line));
+    }
+    res
+}
+
+fn to_json(reported: &[Reported]) -> serde_json::Value {
+    let diagnostics = reported
+        .iter()
+        .map(|it| {
+            json!({
+                "file": it.path,
+                "code": it.diagnostic.code.as_str(),
+                "severity": severity_name(it.diagnostic.severity),
+                "message": it.diagnostic.message,
+                "start": { "line": it.start.0, "column": it.start.1 },
+                "end": { "line": it.end.0, "column": it.end.1 },
+                "fixes": it.fixes.iter().map(|(label, patch)| json!({
+                    "label": label,
+                    "patch": patch,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(diagnostics)
+}
+
+/// A SARIF 2.1.0 log, which code scanning services understand.
+fn to_sarif(reported: &[Reported]) -> serde_json::Value {
+    let mut rules: Vec<&str> = reported.iter().map(|it| it.diagnostic.code.as_str()).collect();
+    rules.sort_unstable();
+    rules.dedup();
+    let results = reported
+        .iter()
+        .map(|it| {
+            let level = match it.diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::WeakWarning | Severity::Hint => "note",
+            };
+            let location = json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": it.path },
+                    "region": {
+                        "startLine": it.start.0,
+                        "startColumn": it.start.1,
+                        "endLine": it.end.0,
+                        "endColumn": it.end.1,
+                    },
+                },
+            });
+            json!({
+                "ruleId": it.diagnostic.code.as_str(),
+                "level": level,
+                "message": { "text": it.diagnostic.message },
+                "locations": [location],
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "rust-analyzer",
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+/// A Checkstyle XML report, which many CI systems can display.
+fn to_checkstyle(reported: &[Reported]) -> String {
+    let mut res = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    res.push_str("<checkstyle version=\"4.3\">\n");
+    let mut files: Vec<&str> = reported.iter().map(|it| it.path.as_str()).collect();
+    files.dedup();
+    for file in files {
+        res.push_str(&format!("  <file name=\"{}\">\n", Xml(file)));
+        for it in reported.iter().filter(|it| it.path == file) {
+            let severity = match it.diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::WeakWarning | Severity::Hint => "info",
+            };
+            res.push_str(&format!(
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"rust-analyzer.{}\"/>\n",
+                it.start.0,
+                it.start.1,
+                severity,
+                Xml(&it.diagnostic.message),
+                Xml(it.diagnostic.code.as_str()),
+            ));
+        }
+        res.push_str("  </file>\n");
+    }
+    res.push_str("</checkstyle>\n");
+    res
+}
+
+/// Escapes text for use in XML attributes.
+struct Xml<'a>(&'a str);
+
+impl fmt::Display for Xml<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                '\n' => f.write_str("&#10;")?,
+                _ => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use syntax::TextRange;
+
+    use super::*;
+
+    #[test]
+    fn unified_diff_hunks() {
+        let text = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn";
+        let mut builder = TextEdit::builder();
+        builder.replace(TextRange::new(2.into(), 3.into()), "B\nB2".to_string());
+        builder.delete(TextRange::new(22.into(), 24.into()));
+        builder.insert(27.into(), "!".to_string());
+        let edit = builder.finish();
+        expect![[r#"
+            --- a/src/lib.rs
+            +++ b/src/lib.rs
+            @@ -1,5 +1,6 @@
+             a
+            -b
+            +B
+            +B2
+             c
+             d
+             e
+            @@ -9,6 +10,5 @@
+             i
+             j
+             k
+            -l
+            -m
+            -n
+            \ No newline at end of file
+            +m
+            +n!
+            \ No newline at end of file
+        "#]]
+        .assert_eq(&unified_diff("src/lib.rs", text, &edit));
+    }
+
+    #[test]
+    fn checkstyle_escapes_messages() {
+        let diagnostic = Diagnostic {
+            code: ide::DiagnosticCode("unresolved-macro-call"),
+            message: "unresolved macro `<m>!`".to_string(),
+            range: TextRange::default(),
+            severity: Severity::Error,
+            unused: false,
+            experimental: false,
+            lint: None,
+            fixes: None,
+            related: Vec::new(),
+        };
+        let reported = Reported {
+            path: "src/lib.rs".to_string(),
+            start: (1, 1),
+            end: (1, 4),
+            diagnostic,
+            fixes: Vec::new(),
+        };
+        expect![[r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <checkstyle version="4.3">
+              <file name="src/lib.rs">
+                <error line="1" column="1" severity="error" message="unresolved macro `&lt;m&gt;!`" source="rust-analyzer.unresolved-macro-call"/>
+              </file>
+            </checkstyle>
+        "#]]
+        .assert_eq(&to_checkstyle(&[reported]));
+    }
+}
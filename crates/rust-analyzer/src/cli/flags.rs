@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 use ide_ssr::{SsrPattern, SsrRule};
 
-use crate::cli::Verbosity;
+use crate::cli::{diagnostics::DiagnosticsFormat, Verbosity};
 
 xflags::xflags! {
     src "./src/cli/flags.rs"
@@ -86,6 +86,20 @@ xflags::xflags! {
             optional --disable-build-scripts
             /// Don't use expand proc macros.
             optional --disable-proc-macros
+            /// Read rust-analyzer settings, e.g. `{ "diagnostics": { "severity": { .. } } }`,
+            /// from this JSON file.
+            optional --config path: PathBuf
+            /// Output format: `human`, `json`, `sarif` or `checkstyle`.
+            optional --format format: DiagnosticsFormat
+            /// Only report diagnostics at least this severe (`error`, `warning`,
+            /// `weak-warning` or `hint`), and fail if there are any.
+            optional --severity severity: String
+            /// Only report diagnostics with this code.
+            repeated --code code: String
+            /// Only check the files listed on stdin, one path per line.
+            optional --changed-files
+            /// Also print the fixes of the diagnostics, as patches.
+            optional --fixes
         }
 
         cmd ssr
@@ -103,11 +117,61 @@ xflags::xflags! {
 
         cmd proc-macro {}
 
-        cmd json
-            /// Directory with Cargo.toml.
-            required path: PathBuf
-        {}
+        /// Snapshot a project as JSON, or analyze such a snapshot.
+        cmd json {
+            /// Write the project's change set (crate graph, source roots and
+            /// file contents) as JSON.
+            cmd export
+                /// Directory with Cargo.toml or rust-project.json.
+                required path: PathBuf
+            {
+                /// Write to this file instead of stdout.
+                optional -o, --output path: PathBuf
+                /// Only write the project model as rust-project.json, computed from `cargo metadata`.
+                optional --project-json
+                /// Identify files and crates by path instead of by id.
+                optional --path-based
+                /// Don't load sysroot crates (`std`, `core` & friends).
+                optional --no-sysroot
+                /// Don't run build scripts or load `OUT_DIR` values by running `cargo check`.
+                optional --disable-build-scripts
+                /// Don't record proc macros.
+                optional --disable-proc-macros
+            }
+
+            /// Load a snapshot written by `json export` and batch typecheck it.
+            cmd import
+                /// The JSON snapshot.
+                required path: PathBuf
+            {
+                /// Complete the snapshot with the sysroot from this bundle.
+                optional --sysroot-bundle path: PathBuf
+                /// Print diagnostics instead of analysis stats.
+                optional --diagnostics
+                /// Also analyze all dependencies.
+                optional --with-deps
+                /// Don't use expand proc macros.
+                optional --disable-proc-macros
+                /// Only resolve names, don't run type inference.
+                optional --skip-inference
+            }
+
+            /// Bundle the sources of `core`, `alloc` and `std`, for importing
+            /// snapshots exported with `--no-sysroot` without a toolchain.
+            cmd sysroot-bundle {
+                /// Write to this file instead of stdout.
+                optional -o, --output path: PathBuf
+            }
+
+            /// Print the JSON schema of the snapshot formats.
+            cmd schema {}
+        }
 
+        /// Serve analysis requests on `ChangeJson` snapshots over stdio.
+        cmd analysis-server {
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
     }
 }
 
@@ -136,6 +200,7 @@ pub enum RustAnalyzerCmd {
     Search(Search),
     ProcMacro(ProcMacro),
     Json(Json),
+    AnalysisServer(AnalysisServer),
 }
 
 #[derive(Debug)]
@@ -180,6 +245,12 @@ pub struct Diagnostics {
 
     pub disable_build_scripts: bool,
     pub disable_proc_macros: bool,
+    pub config: Option<PathBuf>,
+    pub format: Option<DiagnosticsFormat>,
+    pub severity: Option<String>,
+    pub code: Vec<String>,
+    pub changed_files: bool,
+    pub fixes: bool,
 }
 
 #[derive(Debug)]
@@ -199,7 +270,51 @@ pub struct ProcMacro;
 
 #[derive(Debug)]
 pub struct Json {
+    pub subcommand: JsonCmd,
+}
+
+#[derive(Debug)]
+pub enum JsonCmd {
+    Export(Export),
+    Import(Import),
+    SysrootBundle(SysrootBundle),
+    Schema(Schema),
+}
+
+#[derive(Debug)]
+pub struct Export {
+    pub path: PathBuf,
+
+    pub output: Option<PathBuf>,
+    pub project_json: bool,
+    pub path_based: bool,
+    pub no_sysroot: bool,
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
+#[derive(Debug)]
+pub struct Import {
     pub path: PathBuf,
+
+    pub sysroot_bundle: Option<PathBuf>,
+    pub diagnostics: bool,
+    pub with_deps: bool,
+    pub disable_proc_macros: bool,
+    pub skip_inference: bool,
+}
+
+#[derive(Debug)]
+pub struct SysrootBundle {
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct Schema;
+
+#[derive(Debug)]
+pub struct AnalysisServer {
+    pub disable_proc_macros: bool,
 }
 
 impl RustAnalyzer {
@@ -1,48 +1,235 @@
-//! Read project from Cargo.toml convert to rust-project.json
+//! Snapshots a project as JSON, so that it can be analyzed on a machine
+//! without cargo, and analyzes such snapshots.
 
-use project_model::{ManifestPath, ProjectJson, ProjectManifest, ProjectWorkspace};
+use std::{fs, io::Write, path::Path, sync::Arc};
 
-use std::convert::TryFrom;
-use std::path::Path;
+use anyhow::{bail, Result};
+use crossbeam_channel::{unbounded, Receiver};
+use proc_macro_api::ProcMacroClient;
+use project_model::{
+    json_schema, meta_to_json, CargoConfig, CargoWorkspace, ChangeJson, CrateGraphJson,
+    FormatError, JsonFormat, PathChangeJson, ProjectManifest, ProjectWorkspace, Sysroot,
+    SysrootBundle, VersionedJson, WorkspaceBuildScripts,
+};
+use vfs::{loader::Handle, AbsPath, AbsPathBuf};
 
-use anyhow::Result;
-use project_model::{meta_to_json, CargoConfig, CargoWorkspace};
-use vfs::AbsPathBuf;
-
-use crate::cli::flags;
+use crate::{
+    cli::{
+        diagnostics::{run_diagnostics, DiagnosticsOptions},
+        flags,
+        load_cargo::{load_change_json, load_proc_macro_client, LoadCargoConfig},
+        Verbosity,
+    },
+    reload::{load_proc_macro, ProjectFolders, SourceRootConfig},
+};
 
 impl flags::Json {
-    pub fn run(self) -> anyhow::Result<()> {
-        let cargo_config = Default::default();
-        let _p = profile::span("json");
-        //   let root = self.path;
-        //   let root = AbsPathBuf::assert(std::env::current_dir()?.join(root));
-        //  let root = ProjectManifest::discover_single(&root)?;
-        //  let workspace = ProjectWorkspace::load(root, &cargo_config, &|_|{})?;
-        //  println!("es: {:?}", workspace);
-        let _res = load_workspace_at(&self.path, &cargo_config, &|_| {});
+    pub fn run(self, verbosity: Verbosity) -> Result<()> {
+        match self.subcommand {
+            flags::JsonCmd::Export(cmd) => cmd.run(),
+            flags::JsonCmd::Import(cmd) => cmd.run(verbosity),
+            flags::JsonCmd::SysrootBundle(cmd) => cmd.run(),
+            flags::JsonCmd::Schema(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl flags::Export {
+    pub fn run(self) -> Result<()> {
+        let _p = profile::span("json export");
+        let mut cargo_config = CargoConfig::default();
+        cargo_config.no_sysroot = self.no_sysroot;
+        let root = AbsPathBuf::assert(std::env::current_dir()?.join(&self.path));
+        let manifest = ProjectManifest::discover_single(&root)?;
+
+        let json = if self.project_json {
+            let cargo_toml = match manifest {
+                ProjectManifest::CargoToml(cargo_toml) => cargo_toml,
+                ProjectManifest::ProjectJson(_) => {
+                    bail!(
+                        "--project-json needs a cargo project, {} has a rust-project.json",
+                        root.display()
+                    )
+                }
+            };
+            let meta = CargoWorkspace::fetch_metadata(&cargo_toml, &cargo_config, &|_| {})?;
+            serde_json::to_string_pretty(&meta_to_json(meta))?
+        } else {
+            let load_cargo_config = LoadCargoConfig {
+                load_out_dirs_from_check: !self.disable_build_scripts,
+                with_proc_macro: !self.disable_proc_macros,
+                prefill_caches: false,
+            };
+            let workspace = ProjectWorkspace::load(manifest, &cargo_config, &|_| {})?;
+            let (change_json, _proc_macro) =
+                workspace_to_change_json(workspace, &cargo_config, &load_cargo_config, &|_| {})?;
+            if self.path_based {
+                PathChangeJson::from_change_json(&change_json).to_versioned_json()?
+            } else {
+                change_json.to_versioned_json()?
+            }
+        };
+
+        write_output(self.output.as_deref(), &json)
+    }
+}
+
+impl flags::SysrootBundle {
+    pub fn run(self) -> Result<()> {
+        let sysroot = Sysroot::discover(&AbsPathBuf::assert(std::env::current_dir()?))?;
+        let bundle = SysrootBundle::new(&sysroot)?;
+        write_output(self.output.as_deref(), &bundle.to_versioned_json()?)
+    }
+}
+
+impl flags::Schema {
+    pub fn run(self) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(&json_schema())?);
         Ok(())
     }
 }
 
-fn load_workspace_at(
-    root: &Path,
+fn write_output(output: Option<&Path>, json: &str) -> Result<()> {
+    match output {
+        Some(output) => fs::write(output, json)?,
+        None => {
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            stdout.write_all(json.as_bytes())?;
+            writeln!(stdout)?;
+        }
+    }
+    Ok(())
+}
+
+impl flags::Import {
+    pub fn run(self, verbosity: Verbosity) -> Result<()> {
+        let mut db_load_sw = profile::StopWatch::start();
+        let json = fs::read_to_string(&self.path)?;
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: false,
+            with_proc_macro: !self.disable_proc_macros,
+            prefill_caches: false,
+        };
+        let mut change_json = match ChangeJson::from_versioned_json(&json) {
+            Err(FormatError::WrongFormat { found, .. })
+                if found == JsonFormat::PathChange.to_string() =>
+            {
+                PathChangeJson::from_versioned_json(&json)?.to_change_json(&mut vfs::Vfs::default())
+            }
+            res => res?,
+        };
+        if let Some(sysroot_bundle) = &self.sysroot_bundle {
+            let sysroot_bundle =
+                SysrootBundle::from_versioned_json(&fs::read_to_string(sysroot_bundle)?)?;
+            sysroot_bundle.add_to(&mut change_json);
+        }
+        let (host, vfs, _proc_macro) = load_change_json(&change_json, &load_cargo_config)?;
+
+        if self.diagnostics {
+            return run_diagnostics(&host, &vfs, &DiagnosticsOptions::default());
+        }
+
+        eprintln!("{:<20} {}", "Database loaded:", db_load_sw.elapsed());
+        let analysis_stats = flags::AnalysisStats {
+            path: self.path,
+            randomize: false,
+            parallel: false,
+            memory_usage: false,
+            source_stats: false,
+            only: None,
+            with_deps: self.with_deps,
+            no_sysroot: false,
+            disable_build_scripts: true,
+            disable_proc_macros: self.disable_proc_macros,
+            skip_inference: self.skip_inference,
+        };
+        analysis_stats.run_on(host, vfs, verbosity)
+    }
+}
+
+pub(crate) fn workspace_to_change_json(
+    mut ws: ProjectWorkspace,
     cargo_config: &CargoConfig,
+    load_config: &LoadCargoConfig,
     progress: &dyn Fn(String),
-) -> Result<()> {
-    let cargo_toml = AbsPathBuf::assert(std::env::current_dir()?.join(root.clone()));
-    let cargo_toml = ManifestPath::try_from(cargo_toml).unwrap();
-    let meta = CargoWorkspace::fetch_metadata(&cargo_toml, cargo_config, progress)?;
-    // let json = serde_json::to_string(&meta).expect("serialization of crate_graph must work");
-    // println!("{:}", json);
-    let json = meta_to_json(meta);
-    let root = AbsPathBuf::assert(std::env::current_dir()?.join(root));
-    let json = ProjectJson::new(&root, json);
-    let ws = ProjectWorkspace::load_inline(json, None).unwrap();
-    // println!("{:?}", ws);
-    // let json = serde_json::to_string(&json).expect("serialization of crate_graph must work");
-    // println!("{:?}", json);
-    //    let project = ProjectJson::new(&cargo_toml.parent().to_path_buf(), json);
-    //    println!("success: {:?}", project);
-    Ok(())
+) -> Result<(ChangeJson, Option<ProcMacroClient>)> {
+    let (sender, receiver) = unbounded();
+    let mut vfs = vfs::Vfs::default();
+    let mut loader = {
+        let loader =
+            vfs_notify::NotifyHandle::spawn(Box::new(move |msg| sender.send(msg).unwrap()));
+        Box::new(loader)
+    };
+
+    let proc_macro_client = load_proc_macro_client(load_config)?;
+
+    ws.set_build_scripts(if load_config.load_out_dirs_from_check {
+        ws.run_build_scripts(cargo_config, progress)?
+    } else {
+        WorkspaceBuildScripts::default()
+    });
+
+    let crate_graph_json = ws.to_crate_graph_json(
+        &mut |path: &AbsPath| load_proc_macro(proc_macro_client.as_ref(), path),
+        &mut |path: &AbsPath| {
+            let contents = loader.load_sync(path);
+            let path = vfs::VfsPath::from(path.to_path_buf());
+            vfs.set_file_contents(path.clone(), contents);
+            vfs.file_id(&path)
+        },
+    )?;
+
+    let project_folders = ProjectFolders::new(&[ws], &[]);
+    loader.set_config(vfs::loader::Config {
+        load: project_folders.load,
+        watch: vec![],
+        version: 0,
+    });
+
+    log::debug!("crate graph: {:?}", crate_graph_json);
+
+    let change_json =
+        load_files(crate_graph_json, project_folders.source_root_config, &mut vfs, &receiver);
+
+    Ok((change_json, proc_macro_client))
+}
+
+fn load_files(
+    crate_graph_json: CrateGraphJson,
+    source_root_config: SourceRootConfig,
+    vfs: &mut vfs::Vfs,
+    receiver: &Receiver<vfs::loader::Message>,
+) -> ChangeJson {
+    let mut change_json = ChangeJson::default();
+    // wait until Vfs has loaded all roots
+    for task in receiver {
+        match task {
+            vfs::loader::Message::Progress { n_done, n_total, config_version: _ } => {
+                if n_done == n_total {
+                    break;
+                }
+            }
+            vfs::loader::Message::Loaded { files } => {
+                for (path, contents) in files {
+                    vfs.set_file_contents(path.into(), contents);
+                }
+            }
+        }
+    }
+    let changes = vfs.take_changes();
+    for file in changes {
+        if file.exists() {
+            let contents = vfs.file_contents(file.file_id).to_vec();
+            if let Ok(text) = String::from_utf8(contents) {
+                change_json.change_file(file.file_id, Some(Arc::new(text)))
+            }
+        }
+    }
+    let source_roots = source_root_config.partition(vfs);
+    change_json.set_roots(source_roots);
+
+    change_json.set_crate_graph(crate_graph_json);
+
+    change_json
 }
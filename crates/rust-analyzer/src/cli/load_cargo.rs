@@ -8,8 +8,10 @@ use hir::db::DefDatabase;
 use ide::{AnalysisHost, Change};
 use ide_db::base_db::CrateGraph;
 use proc_macro_api::ProcMacroClient;
-use project_model::{CargoConfig, ProjectManifest, ProjectWorkspace, WorkspaceBuildScripts};
-use vfs::{loader::Handle, AbsPath, AbsPathBuf};
+use project_model::{
+    CargoConfig, ChangeJson, ProjectManifest, ProjectWorkspace, WorkspaceBuildScripts,
+};
+use vfs::{loader::Handle, AbsPath, AbsPathBuf, VfsPath};
 
 use crate::reload::{load_proc_macro, ProjectFolders, SourceRootConfig};
 
@@ -55,12 +57,7 @@ pub fn load_workspace(
         Box::new(loader)
     };
 
-    let proc_macro_client = if load_config.with_proc_macro {
-        let path = AbsPathBuf::assert(std::env::current_exe()?);
-        Some(ProcMacroClient::extern_process(path, &["proc-macro"]).unwrap())
-    } else {
-        None
-    };
+    let proc_macro_client = load_proc_macro_client(load_config)?;
 
     ws.set_build_scripts(if load_config.load_out_dirs_from_check {
         ws.run_build_scripts(cargo_config, progress)?
@@ -95,6 +92,54 @@ pub fn load_workspace(
     Ok((host, vfs, proc_macro_client))
 }
 
+// Note: Since this function is used by external tools that use rust-analyzer as a library
+// what otherwise would be `pub(crate)` has to be `pub` here instead.
+//
+// Loads a snapshot written by `rust-analyzer json export`. No cargo is needed, only the
+// proc macro dylibs recorded in the snapshot have to be present.
+pub fn load_change_json(
+    change_json: &ChangeJson,
+    load_config: &LoadCargoConfig,
+) -> Result<(AnalysisHost, vfs::Vfs, Option<ProcMacroClient>)> {
+    // Rebuild the `Vfs`, so that it hands out the same `FileId`s as the snapshot.
+    let mut vfs = vfs::Vfs::default();
+    for (file_id, path, text) in change_json.files() {
+        while vfs.len() < file_id.0 as usize {
+            let placeholder = VfsPath::new_virtual_path(format!("/missing/{}", vfs.len()));
+            vfs.set_file_contents(placeholder, None);
+        }
+        vfs.set_file_contents(path, text.map(|text| text.as_bytes().to_vec()));
+    }
+    vfs.take_changes();
+
+    let proc_macro_client = load_proc_macro_client(load_config)?;
+
+    let change = change_json
+        .to_change(&mut |path: &AbsPath| load_proc_macro(proc_macro_client.as_ref(), path))?;
+
+    let lru_cap = std::env::var("RA_LRU_CAP").ok().and_then(|it| it.parse::<usize>().ok());
+    let mut host = AnalysisHost::new(lru_cap);
+    host.raw_database_mut().set_enable_proc_attr_macros(true);
+    host.apply_change(change);
+
+    if load_config.prefill_caches {
+        host.analysis().prime_caches(|_| {})?;
+    }
+    Ok((host, vfs, proc_macro_client))
+}
+
+/// Spawns the proc macro server, which is this binary started with the
+/// `proc-macro` subcommand, if `load_config` asks for proc macros.
+pub(crate) fn load_proc_macro_client(
+    load_config: &LoadCargoConfig,
+) -> Result<Option<ProcMacroClient>> {
+    if !load_config.with_proc_macro {
+        return Ok(None);
+    }
+    let path = AbsPathBuf::assert(std::env::current_exe()?);
+    Ok(Some(ProcMacroClient::extern_process(path, &["proc-macro"])?))
+}
+
 fn load_crate_graph(
     crate_graph: CrateGraph,
     source_root_config: SourceRootConfig,
@@ -145,6 +190,9 @@ mod tests {
     use super::*;
 
     use hir::Crate;
+    use ide_db::base_db::{FileId, SourceDatabase, SourceDatabaseExt};
+    use oorandom::Rand32;
+    use project_model::{ChangeJsonDelta, CrateGraphJson};
 
     #[test]
     fn test_loading_rust_analyzer() {
@@ -162,4 +210,134 @@ mod tests {
         // RA has quite a few crates, but the exact count doesn't matter
         assert!(n_crates > 20);
     }
+
+    #[test]
+    fn change_json_deltas_match_snapshots() {
+        let load_config = LoadCargoConfig {
+            load_out_dirs_from_check: false,
+            with_proc_macro: false,
+            prefill_caches: false,
+        };
+        for seed in 0..20 {
+            let mut rng = Rand32::new(seed);
+            let mut snapshot = ChangeJson::default();
+            let (mut host, _vfs, _proc_macro) = load_change_json(&snapshot, &load_config).unwrap();
+            let mut live_files: Vec<(FileId, VfsPath)> = Vec::new();
+            let mut next_file_id = 0;
+            // The number of local and library roots.
+            let mut n_roots = [0, 0];
+
+            for step in 0..30 {
+                let mut delta = ChangeJsonDelta::default();
+                let mut text_only = true;
+                let mut removed = false;
+                for _ in 0..rng.rand_range(1..4) {
+                    let text = Arc::new(format!("fn f{}_{}() {{}}", seed, step));
+                    match rng.rand_range(0..5) {
+                        0 if !live_files.is_empty() => {
+                            let (file_id, _) = pick(&mut rng, &live_files);
+                            delta.change_file(file_id, Some(text));
+                        }
+                        1 | 0 => {
+                            let file_id = FileId(next_file_id);
+                            next_file_id += 1;
+                            let path = VfsPath::new_virtual_path(format!("/{}.rs", file_id.0));
+                            let library = rng.rand_range(0..2) == 0;
+                            let root = pick_root(&mut rng, n_roots[library as usize]);
+                            delta.add_file(library, root, file_id, &path);
+                            delta.change_file(file_id, Some(text));
+                            live_files.push((file_id, path));
+                            text_only = false;
+                        }
+                        2 if !live_files.is_empty() => {
+                            let idx = rng.rand_range(0..live_files.len() as u32) as usize;
+                            let (file_id, _) = live_files.swap_remove(idx);
+                            delta.remove_file(file_id);
+                            removed = true;
+                            text_only = false;
+                        }
+                        3 if !live_files.is_empty() => {
+                            let (file_id, path) = pick(&mut rng, &live_files);
+                            let library = rng.rand_range(0..2) == 0;
+                            let root = pick_root(&mut rng, n_roots[library as usize]);
+                            delta.add_file(library, root, file_id, &path);
+                            text_only = false;
+                        }
+                        _ => {
+                            delta.set_crate_graph(random_crate_graph(&mut rng, &live_files));
+                            text_only = false;
+                        }
+                    }
+                }
+
+                if removed {
+                    // The removed files may be crate roots.
+                    delta.set_crate_graph(random_crate_graph(&mut rng, &live_files));
+                }
+
+                // Deltas are meant to be streamed, so always go through JSON.
+                let delta: ChangeJsonDelta =
+                    serde_json::from_str(&serde_json::to_string(&delta).unwrap()).unwrap();
+                let change = snapshot.apply_delta(delta, &mut |_| Vec::new()).unwrap();
+                let json = serde_json::to_value(&snapshot).unwrap();
+                for (n_roots, roots) in n_roots.iter_mut().zip(["local_roots", "library_roots"]) {
+                    *n_roots = json[roots]["roots"].as_array().unwrap().len();
+                }
+                if text_only {
+                    assert!(change.roots.is_none() && change.crate_graph.is_none());
+                }
+                host.apply_change(change);
+
+                let (fresh, _vfs, _proc_macro) = load_change_json(&snapshot, &load_config).unwrap();
+                let (db, fresh_db) = (host.raw_database(), fresh.raw_database());
+                for (file_id, _, _) in snapshot.files() {
+                    assert_eq!(db.file_text(file_id), fresh_db.file_text(file_id));
+                    assert_eq!(
+                        db.source_root(db.file_source_root(file_id)),
+                        fresh_db.source_root(fresh_db.file_source_root(file_id)),
+                    );
+                }
+                assert_eq!(
+                    format!("{:?}", db.crate_graph()),
+                    format!("{:?}", fresh_db.crate_graph())
+                );
+            }
+        }
+    }
+
+    fn pick(rng: &mut Rand32, files: &[(FileId, VfsPath)]) -> (FileId, VfsPath) {
+        files[rng.rand_range(0..files.len() as u32) as usize].clone()
+    }
+
+    /// Picks one of the at most three roots, or the one past the last root.
+    fn pick_root(rng: &mut Rand32, n_roots: usize) -> usize {
+        rng.rand_range(0..n_roots.min(2) as u32 + 1) as usize
+    }
+
+    fn random_crate_graph(rng: &mut Rand32, files: &[(FileId, VfsPath)]) -> CrateGraphJson {
+        let roots = files
+            .iter()
+            .filter(|_| rng.rand_range(0..2) == 0)
+            .enumerate()
+            .map(|(idx, (file_id, _))| {
+                serde_json::json!({
+                    "file_id": file_id.0,
+                    "edition": "2018",
+                    "display_name": format!("c{}", idx),
+                    "cfg_options": [["test", []]],
+                    "potential_cfg_options": [],
+                    "env": [],
+                    "proc_macro_dylib_path": null,
+                    "proc_macro": [],
+                })
+            })
+            .collect::<Vec<_>>();
+        let deps = (1..roots.len())
+            .map(|from| {
+                let to = rng.rand_range(0..from as u32);
+                serde_json::json!({ "from": from, "name": format!("c{}", to), "to": to })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({ "roots": roots, "deps": deps })).unwrap()
+    }
 }
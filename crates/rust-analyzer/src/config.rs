@@ -12,7 +12,7 @@ use std::{ffi::OsString, iter, path::PathBuf};
 use flycheck::FlycheckConfig;
 use ide::{
     AssistConfig, CompletionConfig, DiagnosticsConfig, HighlightRelatedConfig, HoverConfig,
-    HoverDocFormat, InlayHintsConfig, JoinLinesConfig,
+    HoverDocFormat, InlayHintsConfig, JoinLinesConfig, Severity,
 };
 use ide_db::helpers::{
     insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
@@ -126,6 +126,11 @@ config_data! {
         diagnostics_enableExperimental: bool    = "true",
         /// List of rust-analyzer diagnostics to disable.
         diagnostics_disabled: FxHashSet<String> = "[]",
+        /// Map of rust-analyzer diagnostic codes to the severity to report
+        /// them with: `error`, `warning`, `weak-warning` or `hint`. Lint
+        /// attributes like `#[deny(non_snake_case)]` in the source take
+        /// precedence.
+        diagnostics_severity: FxHashMap<String, DiagnosticSeverityDef> = "{}",
         /// Map of prefixes to be substituted when parsing diagnostic file paths.
         /// This should be the reverse mapping of what is passed to `rustc` as `--remap-path-prefix`.
         diagnostics_remapPrefix: FxHashMap<String, String> = "{}",
@@ -166,11 +171,15 @@ config_data! {
         /// their contents.
         highlighting_strings: bool = "true",
 
+        /// Whether to show the values of constants, statics and enum discriminants on hover.
+        hover_constValues: bool         = "true",
         /// Whether to show documentation on hover.
         hover_documentation: bool       = "true",
         /// Use markdown syntax for links in hover.
         hover_linksInHover |
         hoverActions_linksInHover: bool = "true",
+        /// Whether to show the size and alignment of types, and the offsets of fields, on hover.
+        hover_memoryLayout: bool        = "true",
 
         /// Whether to show `Debug` action. Only applies when
         /// `#rust-analyzer.hoverActions.enable#` is set.
@@ -190,15 +199,30 @@ config_data! {
         /// `#rust-analyzer.hoverActions.enable#` is set.
         hoverActions_run: bool             = "true",
 
+        /// Whether to show implicit reborrows, auto-derefs and auto-refs as
+        /// inlay hints.
+        inlayHints_adjustmentHints: bool            = "false",
         /// Whether to show inlay type hints for method chains.
-        inlayHints_chainingHints: bool      = "true",
+        inlayHints_chainingHints: bool              = "true",
+        /// Whether to show inlay hints after the closing brace of a long item
+        /// naming the item it belongs to.
+        inlayHints_closingBraceHints: bool          = "true",
+        /// Minimum number of lines an item has to span for a closing brace
+        /// hint to be shown.
+        inlayHints_closingBraceHintsMinLines: usize = "25",
+        /// Whether to show inlay type hints for return types of closures with
+        /// a block body.
+        inlayHints_closureReturnTypeHints: bool     = "false",
+        /// Whether to show inlay hints for elided lifetimes in function
+        /// signatures.
+        inlayHints_lifetimeElisionHints: bool       = "false",
         /// Maximum length for inlay hints. Set to null to have an unlimited length.
-        inlayHints_maxLength: Option<usize> = "25",
+        inlayHints_maxLength: Option<usize>         = "25",
         /// Whether to show function parameter name inlay hints at the call
         /// site.
-        inlayHints_parameterHints: bool     = "true",
+        inlayHints_parameterHints: bool             = "true",
         /// Whether to show inlay type hints for variables.
-        inlayHints_typeHints: bool          = "true",
+        inlayHints_typeHints: bool                  = "true",
 
         /// Join lines inserts else between consecutive ifs.
         joinLines_joinElseIf: bool = "true",
@@ -607,6 +631,21 @@ impl Config {
         DiagnosticsConfig {
             disable_experimental: !self.data.diagnostics_enableExperimental,
             disabled: self.data.diagnostics_disabled.clone(),
+            severity: self
+                .data
+                .diagnostics_severity
+                .iter()
+                .map(|(code, severity)| {
+                    let severity = match severity {
+                        DiagnosticSeverityDef::Error => Severity::Error,
+                        DiagnosticSeverityDef::Warning => Severity::Warning,
+                        DiagnosticSeverityDef::WeakWarning => Severity::WeakWarning,
+                        DiagnosticSeverityDef::Hint => Severity::Hint,
+                    };
+                    (code.clone(), severity)
+                })
+                .collect(),
+            insert_use: Some(self.insert_use_config()),
         }
     }
     pub fn diagnostics_map(&self) -> DiagnosticsMapConfig {
@@ -731,6 +770,14 @@ impl Config {
             type_hints: self.data.inlayHints_typeHints,
             parameter_hints: self.data.inlayHints_parameterHints,
             chaining_hints: self.data.inlayHints_chainingHints,
+            closure_return_type_hints: self.data.inlayHints_closureReturnTypeHints,
+            lifetime_elision_hints: self.data.inlayHints_lifetimeElisionHints,
+            adjustment_hints: self.data.inlayHints_adjustmentHints,
+            closing_brace_hints: if self.data.inlayHints_closingBraceHints {
+                Some(self.data.inlayHints_closingBraceHintsMinLines)
+            } else {
+                None
+            },
             max_length: self.data.inlayHints_maxLength,
         }
     }
@@ -835,6 +882,8 @@ impl Config {
                     HoverDocFormat::PlainText
                 }
             }),
+            const_values: self.data.hover_constValues,
+            memory_layout: self.data.hover_memoryLayout,
         }
     }
 
@@ -920,6 +969,15 @@ enum ImportGranularityDef {
     Module,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+enum DiagnosticSeverityDef {
+    Error,
+    Warning,
+    WeakWarning,
+    Hint,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 enum ImportPrefixDef {
@@ -1073,6 +1131,17 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
+        "FxHashMap<String, DiagnosticSeverityDef>" => set! {
+            "type": "object",
+            "additionalProperties": {
+                "type": "string",
+                "enum": ["error", "warning", "weak-warning", "hint"],
+            },
+        },
+        "usize" => set! {
+            "type": "integer",
+            "minimum": 0,
+        },
         "Option<usize>" => set! {
             "type": ["null", "integer"],
             "minimum": 0,
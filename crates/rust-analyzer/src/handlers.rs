@@ -9,8 +9,8 @@ use std::{
 
 use ide::{
     AnnotationConfig, AssistKind, AssistResolveStrategy, FileId, FilePosition, FileRange,
-    HoverAction, HoverGotoTypeData, Query, RangeInfo, Runnable, RunnableKind, SingleResolve,
-    SourceChange, TextEdit,
+    FixAllScope, HoverAction, HoverGotoTypeData, Query, RangeInfo, Runnable, RunnableKind,
+    SingleResolve, SourceChange, TextEdit,
 };
 use ide_db::SymbolKind;
 use itertools::Itertools;
@@ -18,11 +18,12 @@ use lsp_server::ErrorCode;
 use lsp_types::{
     CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
     CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
-    CodeLens, CompletionItem, Diagnostic, DiagnosticTag, DocumentFormattingParams, FoldingRange,
-    FoldingRangeParams, HoverContents, Location, NumberOrString, Position, PrepareRenameResponse,
-    Range, RenameParams, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
-    SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
-    SemanticTokensResult, SymbolInformation, SymbolTag, TextDocumentIdentifier, Url, WorkspaceEdit,
+    CodeLens, CompletionItem, Diagnostic, DiagnosticRelatedInformation, DiagnosticTag,
+    DocumentFormattingParams, FoldingRange, FoldingRangeParams, HoverContents, Location,
+    NumberOrString, Position, PrepareRenameResponse, Range, RenameParams,
+    SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensParams,
+    SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult, SymbolInformation,
+    SymbolTag, TextDocumentIdentifier, Url, WorkspaceEdit,
 };
 use project_model::TargetKind;
 use serde_json::json;
@@ -1021,6 +1022,59 @@ pub(crate) fn handle_code_action(
         }
     }
 
+    // Fixes for all diagnostics of the file at once. Computing those means
+    // computing the fixes of every diagnostic, so only do it when asked for.
+    let fix_all_kind = lsp_ext::FIX_ALL_CODE_ACTION_KIND;
+    let requested = params
+        .context
+        .only
+        .iter()
+        .flatten()
+        .map(|it| it.as_str())
+        .filter(|&kind| {
+            kind == fix_all_kind.as_str()
+                || fix_all_kind.as_str().starts_with(&format!("{}.", kind))
+        })
+        .collect_vec();
+    if !requested.is_empty() {
+        let config = snap.config.diagnostics();
+        let codes = if requested.iter().any(|kind| kind.starts_with("source.fixAll")) {
+            // Asked for explicitly, e.g. on save: fix everything with a
+            // single action, as the client applies all of them.
+            vec![None]
+        } else {
+            snap.analysis
+                .diagnostics(&config, AssistResolveStrategy::None, frange.file_id)?
+                .into_iter()
+                .filter(|it| it.fixes.is_some() && it.range.intersect(frange.range).is_some())
+                .map(|it| Some(it.code.0))
+                .unique()
+                .collect()
+        };
+        for code in codes {
+            let fix_all =
+                snap.analysis.fix_all(&config, FixAllScope::File(frange.file_id), code)?;
+            if fix_all.fixed == 0 {
+                continue;
+            }
+            let mut title = match code {
+                Some(code) => format!("Fix all `{}` problems in this file", code),
+                None => "Fix all auto-fixable problems".to_string(),
+            };
+            if !fix_all.conflicts.is_empty() {
+                format_to!(title, " ({} overlapping fixes left out)", fix_all.conflicts.len());
+            }
+            res.push(lsp_ext::CodeAction {
+                title,
+                group: None,
+                kind: Some(fix_all_kind.clone()),
+                edit: Some(to_proto::snippet_workspace_edit(&snap, fix_all.source_change)?),
+                is_preferred: None,
+                data: None,
+            });
+        }
+    }
+
     Ok(Some(res))
 }
 
@@ -1225,6 +1279,21 @@ pub(crate) fn publish_diagnostics(
         .into_iter()
         .map(|d| Diagnostic {
             range: to_proto::range(&line_index, d.range),
+            related_information: if d.related.is_empty() {
+                None
+            } else {
+                Some(
+                    d.related
+                        .iter()
+                        .filter_map(|(range, message)| {
+                            Some(DiagnosticRelatedInformation {
+                                location: to_proto::location(snap, *range).ok()?,
+                                message: message.clone(),
+                            })
+                        })
+                        .collect(),
+                )
+            },
             severity: Some(to_proto::diagnostic_severity(d.severity)),
             code: Some(NumberOrString::String(d.code.as_str().to_string())),
             code_description: Some(lsp_types::CodeDescription {
@@ -1236,7 +1305,6 @@ pub(crate) fn publish_diagnostics(
             }),
             source: Some("rust-analyzer".to_string()),
             message: d.message,
-            related_information: None,
             tags: if d.unused { Some(vec![DiagnosticTag::Unnecessary]) } else { None },
             data: None,
         })
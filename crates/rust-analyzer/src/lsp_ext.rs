@@ -9,6 +9,11 @@ use lsp_types::{
 };
 use serde::{Deserialize, Serialize};
 
+/// The kind of the code actions which apply the fixes for many diagnostics at
+/// once.
+pub const FIX_ALL_CODE_ACTION_KIND: CodeActionKind =
+    CodeActionKind::new("source.fixAll.rust-analyzer");
+
 pub enum AnalyzerStatus {}
 
 impl Request for AnalyzerStatus {
@@ -239,6 +244,10 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    ClosureReturnTypeHint,
+    LifetimeHint,
+    AdjustmentHint,
+    ClosingBraceHint,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -84,7 +84,8 @@ pub(crate) fn document_highlight_kind(
 pub(crate) fn diagnostic_severity(severity: Severity) -> lsp_types::DiagnosticSeverity {
     match severity {
         Severity::Error => lsp_types::DiagnosticSeverity::Error,
-        Severity::WeakWarning => lsp_types::DiagnosticSeverity::Hint,
+        Severity::Warning => lsp_types::DiagnosticSeverity::Warning,
+        Severity::WeakWarning | Severity::Hint => lsp_types::DiagnosticSeverity::Hint,
     }
 }
 
@@ -404,6 +405,10 @@ pub(crate) fn inlay_hint(line_index: &LineIndex, inlay_hint: InlayHint) -> lsp_e
             InlayKind::ParameterHint => lsp_ext::InlayKind::ParameterHint,
             InlayKind::TypeHint => lsp_ext::InlayKind::TypeHint,
             InlayKind::ChainingHint => lsp_ext::InlayKind::ChainingHint,
+            InlayKind::ClosureReturnTypeHint => lsp_ext::InlayKind::ClosureReturnTypeHint,
+            InlayKind::LifetimeHint => lsp_ext::InlayKind::LifetimeHint,
+            InlayKind::AdjustmentHint => lsp_ext::InlayKind::AdjustmentHint,
+            InlayKind::ClosingBraceHint => lsp_ext::InlayKind::ClosingBraceHint,
         },
     }
 }
@@ -0,0 +1,113 @@
+//! Checks that the JSON snapshots work with real proc macros, built by cargo
+//! and expanded by the proc macro server.
+
+use std::{path::PathBuf, process::Command, sync::Arc};
+
+use ide_db::base_db::{Env, FileId, ProcMacro, ProcMacroExpander, ProcMacroKind};
+use proc_macro_api::{ProcMacroClient, ProcMacroProcessExpander};
+use project_model::{CrateGraphJson, ProjectJson, ProjectJsonData, ProjectWorkspace};
+use serde_json::{json, Value};
+use test_utils::{project_root, skip_slow_tests};
+use vfs::{AbsPath, AbsPathBuf};
+
+use crate::testdir::TestDir;
+
+#[test]
+fn crate_graph_json_round_trips_real_proc_macros() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let target_dir = TestDir::new();
+    let dylib = build_proc_macro_test(target_dir.path());
+    let server = AbsPathBuf::assert(PathBuf::from(env!("CARGO_BIN_EXE_rust-analyzer")));
+    let client = ProcMacroClient::extern_process(server, &["proc-macro"]).unwrap();
+    let mut load_proc_macro = |path: &AbsPath| -> Vec<ProcMacro> {
+        client.by_dylib_path(path).into_iter().map(to_proc_macro).collect()
+    };
+
+    let data: ProjectJsonData = serde_json::from_value(json!({
+        "crates": [{
+            "root_module": "crates/proc_macro_test/imp/src/lib.rs",
+            "edition": "2018",
+            "deps": [],
+            "is_proc_macro": true,
+            "proc_macro_dylib_path": dylib,
+        }],
+    }))
+    .unwrap();
+    let project = ProjectJson::new(&AbsPathBuf::assert(project_root()), data);
+    let workspace = ProjectWorkspace::Json { project, sysroot: None, rustc_cfg: Vec::new() };
+    let mut load = |_: &AbsPath| Some(FileId(0));
+
+    let direct = workspace.to_crate_graph(&mut load_proc_macro, &mut load);
+    let crate_graph_json = workspace.to_crate_graph_json(&mut load_proc_macro, &mut load).unwrap();
+    let json = serde_json::to_string(&crate_graph_json).unwrap();
+    let crate_graph_json: CrateGraphJson = serde_json::from_str(&json).unwrap();
+    let via_json = crate_graph_json.to_crate_graph(&mut load_proc_macro).unwrap();
+
+    let input = tt::Subtree::default();
+    let env = Env::default();
+    let krate = direct.iter().next().unwrap();
+    let (direct, via_json) = (&direct[krate].proc_macro, &via_json[krate].proc_macro);
+    assert!(direct.iter().any(|it| &*it.name == "fn_like_noop"));
+    assert_eq!(format!("{:?}", direct), format!("{:?}", via_json));
+    for (direct, via_json) in direct.iter().zip(via_json) {
+        assert_eq!(
+            direct.expander.expand(&input, None, &env),
+            via_json.expander.expand(&input, None, &env)
+        );
+    }
+}
+
+/// Builds the proc macros of `proc_macro_test` like its build script does,
+/// and returns the path of the dylib.
+fn build_proc_macro_test(target_dir: &std::path::Path) -> PathBuf {
+    let output = Command::new(toolchain::cargo())
+        .current_dir(project_root().join("crates/proc_macro_test/imp"))
+        .args(&["build", "-p", "proc_macro_test_impl", "--message-format", "json"])
+        .arg("--target-dir")
+        .arg(target_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Reads the messages by hand, as `cargo_metadata` can't parse the package
+    // ids of newer cargos.
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter(|message| {
+            message["target"]["kind"]
+                .as_array()
+                .map_or(false, |kinds| kinds.contains(&"proc-macro".into()))
+        })
+        .find_map(|message| Some(PathBuf::from(message["filenames"][0].as_str()?)))
+        .expect("no dylib for proc_macro_test_impl found")
+}
+
+fn to_proc_macro(expander: ProcMacroProcessExpander) -> ProcMacro {
+    let kind = match expander.kind() {
+        proc_macro_api::ProcMacroKind::CustomDerive => ProcMacroKind::CustomDerive,
+        proc_macro_api::ProcMacroKind::FuncLike => ProcMacroKind::FuncLike,
+        proc_macro_api::ProcMacroKind::Attr => ProcMacroKind::Attr,
+    };
+    ProcMacro { name: expander.name().into(), kind, expander: Arc::new(Expander(expander)) }
+}
+
+#[derive(Debug)]
+struct Expander(ProcMacroProcessExpander);
+
+impl ProcMacroExpander for Expander {
+    fn expand(
+        &self,
+        subtree: &tt::Subtree,
+        attrs: Option<&tt::Subtree>,
+        env: &Env,
+    ) -> Result<tt::Subtree, tt::ExpansionError> {
+        let env = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        self.0.expand(subtree, attrs, env)
+    }
+}
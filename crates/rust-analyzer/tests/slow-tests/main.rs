@@ -12,6 +12,7 @@ mod sourcegen;
 mod tidy;
 mod testdir;
 mod support;
+mod json;
 
 use std::{collections::HashMap, path::PathBuf, time::Instant};
 
@@ -22,10 +23,10 @@ use lsp_types::{
         CodeActionRequest, Completion, Formatting, GotoTypeDefinition, HoverRequest,
         WillRenameFiles,
     },
-    CodeActionContext, CodeActionParams, CompletionParams, DidOpenTextDocumentParams,
-    DocumentFormattingParams, FileRename, FormattingOptions, GotoDefinitionParams, HoverParams,
-    PartialResultParams, Position, Range, RenameFilesParams, TextDocumentItem,
-    TextDocumentPositionParams, WorkDoneProgressParams,
+    CodeActionContext, CodeActionKind, CodeActionParams, CompletionParams,
+    DidOpenTextDocumentParams, DocumentFormattingParams, FileRename, FormattingOptions,
+    GotoDefinitionParams, HoverParams, PartialResultParams, Position, Range, RenameFilesParams,
+    TextDocumentItem, TextDocumentPositionParams, WorkDoneProgressParams,
 };
 use rust_analyzer::lsp_ext::{OnEnter, Runnables, RunnablesParams};
 use serde_json::json;
@@ -402,6 +403,83 @@ fn main() {}
     );
 }
 
+#[test]
+fn test_fix_all_code_action() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = project(
+        r#"
+//- /Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+//- /src/lib.rs
+struct S { a: i32, b: i32 }
+fn f(a: i32, b: i32) -> S {
+    S { a: a, b: b }
+}
+"#,
+    )
+    .wait_until_workspace_is_loaded();
+
+    server.request::<CodeActionRequest>(
+        CodeActionParams {
+            text_document: server.doc_id("src/lib.rs"),
+            range: Range::new(Position::new(2, 8), Position::new(2, 8)),
+            context: CodeActionContext {
+                diagnostics: Vec::new(),
+                only: Some(vec![CodeActionKind::from("source.fixAll")]),
+            },
+            partial_result_params: PartialResultParams::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        },
+        json!([{
+            "title": "Fix all auto-fixable problems",
+            "kind": "source.fixAll.rust-analyzer",
+            "edit": {
+              "documentChanges": [
+                {
+                  "textDocument": { "uri": "file:///[..]/src/lib.rs", "version": null },
+                  "edits": [
+                    {
+                      "range": {
+                        "start": { "line": 2, "character": 8 },
+                        "end": { "line": 2, "character": 8 }
+                      },
+                      "newText": "a"
+                    },
+                    {
+                      "range": {
+                        "start": { "line": 2, "character": 8 },
+                        "end": { "line": 2, "character": 12 }
+                      },
+                      "newText": ""
+                    },
+                    {
+                      "range": {
+                        "start": { "line": 2, "character": 14 },
+                        "end": { "line": 2, "character": 14 }
+                      },
+                      "newText": "b"
+                    },
+                    {
+                      "range": {
+                        "start": { "line": 2, "character": 14 },
+                        "end": { "line": 2, "character": 18 }
+                      },
+                      "newText": ""
+                    }
+                  ]
+                }
+              ]
+            }
+        }]),
+    );
+}
+
 #[test]
 fn test_missing_module_code_action_in_json_project() {
     if skip_slow_tests() {
@@ -396,6 +396,11 @@ pub fn literal_pat(lit: &str) -> ast::LiteralPat {
     }
 }
 
+// FIXME: should not be pub
+pub fn pat_from_text(text: &str) -> ast::Pat {
+    ast_from_text(&format!("fn f() {{ match x {{ {} => {{}} }} }}", text))
+}
+
 /// Creates a tuple of patterns from an iterator of patterns.
 ///
 /// Invariant: `pats` must be length > 0
@@ -807,7 +807,7 @@ impl ast::RangePat {
     pub fn start(&self) -> Option<ast::Pat> {
         self.syntax()
             .children_with_tokens()
-            .take_while(|it| !(it.kind() == T![..] || it.kind() == T![..=]))
+            .take_while(|it| !is_range_pat_op(it.kind()))
             .filter_map(|it| it.into_node())
             .find_map(ast::Pat::cast)
     }
@@ -815,10 +815,23 @@ impl ast::RangePat {
     pub fn end(&self) -> Option<ast::Pat> {
         self.syntax()
             .children_with_tokens()
-            .skip_while(|it| !(it.kind() == T![..] || it.kind() == T![..=]))
+            .skip_while(|it| !is_range_pat_op(it.kind()))
             .filter_map(|it| it.into_node())
             .find_map(ast::Pat::cast)
     }
+
+    pub fn op_kind(&self) -> Option<ast::RangeOp> {
+        self.syntax().children_with_tokens().find_map(|it| match it.kind() {
+            T![..] => Some(ast::RangeOp::Exclusive),
+            // `...` is the obsolete spelling of `..=`.
+            T![..=] | T![...] => Some(ast::RangeOp::Inclusive),
+            _ => None,
+        })
+    }
+}
+
+fn is_range_pat_op(kind: SyntaxKind) -> bool {
+    matches!(kind, T![..] | T![..=] | T![...])
 }
 
 impl ast::TokenTree {
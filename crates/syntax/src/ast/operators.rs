@@ -62,6 +62,17 @@ pub enum ArithOp {
     BitAnd,
 }
 
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            UnaryOp::Deref => "*",
+            UnaryOp::Not => "!",
+            UnaryOp::Neg => "-",
+        };
+        f.write_str(res)
+    }
+}
+
 impl fmt::Display for LogicOp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
@@ -5,7 +5,7 @@ use std::{
     convert::{TryFrom, TryInto},
 };
 
-use rustc_lexer::unescape::{unescape_literal, Mode};
+use rustc_lexer::unescape::{unescape_byte, unescape_char, unescape_literal, Mode};
 
 use crate::{
     ast::{self, AstToken},
@@ -676,6 +676,22 @@ impl ast::FloatNumber {
     }
 }
 
+impl ast::Char {
+    pub fn value(&self) -> Option<char> {
+        let text = self.text();
+        let text = text.strip_prefix('\'')?.strip_suffix('\'')?;
+        unescape_char(text).ok()
+    }
+}
+
+impl ast::Byte {
+    pub fn value(&self) -> Option<u8> {
+        let text = self.text();
+        let text = text.strip_prefix("b'")?.strip_suffix('\'')?;
+        unescape_byte(text).ok()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Radix {
     Binary = 2,
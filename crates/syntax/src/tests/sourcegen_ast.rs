@@ -503,7 +503,7 @@ impl Field {
 fn lower(grammar: &Grammar) -> AstSrc {
     let mut res = AstSrc::default();
 
-    res.tokens = "Whitespace Comment String ByteString IntNumber FloatNumber"
+    res.tokens = "Whitespace Comment String ByteString IntNumber FloatNumber Char Byte"
         .split_ascii_whitespace()
         .map(|it| it.to_string())
         .collect::<Vec<_>>();
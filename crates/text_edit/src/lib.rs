@@ -124,6 +124,13 @@ impl TextEdit {
         Ok(())
     }
 
+    /// Whether the two edits change overlapping ranges, so that `union` would
+    /// reject them.
+    pub fn intersects(&self, other: &TextEdit) -> bool {
+        let mut all = self.iter().chain(other.iter()).collect::<Vec<_>>();
+        !check_disjoint(&mut all)
+    }
+
     pub fn apply_to_offset(&self, offset: TextSize) -> Option<TextSize> {
         let mut res = offset;
         for indel in self.indels.iter() {
@@ -115,6 +115,10 @@ export class Config {
             typeHints: this.get<boolean>("inlayHints.typeHints"),
             parameterHints: this.get<boolean>("inlayHints.parameterHints"),
             chainingHints: this.get<boolean>("inlayHints.chainingHints"),
+            closureReturnTypeHints: this.get<boolean>("inlayHints.closureReturnTypeHints"),
+            lifetimeElisionHints: this.get<boolean>("inlayHints.lifetimeElisionHints"),
+            adjustmentHints: this.get<boolean>("inlayHints.adjustmentHints"),
+            closingBraceHints: this.get<boolean>("inlayHints.closingBraceHints"),
             smallerHints: this.get<boolean>("inlayHints.smallerHints"),
             maxLength: this.get<null | number>("inlayHints.maxLength"),
         };
@@ -14,6 +14,10 @@ interface InlayHintsStyles {
     typeHints: InlayHintStyle;
     paramHints: InlayHintStyle;
     chainingHints: InlayHintStyle;
+    closureReturnTypeHints: InlayHintStyle;
+    lifetimeHints: InlayHintStyle;
+    adjustmentHints: InlayHintStyle;
+    closingBraceHints: InlayHintStyle;
 }
 
 
@@ -23,7 +27,11 @@ export function activateInlayHints(ctx: Ctx) {
         async onConfigChange() {
             const anyEnabled = ctx.config.inlayHints.typeHints
                 || ctx.config.inlayHints.parameterHints
-                || ctx.config.inlayHints.chainingHints;
+                || ctx.config.inlayHints.chainingHints
+                || ctx.config.inlayHints.closureReturnTypeHints
+                || ctx.config.inlayHints.lifetimeElisionHints
+                || ctx.config.inlayHints.adjustmentHints
+                || ctx.config.inlayHints.closingBraceHints;
             const enabled = ctx.config.inlayHints.enable && anyEnabled;
 
             if (!enabled) return this.dispose();
@@ -51,13 +59,19 @@ export function activateInlayHints(ctx: Ctx) {
     maybeUpdater.onConfigChange().catch(console.error);
 }
 
-function createHintStyle(hintKind: "type" | "parameter" | "chaining", smallerHints: boolean): InlayHintStyle {
+type HintKind = "type" | "parameter" | "chaining" | "closureReturnType" | "lifetime" | "adjustment" | "closingBrace";
+
+function createHintStyle(hintKind: HintKind, smallerHints: boolean): InlayHintStyle {
     // U+200C is a zero-width non-joiner to prevent the editor from forming a ligature
     // between code and type hints
     const [pos, render] = ({
         type: ["after", (label: string) => `\u{200c}: ${label}`],
         parameter: ["before", (label: string) => `${label}: `],
         chaining: ["after", (label: string) => `\u{200c}: ${label}`],
+        closureReturnType: ["after", (label: string) => `\u{200c} -> ${label}`],
+        lifetime: ["after", (label: string) => `${label} `],
+        adjustment: ["before", (label: string) => label],
+        closingBrace: ["after", (label: string) => ` // ${label}`],
     } as const)[hintKind];
 
     const fg = new vscode.ThemeColor(`rust_analyzer.inlayHints.foreground.${hintKind}Hints`);
@@ -85,12 +99,20 @@ const smallHintsStyles = {
     typeHints: createHintStyle("type", true),
     paramHints: createHintStyle("parameter", true),
     chainingHints: createHintStyle("chaining", true),
+    closureReturnTypeHints: createHintStyle("closureReturnType", true),
+    lifetimeHints: createHintStyle("lifetime", true),
+    adjustmentHints: createHintStyle("adjustment", true),
+    closingBraceHints: createHintStyle("closingBrace", true),
 };
 
 const biggerHintsStyles = {
     typeHints: createHintStyle("type", false),
     paramHints: createHintStyle("parameter", false),
     chainingHints: createHintStyle("chaining", false),
+    closureReturnTypeHints: createHintStyle("closureReturnType", false),
+    lifetimeHints: createHintStyle("lifetime", false),
+    adjustmentHints: createHintStyle("adjustment", false),
+    closingBraceHints: createHintStyle("closingBrace", false),
 };
 
 class HintsUpdater implements Disposable {
@@ -128,7 +150,7 @@ class HintsUpdater implements Disposable {
 
     dispose() {
         this.sourceFiles.forEach(file => file.inlaysRequest?.cancel());
-        this.ctx.visibleRustEditors.forEach(editor => this.renderDecorations(editor, { param: [], type: [], chaining: [] }));
+        this.ctx.visibleRustEditors.forEach(editor => this.renderDecorations(editor, emptyDecorations()));
         this.disposables.forEach(d => d.dispose());
     }
 
@@ -193,21 +215,29 @@ class HintsUpdater implements Disposable {
     }
 
     private renderDecorations(editor: RustEditor, decorations: InlaysDecorations) {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
+        const styles = this.inlayHintsStyles;
         if (this.pendingDisposeDecorations !== undefined) {
-            const { typeHints, paramHints, chainingHints } = this.pendingDisposeDecorations;
-            editor.setDecorations(typeHints.decorationType, []);
-            editor.setDecorations(paramHints.decorationType, []);
-            editor.setDecorations(chainingHints.decorationType, []);
+            const pending = this.pendingDisposeDecorations;
+            editor.setDecorations(pending.typeHints.decorationType, []);
+            editor.setDecorations(pending.paramHints.decorationType, []);
+            editor.setDecorations(pending.chainingHints.decorationType, []);
+            editor.setDecorations(pending.closureReturnTypeHints.decorationType, []);
+            editor.setDecorations(pending.lifetimeHints.decorationType, []);
+            editor.setDecorations(pending.adjustmentHints.decorationType, []);
+            editor.setDecorations(pending.closingBraceHints.decorationType, []);
         }
-        editor.setDecorations(typeHints.decorationType, decorations.type);
-        editor.setDecorations(paramHints.decorationType, decorations.param);
-        editor.setDecorations(chainingHints.decorationType, decorations.chaining);
+        editor.setDecorations(styles.typeHints.decorationType, decorations.type);
+        editor.setDecorations(styles.paramHints.decorationType, decorations.param);
+        editor.setDecorations(styles.chainingHints.decorationType, decorations.chaining);
+        editor.setDecorations(styles.closureReturnTypeHints.decorationType, decorations.closureReturnType);
+        editor.setDecorations(styles.lifetimeHints.decorationType, decorations.lifetime);
+        editor.setDecorations(styles.adjustmentHints.decorationType, decorations.adjustment);
+        editor.setDecorations(styles.closingBraceHints.decorationType, decorations.closingBrace);
     }
 
     private hintsToDecorations(hints: ra.InlayHint[]): InlaysDecorations {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
-        const decorations: InlaysDecorations = { type: [], param: [], chaining: [] };
+        const { typeHints, paramHints, chainingHints, closureReturnTypeHints, lifetimeHints, adjustmentHints, closingBraceHints } = this.inlayHintsStyles;
+        const decorations = emptyDecorations();
         const conv = this.ctx.client.protocol2CodeConverter;
 
         for (const hint of hints) {
@@ -224,6 +254,22 @@ class HintsUpdater implements Disposable {
                     decorations.chaining.push(chainingHints.toDecoration(hint, conv));
                     continue;
                 }
+                case ra.InlayHint.Kind.ClosureReturnTypeHint: {
+                    decorations.closureReturnType.push(closureReturnTypeHints.toDecoration(hint, conv));
+                    continue;
+                }
+                case ra.InlayHint.Kind.LifetimeHint: {
+                    decorations.lifetime.push(lifetimeHints.toDecoration(hint, conv));
+                    continue;
+                }
+                case ra.InlayHint.Kind.AdjustmentHint: {
+                    decorations.adjustment.push(adjustmentHints.toDecoration(hint, conv));
+                    continue;
+                }
+                case ra.InlayHint.Kind.ClosingBraceHint: {
+                    decorations.closingBrace.push(closingBraceHints.toDecoration(hint, conv));
+                    continue;
+                }
             }
         }
         return decorations;
@@ -251,6 +297,14 @@ interface InlaysDecorations {
     type: vscode.DecorationOptions[];
     param: vscode.DecorationOptions[];
     chaining: vscode.DecorationOptions[];
+    closureReturnType: vscode.DecorationOptions[];
+    lifetime: vscode.DecorationOptions[];
+    adjustment: vscode.DecorationOptions[];
+    closingBrace: vscode.DecorationOptions[];
+}
+
+function emptyDecorations(): InlaysDecorations {
+    return { type: [], param: [], chaining: [], closureReturnType: [], lifetime: [], adjustment: [], closingBrace: [] };
 }
 
 interface RustSourceFile {
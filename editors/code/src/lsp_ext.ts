@@ -98,13 +98,24 @@ export interface TestInfo {
 
 export const relatedTests = new lc.RequestType<lc.TextDocumentPositionParams, TestInfo[], void>("rust-analyzer/relatedTests");
 
-export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint;
+export type InlayHint =
+    | InlayHint.TypeHint
+    | InlayHint.ParamHint
+    | InlayHint.ChainingHint
+    | InlayHint.ClosureReturnTypeHint
+    | InlayHint.LifetimeHint
+    | InlayHint.AdjustmentHint
+    | InlayHint.ClosingBraceHint;
 
 export namespace InlayHint {
     export const enum Kind {
         TypeHint = "TypeHint",
         ParamHint = "ParameterHint",
         ChainingHint = "ChainingHint",
+        ClosureReturnTypeHint = "ClosureReturnTypeHint",
+        LifetimeHint = "LifetimeHint",
+        AdjustmentHint = "AdjustmentHint",
+        ClosingBraceHint = "ClosingBraceHint",
     }
     interface Common {
         range: lc.Range;
@@ -113,6 +124,10 @@ export namespace InlayHint {
     export type TypeHint = Common & { kind: Kind.TypeHint };
     export type ParamHint = Common & { kind: Kind.ParamHint };
     export type ChainingHint = Common & { kind: Kind.ChainingHint };
+    export type ClosureReturnTypeHint = Common & { kind: Kind.ClosureReturnTypeHint };
+    export type LifetimeHint = Common & { kind: Kind.LifetimeHint };
+    export type AdjustmentHint = Common & { kind: Kind.AdjustmentHint };
+    export type ClosingBraceHint = Common & { kind: Kind.ClosingBraceHint };
 }
 export interface InlayHintsParams {
     textDocument: lc.TextDocumentIdentifier;